@@ -8,7 +8,10 @@ import log from 'loglevel';
 import {
 	Admin,
 	ClearingHouseUser,
+	formatMarket,
+	formatUser,
 	initialize,
+	RestGateway,
 	Markets,
 	Wallet,
 } from '@drift-labs/sdk';
@@ -447,6 +450,56 @@ commandWithDefaultOption('deposit')
 		);
 	});
 
+commandWithDefaultOption('user').action(async (options: OptionValues) => {
+	await wrapActionInUserSubscribeUnsubscribe(
+		options,
+		async (user: ClearingHouseUser) => {
+			log.info(
+				formatUser(
+					user.getUserAccount(),
+					user.getUserPositionsAccount(),
+					user.clearingHouse.getMarketsAccount().markets
+				)
+			);
+		}
+	);
+});
+
+commandWithDefaultOption('market')
+	.argument('<market>', 'The market to display')
+	.action(async (market, options: OptionValues) => {
+		await wrapActionInAdminSubscribeUnsubscribe(
+			options,
+			async (admin: Admin) => {
+				market = marketIndexFromSymbol(market);
+				log.info(formatMarket(admin.getMarket(market), market));
+			}
+		);
+	});
+
+commandWithDefaultOption('gateway')
+	.option('-p, --port <port>', 'port to listen on', '8080')
+	.option('--host <host>', 'interface to listen on', '127.0.0.1')
+	.option('--api-key <apiKey>', 'require this x-api-key header')
+	.action(async (options: OptionValues) => {
+		await wrapActionInUserSubscribeUnsubscribe(
+			options,
+			async (user: ClearingHouseUser) => {
+				const gateway = new RestGateway(user.clearingHouse, user, {
+					port: Number(options.port),
+					host: options.host,
+					apiKey: options.apiKey,
+				});
+				await gateway.start();
+				log.info(`Gateway listening on ${options.host}:${options.port}`);
+
+				await new Promise<void>((resolve) => process.once('SIGINT', resolve));
+				await gateway.stop();
+				log.info(`Gateway stopped`);
+			}
+		);
+	});
+
 function getConfigFileDir(): string {
 	return os.homedir() + `/.config/drift-v1`;
 }
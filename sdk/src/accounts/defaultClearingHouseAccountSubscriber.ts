@@ -1,4 +1,6 @@
 import {
+	AccountSubscriberFactory,
+	ClearingHouseAccountName,
 	ClearingHouseAccountSubscriber,
 	ClearingHouseAccountEvents,
 	ClearingHouseAccountTypes,
@@ -15,10 +17,56 @@ import {
 	TradeHistoryAccount,
 } from '../types';
 import { Program } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
 import StrictEventEmitter from 'strict-event-emitter-types';
 import { EventEmitter } from 'events';
 import { getClearingHouseStateAccountPublicKey } from '../addresses';
 import { WebSocketAccountSubscriber } from './webSocketAccountSubscriber';
+import { PollingAccountSubscriber } from './pollingAccountSubscriber';
+import { StaticAccountSubscriber } from './staticAccountSubscriber';
+import { HistoryAccountSubscriber } from './historyAccountSubscriber';
+import { ClearingHouseNotInitializedError } from '../errors';
+
+export const webSocketSubscriberFactory: AccountSubscriberFactory = (
+	accountName,
+	program,
+	accountPublicKey
+) => new WebSocketAccountSubscriber(accountName, program, accountPublicKey);
+
+export function getPollingSubscriberFactory(
+	pollingIntervalMs = 1000
+): AccountSubscriberFactory {
+	return (accountName, program, accountPublicKey) =>
+		new PollingAccountSubscriber(
+			accountName,
+			program,
+			accountPublicKey,
+			pollingIntervalMs
+		);
+}
+
+export const staticSubscriberFactory: AccountSubscriberFactory = (
+	accountName,
+	program,
+	accountPublicKey
+) => new StaticAccountSubscriber(accountName, program, accountPublicKey);
+
+/**
+ * Websocket subscriber for history accounts decoding only the new records of each update, see {@link HistoryAccountSubscriber}
+ */
+export const historySubscriberFactory: AccountSubscriberFactory = (
+	accountName,
+	program,
+	accountPublicKey
+) => new HistoryAccountSubscriber(accountName, program, accountPublicKey);
+
+/**
+ * Subscriber backend per account. History accounts left out use {@link historySubscriberFactory}, the others websockets.
+ * E.g. { tradeHistory: staticSubscriberFactory } for a batch job reading the trade history once, or a custom factory for a geyser stream
+ */
+export type AccountSubscriberFactories = Partial<
+	Record<ClearingHouseAccountName, AccountSubscriberFactory>
+>;
 
 export class DefaultClearingHouseAccountSubscriber
 	implements ClearingHouseAccountSubscriber
@@ -36,17 +84,43 @@ export class DefaultClearingHouseAccountSubscriber
 	liquidationHistoryAccountSubscriber?: AccountSubscriber<LiquidationHistoryAccount>;
 
 	optionalExtraSubscriptions: ClearingHouseAccountTypes[] = [];
+	subscriberFactories: AccountSubscriberFactories;
 
 	private isSubscribing = false;
 	private subscriptionPromise: Promise<boolean>;
 	private subscriptionPromiseResolver: (val: boolean) => void;
 
-	public constructor(program: Program) {
+	/**
+	 * @param program
+	 * @param subscriberFactories subscriber backend per account, websockets for accounts left out
+	 */
+	public constructor(
+		program: Program,
+		subscriberFactories: AccountSubscriberFactories = {}
+	) {
 		this.isSubscribed = false;
 		this.program = program;
 		this.eventEmitter = new EventEmitter();
+		this.subscriberFactories = subscriberFactories;
+	}
+
+	private createSubscriber<T>(
+		accountName: ClearingHouseAccountName,
+		accountPublicKey: PublicKey
+	): AccountSubscriber<T> {
+		const defaultFactory =
+			accountName === 'state' || accountName === 'markets'
+				? webSocketSubscriberFactory
+				: historySubscriberFactory;
+		const factory = this.subscriberFactories[accountName] ?? defaultFactory;
+		return factory<T>(accountName, this.program, accountPublicKey);
 	}
 
+	/**
+	 * @param optionalSubscriptions history accounts to subscribe to as well
+	 * @returns false to concurrent callers if the subscription failed
+	 * @throws ClearingHouseNotInitializedError if the clearing house state doesn't exist yet
+	 */
 	public async subscribe(
 		optionalSubscriptions?: ClearingHouseAccountTypes[]
 	): Promise<boolean> {
@@ -64,26 +138,55 @@ export class DefaultClearingHouseAccountSubscriber
 			this.subscriptionPromiseResolver = res;
 		});
 
+		try {
+			await this.subscribeToAccounts(optionalSubscriptions);
+		} catch (e) {
+			// leave the subscriber as if subscribe was never called, so it can be retried, e.g. once the clearing house
+			// is initialized or the rpc recovers
+			await this.unsubscribeFromAccounts();
+			this.isSubscribing = false;
+			this.subscriptionPromiseResolver(false);
+			throw e;
+		}
+
+		this.isSubscribing = false;
+		this.isSubscribed = true;
+		this.subscriptionPromiseResolver(true);
+
+		return true;
+	}
+
+	private async subscribeToAccounts(
+		optionalSubscriptions?: ClearingHouseAccountTypes[]
+	): Promise<void> {
 		const statePublicKey = await getClearingHouseStateAccountPublicKey(
 			this.program.programId
 		);
 
 		// create and activate main state account subscription
-		this.stateAccountSubscriber = new WebSocketAccountSubscriber(
+		this.stateAccountSubscriber = this.createSubscriber(
 			'state',
-			this.program,
 			statePublicKey
 		);
-		await this.stateAccountSubscriber.subscribe((data: StateAccount) => {
-			this.eventEmitter.emit('stateAccountUpdate', data);
-			this.eventEmitter.emit('update');
-		});
+		try {
+			await this.stateAccountSubscriber.subscribe((data: StateAccount) => {
+				this.eventEmitter.emit('stateAccountUpdate', data);
+				this.eventEmitter.emit('update');
+			});
+		} catch (e) {
+			const stateAccountInfo = await this.program.provider.connection
+				.getAccountInfo(statePublicKey)
+				.catch(() => undefined);
+			if (stateAccountInfo === null) {
+				throw new ClearingHouseNotInitializedError(statePublicKey);
+			}
+			throw e;
+		}
 
 		const state = this.stateAccountSubscriber.data;
 
-		this.marketsAccountSubscriber = new WebSocketAccountSubscriber(
+		this.marketsAccountSubscriber = this.createSubscriber(
 			'markets',
-			this.program,
 			state.markets
 		);
 
@@ -94,40 +197,33 @@ export class DefaultClearingHouseAccountSubscriber
 
 		// create subscribers for other state accounts
 
-		this.tradeHistoryAccountSubscriber = new WebSocketAccountSubscriber(
+		this.tradeHistoryAccountSubscriber = this.createSubscriber(
 			'tradeHistory',
-			this.program,
 			state.tradeHistory
 		);
 
-		this.depositHistoryAccountSubscriber = new WebSocketAccountSubscriber(
+		this.depositHistoryAccountSubscriber = this.createSubscriber(
 			'depositHistory',
-			this.program,
 			state.depositHistory
 		);
 
-		this.fundingPaymentHistoryAccountSubscriber =
-			new WebSocketAccountSubscriber(
-				'fundingPaymentHistory',
-				this.program,
-				state.fundingPaymentHistory
-			);
+		this.fundingPaymentHistoryAccountSubscriber = this.createSubscriber(
+			'fundingPaymentHistory',
+			state.fundingPaymentHistory
+		);
 
-		this.fundingRateHistoryAccountSubscriber = new WebSocketAccountSubscriber(
+		this.fundingRateHistoryAccountSubscriber = this.createSubscriber(
 			'fundingRateHistory',
-			this.program,
 			state.fundingRateHistory
 		);
 
-		this.liquidationHistoryAccountSubscriber = new WebSocketAccountSubscriber(
+		this.liquidationHistoryAccountSubscriber = this.createSubscriber(
 			'liquidationHistory',
-			this.program,
 			state.liquidationHistory
 		);
 
-		this.curveHistoryAccountSubscriber = new WebSocketAccountSubscriber(
+		this.curveHistoryAccountSubscriber = this.createSubscriber(
 			'curveHistory',
-			this.program,
 			state.curveHistory
 		);
 
@@ -186,12 +282,28 @@ export class DefaultClearingHouseAccountSubscriber
 		);
 
 		this.eventEmitter.emit('update');
+	}
 
-		this.isSubscribing = false;
-		this.isSubscribed = true;
-		this.subscriptionPromiseResolver(true);
-
-		return true;
+	/**
+	 * Tears down whatever subscriptions a failed subscribe got to
+	 */
+	private async unsubscribeFromAccounts(): Promise<void> {
+		const subscribers: AccountSubscriber<any>[] = [
+			this.stateAccountSubscriber,
+			this.marketsAccountSubscriber,
+			this.tradeHistoryAccountSubscriber,
+			this.depositHistoryAccountSubscriber,
+			this.fundingPaymentHistoryAccountSubscriber,
+			this.fundingRateHistoryAccountSubscriber,
+			this.liquidationHistoryAccountSubscriber,
+			this.curveHistoryAccountSubscriber,
+		];
+		await Promise.all(
+			subscribers
+				.filter((subscriber) => subscriber !== undefined)
+				.map((subscriber) => subscriber.unsubscribe().catch(() => undefined))
+		);
+		this.optionalExtraSubscriptions = [];
 	}
 
 	public async fetch(): Promise<void> {
@@ -212,6 +324,14 @@ export class DefaultClearingHouseAccountSubscriber
 		await Promise.all(promises);
 	}
 
+	public async fetchMarketsAccount(): Promise<void> {
+		if (!this.isSubscribed) {
+			return;
+		}
+
+		await this.marketsAccountSubscriber.fetch();
+	}
+
 	public async unsubscribe(): Promise<void> {
 		if (!this.isSubscribed) {
 			return;
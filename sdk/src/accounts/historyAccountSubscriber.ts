@@ -0,0 +1,121 @@
+import { AccountSubscriber } from './types';
+import { BN, Program } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import {
+	fetchNewRecords,
+	HistoryAccountName,
+	HistoryAccountView,
+} from './historyAccountView';
+
+/**
+ * Subscribes to a history account over a websocket, decoding only the records written since the last update. The
+ * {@link WebSocketAccountSubscriber} decodes all of the ring buffer's records and compares the whole account on every update,
+ * and refetches the whole account, where {@link fetch} reads only the head and the new records, see {@link HistoryAccountView}
+ */
+export class HistoryAccountSubscriber<T> implements AccountSubscriber<T> {
+	data?: T;
+	/** account client name, e.g. tradeHistory */
+	accountName: string;
+	program: Program;
+	accountPublicKey: PublicKey;
+	onChange: (data: T) => void;
+
+	private historyAccountName: HistoryAccountName;
+	private view?: HistoryAccountView<HistoryAccountName>;
+	private listenerId?: number;
+
+	public constructor(
+		accountName: string,
+		program: Program,
+		accountPublicKey: PublicKey
+	) {
+		this.accountName = accountName;
+		this.program = program;
+		this.accountPublicKey = accountPublicKey;
+		// idl account name, e.g. TradeHistory
+		this.historyAccountName = (accountName.charAt(0).toUpperCase() +
+			accountName.slice(1)) as HistoryAccountName;
+	}
+
+	async subscribe(onChange: (data: T) => void): Promise<void> {
+		this.onChange = onChange;
+		await this.fetch();
+
+		this.listenerId = this.program.provider.connection.onAccountChange(
+			this.accountPublicKey,
+			(accountInfo) => {
+				const lastRecordId = this.view.getLatestRecordId();
+				this.view = new HistoryAccountView(
+					this.program.idl,
+					this.historyAccountName,
+					accountInfo.data
+				);
+				this.applyRecords(this.view.getIndexesSince(lastRecordId));
+			},
+			this.program.provider.opts.commitment
+		);
+	}
+
+	async fetch(): Promise<void> {
+		const connection = this.program.provider.connection;
+		const commitment = this.program.provider.opts.commitment;
+		if (this.view) {
+			this.applyRecords(
+				await fetchNewRecords(
+					connection,
+					this.accountPublicKey,
+					this.view,
+					commitment
+				)
+			);
+			return;
+		}
+
+		const accountInfo = await connection.getAccountInfo(
+			this.accountPublicKey,
+			commitment
+		);
+		if (accountInfo === null) {
+			throw new Error(
+				`Account does not exist ${this.accountPublicKey.toBase58()}`
+			);
+		}
+		this.view = new HistoryAccountView(
+			this.program.idl,
+			this.historyAccountName,
+			accountInfo.data
+		);
+		this.data = this.view.getAccount() as unknown as T;
+		this.onChange(this.data);
+	}
+
+	async unsubscribe(): Promise<void> {
+		if (this.listenerId !== undefined) {
+			await this.program.provider.connection.removeAccountChangeListener(
+				this.listenerId
+			);
+			this.listenerId = undefined;
+		}
+	}
+
+	/**
+	 * Decodes the records at the indexes into a copy of the account, unchanged records are shared with the previous one
+	 */
+	private applyRecords(indexes: number[]): void {
+		if (indexes.length === 0) {
+			return;
+		}
+
+		const recordsFieldName = this.view.recordsFieldName;
+		const records = (this.data[recordsFieldName] as unknown[]).slice();
+		for (const index of indexes) {
+			records[index] = this.view.getRecord(index);
+		}
+		this.data = {
+			...this.data,
+			head: new BN(this.view.head),
+			[recordsFieldName]: records,
+		} as T;
+		this.onChange(this.data);
+	}
+}
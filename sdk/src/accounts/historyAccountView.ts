@@ -0,0 +1,260 @@
+import { BN, Idl } from '@project-serum/anchor';
+import { Commitment, Connection, PublicKey } from '@solana/web3.js';
+import {
+	CurveRecord,
+	DepositRecord,
+	FundingPaymentRecord,
+	FundingRateRecord,
+	LiquidationRecord,
+	TradeRecord,
+} from '../types';
+import { fetchAccountSlice } from '../util/accountSlice';
+import {
+	ACCOUNT_DISCRIMINATOR_SIZE,
+	getStructLayout,
+	StructLayout,
+} from './zeroCopyLayout';
+
+type HistoryRecords = {
+	TradeHistory: TradeRecord;
+	DepositHistory: DepositRecord;
+	FundingPaymentHistory: FundingPaymentRecord;
+	FundingRateHistory: FundingRateRecord;
+	LiquidationHistory: LiquidationRecord;
+	CurveHistory: CurveRecord;
+};
+
+export type HistoryAccountName = keyof HistoryRecords;
+
+type RecordLayout = {
+	/** offset of the first record in the account data */
+	offset: number;
+	capacity: number;
+	record: StructLayout;
+	recordIdOffset: number;
+	/** name of the records field in the account, e.g. tradeRecords */
+	recordsFieldName: string;
+};
+
+// the account's head, a u64 index of the next record to write, precedes the records
+const RECORDS_OFFSET = ACCOUNT_DISCRIMINATOR_SIZE + 8;
+
+function getRecordLayout(idl: Idl, accountName: string): RecordLayout {
+	const account = idl.accounts.find((a) => a.name === accountName);
+	const recordsField = account.type.fields[1];
+	const [recordType, capacity] = (recordsField.type as any).array;
+	const record = getStructLayout(idl, recordType.defined);
+	return {
+		offset: RECORDS_OFFSET,
+		capacity,
+		record,
+		recordIdOffset: record.fields.find((field) => field.name === 'recordId')
+			.offset,
+		recordsFieldName: recordsField.name,
+	};
+}
+
+// keyed by idl, so programs deployed with different idls don't share layouts
+const layoutCaches = new WeakMap<Idl, Map<string, RecordLayout>>();
+
+/**
+ * # HistoryAccountView
+ * Reads a history account's records straight from the account data instead of decoding the whole ring buffer, which is
+ * up to 1024 records and hundreds of KB. History accounts are zero copy, so each record sits at a fixed offset, see {@link StructLayout}:
+ * only the records asked for are decoded, and finding new records reads just their ids
+ */
+export class HistoryAccountView<N extends HistoryAccountName> {
+	data: Buffer;
+
+	private layout: RecordLayout;
+
+	/**
+	 * @param idl the clearing house idl, e.g. program.idl
+	 * @param accountName
+	 * @param data the history account's data, not copied
+	 */
+	public constructor(idl: Idl, accountName: N, data: Buffer) {
+		let layoutCache = layoutCaches.get(idl);
+		if (!layoutCache) {
+			layoutCache = new Map();
+			layoutCaches.set(idl, layoutCache);
+		}
+		if (!layoutCache.has(accountName)) {
+			layoutCache.set(accountName, getRecordLayout(idl, accountName));
+		}
+		this.layout = layoutCache.get(accountName);
+		this.data = data;
+	}
+
+	/**
+	 * index of the next record the program writes
+	 */
+	public get head(): number {
+		// a u64, but always below the capacity
+		return this.data.readUInt32LE(ACCOUNT_DISCRIMINATOR_SIZE);
+	}
+
+	/**
+	 * number of records the ring buffer holds
+	 */
+	public get capacity(): number {
+		return this.layout.capacity;
+	}
+
+	/**
+	 * size of a record in bytes
+	 */
+	public get recordSize(): number {
+		return this.layout.record.size;
+	}
+
+	/**
+	 * name of the records field in the decoded account, e.g. tradeRecords
+	 */
+	public get recordsFieldName(): string {
+		return this.layout.recordsFieldName;
+	}
+
+	/**
+	 * @param index position in the ring buffer
+	 * @returns offset of the record in the account data
+	 */
+	public getRecordOffset(index: number): number {
+		return this.layout.offset + index * this.layout.record.size;
+	}
+
+	/**
+	 * @param index position in the ring buffer
+	 * @returns record id, zero if the slot hasn't been written
+	 */
+	public getRecordId(index: number): BN {
+		const offset = this.getRecordOffset(index) + this.layout.recordIdOffset;
+		return new BN(this.data.slice(offset, offset + 16), 'le');
+	}
+
+	/**
+	 * @param index position in the ring buffer
+	 */
+	public getRecord(index: number): HistoryRecords[N] {
+		return this.layout.record.read(
+			this.data,
+			this.getRecordOffset(index)
+		) as HistoryRecords[N];
+	}
+
+	/**
+	 * @returns the highest record id written, zero if none
+	 */
+	public getLatestRecordId(): BN {
+		const latestIndex = (this.head + this.capacity - 1) % this.capacity;
+		return this.getRecordId(latestIndex);
+	}
+
+	/**
+	 * Decodes only the records newer than a record id, e.g. the last one a stream processed
+	 * @param recordId
+	 * @returns records ordered by record id
+	 */
+	public getRecordsSince(recordId: BN): HistoryRecords[N][] {
+		return this.getIndexesSince(recordId).map((index) => this.getRecord(index));
+	}
+
+	/**
+	 * @param recordId
+	 * @returns positions in the ring buffer of the records newer than the record id, ordered by record id
+	 */
+	public getIndexesSince(recordId: BN): number[] {
+		const indexes: number[] = [];
+		// walk back from the newest record until reaching one already seen
+		for (let i = 1; i <= this.capacity; i++) {
+			const index = (this.head + this.capacity - i) % this.capacity;
+			if (this.getRecordId(index).lte(recordId)) {
+				break;
+			}
+			indexes.push(index);
+		}
+		return indexes.reverse();
+	}
+
+	/**
+	 * @returns every written record ordered by record id
+	 */
+	public getRecords(): HistoryRecords[N][] {
+		return this.getRecordsSince(new BN(0));
+	}
+
+	/**
+	 * Decodes the whole account, to the same shape as the anchor coder's
+	 */
+	public getAccount(): { head: BN } & Record<string, unknown> {
+		const records = [];
+		for (let index = 0; index < this.capacity; index++) {
+			records.push(this.getRecord(index));
+		}
+		return {
+			head: new BN(this.head),
+			[this.recordsFieldName]: records,
+		};
+	}
+}
+
+/**
+ * Brings a view of a history account up to date, fetching only the account's head and the records written since the view's
+ * head instead of the whole account. Misses updates of more than the account's capacity, e.g. 1024 records, between calls
+ * @param connection
+ * @param publicKey the history account
+ * @param view mutated in place
+ * @param commitment defaults to the connection's
+ * @returns positions in the ring buffer of the records written since, oldest first
+ */
+export async function fetchNewRecords(
+	connection: Connection,
+	publicKey: PublicKey,
+	view: HistoryAccountView<HistoryAccountName>,
+	commitment: Commitment = connection.commitment
+): Promise<number[]> {
+	const headData = await fetchAccountSlice(
+		connection,
+		publicKey,
+		ACCOUNT_DISCRIMINATOR_SIZE,
+		8,
+		commitment
+	);
+	if (headData === null) {
+		throw new Error(`History account ${publicKey.toBase58()} not found`);
+	}
+
+	const head = headData.readUInt32LE(0);
+	const written = (head - view.head + view.capacity) % view.capacity;
+	if (written === 0) {
+		return [];
+	}
+
+	// the records written since are at most two ranges, the second one once the ring buffer wraps around
+	const firstCount = Math.min(written, view.capacity - view.head);
+	const ranges: Array<[number, number]> = [[view.head, firstCount]];
+	if (firstCount < written) {
+		ranges.push([0, written - firstCount]);
+	}
+	const slices = await Promise.all(
+		ranges.map(([index, count]) =>
+			fetchAccountSlice(
+				connection,
+				publicKey,
+				view.getRecordOffset(index),
+				count * view.recordSize,
+				commitment
+			)
+		)
+	);
+
+	const indexes: number[] = [];
+	ranges.forEach(([index, count], i) => {
+		slices[i].copy(view.data, view.getRecordOffset(index));
+		for (let j = 0; j < count; j++) {
+			indexes.push(index + j);
+		}
+	});
+	headData.copy(view.data, ACCOUNT_DISCRIMINATOR_SIZE);
+	return indexes;
+}
@@ -0,0 +1,59 @@
+import { Idl } from '@project-serum/anchor';
+import { Market } from '../types';
+import {
+	ACCOUNT_DISCRIMINATOR_SIZE,
+	getStructLayout,
+	StructLayout,
+} from './zeroCopyLayout';
+
+const MARKETS_CAPACITY = 64;
+
+/**
+ * @param idl the clearing house idl, e.g. program.idl
+ * @returns layout of a single market in the markets account
+ */
+export function getMarketLayout(idl: Idl): StructLayout {
+	return getStructLayout(idl, 'Market');
+}
+
+/**
+ * @param idl
+ * @param marketIndex
+ * @returns offset of the market in the markets account data
+ */
+export function getMarketOffset(idl: Idl, marketIndex: number): number {
+	return ACCOUNT_DISCRIMINATOR_SIZE + marketIndex * getMarketLayout(idl).size;
+}
+
+/**
+ * # MarketsAccountView
+ * Decodes single markets straight from the markets account data instead of all 64, for hot loops that read one or two
+ * markets per update. The markets account is zero copy, so each market sits at a fixed offset, see {@link StructLayout}
+ */
+export class MarketsAccountView {
+	idl: Idl;
+	data: Buffer;
+
+	/**
+	 * @param idl the clearing house idl, e.g. program.idl
+	 * @param data the markets account's data, not copied
+	 */
+	public constructor(idl: Idl, data: Buffer) {
+		this.idl = idl;
+		this.data = data;
+	}
+
+	/**
+	 * number of markets the account holds, initialized or not
+	 */
+	public get capacity(): number {
+		return MARKETS_CAPACITY;
+	}
+
+	public getMarket(marketIndex: number): Market {
+		return getMarketLayout(this.idl).read(
+			this.data,
+			getMarketOffset(this.idl, marketIndex)
+		) as Market;
+	}
+}
@@ -0,0 +1,62 @@
+import { AccountSubscriber } from './types';
+import { Program } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+
+/**
+ * Refetches the account on an interval instead of holding a websocket subscription, for environments where websockets
+ * are unavailable or unreliable
+ */
+export class PollingAccountSubscriber<T> implements AccountSubscriber<T> {
+	data?: T;
+	accountName: string;
+	program: Program;
+	accountPublicKey: PublicKey;
+	pollingIntervalMs: number;
+	onChange: (data: T) => void;
+
+	private intervalId?: ReturnType<typeof setInterval>;
+
+	public constructor(
+		accountName: string,
+		program: Program,
+		accountPublicKey: PublicKey,
+		pollingIntervalMs = 1000
+	) {
+		this.accountName = accountName;
+		this.program = program;
+		this.accountPublicKey = accountPublicKey;
+		this.pollingIntervalMs = pollingIntervalMs;
+	}
+
+	async subscribe(onChange: (data: T) => void): Promise<void> {
+		this.onChange = onChange;
+		await this.fetch();
+
+		this.intervalId = setInterval(async () => {
+			try {
+				await this.fetch();
+			} catch (e) {
+				// the next poll retries
+			}
+		}, this.pollingIntervalMs);
+	}
+
+	async fetch(): Promise<void> {
+		const newData = (await this.program.account[this.accountName].fetch(
+			this.accountPublicKey
+		)) as T;
+
+		// if data has changed trigger update
+		if (JSON.stringify(newData) !== JSON.stringify(this.data)) {
+			this.data = newData;
+			this.onChange(this.data);
+		}
+	}
+
+	async unsubscribe(): Promise<void> {
+		if (this.intervalId) {
+			clearInterval(this.intervalId);
+			this.intervalId = undefined;
+		}
+	}
+}
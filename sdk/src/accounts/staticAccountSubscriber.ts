@@ -0,0 +1,41 @@
+import { AccountSubscriber } from './types';
+import { Program } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+
+/**
+ * Fetches the account once on subscribe and again only when {@link fetch} is called, for read-only batch jobs
+ * that don't need live updates
+ */
+export class StaticAccountSubscriber<T> implements AccountSubscriber<T> {
+	data?: T;
+	accountName: string;
+	program: Program;
+	accountPublicKey: PublicKey;
+	onChange: (data: T) => void;
+
+	public constructor(
+		accountName: string,
+		program: Program,
+		accountPublicKey: PublicKey
+	) {
+		this.accountName = accountName;
+		this.program = program;
+		this.accountPublicKey = accountPublicKey;
+	}
+
+	async subscribe(onChange: (data: T) => void): Promise<void> {
+		this.onChange = onChange;
+		await this.fetch();
+	}
+
+	async fetch(): Promise<void> {
+		this.data = (await this.program.account[this.accountName].fetch(
+			this.accountPublicKey
+		)) as T;
+		this.onChange(this.data);
+	}
+
+	async unsubscribe(): Promise<void> {
+		// nothing to tear down
+	}
+}
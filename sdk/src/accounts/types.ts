@@ -12,6 +12,8 @@ import {
 } from '../types';
 import StrictEventEmitter from 'strict-event-emitter-types';
 import { EventEmitter } from 'events';
+import { Program } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
 
 export interface AccountSubscriber<T> {
 	data?: T;
@@ -20,6 +22,28 @@ export interface AccountSubscriber<T> {
 	unsubscribe(): void;
 }
 
+/**
+ * Creates the subscriber backing one account, e.g. a websocket, polling or static subscriber
+ */
+export type AccountSubscriberFactory = <T>(
+	accountName: string,
+	program: Program,
+	accountPublicKey: PublicKey
+) => AccountSubscriber<T>;
+
+/**
+ * Clearing house accounts by account client name
+ */
+export type ClearingHouseAccountName =
+	| 'state'
+	| 'markets'
+	| 'tradeHistory'
+	| 'depositHistory'
+	| 'fundingPaymentHistory'
+	| 'fundingRateHistory'
+	| 'curveHistory'
+	| 'liquidationHistory';
+
 export class NotSubscribedError extends Error {
 	name = 'NotSubscribedError';
 }
@@ -56,6 +80,8 @@ export interface ClearingHouseAccountSubscriber {
 		optionalSubscriptions?: ClearingHouseAccountTypes[]
 	): Promise<boolean>;
 	fetch(): Promise<void>;
+	/** refetches only the markets account, the one trades change */
+	fetchMarketsAccount?(): Promise<void>;
 	unsubscribe(): Promise<void>;
 
 	getStateAccount(): StateAccount;
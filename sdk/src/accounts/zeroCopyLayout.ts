@@ -0,0 +1,131 @@
+import { BN, Idl } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+
+export const ACCOUNT_DISCRIMINATOR_SIZE = 8;
+
+type FieldReader = (data: Buffer, offset: number) => unknown;
+
+export type FieldLayout = {
+	name: string;
+	/** from the start of the struct */
+	offset: number;
+	size: number;
+	read: FieldReader;
+};
+
+/**
+ * Byte layout of an idl type in a zero copy account. Zero copy types are packed, so fields follow each other
+ * without padding, in the same order and sizes as their borsh encoding
+ */
+export type StructLayout = {
+	size: number;
+	fields: FieldLayout[];
+	/** decodes the struct at an offset to the same shape as the anchor coder's */
+	read: FieldReader;
+};
+
+const PRIMITIVE_SIZES = {
+	bool: 1,
+	u8: 1,
+	u64: 8,
+	i64: 8,
+	u128: 16,
+	i128: 16,
+	publicKey: 32,
+};
+
+function readInteger(size: number, signed: boolean): FieldReader {
+	return (data, offset) => {
+		const value = new BN(data.slice(offset, offset + size), 'le');
+		return signed ? value.fromTwos(size * 8) : value;
+	};
+}
+
+// variants are decoded the way the anchor coder names them, e.g. Long as long and DEPOSIT as deposit
+function toVariantName(name: string): string {
+	return name === name.toUpperCase()
+		? name.toLowerCase()
+		: name.charAt(0).toLowerCase() + name.slice(1);
+}
+
+function getFieldReader(idl: Idl, type: any): [FieldReader, number] {
+	switch (type) {
+		case 'bool':
+			return [(data, offset) => data[offset] !== 0, 1];
+		case 'u8':
+			return [(data, offset) => data[offset], 1];
+		case 'publicKey':
+			return [
+				(data, offset) => new PublicKey(data.slice(offset, offset + 32)),
+				32,
+			];
+		case 'u64':
+		case 'u128':
+			return [
+				readInteger(PRIMITIVE_SIZES[type], false),
+				PRIMITIVE_SIZES[type],
+			];
+		case 'i64':
+		case 'i128':
+			return [
+				readInteger(PRIMITIVE_SIZES[type], true),
+				PRIMITIVE_SIZES[type],
+			];
+	}
+
+	const definition = idl.types.find((t) => t.name === type.defined);
+	if (definition?.type.kind === 'struct') {
+		const layout = getStructLayout(idl, definition.name);
+		return [layout.read, layout.size];
+	}
+	if (definition?.type.kind === 'enum') {
+		const variants = definition.type.variants.map((variant) =>
+			toVariantName(variant.name)
+		);
+		return [(data, offset) => ({ [variants[data[offset]]]: {} }), 1];
+	}
+	throw new Error(`Unsupported zero copy field ${JSON.stringify(type)}`);
+}
+
+// keyed by idl, so programs deployed with different idls don't share layouts
+const layoutCaches = new WeakMap<Idl, Map<string, StructLayout>>();
+
+/**
+ * @param idl the clearing house idl, e.g. program.idl
+ * @param typeName an idl struct type, e.g. Market or TradeRecord
+ * @returns the type's layout, computed once per idl and type
+ */
+export function getStructLayout(idl: Idl, typeName: string): StructLayout {
+	let layoutCache = layoutCaches.get(idl);
+	if (!layoutCache) {
+		layoutCache = new Map();
+		layoutCaches.set(idl, layoutCache);
+	}
+	const cached = layoutCache.get(typeName);
+	if (cached) {
+		return cached;
+	}
+
+	const definition = idl.types.find((t) => t.name === typeName);
+	let size = 0;
+	const fields = definition.type.fields.map((field) => {
+		const [read, fieldSize] = getFieldReader(idl, field.type);
+		const layout = { name: field.name, offset: size, size: fieldSize, read };
+		size += fieldSize;
+		return layout;
+	});
+
+	const layout = {
+		size,
+		fields,
+		read: (data: Buffer, offset: number) => {
+			const struct = {};
+			for (const field of fields) {
+				struct[field.name] = field.read(data, offset + field.offset);
+			}
+			return struct;
+		},
+	};
+	layoutCache.set(typeName, layout);
+	return layout;
+}
@@ -32,3 +32,63 @@ export async function getUserAccountPublicKey(
 ): Promise<PublicKey> {
 	return (await getUserAccountPublicKeyAndNonce(programId, authority))[0];
 }
+
+export async function getCollateralVaultPublicKeyAndNonce(
+	programId: PublicKey
+): Promise<[PublicKey, number]> {
+	return anchor.web3.PublicKey.findProgramAddress(
+		[Buffer.from(anchor.utils.bytes.utf8.encode('collateral_vault'))],
+		programId
+	);
+}
+
+export async function getCollateralVaultPublicKey(
+	programId: PublicKey
+): Promise<PublicKey> {
+	return (await getCollateralVaultPublicKeyAndNonce(programId))[0];
+}
+
+export async function getCollateralVaultAuthorityPublicKeyAndNonce(
+	programId: PublicKey
+): Promise<[PublicKey, number]> {
+	return anchor.web3.PublicKey.findProgramAddress(
+		[(await getCollateralVaultPublicKey(programId)).toBuffer()],
+		programId
+	);
+}
+
+export async function getCollateralVaultAuthorityPublicKey(
+	programId: PublicKey
+): Promise<PublicKey> {
+	return (await getCollateralVaultAuthorityPublicKeyAndNonce(programId))[0];
+}
+
+export async function getInsuranceVaultPublicKeyAndNonce(
+	programId: PublicKey
+): Promise<[PublicKey, number]> {
+	return anchor.web3.PublicKey.findProgramAddress(
+		[Buffer.from(anchor.utils.bytes.utf8.encode('insurance_vault'))],
+		programId
+	);
+}
+
+export async function getInsuranceVaultPublicKey(
+	programId: PublicKey
+): Promise<PublicKey> {
+	return (await getInsuranceVaultPublicKeyAndNonce(programId))[0];
+}
+
+export async function getInsuranceVaultAuthorityPublicKeyAndNonce(
+	programId: PublicKey
+): Promise<[PublicKey, number]> {
+	return anchor.web3.PublicKey.findProgramAddress(
+		[(await getInsuranceVaultPublicKey(programId)).toBuffer()],
+		programId
+	);
+}
+
+export async function getInsuranceVaultAuthorityPublicKey(
+	programId: PublicKey
+): Promise<PublicKey> {
+	return (await getInsuranceVaultAuthorityPublicKeyAndNonce(programId))[0];
+}
@@ -8,22 +8,39 @@ import {
 import { FeeStructure, IWallet, OracleGuardRails, OracleSource } from './types';
 import { BN, Idl, Program, Provider } from '@project-serum/anchor';
 import * as anchor from '@project-serum/anchor';
-import { getClearingHouseStateAccountPublicKeyAndNonce } from './addresses';
+import {
+	getClearingHouseStateAccountPublicKeyAndNonce,
+	getCollateralVaultAuthorityPublicKey,
+	getCollateralVaultPublicKeyAndNonce,
+	getInsuranceVaultAuthorityPublicKey,
+	getInsuranceVaultPublicKeyAndNonce,
+} from './addresses';
 import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
 import { ClearingHouse } from './clearingHouse';
 import { PEG_PRECISION } from './constants/numericConstants';
 import clearingHouseIDL from './idl/clearing_house.json';
-import { DefaultClearingHouseAccountSubscriber } from './accounts/defaultClearingHouseAccountSubscriber';
+import {
+	AccountSubscriberFactories,
+	DefaultClearingHouseAccountSubscriber,
+} from './accounts/defaultClearingHouseAccountSubscriber';
 import { DefaultTxSender } from './tx/defaultTxSender';
 import { calculateTargetPriceTrade } from './math/trade';
 import { calculateAmmReservesAfterSwap, getSwapDirection } from './math/amm';
+import {
+	getCreateAccountIx,
+	getCreateAccountIxs,
+	RentCache,
+} from './util/rent';
 
 export class Admin extends ClearingHouse {
+	rentCache = new RentCache(this.connection);
+
 	public static from(
 		connection: Connection,
 		wallet: IWallet,
 		clearingHouseProgramId: PublicKey,
-		opts: ConfirmOptions = Provider.defaultOptions()
+		opts: ConfirmOptions = Provider.defaultOptions(),
+		accountSubscriberFactories: AccountSubscriberFactories = {}
 	): Admin {
 		const provider = new Provider(connection, wallet, opts);
 		const program = new Program(
@@ -32,7 +49,8 @@ export class Admin extends ClearingHouse {
 			provider
 		);
 		const accountSubscriber = new DefaultClearingHouseAccountSubscriber(
-			program
+			program,
+			accountSubscriberFactories
 		);
 		const txSender = new DefaultTxSender(provider);
 		return new Admin(
@@ -57,28 +75,17 @@ export class Admin extends ClearingHouse {
 		}
 
 		const [collateralVaultPublicKey, collateralVaultNonce] =
-			await PublicKey.findProgramAddress(
-				[Buffer.from(anchor.utils.bytes.utf8.encode('collateral_vault'))],
-				this.program.programId
-			);
+			await getCollateralVaultPublicKeyAndNonce(this.program.programId);
 
-		const [collateralVaultAuthority, _collateralVaultAuthorityNonce] =
-			await PublicKey.findProgramAddress(
-				[collateralVaultPublicKey.toBuffer()],
-				this.program.programId
-			);
+		const collateralVaultAuthority =
+			await getCollateralVaultAuthorityPublicKey(this.program.programId);
 
 		const [insuranceVaultPublicKey, insuranceVaultNonce] =
-			await PublicKey.findProgramAddress(
-				[Buffer.from(anchor.utils.bytes.utf8.encode('insurance_vault'))],
-				this.program.programId
-			);
+			await getInsuranceVaultPublicKeyAndNonce(this.program.programId);
 
-		const [insuranceVaultAuthority, _insuranceVaultAuthorityNonce] =
-			await PublicKey.findProgramAddress(
-				[insuranceVaultPublicKey.toBuffer()],
-				this.program.programId
-			);
+		const insuranceVaultAuthority = await getInsuranceVaultAuthorityPublicKey(
+			this.program.programId
+		);
 
 		const markets = anchor.web3.Keypair.generate();
 		const depositHistory = anchor.web3.Keypair.generate();
@@ -112,12 +119,18 @@ export class Admin extends ClearingHouse {
 					tokenProgram: TOKEN_PROGRAM_ID,
 				},
 				instructions: [
-					await this.program.account.markets.createInstruction(markets),
+					await getCreateAccountIx(
+						this.program,
+						this.rentCache,
+						'markets',
+						markets.publicKey,
+						this.wallet.publicKey
+					),
 				],
 			}
 		);
 
-		const initializeTxSig = await this.txSender.send(
+		const initializeTxSig = await this.sendTx(
 			initializeTx,
 			[markets],
 			this.opts
@@ -137,29 +150,22 @@ export class Admin extends ClearingHouse {
 					rent: SYSVAR_RENT_PUBKEY,
 					systemProgram: anchor.web3.SystemProgram.programId,
 				},
-				instructions: [
-					await this.program.account.fundingRateHistory.createInstruction(
-						fundingRateHistory
-					),
-					await this.program.account.fundingPaymentHistory.createInstruction(
-						fundingPaymentHistory
-					),
-					await this.program.account.tradeHistory.createInstruction(
-						tradeHistory
-					),
-					await this.program.account.liquidationHistory.createInstruction(
-						liquidationHistory
-					),
-					await this.program.account.depositHistory.createInstruction(
-						depositHistory
-					),
-					await this.program.account.curveHistory.createInstruction(
-						curveHistory
-					),
-				],
+				instructions: await getCreateAccountIxs(
+					this.program,
+					this.rentCache,
+					[
+						['fundingRateHistory', fundingRateHistory.publicKey],
+						['fundingPaymentHistory', fundingPaymentHistory.publicKey],
+						['tradeHistory', tradeHistory.publicKey],
+						['liquidationHistory', liquidationHistory.publicKey],
+						['depositHistory', depositHistory.publicKey],
+						['curveHistory', curveHistory.publicKey],
+					],
+					this.wallet.publicKey
+				),
 			});
 
-		const initializeHistoryTxSig = await this.txSender.send(
+		const initializeHistoryTxSig = await this.sendTx(
 			initializeHistoryTx,
 			[
 				depositHistory,
@@ -202,7 +208,7 @@ export class Admin extends ClearingHouse {
 				},
 			}
 		);
-		return await this.txSender.send(initializeMarketTx, [], this.opts);
+		return await this.sendTx(initializeMarketTx, [], this.opts);
 	}
 
 	public async moveAmmPrice(
@@ -0,0 +1,202 @@
+import { BN } from '@project-serum/anchor';
+import {
+	LAMPORTS_PER_SOL,
+	PublicKey,
+	TransactionSignature,
+} from '@solana/web3.js';
+import { ClearingHouse } from '../clearingHouse';
+import { ClearingHouseUser } from '../clearingHouseUser';
+import { QUOTE_PRECISION, TEN_THOUSAND } from '../constants/numericConstants';
+import { FundingCrank } from '../keepers/fundingCrank';
+import { LiquidationEvent } from '../history/liquidation';
+import { LiquidationCandidate, LiquidatorBot } from '../keepers/liquidatorBot';
+import { convertToNumber } from '../math/conversion';
+import { BalanceWatcher } from '../monitoring/balanceWatcher';
+import { HealthMonitor, HealthUpdate } from '../monitoring/healthMonitor';
+import { Alert, AlertSink } from './types';
+
+/**
+ * Alerts are best effort, a failing sink must not take down the component raising the alert
+ */
+function sendAlert(sink: AlertSink, alert: Omit<Alert, 'ts'>): void {
+	sink.send({ ...alert, ts: Date.now() }).catch(() => undefined);
+}
+
+/**
+ * Sends an alert for every health status change: warning, critical for danger and info on recovery
+ * @param healthMonitor
+ * @param sink
+ * @returns function detaching the sink
+ */
+export function attachHealthMonitorAlerts(
+	healthMonitor: HealthMonitor,
+	sink: AlertSink
+): () => void {
+	const describe = (update: HealthUpdate) =>
+		`User ${update.user.authority.toString()} margin ratio is ${(
+			convertToNumber(update.marginRatio, TEN_THOUSAND) * 100
+		).toFixed(2)}%`;
+
+	const onStatusChange = (update: HealthUpdate) => {
+		sendAlert(sink, {
+			severity:
+				update.status === 'danger'
+					? 'critical'
+					: update.status === 'warning'
+					? 'warning'
+					: 'info',
+			source: 'healthMonitor',
+			title: `Account health ${update.previousStatus} -> ${update.status}`,
+			message: describe(update),
+		});
+	};
+	const onError = (user: ClearingHouseUser, error: Error) => {
+		sendAlert(sink, {
+			severity: 'warning',
+			source: 'healthMonitor',
+			title: 'Health check failed',
+			message: `User ${user.authority.toString()}: ${error.message}`,
+		});
+	};
+
+	healthMonitor.eventEmitter.on('statusChange', onStatusChange);
+	healthMonitor.eventEmitter.on('error', onError);
+	return () => {
+		healthMonitor.eventEmitter.removeListener('statusChange', onStatusChange);
+		healthMonitor.eventEmitter.removeListener('error', onError);
+	};
+}
+
+/**
+ * Sends an info alert for every liquidation and a warning for every error
+ * @param liquidatorBot
+ * @param sink
+ * @returns function detaching the sink
+ */
+export function attachLiquidatorBotAlerts(
+	liquidatorBot: LiquidatorBot,
+	sink: AlertSink
+): () => void {
+	const onLiquidation = (
+		candidate: LiquidationCandidate,
+		signature: TransactionSignature
+	) => {
+		const expectedFee = convertToNumber(
+			candidate.expectedFeeToLiquidator,
+			QUOTE_PRECISION
+		);
+		sendAlert(sink, {
+			severity: 'info',
+			source: 'liquidatorBot',
+			title: `${candidate.full ? 'Full' : 'Partial'} liquidation sent`,
+			message: `User ${candidate.authority.toString()}, expected fee ${expectedFee}, tx ${signature}`,
+		});
+	};
+	const onError = (
+		candidate: LiquidationCandidate | undefined,
+		error: Error
+	) => {
+		sendAlert(sink, {
+			severity: 'warning',
+			source: 'liquidatorBot',
+			title: 'Liquidator error',
+			message: candidate
+				? `User ${candidate.authority.toString()}: ${error.message}`
+				: error.message,
+		});
+	};
+
+	liquidatorBot.eventEmitter.on('liquidation', onLiquidation);
+	liquidatorBot.eventEmitter.on('error', onError);
+	return () => {
+		liquidatorBot.eventEmitter.removeListener('liquidation', onLiquidation);
+		liquidatorBot.eventEmitter.removeListener('error', onError);
+	};
+}
+
+/**
+ * Sends a warning for every failed funding rate update
+ * @param fundingCrank
+ * @param sink
+ * @returns function detaching the sink
+ */
+export function attachFundingCrankAlerts(
+	fundingCrank: FundingCrank,
+	sink: AlertSink
+): () => void {
+	const onError = (marketIndex: BN | undefined, error: Error) => {
+		sendAlert(sink, {
+			severity: 'warning',
+			source: 'fundingCrank',
+			title: 'Funding rate update failed',
+			message: marketIndex
+				? `Market ${marketIndex.toString()}: ${error.message}`
+				: error.message,
+		});
+	};
+
+	fundingCrank.eventEmitter.on('error', onError);
+	return () => {
+		fundingCrank.eventEmitter.removeListener('error', onError);
+	};
+}
+
+/**
+ * Sends a critical alert for every liquidation of a user
+ * @param clearingHouse subscribed to the liquidation history
+ * @param user the user account, NOT the user's wallet address
+ * @param sink
+ * @returns function detaching the sink
+ */
+export function attachLiquidationAlerts(
+	clearingHouse: ClearingHouse,
+	user: PublicKey,
+	sink: AlertSink
+): () => void {
+	return clearingHouse.watchLiquidations(user, (event: LiquidationEvent) => {
+		const closed = convertToNumber(event.baseAssetValueClosed, QUOTE_PRECISION);
+		const fee = convertToNumber(event.liquidationFee, QUOTE_PRECISION);
+		sendAlert(sink, {
+			severity: 'critical',
+			source: 'liquidationWatcher',
+			title: `${event.type === 'full' ? 'Full' : 'Partial'} liquidation`,
+			message: `User ${user.toString()}, closed ${closed}, fee ${fee}`,
+		});
+	});
+}
+
+/**
+ * Sends a critical alert when the watched balance drops below the threshold and an info alert when it recovers
+ * @param balanceWatcher
+ * @param sink
+ * @returns function detaching the sink
+ */
+export function attachBalanceWatcherAlerts(
+	balanceWatcher: BalanceWatcher,
+	sink: AlertSink
+): () => void {
+	const account = balanceWatcher.publicKey.toString();
+	const onLowBalance = (balanceLamports: number, thresholdLamports: number) => {
+		sendAlert(sink, {
+			severity: 'critical',
+			source: 'balanceWatcher',
+			title: 'Low sol balance',
+			message: `${account} has ${balanceLamports / LAMPORTS_PER_SOL} sol, below ${thresholdLamports / LAMPORTS_PER_SOL}`,
+		});
+	};
+	const onRecovered = (balanceLamports: number) => {
+		sendAlert(sink, {
+			severity: 'info',
+			source: 'balanceWatcher',
+			title: 'Sol balance recovered',
+			message: `${account} has ${balanceLamports / LAMPORTS_PER_SOL} sol`,
+		});
+	};
+
+	balanceWatcher.eventEmitter.on('lowBalance', onLowBalance);
+	balanceWatcher.eventEmitter.on('recovered', onRecovered);
+	return () => {
+		balanceWatcher.eventEmitter.removeListener('lowBalance', onLowBalance);
+		balanceWatcher.eventEmitter.removeListener('recovered', onRecovered);
+	};
+}
@@ -0,0 +1,18 @@
+export type AlertSeverity = 'info' | 'warning' | 'critical';
+
+export type Alert = {
+	severity: AlertSeverity;
+	/** component that raised the alert, e.g. healthMonitor */
+	source: string;
+	title: string;
+	message: string;
+	/** unix timestamp in ms */
+	ts: number;
+};
+
+/**
+ * Destination for operational alerts. Implement it to route alerts to anything besides the built-in webhook sink
+ */
+export interface AlertSink {
+	send(alert: Alert): Promise<void>;
+}
@@ -0,0 +1,89 @@
+import http from 'http';
+import https from 'https';
+import { Alert, AlertSink } from './types';
+
+export type WebhookBodyFormatter = (alert: Alert) => unknown;
+
+/**
+ * Posts the alert as is
+ */
+export const formatJsonBody: WebhookBodyFormatter = (alert) => alert;
+
+/**
+ * Body for a Slack incoming webhook
+ */
+export const formatSlackBody: WebhookBodyFormatter = (alert) => ({
+	text: `*[${alert.severity.toUpperCase()}] ${alert.title}*\n${alert.message}`,
+});
+
+/**
+ * Body for a Discord webhook
+ */
+export const formatDiscordBody: WebhookBodyFormatter = (alert) => ({
+	content: `**[${alert.severity.toUpperCase()}] ${alert.title}**\n${
+		alert.message
+	}`,
+});
+
+export type WebhookAlertSinkConfig = {
+	url: string;
+	formatBody: WebhookBodyFormatter;
+	headers: Record<string, string>;
+	timeoutMs: number;
+};
+
+/**
+ * # WebhookAlertSink
+ * Posts alerts as JSON to an HTTP(S) endpoint. Use formatSlackBody or formatDiscordBody to post to Slack or Discord webhooks
+ */
+export class WebhookAlertSink implements AlertSink {
+	config: WebhookAlertSinkConfig;
+
+	public constructor(
+		config: Partial<WebhookAlertSinkConfig> &
+			Pick<WebhookAlertSinkConfig, 'url'>
+	) {
+		this.config = {
+			formatBody: formatJsonBody,
+			headers: {},
+			timeoutMs: 5000,
+			...config,
+		};
+	}
+
+	send(alert: Alert): Promise<void> {
+		const body = JSON.stringify(this.config.formatBody(alert));
+		const url = new URL(this.config.url);
+		const request = url.protocol === 'http:' ? http.request : https.request;
+
+		return new Promise((resolve, reject) => {
+			const req = request(
+				url,
+				{
+					method: 'POST',
+					headers: {
+						'Content-Type': 'application/json',
+						'Content-Length': Buffer.byteLength(body),
+						...this.config.headers,
+					},
+					timeout: this.config.timeoutMs,
+				},
+				(res) => {
+					res.resume();
+					if (res.statusCode >= 200 && res.statusCode < 300) {
+						resolve();
+					} else {
+						reject(
+							new Error(`Webhook responded with status ${res.statusCode}`)
+						);
+					}
+				}
+			);
+			req.on('timeout', () => {
+				req.destroy(new Error('Webhook request timed out'));
+			});
+			req.on('error', reject);
+			req.end(body);
+		});
+	}
+}
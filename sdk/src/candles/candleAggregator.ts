@@ -0,0 +1,198 @@
+import { BN } from '@project-serum/anchor';
+import { EventEmitter } from 'events';
+import StrictEventEmitter from 'strict-event-emitter-types';
+import { ClearingHouse } from '../clearingHouse';
+import { ZERO } from '../constants/numericConstants';
+import { calculateTradeRecordPrice } from '../history/trade';
+import { getOrderedRecords } from '../history/utils';
+import {
+	toBaseAssetAmount,
+	toPrice,
+	toQuoteAssetAmount,
+} from '../math/conversion';
+import { TradeHistoryAccount, TradeRecord } from '../types';
+import {
+	Candle,
+	CandleEvents,
+	CandleResolution,
+	CANDLE_RESOLUTION_SECONDS,
+} from './types';
+
+/**
+ * # CandleAggregator
+ * Builds per-market OHLCV bars from trade records. Bars are priced at each trade's average fill price.
+ *
+ * Feed it trade records directly with {@link addTradeRecords} (e.g. backfilled history), or {@link subscribe} it to a ClearingHouse
+ * subscribed to the trade history account. Listen to `candleUpdate` for every change to the current bar and `candleClose` for bars that
+ * won't change again.
+ */
+export class CandleAggregator {
+	resolutions: CandleResolution[];
+	maxCandles: number;
+	lastRecordId = ZERO;
+	eventEmitter: StrictEventEmitter<EventEmitter, CandleEvents>;
+
+	private candles = new Map<string, Candle[]>();
+	private clearingHouse?: ClearingHouse;
+	private onTradeHistoryUpdate?: (payload: TradeHistoryAccount) => void;
+
+	/**
+	 * @param resolutions bar intervals to maintain
+	 * @param maxCandles bars kept per market and resolution, oldest are dropped first
+	 */
+	public constructor(
+		resolutions: CandleResolution[] = ['1m', '5m', '1h'],
+		maxCandles = 1000
+	) {
+		this.resolutions = resolutions;
+		this.maxCandles = maxCandles;
+		this.eventEmitter = new EventEmitter();
+	}
+
+	/**
+	 * Ingests the trade history already loaded by the clearing house and every update after it
+	 * @param clearingHouse must be subscribed with the 'tradeHistoryAccount' subscription
+	 */
+	public subscribe(clearingHouse: ClearingHouse): void {
+		if (this.clearingHouse) {
+			return;
+		}
+
+		this.clearingHouse = clearingHouse;
+		this.onTradeHistoryUpdate = (tradeHistoryAccount) => {
+			this.addTradeRecords(tradeHistoryAccount.tradeRecords);
+		};
+		this.addTradeRecords(clearingHouse.getTradeHistoryAccount().tradeRecords);
+		clearingHouse.eventEmitter.on(
+			'tradeHistoryAccountUpdate',
+			this.onTradeHistoryUpdate
+		);
+	}
+
+	public unsubscribe(): void {
+		if (!this.clearingHouse) {
+			return;
+		}
+
+		this.clearingHouse.eventEmitter.removeListener(
+			'tradeHistoryAccountUpdate',
+			this.onTradeHistoryUpdate
+		);
+		this.clearingHouse = undefined;
+		this.onTradeHistoryUpdate = undefined;
+	}
+
+	/**
+	 * Adds trade records to the bars. Records at or below the last processed record id are ignored, so the same
+	 * ring buffer can be passed in repeatedly
+	 * @param tradeRecords
+	 */
+	public addTradeRecords(tradeRecords: TradeRecord[]): void {
+		for (const record of getOrderedRecords(tradeRecords)) {
+			if (record.recordId.lte(this.lastRecordId)) {
+				continue;
+			}
+			this.lastRecordId = record.recordId;
+
+			for (const resolution of this.resolutions) {
+				this.addTradeRecordToCandles(record, resolution);
+			}
+		}
+	}
+
+	/**
+	 * @param marketIndex
+	 * @param resolution
+	 * @param startTs inclusive, unbounded if undefined
+	 * @param endTs exclusive, unbounded if undefined
+	 * @returns bars ordered oldest to newest. Intervals without trades have no bar
+	 */
+	public getCandles(
+		marketIndex: BN,
+		resolution: CandleResolution,
+		startTs?: number,
+		endTs?: number
+	): Candle[] {
+		const candles = this.candles.get(this.getKey(marketIndex, resolution));
+		if (!candles) {
+			return [];
+		}
+
+		return candles.filter(
+			(candle) =>
+				(startTs === undefined || candle.start >= startTs) &&
+				(endTs === undefined || candle.start < endTs)
+		);
+	}
+
+	/**
+	 * @param marketIndex
+	 * @param resolution
+	 * @returns the most recent bar, undefined if the market hasn't traded
+	 */
+	public getLatestCandle(
+		marketIndex: BN,
+		resolution: CandleResolution
+	): Candle | undefined {
+		const candles = this.candles.get(this.getKey(marketIndex, resolution));
+		return candles ? candles[candles.length - 1] : undefined;
+	}
+
+	private addTradeRecordToCandles(
+		record: TradeRecord,
+		resolution: CandleResolution
+	): void {
+		const key = this.getKey(record.marketIndex, resolution);
+		let candles = this.candles.get(key);
+		if (!candles) {
+			candles = [];
+			this.candles.set(key, candles);
+		}
+
+		const resolutionSeconds = CANDLE_RESOLUTION_SECONDS[resolution];
+		const ts = record.ts.toNumber();
+		const start = ts - (ts % resolutionSeconds);
+		const price = calculateTradeRecordPrice(record);
+
+		let candle = candles[candles.length - 1];
+		if (!candle || candle.start < start) {
+			if (candle) {
+				this.eventEmitter.emit('candleClose', candle);
+			}
+
+			candle = {
+				marketIndex: record.marketIndex,
+				resolution,
+				start,
+				open: price,
+				high: price,
+				low: price,
+				close: price,
+				quoteVolume: toQuoteAssetAmount(ZERO),
+				baseVolume: toBaseAssetAmount(ZERO),
+				tradeCount: 0,
+			};
+			candles.push(candle);
+			if (candles.length > this.maxCandles) {
+				candles.shift();
+			}
+		}
+
+		candle.high = toPrice(BN.max(candle.high, price));
+		candle.low = toPrice(BN.min(candle.low, price));
+		candle.close = price;
+		candle.quoteVolume = toQuoteAssetAmount(
+			candle.quoteVolume.add(record.quoteAssetAmount)
+		);
+		candle.baseVolume = toBaseAssetAmount(
+			candle.baseVolume.add(record.baseAssetAmount)
+		);
+		candle.tradeCount++;
+
+		this.eventEmitter.emit('candleUpdate', candle);
+	}
+
+	private getKey(marketIndex: BN, resolution: CandleResolution): string {
+		return `${marketIndex.toString()}-${resolution}`;
+	}
+}
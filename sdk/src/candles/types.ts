@@ -0,0 +1,38 @@
+import { BN } from '@project-serum/anchor';
+import { BaseAssetAmount, Price, QuoteAssetAmount } from '../types';
+
+export type CandleResolution = '1m' | '5m' | '15m' | '1h' | '4h' | '1d';
+
+export const CANDLE_RESOLUTION_SECONDS: Record<CandleResolution, number> = {
+	'1m': 60,
+	'5m': 5 * 60,
+	'15m': 15 * 60,
+	'1h': 60 * 60,
+	'4h': 4 * 60 * 60,
+	'1d': 24 * 60 * 60,
+};
+
+export type Candle = {
+	marketIndex: BN;
+	resolution: CandleResolution;
+	/** unix timestamp in seconds the bar opens at */
+	start: number;
+	/** Precision MARK_PRICE_PRECISION */
+	open: Price;
+	/** Precision MARK_PRICE_PRECISION */
+	high: Price;
+	/** Precision MARK_PRICE_PRECISION */
+	low: Price;
+	/** Precision MARK_PRICE_PRECISION */
+	close: Price;
+	/** Precision QUOTE_PRECISION */
+	quoteVolume: QuoteAssetAmount;
+	/** Precision AMM_RESERVE_PRECISION */
+	baseVolume: BaseAssetAmount;
+	tradeCount: number;
+};
+
+export interface CandleEvents {
+	candleUpdate: (candle: Candle) => void;
+	candleClose: (candle: Candle) => void;
+}
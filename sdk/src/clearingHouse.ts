@@ -1,6 +1,7 @@
 import { BN, Idl, Program, Provider } from '@project-serum/anchor';
 import {
 	ASSOCIATED_TOKEN_PROGRAM_ID,
+	NATIVE_MINT,
 	Token,
 	TOKEN_PROGRAM_ID,
 } from '@solana/spl-token';
@@ -17,6 +18,14 @@ import {
 	TradeHistoryAccount,
 	UserAccount,
 	Market,
+	CollateralAmount,
+	QuoteAssetAmount,
+	Price,
+	MarginCategory,
+	MarginRequirements,
+	MarketStats,
+	FundingDashboardEntry,
+	OpenPositionOptions,
 } from './types';
 import * as anchor from '@project-serum/anchor';
 import clearingHouseIDL from './idl/clearing_house.json';
@@ -29,6 +38,7 @@ import {
 	ConfirmOptions,
 	Transaction,
 	TransactionInstruction,
+	Signer,
 } from '@solana/web3.js';
 
 import { MockUSDCFaucet } from './mockUSDCFaucet';
@@ -44,10 +54,72 @@ import {
 	ClearingHouseAccountEvents,
 	ClearingHouseAccountTypes,
 } from './accounts/types';
-import { DefaultClearingHouseAccountSubscriber } from './accounts/defaultClearingHouseAccountSubscriber';
-import { TxSender } from './tx/types';
+import {
+	AccountSubscriberFactories,
+	DefaultClearingHouseAccountSubscriber,
+} from './accounts/defaultClearingHouseAccountSubscriber';
+import {
+	fetchNewRecords,
+	HistoryAccountName,
+	HistoryAccountView,
+} from './accounts/historyAccountView';
+import {
+	getMarketLayout,
+	getMarketOffset,
+} from './accounts/marketsAccountView';
+import { TxContext, TxObserver, TxSender } from './tx/types';
 import { DefaultTxSender } from './tx/defaultTxSender';
+import { assertSufficientSolForTx } from './tx/feePreflight';
+import { partiallySignTx } from './tx/partialSign';
 import { wrapInTx } from './tx/utils';
+import { TEN_THOUSAND, ZERO } from './constants/numericConstants';
+import { calculateMarketStats, calculateMarkPrice } from './math/market';
+import { calculateFundingDashboardEntry } from './math/funding';
+import { convertToNumber, toPrice } from './math/conversion';
+import {
+	calculateMarkOracleSpreadPct,
+	calculateOracleValidity,
+	isOracleMarkTooDivergent,
+} from './math/oracle';
+import { calculateTradeSlippage } from './math/trade';
+import {
+	ExchangePausedError,
+	NotWhitelistedError,
+	OracleInvalidError,
+	UserAccountNotFoundError,
+} from './errors';
+import { parsePriceData } from '@pythnetwork/client';
+import { ClearingHouseUser } from './clearingHouseUser';
+import { fillClosePosition, fillOpenPosition } from './math/fill';
+import { SdkMetrics } from './metrics/sdkMetrics';
+import { resolveReferrer } from './referral';
+import { fetchAccountSlice } from './util/accountSlice';
+import { ChainClock } from './util/clock';
+import {
+	estimatePriorityFee,
+	getComputeUnitLimitIx,
+	hasComputeUnitLimitIx,
+} from './util/computeBudget';
+import {
+	MAX_COMPUTE_UNIT_LIMIT,
+	simulateComputeUnits,
+} from './util/computeUnits';
+import { getMemoIx } from './util/memo';
+import {
+	fetchTokenAccounts,
+	findTokenAccounts,
+	getOrCreateAssociatedTokenAccount,
+	getTokenAccountBalance,
+	TokenAccount,
+	TokenAccountCandidate,
+} from './util/token';
+import { getTemporaryWrappedSolAccount } from './util/wrappedSol';
+import { CurveEvent, getCurveEvents } from './history/curve';
+import { getLatestRecordId } from './history/utils';
+import {
+	getLiquidationEventsForUser,
+	LiquidationEvent,
+} from './history/liquidation';
 
 /**
  * # ClearingHouse
@@ -66,12 +138,38 @@ export class ClearingHouse {
 	eventEmitter: StrictEventEmitter<EventEmitter, ClearingHouseAccountEvents>;
 	isSubscribed = false;
 	txSender: TxSender;
+	/** refetch the markets account and the {@link refreshUser}'s accounts in the background after every transaction, see {@link enableRefreshAfterTx} */
+	refreshAccountsAfterTx = false;
+	refreshUser?: ClearingHouseUser;
+	/** user whose cached accounts get the expected fills of trades as soon as they confirm, see {@link enableOptimisticUpdates} */
+	optimisticUser?: ClearingHouseUser;
+	/** set by {@link SdkMetrics.instrumentClearingHouse} */
+	metrics?: SdkMetrics;
+	txObservers: TxObserver[] = [];
+	/** appended as an SPL Memo instruction to every transaction sent through the clearing house, e.g. a client name and version or an order id */
+	memo?: string;
+	/** check the fee payer's sol covers the fees and rent of every transaction before sending it, see {@link assertSufficientSolForTx} */
+	feePreflight = false;
+	/** user account passed as the referrer of trades that don't set one, see {@link setDefaultReferrer} */
+	defaultReferrer?: PublicKey;
+	/** if set, every transaction is simulated first and requests the compute units it consumed times this margin, e.g. 1.2 */
+	computeUnitMargin?: number;
+	/** serves {@link getChainTime} and {@link getChainSlot} */
+	chainClock: ChainClock;
+	/** how long token accounts read through {@link getTokenAccount} are cached, 0 to always fetch */
+	tokenAccountCacheMs = 1000;
+
+	private tokenAccountCache = new Map<
+		string,
+		{ tokenAccount: TokenAccount; fetchedTs: number }
+	>();
 
 	public static from(
 		connection: Connection,
 		wallet: IWallet,
 		clearingHouseProgramId: PublicKey,
-		opts: ConfirmOptions = Provider.defaultOptions()
+		opts: ConfirmOptions = Provider.defaultOptions(),
+		accountSubscriberFactories: AccountSubscriberFactories = {}
 	): ClearingHouse {
 		const provider = new Provider(connection, wallet, opts);
 		const program = new Program(
@@ -80,7 +178,8 @@ export class ClearingHouse {
 			provider
 		);
 		const accountSubscriber = new DefaultClearingHouseAccountSubscriber(
-			program
+			program,
+			accountSubscriberFactories
 		);
 		const txSender = new DefaultTxSender(provider);
 		return new ClearingHouse(
@@ -93,6 +192,24 @@ export class ClearingHouse {
 		);
 	}
 
+	/**
+	 * Builds a ClearingHouse on an existing anchor Program of the clearing house, sharing its provider's connection, wallet and
+	 * confirm options. Lets anchor's program.rpc, program.instruction and program.account builders cover anything the sdk doesn't yet
+	 * @param program
+	 * @returns clearingHouse
+	 */
+	public static fromProgram(program: Program): ClearingHouse {
+		const provider = program.provider;
+		return new ClearingHouse(
+			provider.connection,
+			provider.wallet,
+			program,
+			new DefaultClearingHouseAccountSubscriber(program),
+			new DefaultTxSender(provider),
+			provider.opts
+		);
+	}
+
 	public constructor(
 		connection: Connection,
 		wallet: IWallet,
@@ -108,6 +225,7 @@ export class ClearingHouse {
 		this.accountSubscriber = accountSubscriber;
 		this.eventEmitter = this.accountSubscriber.eventEmitter;
 		this.txSender = txSender;
+		this.chainClock = new ChainClock(connection);
 	}
 
 	/**
@@ -165,10 +283,57 @@ export class ClearingHouse {
 		return this.statePublicKey;
 	}
 
+	public isExchangePaused(): boolean {
+		return this.getStateAccount().exchangePaused;
+	}
+
+	/**
+	 * @throws ExchangePausedError for instructions the program rejects while the exchange is paused
+	 */
+	private assertExchangeNotPaused(): void {
+		if (this.isExchangePaused()) {
+			throw new ExchangePausedError('Exchange is paused');
+		}
+	}
+
 	public getStateAccount(): StateAccount {
 		return this.accountSubscriber.getStateAccount();
 	}
 
+	/**
+	 * @returns the margin ratios for opening positions (initial), partial liquidation and full liquidation (maintenance) : Precision TEN_THOUSAND
+	 */
+	public getMarginRequirements(): MarginRequirements {
+		const state = this.getStateAccount();
+		return {
+			initial: state.marginRatioInitial,
+			partial: state.marginRatioPartial,
+			maintenance: state.marginRatioMaintenance,
+		};
+	}
+
+	/**
+	 * calculates the max leverage allowed before hitting a margin requirement, e.g. 50000 (5x) for an initial margin ratio of 2000 (20%)
+	 * @param category defaults to Initial
+	 * @returns : Precision TEN_THOUSAND
+	 */
+	public getMaxLeverage(category: MarginCategory = 'Initial'): BN {
+		const marginRequirements = this.getMarginRequirements();
+		let marginRatio: BN;
+		switch (category) {
+			case 'Partial':
+				marginRatio = marginRequirements.partial;
+				break;
+			case 'Maintenance':
+				marginRatio = marginRequirements.maintenance;
+				break;
+			default:
+				marginRatio = marginRequirements.initial;
+				break;
+		}
+		return TEN_THOUSAND.mul(TEN_THOUSAND).div(marginRatio);
+	}
+
 	public getMarketsAccount(): MarketsAccount {
 		return this.accountSubscriber.getMarketsAccount();
 	}
@@ -180,6 +345,122 @@ export class ClearingHouse {
 		return this.getMarketsAccount().markets[marketIndex];
 	}
 
+	/**
+	 * Summarizes open interest, fees and funding for every initialized market
+	 * @returns marketStats
+	 */
+	public getMarketsStats(): MarketStats[] {
+		return this.getMarketsAccount().markets.reduce(
+			(marketsStats, market, marketIndex) => {
+				if (market.initialized) {
+					marketsStats.push(
+						calculateMarketStats(market, new BN(marketIndex))
+					);
+				}
+				return marketsStats;
+			},
+			[] as MarketStats[]
+		);
+	}
+
+	/**
+	 * Current and predicted funding for every initialized market, fetching all oracles in a single request.
+	 * Sorted by the absolute predicted annualized rate, largest first, so funding arbitrage targets come first.
+	 * Markets whose oracle account doesn't exist are left out
+	 * @returns fundingDashboard
+	 */
+	public async fetchFundingDashboard(): Promise<FundingDashboardEntry[]> {
+		const marketIndexes = this.getMarketsAccount()
+			.markets.map((market, marketIndex) =>
+				market.initialized ? marketIndex : undefined
+			)
+			.filter((marketIndex) => marketIndex !== undefined);
+		const oracleAccounts = await this.connection.getMultipleAccountsInfo(
+			marketIndexes.map((marketIndex) => this.getMarket(marketIndex).amm.oracle)
+		);
+
+		return marketIndexes
+			.map((marketIndex, i) =>
+				oracleAccounts[i] === null
+					? undefined
+					: calculateFundingDashboardEntry(
+							this.getMarket(marketIndex),
+							new BN(marketIndex),
+							toPrice(parsePriceData(oracleAccounts[i].data).price)
+					  )
+			)
+			.filter((entry) => entry !== undefined)
+			.sort(
+				(a, b) =>
+					Math.abs(b.predictedFundingRateAnnualizedPct) -
+					Math.abs(a.predictedFundingRateAnnualizedPct)
+			);
+	}
+
+	/**
+	 * Fetches a single market from the rpc, requesting only its bytes of the markets account rather than all 64 markets.
+	 * For bots polling one market without a subscription
+	 * @param marketIndex
+	 */
+	public async fetchMarket(marketIndex: BN | number): Promise<Market> {
+		if (marketIndex instanceof BN) {
+			marketIndex = marketIndex.toNumber();
+		}
+
+		const layout = getMarketLayout(this.program.idl);
+		const data = await fetchAccountSlice(
+			this.connection,
+			this.getStateAccount().markets,
+			getMarketOffset(this.program.idl, marketIndex),
+			layout.size,
+			this.opts?.commitment
+		);
+		if (data === null) {
+			throw new Error('Markets account not found');
+		}
+		return layout.read(data, 0) as Market;
+	}
+
+	/**
+	 * Fetches a history account without decoding it, for reading a few records or only the new ones, see {@link HistoryAccountView}.
+	 * Pass the view of a previous call to fetch only the head and the records written since into it, instead of the whole account
+	 * @param accountName
+	 * @param view a view of the same account to update in place
+	 */
+	public async fetchHistoryAccountView<N extends HistoryAccountName>(
+		accountName: N,
+		view?: HistoryAccountView<N>
+	): Promise<HistoryAccountView<N>> {
+		const state = this.getStateAccount();
+		const publicKey = {
+			TradeHistory: state.tradeHistory,
+			DepositHistory: state.depositHistory,
+			FundingPaymentHistory: state.fundingPaymentHistory,
+			FundingRateHistory: state.fundingRateHistory,
+			LiquidationHistory: state.liquidationHistory,
+			CurveHistory: state.curveHistory,
+		}[accountName];
+		if (view) {
+			await fetchNewRecords(
+				this.connection,
+				publicKey,
+				view,
+				this.opts?.commitment
+			);
+			return view;
+		}
+
+		const accountInfo = await this.connection.getAccountInfo(
+			publicKey,
+			this.opts?.commitment
+		);
+		return new HistoryAccountView(
+			this.program.idl,
+			accountName,
+			accountInfo.data
+		);
+	}
+
 	public getFundingPaymentHistoryAccount(): FundingPaymentHistoryAccount {
 		return this.accountSubscriber.getFundingPaymentHistoryAccount();
 	}
@@ -196,6 +477,44 @@ export class ClearingHouse {
 		return this.accountSubscriber.getLiquidationHistoryAccount();
 	}
 
+	/**
+	 * Calls back with each liquidation of a user as the liquidation history streams in, e.g. for margin call notifications.
+	 * Requires the liquidation history subscription. Only liquidations recorded after the call are delivered
+	 * @param user the user account, NOT the user's wallet address
+	 * @param callback
+	 * @returns function stopping the callbacks
+	 */
+	public watchLiquidations(
+		user: PublicKey,
+		callback: (event: LiquidationEvent) => void
+	): () => void {
+		let lastRecordId = getLatestRecordId(
+			this.getLiquidationHistoryAccount().liquidationRecords
+		);
+		const listener = (liquidationHistoryAccount: LiquidationHistoryAccount) => {
+			const events = getLiquidationEventsForUser(
+				liquidationHistoryAccount,
+				user,
+				lastRecordId
+			);
+			lastRecordId = BN.max(
+				lastRecordId,
+				getLatestRecordId(liquidationHistoryAccount.liquidationRecords)
+			);
+			for (const event of events) {
+				callback(event);
+			}
+		};
+
+		this.eventEmitter.on('liquidationHistoryAccountUpdate', listener);
+		return () => {
+			this.eventEmitter.removeListener(
+				'liquidationHistoryAccountUpdate',
+				listener
+			);
+		};
+	}
+
 	public getDepositHistoryAccount(): DepositHistoryAccount {
 		return this.accountSubscriber.getDepositHistoryAccount();
 	}
@@ -204,6 +523,32 @@ export class ClearingHouse {
 		return this.accountSubscriber.getCurveHistoryAccount();
 	}
 
+	/**
+	 * Calls back with each repeg and k update as the curve history streams in, e.g. to alert on unexpected curve changes.
+	 * Requires the curve history subscription. Only curve changes recorded after the call are delivered
+	 * @param callback
+	 * @returns function stopping the callbacks
+	 */
+	public onCurveEvent(callback: (event: CurveEvent) => void): () => void {
+		let lastRecordId = getLatestRecordId(
+			this.getCurveHistoryAccount().depositRecords
+		);
+		const listener = (curveHistoryAccount: CurveHistoryAccount) => {
+			const events = getCurveEvents(curveHistoryAccount, lastRecordId);
+			if (events.length > 0) {
+				lastRecordId = events[events.length - 1].recordId;
+			}
+			for (const event of events) {
+				callback(event);
+			}
+		};
+
+		this.eventEmitter.on('curveHistoryAccountUpdate', listener);
+		return () => {
+			this.eventEmitter.removeListener('curveHistoryAccountUpdate', listener);
+		};
+	}
+
 	/**
 	 * Update the wallet to use for clearing house transactions and linked user account
 	 * @param newWallet
@@ -222,6 +567,7 @@ export class ClearingHouse {
 		this.program = newProgram;
 		this.txSender = newTxSender;
 		this.userAccountPublicKey = undefined;
+		this.userPositionsPublicKey = undefined;
 		this.userAccount = undefined;
 	}
 
@@ -235,7 +581,7 @@ export class ClearingHouse {
 		] = await this.getInitializeUserInstructions();
 
 		const tx = new Transaction().add(initializeUserAccountIx);
-		const txSig = await this.txSender.send(
+		const txSig = await this.sendTx(
 			tx,
 			[userPositionsAccount],
 			this.opts
@@ -258,7 +604,8 @@ export class ClearingHouse {
 		};
 
 		const state = this.getStateAccount();
-		if (state.whitelistMint) {
+		// the program stores the default public key when there's no whitelist
+		if (!state.whitelistMint.equals(PublicKey.default)) {
 			optionalAccounts.whitelistToken = true;
 			const associatedTokenPublicKey = await Token.getAssociatedTokenAddress(
 				ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -266,6 +613,20 @@ export class ClearingHouse {
 				state.whitelistMint,
 				this.wallet.publicKey
 			);
+			const whitelistTokenBalance = await getTokenAccountBalance(
+				this.connection,
+				associatedTokenPublicKey
+			);
+			if (
+				whitelistTokenBalance === undefined ||
+				whitelistTokenBalance.eq(ZERO)
+			) {
+				throw new NotWhitelistedError(
+					this.wallet.publicKey,
+					state.whitelistMint,
+					associatedTokenPublicKey
+				);
+			}
 			remainingAccounts.push({
 				pubkey: associatedTokenPublicKey,
 				isWritable: false,
@@ -293,12 +654,192 @@ export class ClearingHouse {
 		return [userPositions, userPublicKey, initializeUserAccountIx];
 	}
 
+	/**
+	 * Appends the memo if set, sizes the compute unit limit if {@link computeUnitMargin} is set and sends through the txSender,
+	 * then drops the cached user and token accounts and refetches the subscribed accounts so reads right after the transaction reflect it instead of waiting for the websocket update
+	 */
+	protected async sendTx(
+		tx: Transaction,
+		additionalSigners: Array<Signer> = [],
+		opts?: ConfirmOptions
+	): Promise<TransactionSignature> {
+		if (this.memo) {
+			tx.add(getMemoIx(this.memo));
+		}
+		if (this.computeUnitMargin && !hasComputeUnitLimitIx(tx)) {
+			const unitsConsumed = await simulateComputeUnits(
+				this.connection,
+				tx,
+				this.wallet.publicKey
+			);
+			tx.instructions.unshift(
+				getComputeUnitLimitIx(
+					Math.min(
+						Math.ceil(unitsConsumed * this.computeUnitMargin),
+						MAX_COMPUTE_UNIT_LIMIT
+					)
+				)
+			);
+		}
+		if (this.feePreflight) {
+			await assertSufficientSolForTx(
+				this.connection,
+				tx,
+				this.wallet.publicKey
+			);
+		}
+
+		const ctx: TxContext = { tx, additionalSigners, opts, builtTs: Date.now() };
+		this.notifyTxObservers((observer) => observer.onBuilt?.(ctx));
+
+		let txSig: TransactionSignature;
+		try {
+			this.notifyTxObservers((observer) => observer.onSent?.(ctx));
+			txSig = await this.txSender.send(tx, additionalSigners, opts);
+		} catch (e) {
+			const durationMs = Date.now() - ctx.builtTs;
+			this.notifyTxObservers((observer) =>
+				observer.onFailed?.(ctx, e, durationMs)
+			);
+			throw e;
+		}
+		const durationMs = Date.now() - ctx.builtTs;
+		this.notifyTxObservers((observer) =>
+			observer.onConfirmed?.(ctx, txSig, durationMs)
+		);
+
+		this.userAccount = undefined;
+		this.tokenAccountCache.clear();
+		if (this.refreshAccountsAfterTx) {
+			this.refreshAccounts();
+		}
+
+		return txSig;
+	}
+
+	/**
+	 * Refetches the accounts a transaction changes without blocking it. The history accounts are left to their subscriptions,
+	 * they're hundreds of KB each
+	 */
+	private refreshAccounts(): void {
+		const fetches: Promise<void>[] = [];
+		if (this.isSubscribed && this.accountSubscriber.fetchMarketsAccount) {
+			fetches.push(this.accountSubscriber.fetchMarketsAccount());
+		}
+		if (this.refreshUser?.isSubscribed) {
+			fetches.push(this.refreshUser.fetchAccounts());
+		}
+		Promise.all(fetches).catch(() => {
+			// the subscriptions catch up on their own, the transaction itself succeeded
+		});
+	}
+
+	/**
+	 * Builds a transaction for signers that aren't at hand, e.g. the members of a multisig, instead of sending it. Countersign it
+	 * with {@link countersignTx} and send it with {@link sendCountersignedTx}. The memo is appended, but observers aren't notified
+	 * and cached accounts aren't refreshed, since the clearing house doesn't see the transaction land
+	 * @param tx
+	 * @param absentSigners signers expected to countersign later
+	 * @param additionalSigners signers at hand besides the wallet
+	 * @returns the partially signed transaction
+	 */
+	public async getPartiallySignedTx(
+		tx: Transaction,
+		absentSigners: PublicKey[],
+		additionalSigners: Array<Signer> = []
+	): Promise<Transaction> {
+		if (this.memo) {
+			tx.add(getMemoIx(this.memo));
+		}
+		return await partiallySignTx(
+			this.connection,
+			this.wallet,
+			tx,
+			additionalSigners,
+			absentSigners
+		);
+	}
+
+	/**
+	 * Registers hooks called for every transaction sent through this clearing house
+	 * @param observer
+	 * @returns function removing the observer
+	 */
+	public addTxObserver(observer: TxObserver): () => void {
+		this.txObservers.push(observer);
+		return () => this.removeTxObserver(observer);
+	}
+
+	public removeTxObserver(observer: TxObserver): void {
+		this.txObservers = this.txObservers.filter(
+			(registered) => registered !== observer
+		);
+	}
+
+	private notifyTxObservers(notify: (observer: TxObserver) => void): void {
+		for (const observer of this.txObservers) {
+			try {
+				notify(observer);
+			} catch (e) {
+				// observers must not fail the transaction
+			}
+		}
+	}
+
+	/**
+	 * Opt in to applying the expected position and collateral changes of trades sent through this clearing house to the user's
+	 * cached accounts as soon as they confirm, so reads reflect a trade before the user's next account update arrives.
+	 * @param user the wallet's ClearingHouseUser, must be subscribed
+	 */
+	public enableOptimisticUpdates(user: ClearingHouseUser): void {
+		if (!user.authority.equals(this.wallet.publicKey)) {
+			throw new Error('Optimistic updates only apply to the wallet\'s user');
+		}
+		this.optimisticUser = user;
+	}
+
+	public disableOptimisticUpdates(): void {
+		this.optimisticUser?.clearOptimisticFills();
+		this.optimisticUser = undefined;
+	}
+
+	/**
+	 * @returns the wallet's ClearingHouseUser, if one was passed to {@link enableOptimisticUpdates} or {@link enableRefreshAfterTx}
+	 */
+	private getAttachedUser(): ClearingHouseUser | undefined {
+		if (this.optimisticUser) {
+			return this.optimisticUser;
+		}
+		return this.refreshUser?.authority.equals(this.wallet.publicKey)
+			? this.refreshUser
+			: undefined;
+	}
+
+	/**
+	 * Opt in to refetching the markets account and the user's accounts after every transaction sent through this clearing house,
+	 * for subscriptions that lag, e.g. polling ones. The refetch runs in the background, transactions return as soon as they confirm
+	 * @param user the ClearingHouseUser whose accounts to refetch, e.g. the wallet's
+	 */
+	public enableRefreshAfterTx(user?: ClearingHouseUser): void {
+		this.refreshAccountsAfterTx = true;
+		this.refreshUser = user;
+	}
+
+	public disableRefreshAfterTx(): void {
+		this.refreshAccountsAfterTx = false;
+		this.refreshUser = undefined;
+	}
+
 	userAccountPublicKey?: PublicKey;
 	/**
 	 * Get the address for the Clearing House User's account. NOT the user's wallet address.
 	 * @returns
 	 */
 	public async getUserAccountPublicKey(): Promise<PublicKey> {
+		this.metrics?.recordCacheLookup(
+			'userAccountPublicKey',
+			!!this.userAccountPublicKey
+		);
 		if (this.userAccountPublicKey) {
 			return this.userAccountPublicKey;
 		}
@@ -310,8 +851,28 @@ export class ClearingHouse {
 		return this.userAccountPublicKey;
 	}
 
+	userPositionsPublicKey?: PublicKey;
+	/**
+	 * Get the address of the Clearing House User's positions account. It never changes once the user is initialized,
+	 * so unlike the user account it stays cached across transactions and the trade path doesn't refetch the user account for it
+	 * @returns
+	 */
+	public async getUserPositionsPublicKey(): Promise<PublicKey> {
+		this.metrics?.recordCacheLookup(
+			'userPositionsPublicKey',
+			!!this.userPositionsPublicKey
+		);
+		if (this.userPositionsPublicKey) {
+			return this.userPositionsPublicKey;
+		}
+
+		this.userPositionsPublicKey = (await this.getUserAccount()).positions;
+		return this.userPositionsPublicKey;
+	}
+
 	userAccount?: UserAccount;
 	public async getUserAccount(): Promise<UserAccount> {
+		this.metrics?.recordCacheLookup('userAccount', !!this.userAccount);
 		if (this.userAccount) {
 			return this.userAccount;
 		}
@@ -322,8 +883,159 @@ export class ClearingHouse {
 		return this.userAccount;
 	}
 
+	/**
+	 * Fetches the user account of any authority, e.g. a liquidation candidate or a referrer
+	 * @param authority the user's wallet address
+	 * @returns the user account's address and the account
+	 * @throws UserAccountNotFoundError if the authority hasn't initialized a user account
+	 */
+	public async getUserForAuthority(
+		authority: PublicKey
+	): Promise<[PublicKey, UserAccount]> {
+		const userAccountPublicKey = await getUserAccountPublicKey(
+			this.program.programId,
+			authority
+		);
+		const accountInfo = await this.connection.getAccountInfo(
+			userAccountPublicKey,
+			this.opts?.commitment
+		);
+		if (accountInfo === null) {
+			throw new UserAccountNotFoundError(authority);
+		}
+
+		const userAccount = this.program.coder.accounts.decode(
+			'User',
+			accountInfo.data
+		) as UserAccount;
+		return [userAccountPublicKey, userAccount];
+	}
+
+	/**
+	 * Refers every trade that doesn't pass its own referrer to the given authority
+	 * @param referrerAuthority the referrer's wallet address, undefined to stop referring trades
+	 * @throws UserAccountNotFoundError if the referrer hasn't initialized a user account
+	 */
+	public async setDefaultReferrer(
+		referrerAuthority?: PublicKey
+	): Promise<void> {
+		this.defaultReferrer = referrerAuthority
+			? await resolveReferrer(this, referrerAuthority)
+			: undefined;
+	}
+
+	/**
+	 * The program ignores a user referring itself, so the default referrer's own trades don't pass it
+	 */
+	private getDefaultReferrer(
+		userAccountPublicKey: PublicKey
+	): PublicKey | undefined {
+		if (
+			!this.defaultReferrer ||
+			this.defaultReferrer.equals(userAccountPublicKey)
+		) {
+			return undefined;
+		}
+		return this.defaultReferrer;
+	}
+
+	/**
+	 * Reads what a trade's fee depends on for its optimistic fill, while the trade is in flight
+	 * @param discountToken
+	 * @param referrer
+	 * @returns the discount token balance, undefined if none is passed or it can't be read, and whether a referrer is passed
+	 */
+	private async getTradeFeeInputs(
+		discountToken?: PublicKey,
+		referrer?: PublicKey
+	): Promise<[BN | undefined, boolean]> {
+		const hasReferrer =
+			referrer !== undefined ||
+			this.getDefaultReferrer(await this.getUserAccountPublicKey()) !==
+				undefined;
+		const discountTokenBalance = discountToken
+			? await getTokenAccountBalance(this.connection, discountToken).catch(
+					() => undefined
+			  )
+			: undefined;
+		return [discountTokenBalance, hasReferrer];
+	}
+
+	/**
+	 * Finds the token accounts holding the collateral mint, for deposit flows to offer a choice instead of asking for an address
+	 * @param owner defaults to the clearing house's wallet
+	 * @returns the associated token account first, then the others by balance, highest first
+	 */
+	public async findCollateralAccounts(
+		owner: PublicKey = this.wallet.publicKey
+	): Promise<TokenAccountCandidate[]> {
+		return await findTokenAccounts(
+			this.connection,
+			owner,
+			this.getStateAccount().collateralMint
+		);
+	}
+
+	/**
+	 * Reads a token account, served from cache if it was read less than {@link tokenAccountCacheMs} ago.
+	 * The cache is cleared by every transaction sent through the clearing house
+	 * @param publicKey
+	 * @throws TokenAccountNotFoundError if the account doesn't exist
+	 * @throws InvalidTokenAccountError if the account isn't a token account
+	 */
+	public async getTokenAccount(publicKey: PublicKey): Promise<TokenAccount> {
+		const [tokenAccount] = await this.getTokenAccounts([publicKey]);
+		return tokenAccount;
+	}
+
+	/**
+	 * Reads several token accounts, fetching the ones not cached in a single request, see {@link getTokenAccount}
+	 * @param publicKeys
+	 * @returns token accounts in the order of publicKeys
+	 */
+	public async getTokenAccounts(
+		publicKeys: PublicKey[]
+	): Promise<TokenAccount[]> {
+		const now = Date.now();
+		const isFresh = (publicKey: PublicKey) => {
+			const cached = this.tokenAccountCache.get(publicKey.toString());
+			return cached && now - cached.fetchedTs < this.tokenAccountCacheMs;
+		};
+
+		const missing = publicKeys.filter((publicKey) => !isFresh(publicKey));
+		if (missing.length > 0) {
+			const fetched = await fetchTokenAccounts(this.connection, missing);
+			fetched.forEach((tokenAccount) => {
+				this.tokenAccountCache.set(tokenAccount.publicKey.toString(), {
+					tokenAccount,
+					fetchedTs: now,
+				});
+			});
+		}
+
+		return publicKeys.map(
+			(publicKey) =>
+				this.tokenAccountCache.get(publicKey.toString()).tokenAccount
+		);
+	}
+
+	/**
+	 * Reads the collateral and insurance vaults in a single request
+	 */
+	public async getVaultTokenAccounts(): Promise<{
+		collateralVault: TokenAccount;
+		insuranceVault: TokenAccount;
+	}> {
+		const state = this.getStateAccount();
+		const [collateralVault, insuranceVault] = await this.getTokenAccounts([
+			state.collateralVault,
+			state.insuranceVault,
+		]);
+		return { collateralVault, insuranceVault };
+	}
+
 	public async depositCollateral(
-		amount: BN,
+		amount: CollateralAmount,
 		collateralAccountPublicKey: PublicKey,
 		userPositionsAccountPublicKey?: PublicKey
 	): Promise<TransactionSignature> {
@@ -335,17 +1047,60 @@ export class ClearingHouse {
 
 		const tx = new Transaction().add(depositCollateralIx);
 
-		return await this.txSender.send(tx);
+		return await this.sendTx(tx);
+	}
+
+	/**
+	 * Deposits from the wallet's associated token account of the collateral mint, creating the account in the same transaction if it
+	 * doesn't exist yet
+	 * @param amount
+	 * @returns
+	 */
+	public async depositCollateralAuto(
+		amount: CollateralAmount
+	): Promise<TransactionSignature> {
+		const tx = new Transaction().add(
+			...(await this.getDepositCollateralAutoInstructions(amount))
+		);
+		return await this.sendTx(tx);
+	}
+
+	/**
+	 * @param amount
+	 * @returns the create associated token account instruction if the account doesn't exist, then the deposit instruction
+	 */
+	async getDepositCollateralAutoInstructions(
+		amount: CollateralAmount
+	): Promise<TransactionInstruction[]> {
+		const [collateralAccountPublicKey, createCollateralAccountIx] =
+			await getOrCreateAssociatedTokenAccount(
+				this.connection,
+				this.wallet.publicKey,
+				this.getStateAccount().collateralMint
+			);
+
+		const instructions: TransactionInstruction[] = [];
+		if (createCollateralAccountIx) {
+			instructions.push(createCollateralAccountIx);
+		}
+
+		instructions.push(
+			await this.getDepositCollateralInstruction(
+				amount,
+				collateralAccountPublicKey
+			)
+		);
+		return instructions;
 	}
 
 	async getDepositCollateralInstruction(
-		amount: BN,
+		amount: CollateralAmount,
 		collateralAccountPublicKey: PublicKey,
 		userPositionsAccountPublicKey?: PublicKey
 	): Promise<TransactionInstruction> {
 		const userAccountPublicKey = await this.getUserAccountPublicKey();
 		if (!userPositionsAccountPublicKey) {
-			userPositionsAccountPublicKey = (await this.getUserAccount()).positions;
+			userPositionsAccountPublicKey = await this.getUserPositionsPublicKey();
 		}
 
 		const state = this.getStateAccount();
@@ -372,7 +1127,7 @@ export class ClearingHouse {
 	 * @returns
 	 */
 	public async initializeUserAccountAndDepositCollateral(
-		amount: BN,
+		amount: CollateralAmount,
 		collateralAccountPublicKey: PublicKey
 	): Promise<[TransactionSignature, PublicKey]> {
 		const [
@@ -391,14 +1146,14 @@ export class ClearingHouse {
 			.add(initializeUserAccountIx)
 			.add(depositCollateralIx);
 
-		const txSig = await this.program.provider.send(tx, [userPositionsAccount]);
+		const txSig = await this.sendTx(tx, [userPositionsAccount]);
 
 		return [txSig, userAccountPublicKey];
 	}
 
 	public async initializeUserAccountForDevnet(
 		mockUSDCFaucet: MockUSDCFaucet,
-		amount: BN
+		amount: CollateralAmount
 	): Promise<[TransactionSignature, PublicKey]> {
 		const [associateTokenPublicKey, createAssociatedAccountIx, mintToIx] =
 			await mockUSDCFaucet.createAssociatedTokenAccountAndMintToInstructions(
@@ -424,7 +1179,7 @@ export class ClearingHouse {
 			.add(initializeUserAccountIx)
 			.add(depositCollateralIx);
 
-		const txSig = await this.program.provider.send(tx, [userPositionsAccount]);
+		const txSig = await this.sendTx(tx, [userPositionsAccount]);
 
 		return [txSig, userAccountPublicKey];
 	}
@@ -439,14 +1194,17 @@ export class ClearingHouse {
 				authority: this.wallet.publicKey,
 			},
 		});
-		return this.txSender.send(deleteUserTx, [], this.opts);
+		const txSig = await this.sendTx(deleteUserTx, [], this.opts);
+		this.userPositionsPublicKey = undefined;
+		return txSig;
 	}
 
 	public async withdrawCollateral(
-		amount: BN,
+		amount: CollateralAmount,
 		collateralAccountPublicKey: PublicKey
 	): Promise<TransactionSignature> {
-		return this.txSender.send(
+		this.assertExchangeNotPaused();
+		return await this.sendTx(
 			wrapInTx(
 				await this.getWithdrawCollateralIx(amount, collateralAccountPublicKey)
 			),
@@ -455,14 +1213,102 @@ export class ClearingHouse {
 		);
 	}
 
+	/**
+	 * Withdraws to the wallet's associated token account of the collateral mint, creating the account in the same transaction if it
+	 * doesn't exist yet
+	 * @param amount
+	 * @returns
+	 */
+	public async withdrawCollateralAuto(
+		amount: CollateralAmount
+	): Promise<TransactionSignature> {
+		this.assertExchangeNotPaused();
+		const [collateralAccountPublicKey, createCollateralAccountIx] =
+			await getOrCreateAssociatedTokenAccount(
+				this.connection,
+				this.wallet.publicKey,
+				this.getStateAccount().collateralMint
+			);
+
+		const tx = new Transaction();
+		if (createCollateralAccountIx) {
+			tx.add(createCollateralAccountIx);
+		}
+		tx.add(
+			await this.getWithdrawCollateralIx(amount, collateralAccountPublicKey)
+		);
+		return await this.sendTx(tx, [], this.opts);
+	}
+
+	/**
+	 * Wraps lamports from the wallet into a temporary wrapped sol account, deposits them and closes the account,
+	 * for clearing houses using wrapped sol as collateral
+	 * @param amount lamports
+	 * @returns
+	 */
+	public async depositWrappedSol(
+		amount: CollateralAmount
+	): Promise<TransactionSignature> {
+		this.assertWrappedSolCollateral();
+		const wrappedSolAccount = await getTemporaryWrappedSolAccount(
+			this.connection,
+			this.wallet.publicKey,
+			amount
+		);
+
+		const tx = new Transaction()
+			.add(...wrappedSolAccount.createInstructions)
+			.add(
+				await this.getDepositCollateralInstruction(
+					amount,
+					wrappedSolAccount.keypair.publicKey
+				)
+			)
+			.add(wrappedSolAccount.closeInstruction);
+		return await this.sendTx(tx, [wrappedSolAccount.keypair], this.opts);
+	}
+
+	/**
+	 * Withdraws to a temporary wrapped sol account and closes it, unwrapping the lamports to the wallet,
+	 * for clearing houses using wrapped sol as collateral
+	 * @param amount lamports
+	 * @returns
+	 */
+	public async withdrawWrappedSol(
+		amount: CollateralAmount
+	): Promise<TransactionSignature> {
+		this.assertExchangeNotPaused();
+		this.assertWrappedSolCollateral();
+		const wrappedSolAccount = await getTemporaryWrappedSolAccount(
+			this.connection,
+			this.wallet.publicKey,
+			ZERO
+		);
+
+		const tx = new Transaction()
+			.add(...wrappedSolAccount.createInstructions)
+			.add(
+				await this.getWithdrawCollateralIx(
+					amount,
+					wrappedSolAccount.keypair.publicKey
+				)
+			)
+			.add(wrappedSolAccount.closeInstruction);
+		return await this.sendTx(tx, [wrappedSolAccount.keypair], this.opts);
+	}
+
+	private assertWrappedSolCollateral(): void {
+		if (!this.getStateAccount().collateralMint.equals(NATIVE_MINT)) {
+			throw new Error('Clearing house collateral is not wrapped sol');
+		}
+	}
+
 	public async getWithdrawCollateralIx(
-		amount: BN,
+		amount: CollateralAmount,
 		collateralAccountPublicKey: PublicKey
 	): Promise<TransactionInstruction> {
 		const userAccountPublicKey = await this.getUserAccountPublicKey();
-		const user: any = await this.program.account.user.fetch(
-			userAccountPublicKey
-		);
+		const userPositionsPublicKey = await this.getUserPositionsPublicKey();
 
 		const state = this.getStateAccount();
 		return await this.program.instruction.withdrawCollateral(amount, {
@@ -477,22 +1323,153 @@ export class ClearingHouse {
 				authority: this.wallet.publicKey,
 				tokenProgram: TOKEN_PROGRAM_ID,
 				markets: state.markets,
-				userPositions: user.positions,
+				userPositions: userPositionsPublicKey,
 				fundingPaymentHistory: state.fundingPaymentHistory,
 				depositHistory: state.depositHistory,
 			},
 		});
 	}
 
+	/**
+	 * The cluster's unix timestamp from the Clock sysvar, cached for about a slot, see {@link ChainClock}
+	 * @returns unix timestamp in seconds
+	 */
+	public async getChainTime(): Promise<BN> {
+		return await this.chainClock.getChainTime();
+	}
+
+	/**
+	 * The cluster's slot from the Clock sysvar, cached for about a slot, see {@link ChainClock}
+	 */
+	public async getChainSlot(): Promise<number> {
+		return await this.chainClock.getChainSlot();
+	}
+
+	/**
+	 * Estimates the priority fee to land a trade from the recent fees paid by transactions write locking the markets and history accounts
+	 * @param percentile of the recent fees to pay, between 0 and 100
+	 * @returns priority fee in micro-lamports per compute unit, see {@link getComputeUnitPriceIx}
+	 */
+	public async estimatePriorityFee(percentile = 75): Promise<number> {
+		const state = this.getStateAccount();
+		return await estimatePriorityFee(
+			this.connection,
+			[
+				state.markets,
+				state.tradeHistory,
+				state.fundingPaymentHistory,
+				state.fundingRateHistory,
+			],
+			percentile
+		);
+	}
+
+	/**
+	 * Checks the market's oracle against the oracle guard rails in the state account: staleness, confidence interval,
+	 * volatility and how far the trade would move the mark price from the oracle price. Lets a trade be rejected locally
+	 * instead of after fees and latency. Stricter than the program, which still trades on an invalid oracle
+	 * @param direction
+	 * @param amount : Precision QUOTE_PRECISION
+	 * @param marketIndex
+	 * @throws OracleInvalidError
+	 */
+	public async validateOracleForTrade(
+		direction: PositionDirection,
+		amount: QuoteAssetAmount,
+		marketIndex: BN
+	): Promise<void> {
+		const market = this.getMarket(marketIndex);
+		const guardRails = this.getStateAccount().oracleGuardRails;
+		const [oracleAccount, currentSlot] = await Promise.all([
+			this.connection.getAccountInfo(market.amm.oracle),
+			this.getChainSlot(),
+		]);
+		if (oracleAccount === null) {
+			throw new OracleInvalidError(
+				`Oracle ${market.amm.oracle.toBase58()} for market ${marketIndex.toString()} not found`
+			);
+		}
+
+		const validity = calculateOracleValidity(
+			parsePriceData(oracleAccount.data),
+			currentSlot,
+			guardRails
+		);
+		if (!validity.isValid) {
+			const reasons = [
+				validity.isStale && `stale for ${validity.delay} slots`,
+				validity.isConfidenceTooLarge && 'confidence interval too large',
+				validity.isNonPositive && 'non-positive price',
+				validity.isTooVolatile && 'price too volatile',
+			]
+				.filter(Boolean)
+				.join(', ');
+			throw new OracleInvalidError(
+				`Oracle for market ${marketIndex.toString()} is invalid: ${reasons}`
+			);
+		}
+
+		const spreadBefore = calculateMarkOracleSpreadPct(
+			calculateMarkPrice(market),
+			validity.oraclePrice
+		);
+		const [_pctAvgSlippage, _pctMaxSlippage, _entryPrice, markPriceAfter] =
+			calculateTradeSlippage(direction, amount, market);
+		const spreadAfter = calculateMarkOracleSpreadPct(
+			markPriceAfter,
+			validity.oraclePrice
+		);
+		if (
+			isOracleMarkTooDivergent(spreadAfter, guardRails) &&
+			spreadAfter.abs().gte(spreadBefore.abs())
+		) {
+			throw new OracleInvalidError(
+				`Trade would move the mark price ${(
+					convertToNumber(spreadAfter) * 100
+				).toFixed(2)}% from the oracle price, beyond the divergence guard rail`
+			);
+		}
+	}
+
+	/**
+	 * Opens, increases, reduces or flips a position. If a user is attached with {@link enableOptimisticUpdates} or
+	 * {@link enableRefreshAfterTx}, the trade is first checked with {@link ClearingHouseUser.validateOpenPosition}.
+	 * Set options.validateOracle to also check the oracle guard rails first
+	 * @param direction
+	 * @param amount : Precision QUOTE_PRECISION
+	 * @param marketIndex
+	 * @param limitPrice : Precision MARK_PRICE_PRECISION, no limit if undefined
+	 * @param discountToken
+	 * @param referrer
+	 * @param options
+	 * @throws TradeSizeTooSmallError
+	 * @throws InsufficientCollateralError
+	 * @throws OracleInvalidError
+	 */
 	public async openPosition(
 		direction: PositionDirection,
-		amount: BN,
+		amount: QuoteAssetAmount,
 		marketIndex: BN,
-		limitPrice?: BN,
+		limitPrice?: Price,
 		discountToken?: PublicKey,
-		referrer?: PublicKey
+		referrer?: PublicKey,
+		options: OpenPositionOptions = {}
 	): Promise<TransactionSignature> {
-		return await this.txSender.send(
+		this.assertExchangeNotPaused();
+		if (!options.skipValidation) {
+			this.getAttachedUser()?.validateOpenPosition(
+				direction,
+				amount,
+				marketIndex
+			);
+		}
+		if (options.validateOracle) {
+			await this.validateOracleForTrade(direction, amount, marketIndex);
+		}
+		const applyFill = this.optimisticUser?.prepareOptimisticFill(marketIndex);
+		const feeInputs =
+			applyFill && this.getTradeFeeInputs(discountToken, referrer);
+		const txSig = await this.sendTx(
 			wrapInTx(
 				await this.getOpenPositionIx(
 					direction,
@@ -506,21 +1483,45 @@ export class ClearingHouse {
 			[],
 			this.opts
 		);
+
+		if (applyFill) {
+			const feeStructure = this.getStateAccount().feeStructure;
+			const [discountTokenBalance, hasReferrer] = await feeInputs;
+			try {
+				applyFill((market, userAccount, position) =>
+					fillOpenPosition(
+						market,
+						userAccount,
+						position,
+						direction,
+						amount,
+						feeStructure,
+						undefined,
+						discountTokenBalance,
+						hasReferrer
+					)
+				);
+			} catch (e) {
+				// the trade confirmed, the subscription will pick it up
+			}
+		}
+
+		return txSig;
 	}
 
 	public async getOpenPositionIx(
 		direction: PositionDirection,
-		amount: BN,
+		amount: QuoteAssetAmount,
 		marketIndex: BN,
-		limitPrice?: BN,
+		limitPrice?: Price,
 		discountToken?: PublicKey,
 		referrer?: PublicKey
 	): Promise<TransactionInstruction> {
 		const userAccountPublicKey = await this.getUserAccountPublicKey();
-		const userAccount = await this.getUserAccount();
+		const userPositionsPublicKey = await this.getUserPositionsPublicKey();
 
 		if (limitPrice == undefined) {
-			limitPrice = new BN(0); // no limit
+			limitPrice = toPrice(ZERO); // no limit
 		}
 
 		const optionalAccounts = {
@@ -536,6 +1537,7 @@ export class ClearingHouse {
 				isSigner: false,
 			});
 		}
+		referrer = referrer ?? this.getDefaultReferrer(userAccountPublicKey);
 		if (referrer) {
 			optionalAccounts.referrer = true;
 			remainingAccounts.push({
@@ -561,7 +1563,7 @@ export class ClearingHouse {
 					user: userAccountPublicKey,
 					authority: this.wallet.publicKey,
 					markets: state.markets,
-					userPositions: userAccount.positions,
+					userPositions: userPositionsPublicKey,
 					tradeHistory: state.tradeHistory,
 					fundingPaymentHistory: state.fundingPaymentHistory,
 					fundingRateHistory: state.fundingRateHistory,
@@ -584,13 +1586,38 @@ export class ClearingHouse {
 		discountToken?: PublicKey,
 		referrer?: PublicKey
 	): Promise<TransactionSignature> {
-		return await this.txSender.send(
+		this.assertExchangeNotPaused();
+		const applyFill = this.optimisticUser?.prepareOptimisticFill(marketIndex);
+		const feeInputs =
+			applyFill && this.getTradeFeeInputs(discountToken, referrer);
+		const txSig = await this.sendTx(
 			wrapInTx(
 				await this.getClosePositionIx(marketIndex, discountToken, referrer)
 			),
 			[],
 			this.opts
 		);
+
+		if (applyFill) {
+			const feeStructure = this.getStateAccount().feeStructure;
+			const [discountTokenBalance, hasReferrer] = await feeInputs;
+			try {
+				applyFill((market, userAccount, position) =>
+					fillClosePosition(
+						market,
+						userAccount,
+						position,
+						feeStructure,
+						discountTokenBalance,
+						hasReferrer
+					)
+				);
+			} catch (e) {
+				// the trade confirmed, the subscription will pick it up
+			}
+		}
+
+		return txSig;
 	}
 
 	public async getClosePositionIx(
@@ -599,7 +1626,7 @@ export class ClearingHouse {
 		referrer?: PublicKey
 	): Promise<TransactionInstruction> {
 		const userAccountPublicKey = await this.getUserAccountPublicKey();
-		const userAccount = await this.getUserAccount();
+		const userPositionsPublicKey = await this.getUserPositionsPublicKey();
 
 		const priceOracle =
 			this.getMarketsAccount().markets[marketIndex.toNumber()].amm.oracle;
@@ -617,6 +1644,7 @@ export class ClearingHouse {
 				isSigner: false,
 			});
 		}
+		referrer = referrer ?? this.getDefaultReferrer(userAccountPublicKey);
 		if (referrer) {
 			optionalAccounts.referrer = true;
 			remainingAccounts.push({
@@ -636,7 +1664,7 @@ export class ClearingHouse {
 					user: userAccountPublicKey,
 					authority: this.wallet.publicKey,
 					markets: state.markets,
-					userPositions: userAccount.positions,
+					userPositions: userPositionsPublicKey,
 					tradeHistory: state.tradeHistory,
 					fundingPaymentHistory: state.fundingPaymentHistory,
 					fundingRateHistory: state.fundingRateHistory,
@@ -650,7 +1678,8 @@ export class ClearingHouse {
 	public async liquidate(
 		liquidateeUserAccountPublicKey: PublicKey
 	): Promise<TransactionSignature> {
-		return this.txSender.send(
+		this.assertExchangeNotPaused();
+		return await this.sendTx(
 			wrapInTx(await this.getLiquidateIx(liquidateeUserAccountPublicKey)),
 			[],
 			this.opts
@@ -709,7 +1738,8 @@ export class ClearingHouse {
 		oracle: PublicKey,
 		marketIndex: BN
 	): Promise<TransactionSignature> {
-		return this.txSender.send(
+		this.assertExchangeNotPaused();
+		return await this.sendTx(
 			wrapInTx(await this.getUpdateFundingRateIx(oracle, marketIndex)),
 			[],
 			this.opts
@@ -735,7 +1765,8 @@ export class ClearingHouse {
 		userAccount: PublicKey,
 		userPositionsAccount: PublicKey
 	): Promise<TransactionSignature> {
-		return this.txSender.send(
+		this.assertExchangeNotPaused();
+		return await this.sendTx(
 			wrapInTx(
 				await this.getSettleFundingPaymentIx(userAccount, userPositionsAccount)
 			),
@@ -1,10 +1,36 @@
+import { Program } from '@project-serum/anchor';
 import { PublicKey } from '@solana/web3.js';
 import BN from 'bn.js';
 import { EventEmitter } from 'events';
 import StrictEventEmitter from 'strict-event-emitter-types';
 import { ClearingHouse } from './clearingHouse';
-import { UserAccount, UserPosition, UserPositionsAccount } from './types';
+import {
+	LiquidationHistoryAccount,
+	LiquidationRecord,
+	Market,
+	MarginCategory,
+	PortfolioSummary,
+	PositionSummary,
+	Price,
+	QuoteAssetAmount,
+	TradeHistoryAccount,
+	UserAccount,
+	UserPosition,
+	UserPositionsAccount,
+} from './types';
 import { calculateEntryPrice } from './math/position';
+import { calculateFee } from './math/fees';
+import {
+	toBaseAssetAmount,
+	toCollateralAmount,
+	toPrice,
+	toQuoteAssetAmount,
+} from './math/conversion';
+import {
+	calculateMinimumQuoteAssetAmount,
+	isTradeSizeTooSmall,
+} from './math/trade';
+import { InsufficientCollateralError, TradeSizeTooSmallError } from './errors';
 import {
 	MARK_PRICE_PRECISION,
 	AMM_TO_QUOTE_PRECISION_RATIO,
@@ -28,6 +54,31 @@ import {
 	calculateTradeSlippage,
 } from '.';
 import { getUserAccountPublicKey } from './addresses';
+import {
+	calculateFundingPaymentReport,
+	FundingPaymentReport,
+} from './history/fundingPayment';
+import { getTradeHistoryForUser, UserTrade } from './history/trade';
+import {
+	calculateRealizedPnlReport,
+	RealizedPnlReport,
+} from './history/realizedPnl';
+import { DepositReconciliation, reconcileDeposits } from './history/deposits';
+import {
+	calculateFundingAttribution,
+	FundingAttributionRow,
+} from './history/fundingAttribution';
+import { Fill, getOrCreatePosition, settlePositionFunding } from './math/fill';
+import { getLatestRecordId, getOrderedRecords } from './history/utils';
+
+/**
+ * Fills a trade against the market as it was before the trade and copies of the user's accounts, e.g. {@link fillOpenPosition}
+ */
+export type OptimisticFill = (
+	market: Market,
+	userAccount: UserAccount,
+	position: UserPosition
+) => Fill;
 
 export class ClearingHouseUser {
 	clearingHouse: ClearingHouse;
@@ -37,6 +88,10 @@ export class ClearingHouseUser {
 	isSubscribed = false;
 	eventEmitter: StrictEventEmitter<EventEmitter, UserAccountEvents>;
 
+	private optimisticUserAccount?: UserAccount;
+	private optimisticUserPositionsAccount?: UserPositionsAccount;
+	private fetchedUserPositionsAccount?: UserPositionsAccount;
+
 	public static from(
 		clearingHouse: ClearingHouse,
 		authority: PublicKey
@@ -48,6 +103,22 @@ export class ClearingHouseUser {
 		return new ClearingHouseUser(clearingHouse, authority, accountSubscriber);
 	}
 
+	/**
+	 * Builds a ClearingHouseUser on an existing anchor Program of the clearing house, see {@link ClearingHouse.fromProgram}
+	 * @param program
+	 * @param authority defaults to the program's wallet
+	 * @returns clearingHouseUser
+	 */
+	public static fromProgram(
+		program: Program,
+		authority: PublicKey = program.provider.wallet.publicKey
+	): ClearingHouseUser {
+		return ClearingHouseUser.from(
+			ClearingHouse.fromProgram(program),
+			authority
+		);
+	}
+
 	public constructor(
 		clearingHouse: ClearingHouse,
 		authority: PublicKey,
@@ -57,6 +128,19 @@ export class ClearingHouseUser {
 		this.authority = authority;
 		this.accountSubscriber = accountSubscriber;
 		this.eventEmitter = this.accountSubscriber.eventEmitter;
+		this.eventEmitter.on('userAccountData', () => {
+			this.optimisticUserAccount = undefined;
+		});
+		this.eventEmitter.on('userPositionsData', () => {
+			this.optimisticUserPositionsAccount = undefined;
+		});
+	}
+
+	/**
+	 * @returns the anchor Program the user's clearing house sends through, sharing its wallet and connection
+	 */
+	public getProgram(): Program {
+		return this.clearingHouse.program;
 	}
 
 	/**
@@ -81,14 +165,118 @@ export class ClearingHouseUser {
 	public async unsubscribe(): Promise<void> {
 		await this.accountSubscriber.unsubscribe();
 		this.isSubscribed = false;
+		this.clearOptimisticFills();
 	}
 
 	public getUserAccount(): UserAccount {
-		return this.accountSubscriber.getUserAccount();
+		return (
+			this.optimisticUserAccount ?? this.accountSubscriber.getUserAccount()
+		);
 	}
 
 	public getUserPositionsAccount(): UserPositionsAccount {
-		return this.accountSubscriber.getUserPositionsAccount();
+		return (
+			this.optimisticUserPositionsAccount ??
+			this.accountSubscriber.getUserPositionsAccount()
+		);
+	}
+
+	/**
+	 * Gets the user's positions without requiring a subscription, e.g. for one-off scripts. Served by the subscription when subscribed,
+	 * otherwise fetched from rpc once and cached
+	 * @param force fetch from rpc even if subscribed or cached
+	 * @returns userPositionsAccount
+	 */
+	public async fetchUserPositionsAccount(
+		force = false
+	): Promise<UserPositionsAccount> {
+		if (!force) {
+			if (this.isSubscribed) {
+				return this.getUserPositionsAccount();
+			}
+			if (this.fetchedUserPositionsAccount) {
+				return this.fetchedUserPositionsAccount;
+			}
+		}
+
+		const [, userAccount] = await this.clearingHouse.getUserForAuthority(
+			this.authority
+		);
+		this.fetchedUserPositionsAccount =
+			(await this.clearingHouse.program.account.userPositions.fetch(
+				userAccount.positions
+			)) as UserPositionsAccount;
+		return this.fetchedUserPositionsAccount;
+	}
+
+	/**
+	 * Snapshots the market and the user's accounts before a trade is sent. Calling the returned function once the trade
+	 * confirms applies its expected fill to copies of the accounts, which {@link getUserAccount} and {@link getUserPositionsAccount}
+	 * return until the subscription delivers each account again. Nothing is applied if an account update arrived in the meantime,
+	 * since it already includes the trade.
+	 * @param marketIndex
+	 * @returns function applying the fill, undefined if the user isn't subscribed
+	 */
+	public prepareOptimisticFill(
+		marketIndex: BN
+	): ((fill: OptimisticFill) => Fill | undefined) | undefined {
+		if (!this.isSubscribed) {
+			return undefined;
+		}
+
+		const marketBefore = this.clearingHouse.getMarket(marketIndex);
+		const market = { ...marketBefore, amm: { ...marketBefore.amm } };
+		const userAccountBefore = this.getUserAccount();
+		const userPositionsAccountBefore = this.getUserPositionsAccount();
+
+		return (fill: OptimisticFill) => {
+			if (
+				this.getUserAccount() !== userAccountBefore ||
+				this.getUserPositionsAccount() !== userPositionsAccountBefore
+			) {
+				return undefined;
+			}
+
+			const userAccount = { ...userAccountBefore };
+			const userPositionsAccount = {
+				...userPositionsAccountBefore,
+				positions: userPositionsAccountBefore.positions.map((position) => {
+					return { ...position };
+				}),
+			};
+
+			// the program settles funding on every position before filling the trade
+			for (const position of userPositionsAccount.positions) {
+				settlePositionFunding(
+					position.marketIndex.eq(marketIndex)
+						? market
+						: this.clearingHouse.getMarket(position.marketIndex),
+					userAccount,
+					position
+				);
+			}
+
+			const position = getOrCreatePosition(
+				userPositionsAccount.positions,
+				marketIndex
+			);
+			if (!position) {
+				return undefined;
+			}
+
+			const result = fill(market, userAccount, position);
+			this.optimisticUserAccount = userAccount;
+			this.optimisticUserPositionsAccount = userPositionsAccount;
+			return result;
+		};
+	}
+
+	/**
+	 * Drops optimistically applied fills, reads go back to the subscribed accounts
+	 */
+	public clearOptimisticFills(): void {
+		this.optimisticUserAccount = undefined;
+		this.optimisticUserPositionsAccount = undefined;
 	}
 
 	/**
@@ -102,6 +290,101 @@ export class ClearingHouseUser {
 		);
 	}
 
+	/**
+	 * calculates the average entry price of the user's position in a market. Returns zero if there is no open position
+	 * @param marketIndex
+	 * @returns : Precision MARK_PRICE_PRECISION
+	 */
+	public getEntryPrice(marketIndex: BN): Price {
+		const position =
+			this.getUserPosition(marketIndex) || this.getEmptyPosition(marketIndex);
+		return toPrice(calculateEntryPrice(position));
+	}
+
+	/**
+	 * calculates the price at which closing the position nets to zero: it covers the fees paid to open the position, the fee
+	 * charged on the exit notional to close it and the funding accrued since. Opening fees are taken from the trade history if
+	 * subscribed, otherwise estimated from the fee structure. Returns zero if there is no open position
+	 * @param marketIndex
+	 * @returns : Precision MARK_PRICE_PRECISION
+	 */
+	public getBreakEvenPrice(marketIndex: BN): Price {
+		const position = this.getUserPosition(marketIndex);
+		if (!position || position.baseAssetAmount.eq(ZERO)) {
+			return toPrice(ZERO);
+		}
+
+		const tradeHistorySubscribed =
+			this.clearingHouse.accountSubscriber.optionalExtraSubscriptions.includes(
+				'tradeHistoryAccount'
+			);
+		const fees = tradeHistorySubscribed
+			? this.getFeesPaidForPosition(marketIndex)
+			: this.calculateFeeForQuoteAmount(position.quoteAssetAmount);
+		const fundingPnl = this.getPendingFundingPayment(marketIndex);
+
+		// longs need the price higher to cover costs, shorts need it lower
+		const isLong = position.baseAssetAmount.gt(ZERO);
+		const costs = fees.sub(fundingPnl);
+		const exitQuoteAmount = isLong
+			? position.quoteAssetAmount.add(costs)
+			: position.quoteAssetAmount.sub(costs);
+		const exitFee = this.calculateFeeForQuoteAmount(
+			BN.max(exitQuoteAmount, ZERO)
+		);
+		const breakEvenQuoteAmount = isLong
+			? exitQuoteAmount.add(exitFee)
+			: exitQuoteAmount.sub(exitFee);
+
+		return toPrice(
+			BN.max(breakEvenQuoteAmount, ZERO)
+				.mul(MARK_PRICE_PRECISION)
+				.mul(AMM_TO_QUOTE_PRECISION_RATIO)
+				.div(position.baseAssetAmount.abs())
+		);
+	}
+
+	/**
+	 * sums the fees of the user's trades in a market since the position was last flat. Requires the trade history subscription
+	 * @param marketIndex
+	 * @returns : Precision QUOTE_PRECISION
+	 */
+	private getFeesPaidForPosition(marketIndex: BN): BN {
+		const tradeRecords = this.clearingHouse
+			.getTradeHistoryAccount()
+			.tradeRecords.filter(
+				(record) =>
+					record.userAuthority.equals(this.authority) &&
+					record.marketIndex.eq(marketIndex)
+			)
+			.sort((a, b) => a.recordId.cmp(b.recordId));
+
+		let baseAssetAmount = ZERO;
+		let fees = ZERO;
+		for (const record of tradeRecords) {
+			const signedBaseAssetAmount = record.direction.long
+				? record.baseAssetAmount
+				: record.baseAssetAmount.neg();
+			const newBaseAssetAmount = baseAssetAmount.add(signedBaseAssetAmount);
+
+			if (newBaseAssetAmount.eq(ZERO)) {
+				fees = ZERO;
+			} else if (
+				!baseAssetAmount.eq(ZERO) &&
+				newBaseAssetAmount.isNeg() !== baseAssetAmount.isNeg()
+			) {
+				// the trade that flipped the position also opened the new one
+				fees = record.fee;
+			} else {
+				fees = fees.add(record.fee);
+			}
+
+			baseAssetAmount = newBaseAssetAmount;
+		}
+
+		return fees;
+	}
+
 	public getEmptyPosition(marketIndex: BN): UserPosition {
 		return {
 			baseAssetAmount: ZERO,
@@ -186,6 +469,228 @@ export class ClearingHouseUser {
 			}, ZERO);
 	}
 
+	/**
+	 * Refreshes the clearing house and user accounts in one batch and summarizes the user's portfolio
+	 * @returns portfolioSummary
+	 */
+	public async fetchPortfolioSummary(): Promise<PortfolioSummary> {
+		await Promise.all([
+			this.clearingHouse.fetchAccounts(),
+			this.fetchAccounts(),
+		]);
+		return this.getPortfolioSummary();
+	}
+
+	/**
+	 * Summarizes collateral, leverage, margin and every open position from the currently subscribed account data
+	 * @returns portfolioSummary
+	 */
+	public getPortfolioSummary(): PortfolioSummary {
+		const userAccount = this.getUserAccount();
+		const positions = this.getUserPositionsAccount()
+			.positions.filter((position) => !position.baseAssetAmount.eq(ZERO))
+			.map((position) => this.getPositionSummary(position));
+
+		return {
+			collateral: toCollateralAmount(userAccount.collateral),
+			cumulativeDeposits: toCollateralAmount(userAccount.cumulativeDeposits),
+			totalCollateral: toQuoteAssetAmount(this.getTotalCollateral()),
+			freeCollateral: toQuoteAssetAmount(this.getFreeCollateral()),
+			totalPositionValue: this.getTotalPositionValue(),
+			leverage: this.getLeverage(),
+			marginRatio: this.getMarginRatio(),
+			positions,
+		};
+	}
+
+	private getPositionSummary(position: UserPosition): PositionSummary {
+		const market = this.clearingHouse.getMarket(position.marketIndex);
+		return {
+			marketIndex: position.marketIndex,
+			baseAssetAmount: toBaseAssetAmount(position.baseAssetAmount),
+			quoteAssetAmount: toQuoteAssetAmount(position.quoteAssetAmount),
+			entryPrice: toPrice(calculateEntryPrice(position)),
+			markPrice: toPrice(calculateMarkPrice(market)),
+			positionValue: toQuoteAssetAmount(
+				calculateBaseAssetValue(market, position)
+			),
+			unrealizedPnl: toQuoteAssetAmount(calculatePositionPNL(market, position)),
+			pendingFundingPayment: this.getPendingFundingPayment(
+				position.marketIndex
+			),
+			liquidationPrice: toPrice(this.liquidationPrice(position)),
+		};
+	}
+
+	/**
+	 * calculates the funding payment accrued on a position since it was last settled, from the position's last cumulative funding rate
+	 * versus the market's current cumulative funding rate. Positive means the user receives funding, negative means the user pays
+	 * @param marketIndex
+	 * @returns : Precision QUOTE_PRECISION
+	 */
+	public getPendingFundingPayment(marketIndex: BN): QuoteAssetAmount {
+		const position = this.getUserPosition(marketIndex);
+		if (!position) {
+			return toQuoteAssetAmount(ZERO);
+		}
+
+		const market = this.clearingHouse.getMarket(marketIndex);
+		return toQuoteAssetAmount(
+			calculatePositionFundingPNL(market, position).div(
+				PRICE_TO_QUOTE_PRECISION
+			)
+		);
+	}
+
+	/**
+	 * Aggregates the user's settled funding payments by market. Requires the funding payment history subscription
+	 * @param startTs inclusive, unbounded if undefined
+	 * @param endTs exclusive, unbounded if undefined
+	 * @returns report : Precision QUOTE_PRECISION
+	 */
+	public getFundingPaymentReport(
+		startTs?: BN,
+		endTs?: BN
+	): FundingPaymentReport {
+		return calculateFundingPaymentReport(
+			this.clearingHouse.getFundingPaymentHistoryAccount(),
+			this.authority,
+			startTs,
+			endTs
+		);
+	}
+
+	/**
+	 * Returns the user's fills, oldest to newest. Requires the trade history subscription
+	 * @param sinceRecordId exclusive, returns every fill if undefined
+	 * @returns trades
+	 */
+	public getTradeHistory(sinceRecordId?: BN): UserTrade[] {
+		return getTradeHistoryForUser(
+			this.clearingHouse.getTradeHistoryAccount(),
+			this.authority,
+			sinceRecordId
+		);
+	}
+
+	/**
+	 * Calls back with each of the user's fills as the trade history streams in, including liquidation closes.
+	 * Requires the trade history subscription. Only fills recorded after the call are delivered
+	 * @param callback
+	 * @returns function stopping the callbacks
+	 */
+	public onFill(callback: (trade: UserTrade) => void): () => void {
+		let lastRecordId = getLatestRecordId(
+			this.clearingHouse.getTradeHistoryAccount().tradeRecords
+		);
+		const listener = (tradeHistoryAccount: TradeHistoryAccount) => {
+			const trades = getTradeHistoryForUser(
+				tradeHistoryAccount,
+				this.authority,
+				lastRecordId
+			);
+			lastRecordId = BN.max(
+				lastRecordId,
+				getLatestRecordId(tradeHistoryAccount.tradeRecords)
+			);
+			for (const trade of trades) {
+				callback(trade);
+			}
+		};
+
+		this.clearingHouse.eventEmitter.on('tradeHistoryAccountUpdate', listener);
+		return () => {
+			this.clearingHouse.eventEmitter.removeListener(
+				'tradeHistoryAccountUpdate',
+				listener
+			);
+		};
+	}
+
+	/**
+	 * Calls back with each of the user's liquidations as the liquidation history streams in.
+	 * Requires the liquidation history subscription. Only liquidations recorded after the call are delivered
+	 * @param callback
+	 * @returns function stopping the callbacks
+	 */
+	public onLiquidation(
+		callback: (record: LiquidationRecord) => void
+	): () => void {
+		let lastRecordId = getLatestRecordId(
+			this.clearingHouse.getLiquidationHistoryAccount().liquidationRecords
+		);
+		const listener = (liquidationHistoryAccount: LiquidationHistoryAccount) => {
+			const records = getOrderedRecords(
+				liquidationHistoryAccount.liquidationRecords
+			).filter((record) => record.recordId.gt(lastRecordId));
+			if (records.length > 0) {
+				lastRecordId = records[records.length - 1].recordId;
+			}
+			for (const record of records) {
+				if (record.userAuthority.equals(this.authority)) {
+					callback(record);
+				}
+			}
+		};
+
+		this.clearingHouse.eventEmitter.on(
+			'liquidationHistoryAccountUpdate',
+			listener
+		);
+		return () => {
+			this.clearingHouse.eventEmitter.removeListener(
+				'liquidationHistoryAccountUpdate',
+				listener
+			);
+		};
+	}
+
+	/**
+	 * Reconstructs the user's realized pnl from trade and funding payment history. Requires both history subscriptions
+	 * @param sinceTs inclusive, unbounded if undefined
+	 * @returns report : Precision QUOTE_PRECISION
+	 */
+	public getRealizedPnlReport(sinceTs?: BN): RealizedPnlReport {
+		return calculateRealizedPnlReport(
+			this.clearingHouse.getTradeHistoryAccount(),
+			this.clearingHouse.getFundingPaymentHistoryAccount(),
+			this.authority,
+			sinceTs
+		);
+	}
+
+	/**
+	 * Attributes the user's funding payments to the day and market they accrued in. Requires the funding payment and funding rate history subscriptions
+	 * @param startTs inclusive, unbounded if undefined
+	 * @param endTs exclusive, unbounded if undefined
+	 * @returns rows ordered by day then market index : Precision QUOTE_PRECISION
+	 */
+	public getFundingAttribution(
+		startTs?: BN,
+		endTs?: BN
+	): FundingAttributionRow[] {
+		return calculateFundingAttribution(
+			this.clearingHouse.getFundingPaymentHistoryAccount(),
+			this.clearingHouse.getFundingRateHistoryAccount(),
+			this.authority,
+			startTs,
+			endTs
+		);
+	}
+
+	/**
+	 * Cross-checks the user's cumulative deposits against the deposit history and the collateral vault. Requires the deposit history subscription
+	 * @returns reconciliation with any discrepancies found
+	 */
+	public async reconcileDeposits(): Promise<DepositReconciliation> {
+		return reconcileDeposits(
+			this.clearingHouse.connection,
+			this.clearingHouse.getStateAccount(),
+			this.clearingHouse.getDepositHistoryAccount(),
+			this.getUserAccount()
+		);
+	}
+
 	/**
 	 * calculates TotalCollateral: collateral + unrealized pnl
 	 * @returns : Precision QUOTE_PRECISION
@@ -198,18 +703,40 @@ export class ClearingHouseUser {
 	}
 
 	/**
-	 * calculates sum of position value across all positions
+	 * calculates account value the way the program's margin calculation does: collateral + unrealized pnl, floored at zero.
+	 * The program settles funding before checking margin, so pending funding is included unless withFunding is false
+	 * @param withFunding
 	 * @returns : Precision QUOTE_PRECISION
 	 */
-	getTotalPositionValue(): BN {
-		return this.getUserPositionsAccount().positions.reduce(
-			(positionValue, marketPosition) => {
-				const market = this.clearingHouse.getMarket(marketPosition.marketIndex);
-				return positionValue.add(
-					calculateBaseAssetValue(market, marketPosition)
-				);
-			},
-			ZERO
+	public getAccountValue(withFunding = true): QuoteAssetAmount {
+		return toQuoteAssetAmount(
+			BN.max(
+				this.getUserAccount().collateral.add(
+					this.getUnrealizedPNL(withFunding)
+				),
+				ZERO
+			)
+		);
+	}
+
+	/**
+	 * calculates sum of position value across all positions. Positions are valued at the price of closing them against the amm,
+	 * matching the base asset value used by the program's margin calculation
+	 * @returns : Precision QUOTE_PRECISION
+	 */
+	public getTotalPositionValue(): QuoteAssetAmount {
+		return toQuoteAssetAmount(
+			this.getUserPositionsAccount().positions.reduce(
+				(positionValue, marketPosition) => {
+					const market = this.clearingHouse.getMarket(
+						marketPosition.marketIndex
+					);
+					return positionValue.add(
+						calculateBaseAssetValue(market, marketPosition)
+					);
+				},
+				ZERO
+			)
 		);
 	}
 
@@ -297,26 +824,8 @@ export class ClearingHouseUser {
 	 * @params category {Initial, Partial, Maintenance}
 	 * @returns : Precision TEN_THOUSAND
 	 */
-	public getMaxLeverage(category?: 'Initial' | 'Partial' | 'Maintenance'): BN {
-		const chState = this.clearingHouse.getStateAccount();
-		let marginRatioCategory: BN;
-
-		switch (category) {
-			case 'Initial':
-				marginRatioCategory = chState.marginRatioInitial;
-				break;
-			case 'Maintenance':
-				marginRatioCategory = chState.marginRatioMaintenance;
-				break;
-			case 'Partial':
-				marginRatioCategory = chState.marginRatioPartial;
-				break;
-			default:
-				marginRatioCategory = chState.marginRatioInitial;
-				break;
-		}
-		const maxLeverage = TEN_THOUSAND.mul(TEN_THOUSAND).div(marginRatioCategory);
-		return maxLeverage;
+	public getMaxLeverage(category?: MarginCategory): BN {
+		return this.clearingHouse.getMaxLeverage(category);
 	}
 
 	/**
@@ -785,13 +1294,13 @@ export class ClearingHouseUser {
 	// TODO - should this take the price impact of the trade into account for strict accuracy?
 
 	/**
-	 * Returns the leverage ratio for the account after adding (or subtracting) the given quote size to the given position
+	 * Returns the total position value for the account after adding (or subtracting) the given quote size to the given position
 	 * @param targetMarketIndex
-	 * @param positionMarketIndex
 	 * @param tradeQuoteAmount
-	 * @returns leverageRatio : Precision TEN_THOUSAND
+	 * @param tradeSide
+	 * @returns positionValue : Precision QUOTE_PRECISION
 	 */
-	public accountLeverageRatioAfterTrade(
+	public getTotalPositionValueAfterTrade(
 		targetMarketIndex: BN,
 		tradeQuoteAmount: BN,
 		tradeSide: PositionDirection
@@ -820,12 +1329,31 @@ export class ClearingHouseUser {
 		const totalPositionAfterTradeExcludingTargetMarket =
 			this.getTotalPositionValueExcludingMarket(targetMarketIndex);
 
+		return currentMarketPositionAfterTrade
+			.add(totalPositionAfterTradeExcludingTargetMarket)
+			.abs();
+	}
+
+	/**
+	 * Returns the leverage ratio for the account after adding (or subtracting) the given quote size to the given position
+	 * @param targetMarketIndex
+	 * @param tradeQuoteAmount
+	 * @param tradeSide
+	 * @returns leverageRatio : Precision TEN_THOUSAND
+	 */
+	public accountLeverageRatioAfterTrade(
+		targetMarketIndex: BN,
+		tradeQuoteAmount: BN,
+		tradeSide: PositionDirection
+	): BN {
 		const totalCollateral = this.getTotalCollateral();
 
 		if (totalCollateral.gt(ZERO)) {
-			const newLeverage = currentMarketPositionAfterTrade
-				.add(totalPositionAfterTradeExcludingTargetMarket)
-				.abs()
+			const newLeverage = this.getTotalPositionValueAfterTrade(
+				targetMarketIndex,
+				tradeQuoteAmount,
+				tradeSide
+			)
 				.mul(TEN_THOUSAND)
 				.div(totalCollateral);
 			return newLeverage;
@@ -834,17 +1362,88 @@ export class ClearingHouseUser {
 		}
 	}
 
+	/**
+	 * Checks an openPosition trade against the market's minimum trade size and the user's collateral the same way the program does,
+	 * so the trade can be rejected locally with a descriptive error instead of failing on-chain
+	 * @param direction
+	 * @param amount : Precision QUOTE_PRECISION
+	 * @param marketIndex
+	 * @throws TradeSizeTooSmallError
+	 * @throws InsufficientCollateralError
+	 */
+	public validateOpenPosition(
+		direction: PositionDirection,
+		amount: QuoteAssetAmount,
+		marketIndex: BN
+	): void {
+		const market = this.clearingHouse.getMarket(marketIndex);
+		const position =
+			this.getUserPosition(marketIndex) || this.getEmptyPosition(marketIndex);
+		const reducing =
+			!position.baseAssetAmount.eq(ZERO) &&
+			this.getPositionSide(position) !== direction;
+
+		let swapAmount: BN = amount;
+		let potentiallyRiskIncreasing = true;
+		if (reducing) {
+			const positionValue = calculateBaseAssetValue(market, position);
+
+			// the program rounds trades within the minimum trade size of the position value to a full close
+			if (isTradeSizeTooSmall(market, amount.sub(positionValue).abs())) {
+				swapAmount = positionValue;
+			}
+
+			if (positionValue.gt(swapAmount)) {
+				potentiallyRiskIncreasing = false;
+			} else {
+				// close the position, then open a new one with what's left
+				swapAmount = swapAmount.sub(positionValue);
+				potentiallyRiskIncreasing = swapAmount.gte(positionValue);
+			}
+		}
+
+		if (!swapAmount.eq(ZERO) && isTradeSizeTooSmall(market, swapAmount)) {
+			const minimumAmount = calculateMinimumQuoteAssetAmount(market);
+			throw new TradeSizeTooSmallError(
+				`Trade size ${swapAmount.toString()} is below the minimum trade size ${minimumAmount.toString()} for market ${marketIndex.toString()} (QUOTE_PRECISION)`
+			);
+		}
+
+		if (potentiallyRiskIncreasing) {
+			const leverageAfterTrade = this.accountLeverageRatioAfterTrade(
+				marketIndex,
+				amount,
+				direction
+			);
+			const maxLeverage = this.getMaxLeverage('Initial');
+			if (leverageAfterTrade.gt(maxLeverage)) {
+				throw new InsufficientCollateralError(
+					`Trade would bring leverage to ${leverageAfterTrade.toString()}, above the max leverage ${maxLeverage.toString()} (TEN_THOUSAND). Free collateral: ${this.getFreeCollateral().toString()} (QUOTE_PRECISION)`
+				);
+			}
+		}
+	}
+
 	/**
 	 * Calculates how much fee will be taken for a given sized trade
 	 * @param quoteAmount
+	 * @param discountTokenBalance balance of the discount token account passed with the trade, if any
+	 * @param hasReferrer whether a referrer is passed with the trade
 	 * @returns feeForQuote : Precision QUOTE_PRECISION
 	 */
-	public calculateFeeForQuoteAmount(quoteAmount: BN): BN {
+	public calculateFeeForQuoteAmount(
+		quoteAmount: BN,
+		discountTokenBalance?: BN,
+		hasReferrer = false
+	): BN {
 		const feeStructure = this.clearingHouse.getStateAccount().feeStructure;
 
-		return quoteAmount
-			.mul(feeStructure.feeNumerator)
-			.div(feeStructure.feeDenominator);
+		return calculateFee(
+			quoteAmount,
+			feeStructure,
+			discountTokenBalance,
+			hasReferrer
+		).userFee;
 	}
 
 	/**
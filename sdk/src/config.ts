@@ -3,6 +3,8 @@ type DriftConfig = {
 	PYTH_ORACLE_MAPPING_ADDRESS: string;
 	CLEARING_HOUSE_PROGRAM_ID: string;
 	USDC_MINT_ADDRESS: string;
+	/** program minting the mock usdc collateral of test deployments, devnet only */
+	MOCK_USDC_FAUCET_PROGRAM_ID?: string;
 };
 
 export type DriftEnv = 'devnet' | 'mainnet-beta';
@@ -13,6 +15,7 @@ export const configs: { [key in DriftEnv]: DriftConfig } = {
 		PYTH_ORACLE_MAPPING_ADDRESS: 'BmA9Z6FjioHJPpjT39QazZyhDRUdZy2ezwx4GiDdE2u2',
 		CLEARING_HOUSE_PROGRAM_ID: 'AsW7LnXB9UA1uec9wi9MctYTgTz7YH9snhxd16GsFaGX',
 		USDC_MINT_ADDRESS: '8zGuJQqwhZafTah7Uc7Z4tXRnguqkn5KLFAP8oV6PHe2',
+		MOCK_USDC_FAUCET_PROGRAM_ID: 'GvMhkYZmtnCL7jfVsTKz5zi1Jmd5dqRTHaXJL2ps1Gb',
 	},
 	'mainnet-beta': {
 		ENV: 'mainnet-beta',
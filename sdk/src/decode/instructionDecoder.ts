@@ -0,0 +1,171 @@
+import { BN, Program } from '@project-serum/anchor';
+import {
+	Message,
+	PublicKey,
+	Transaction,
+	TransactionInstruction,
+} from '@solana/web3.js';
+import { PositionDirection } from '../types';
+
+export type ManagePositionOptionalAccounts = {
+	discountToken: boolean;
+	referrer: boolean;
+};
+
+export type InitializeUserOptionalAccounts = {
+	whitelistToken: boolean;
+};
+
+/** instructions only the admin can send, decoded with their args as the anchor coder returns them */
+export type AdminIxName =
+	| 'initialize'
+	| 'initializeHistory'
+	| 'initializeMarket'
+	| 'moveAmmPrice'
+	| 'withdrawFees'
+	| 'withdrawFromInsuranceVault'
+	| 'withdrawFromInsuranceVaultToMarket'
+	| 'repegAmmCurve'
+	| 'updateK'
+	| 'updateMarginRatio'
+	| 'updatePartialLiquidationClosePercentage'
+	| 'updatePartialLiquidationPenaltyPercentage'
+	| 'updateFullLiquidationPenaltyPercentage'
+	| 'updatePartialLiquidationLiquidatorShareDenominator'
+	| 'updateFullLiquidationLiquidatorShareDenominator'
+	| 'updateFee'
+	| 'updateOracleGuardRails'
+	| 'updateMarketOracle'
+	| 'updateMarketMinimumTradeSize'
+	| 'updateAdmin'
+	| 'updateWhitelistMint'
+	| 'updateDiscountMint'
+	| 'updateMaxDeposit'
+	| 'updateExchangePaused'
+	| 'disableAdminControlsPrices'
+	| 'updateFundingPaused';
+
+export type DecodedIxArgs =
+	| { name: 'depositCollateral'; args: { amount: BN } }
+	| { name: 'withdrawCollateral'; args: { amount: BN } }
+	| {
+			name: 'openPosition';
+			args: {
+				direction: PositionDirection;
+				/** Precision QUOTE_PRECISION */
+				quoteAssetAmount: BN;
+				marketIndex: BN;
+				/** Precision MARK_PRICE_PRECISION, zero for no limit */
+				limitPrice: BN;
+				optionalAccounts: ManagePositionOptionalAccounts;
+			};
+	  }
+	| {
+			name: 'closePosition';
+			args: {
+				marketIndex: BN;
+				optionalAccounts: ManagePositionOptionalAccounts;
+			};
+	  }
+	| { name: 'liquidate'; args: Record<string, never> }
+	| { name: 'settleFundingPayment'; args: Record<string, never> }
+	| { name: 'updateFundingRate'; args: { marketIndex: BN } }
+	| {
+			name: 'initializeUser';
+			args: {
+				userNonce: number;
+				optionalAccounts: InitializeUserOptionalAccounts;
+			};
+	  }
+	| {
+			name: 'initializeUserWithExplicitPayer';
+			args: {
+				userNonce: number;
+				optionalAccounts: InitializeUserOptionalAccounts;
+			};
+	  }
+	| { name: 'deleteUser'; args: Record<string, never> }
+	| { name: AdminIxName; args: Record<string, unknown> };
+
+export type DecodedIx = DecodedIxArgs & {
+	/** accounts keyed by their name in the idl */
+	accounts: Record<string, PublicKey>;
+	/** accounts past the ones named in the idl, e.g. the discount token and referrer */
+	remainingAccounts: PublicKey[];
+};
+
+/**
+ * Decodes a clearing_house instruction's data into its name and typed args
+ * @param program
+ * @param data
+ * @returns decoded args, undefined if the data doesn't match any instruction
+ */
+export function decodeInstructionData(
+	program: Program,
+	data: Buffer
+): DecodedIxArgs | undefined {
+	const decoded = program.coder.instruction.decode(data);
+	if (!decoded) {
+		return undefined;
+	}
+
+	const args = decoded.data as Record<string, unknown>;
+	if (args.direction) {
+		args.direction =
+			'long' in (args.direction as Record<string, unknown>)
+				? PositionDirection.LONG
+				: PositionDirection.SHORT;
+	}
+	return { name: decoded.name, args } as DecodedIxArgs;
+}
+
+/**
+ * Decodes a clearing_house instruction, naming its accounts after the idl
+ * @param program
+ * @param ix
+ * @returns decoded instruction, undefined if it isn't a clearing_house instruction
+ */
+export function decodeInstruction(
+	program: Program,
+	ix: TransactionInstruction
+): DecodedIx | undefined {
+	if (!ix.programId.equals(program.programId)) {
+		return undefined;
+	}
+
+	const decodedArgs = decodeInstructionData(program, ix.data);
+	if (!decodedArgs) {
+		return undefined;
+	}
+
+	const idlIx = program.idl.instructions.find(
+		(idlIx) => idlIx.name === decodedArgs.name
+	);
+	const accounts: Record<string, PublicKey> = {};
+	idlIx.accounts.forEach((account, i) => {
+		if (i < ix.keys.length) {
+			accounts[account.name] = ix.keys[i].pubkey;
+		}
+	});
+	const remainingAccounts = ix.keys
+		.slice(idlIx.accounts.length)
+		.map((key) => key.pubkey);
+
+	return { ...decodedArgs, accounts, remainingAccounts };
+}
+
+/**
+ * Decodes every clearing_house instruction in a transaction, in order. Other programs' instructions are skipped
+ * @param program
+ * @param tx a Transaction, or the message of a confirmed transaction from connection.getTransaction
+ * @returns decoded instructions
+ */
+export function decodeTransaction(
+	program: Program,
+	tx: Transaction | Message
+): DecodedIx[] {
+	const transaction = tx instanceof Transaction ? tx : Transaction.populate(tx);
+	return transaction.instructions
+		.map((ix) => decodeInstruction(program, ix))
+		.filter((decoded) => decoded !== undefined);
+}
@@ -0,0 +1,145 @@
+import { Program } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+
+export type DriftEvent =
+	| { type: 'invoke'; programId: PublicKey; depth: number }
+	| { type: 'log'; programId: PublicKey; message: string }
+	| {
+			type: 'event';
+			programId: PublicKey;
+			/** name of the anchor event emitted by the clearing house */
+			name: string;
+			data: Record<string, unknown>;
+	  }
+	| {
+			type: 'computeUnits';
+			programId: PublicKey;
+			consumed: number;
+			limit: number;
+	  }
+	| { type: 'success'; programId: PublicKey }
+	| {
+			type: 'error';
+			programId: PublicKey;
+			/** custom program error code, if the failure was one */
+			code?: number;
+			/** idl name and message of a clearing house error code */
+			name?: string;
+			msg?: string;
+			/** the failure as logged by the runtime */
+			message: string;
+	  };
+
+const INVOKE_PATTERN = /^Program (\w+) invoke \[(\d+)\]$/;
+const LOG_PREFIX = 'Program log: ';
+const DATA_PREFIX = 'Program data: ';
+const CONSUMED_PATTERN =
+	/^Program (\w+) consumed (\d+) of (\d+) compute units$/;
+const SUCCESS_PATTERN = /^Program (\w+) success$/;
+const FAILED_PATTERN = /^Program (\w+) failed: (.*)$/;
+const CUSTOM_ERROR_PATTERN = /custom program error: (0x[0-9a-fA-F]+)/;
+
+/**
+ * Parses a transaction's log messages into events: program invocations, logs, anchor events emitted by the clearing house,
+ * compute units consumed and failures, with clearing house error codes resolved to their idl name and message
+ * @param program
+ * @param logs meta.logMessages of a confirmed or simulated transaction
+ * @returns events in log order
+ */
+export function parseLogs(program: Program, logs: string[]): DriftEvent[] {
+	const events: DriftEvent[] = [];
+	// the program whose invocation is currently executing
+	const stack: PublicKey[] = [];
+
+	for (const log of logs) {
+		const current = stack[stack.length - 1];
+
+		let match = log.match(INVOKE_PATTERN);
+		if (match) {
+			const programId = new PublicKey(match[1]);
+			stack.push(programId);
+			events.push({ type: 'invoke', programId, depth: Number(match[2]) });
+			continue;
+		}
+
+		if (log.startsWith(LOG_PREFIX) || log.startsWith(DATA_PREFIX)) {
+			const message = log.startsWith(LOG_PREFIX)
+				? log.slice(LOG_PREFIX.length)
+				: log.slice(DATA_PREFIX.length);
+			const event =
+				current && current.equals(program.programId)
+					? decodeEvent(program, message)
+					: undefined;
+			if (event) {
+				events.push({ type: 'event', programId: current, ...event });
+			} else {
+				events.push({ type: 'log', programId: current, message });
+			}
+			continue;
+		}
+
+		match = log.match(CONSUMED_PATTERN);
+		if (match) {
+			events.push({
+				type: 'computeUnits',
+				programId: new PublicKey(match[1]),
+				consumed: Number(match[2]),
+				limit: Number(match[3]),
+			});
+			continue;
+		}
+
+		match = log.match(SUCCESS_PATTERN);
+		if (match) {
+			stack.pop();
+			events.push({ type: 'success', programId: new PublicKey(match[1]) });
+			continue;
+		}
+
+		match = log.match(FAILED_PATTERN);
+		if (match) {
+			stack.pop();
+			const programId = new PublicKey(match[1]);
+			events.push({
+				type: 'error',
+				programId,
+				...parseError(program, programId, match[2]),
+				message: match[2],
+			});
+		}
+	}
+
+	return events;
+}
+
+function decodeEvent(
+	program: Program,
+	message: string
+): { name: string; data: Record<string, unknown> } | undefined {
+	try {
+		const event = program.coder.events.decode(message);
+		return event
+			? { name: event.name, data: event.data as Record<string, unknown> }
+			: undefined;
+	} catch (e) {
+		// a plain msg! log, not base64 event data
+		return undefined;
+	}
+}
+
+function parseError(
+	program: Program,
+	programId: PublicKey,
+	message: string
+): { code?: number; name?: string; msg?: string } {
+	const match = message.match(CUSTOM_ERROR_PATTERN);
+	if (!match) {
+		return {};
+	}
+
+	const code = parseInt(match[1], 16);
+	const idlError = programId.equals(program.programId)
+		? (program.idl.errors ?? []).find((error) => error.code === code)
+		: undefined;
+	return { code, name: idlError?.name, msg: idlError?.msg };
+}
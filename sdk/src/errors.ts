@@ -0,0 +1,129 @@
+import { PublicKey } from '@solana/web3.js';
+
+/**
+ * Errors thrown by the SDK when it can tell locally that a transaction would fail on-chain
+ */
+export class TradeSizeTooSmallError extends Error {
+	name = 'TradeSizeTooSmallError';
+}
+
+export class InsufficientCollateralError extends Error {
+	name = 'InsufficientCollateralError';
+}
+
+export class SlippageOutsideLimitError extends Error {
+	name = 'SlippageOutsideLimitError';
+}
+
+export class MaxNumberOfPositionsError extends Error {
+	name = 'MaxNumberOfPositionsError';
+}
+
+export class TxSimulationError extends Error {
+	name = 'TxSimulationError';
+	logs: string[];
+
+	constructor(message: string, logs: string[] = []) {
+		super(message);
+		this.logs = logs;
+	}
+}
+
+export class TxExpiredError extends Error {
+	name = 'TxExpiredError';
+}
+
+export class OracleInvalidError extends Error {
+	name = 'OracleInvalidError';
+}
+
+export class ExchangePausedError extends Error {
+	name = 'ExchangePausedError';
+}
+
+export class IncompatibleProgramVersionError extends Error {
+	name = 'IncompatibleProgramVersionError';
+}
+
+export class UserAccountNotFoundError extends Error {
+	name = 'UserAccountNotFoundError';
+	authority: PublicKey;
+
+	constructor(authority: PublicKey) {
+		super(`No user account for authority ${authority.toBase58()}`);
+		this.authority = authority;
+	}
+}
+
+export class InsufficientSolForFeesError extends Error {
+	name = 'InsufficientSolForFeesError';
+	feePayer: PublicKey;
+	requiredLamports: number;
+	balanceLamports: number;
+
+	constructor(
+		feePayer: PublicKey,
+		requiredLamports: number,
+		balanceLamports: number
+	) {
+		super(
+			`Fee payer ${feePayer.toBase58()} has ${balanceLamports} lamports, the transaction needs ${requiredLamports}`
+		);
+		this.feePayer = feePayer;
+		this.requiredLamports = requiredLamports;
+		this.balanceLamports = balanceLamports;
+	}
+}
+
+export class NotWhitelistedError extends Error {
+	name = 'NotWhitelistedError';
+	authority: PublicKey;
+	whitelistMint: PublicKey;
+	/** the associated token account expected to hold the whitelist token */
+	associatedTokenAccount: PublicKey;
+
+	constructor(
+		authority: PublicKey,
+		whitelistMint: PublicKey,
+		associatedTokenAccount: PublicKey
+	) {
+		super(
+			`Authority ${authority.toBase58()} doesn't hold whitelist token ${whitelistMint.toBase58()} in ${associatedTokenAccount.toBase58()}`
+		);
+		this.authority = authority;
+		this.whitelistMint = whitelistMint;
+		this.associatedTokenAccount = associatedTokenAccount;
+	}
+}
+
+export class TokenAccountNotFoundError extends Error {
+	name = 'TokenAccountNotFoundError';
+	publicKey: PublicKey;
+
+	constructor(publicKey: PublicKey) {
+		super(`Token account ${publicKey.toBase58()} doesn't exist`);
+		this.publicKey = publicKey;
+	}
+}
+
+export class InvalidTokenAccountError extends Error {
+	name = 'InvalidTokenAccountError';
+	publicKey: PublicKey;
+
+	constructor(publicKey: PublicKey) {
+		super(`Account ${publicKey.toBase58()} isn't a token account`);
+		this.publicKey = publicKey;
+	}
+}
+
+export class ClearingHouseNotInitializedError extends Error {
+	name = 'ClearingHouseNotInitializedError';
+	statePublicKey: PublicKey;
+
+	constructor(statePublicKey: PublicKey) {
+		super(
+			`Clearing house state ${statePublicKey.toBase58()} doesn't exist, the clearing house hasn't been initialized`
+		);
+		this.statePublicKey = statePublicKey;
+	}
+}
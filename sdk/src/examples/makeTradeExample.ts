@@ -13,6 +13,8 @@ import {
 	calculateTradeSlippage,
 	MARK_PRICE_PRECISION,
 	QUOTE_PRECISION,
+	toCollateralAmount,
+	toQuoteAssetAmount,
 } from '..';
 
 export const getTokenAddress = (
@@ -74,7 +76,7 @@ const main = async () => {
 
 	if (!userAccountExists) {
 		//// Create a Clearing House account by Depositing some USDC ($10,000 in this case)
-		const depositAmount = new BN(10000).mul(QUOTE_PRECISION);
+		const depositAmount = toCollateralAmount(10000);
 		await clearingHouse.initializeUserAccountAndDepositCollateral(
 			depositAmount,
 			await getTokenAddress(
@@ -118,7 +120,7 @@ const main = async () => {
 	// Make a $5000 LONG trade
 	await clearingHouse.openPosition(
 		PositionDirection.LONG,
-		new BN(5000).mul(QUOTE_PRECISION),
+		toQuoteAssetAmount(5000),
 		solMarketInfo.marketIndex
 	);
 	console.log(`LONGED $5000 SOL`);
@@ -126,7 +128,7 @@ const main = async () => {
 	// Make a $5000 LONG trade
 	await clearingHouse.openPosition(
 		PositionDirection.LONG,
-		new BN(5000).mul(QUOTE_PRECISION),
+		toQuoteAssetAmount(5000),
 		solMarketInfo.marketIndex
 	);
 	console.log(`LONGED $5000 worth of SOL`);
@@ -134,7 +136,7 @@ const main = async () => {
 	// Reduce the position by $2000
 	await clearingHouse.openPosition(
 		PositionDirection.SHORT,
-		new BN(2000).mul(QUOTE_PRECISION),
+		toQuoteAssetAmount(2000),
 		solMarketInfo.marketIndex
 	);
 
@@ -0,0 +1,216 @@
+import { BN } from '@project-serum/anchor';
+import { TransactionSignature } from '@solana/web3.js';
+import { EventEmitter } from 'events';
+import StrictEventEmitter from 'strict-event-emitter-types';
+import { ClearingHouse } from '../clearingHouse';
+import { MARK_PRICE_PRECISION, ZERO } from '../constants/numericConstants';
+import { toPrice, toQuoteAssetAmount } from '../math/conversion';
+import { calculateMarkPrice } from '../math/market';
+import { calculateTradeSlippage, isTradeSizeTooSmall } from '../math/trade';
+import { PositionDirection, Price, QuoteAssetAmount } from '../types';
+
+export type IcebergExecutorConfig = {
+	/** size of each child trade : Precision QUOTE_PRECISION */
+	childAmount: QuoteAssetAmount;
+	/** child trades are only sent while their estimated max price impact is at or below this : Precision MARK_PRICE_PRECISION */
+	maxPriceImpact: BN;
+	/** delay between child trades, in ms */
+	childIntervalMs: number;
+	/** while paused, how long to wait for a markets update before checking the price impact again, in ms */
+	resumeCheckMs: number;
+	/** consecutive failed child trades before the execution is aborted */
+	maxConsecutiveFailures: number;
+};
+
+export const DEFAULT_ICEBERG_EXECUTOR_CONFIG: Omit<
+	IcebergExecutorConfig,
+	'childAmount'
+> = {
+	maxPriceImpact: MARK_PRICE_PRECISION.divn(200), // 0.5%
+	childIntervalMs: 1000,
+	resumeCheckMs: 5000,
+	maxConsecutiveFailures: 3,
+};
+
+export type IcebergExecution = {
+	direction: PositionDirection;
+	marketIndex: BN;
+	/** Precision QUOTE_PRECISION */
+	totalAmount: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	filledAmount: QuoteAssetAmount;
+	signatures: TransactionSignature[];
+	/** false if cancelled or aborted before the total amount was filled */
+	completed: boolean;
+};
+
+export interface IcebergExecutorEvents {
+	childFilled: (
+		signature: TransactionSignature,
+		amount: QuoteAssetAmount,
+		execution: IcebergExecution
+	) => void;
+	paused: (priceImpact: BN, execution: IcebergExecution) => void;
+	resumed: (execution: IcebergExecution) => void;
+	error: (error: Error, execution: IcebergExecution) => void;
+}
+
+/**
+ * # IcebergExecutor
+ * Splits a large order into child trades and only sends a child while its estimated price impact against the AMM
+ * stays under a threshold. When impact is too high, execution pauses until markets updates show the AMM has recovered.
+ *
+ * Each child carries a limit price at the impact threshold so a stale estimate can't fill worse than allowed.
+ * The ClearingHouse must be subscribed so the markets account is current.
+ */
+export class IcebergExecutor {
+	clearingHouse: ClearingHouse;
+	config: IcebergExecutorConfig;
+	eventEmitter: StrictEventEmitter<EventEmitter, IcebergExecutorEvents>;
+
+	private cancelled = false;
+
+	public constructor(
+		clearingHouse: ClearingHouse,
+		config: Partial<IcebergExecutorConfig> &
+			Pick<IcebergExecutorConfig, 'childAmount'>
+	) {
+		this.clearingHouse = clearingHouse;
+		this.config = { ...DEFAULT_ICEBERG_EXECUTOR_CONFIG, ...config };
+		this.eventEmitter = new EventEmitter();
+	}
+
+	/**
+	 * Stops the running execution after its current child trade
+	 */
+	public cancel(): void {
+		this.cancelled = true;
+	}
+
+	/**
+	 * @param direction
+	 * @param totalAmount : Precision QUOTE_PRECISION
+	 * @param marketIndex
+	 * @returns once the total amount is filled, or the execution was cancelled or aborted
+	 */
+	public async execute(
+		direction: PositionDirection,
+		totalAmount: QuoteAssetAmount,
+		marketIndex: BN
+	): Promise<IcebergExecution> {
+		this.cancelled = false;
+		const execution: IcebergExecution = {
+			direction,
+			marketIndex,
+			totalAmount,
+			filledAmount: toQuoteAssetAmount(ZERO),
+			signatures: [],
+			completed: false,
+		};
+
+		let paused = false;
+		let consecutiveFailures = 0;
+		while (!this.cancelled) {
+			const remaining = toQuoteAssetAmount(
+				totalAmount.sub(execution.filledAmount)
+			);
+			const market = this.clearingHouse.getMarket(marketIndex);
+			if (remaining.lte(ZERO) || isTradeSizeTooSmall(market, remaining)) {
+				execution.completed = true;
+				break;
+			}
+
+			// fold a remainder too small to trade on its own into the last child
+			let childAmount = toQuoteAssetAmount(
+				BN.min(this.config.childAmount, remaining)
+			);
+			if (isTradeSizeTooSmall(market, remaining.sub(childAmount))) {
+				childAmount = remaining;
+			}
+
+			const [_pctAvgSlippage, priceImpact, _entryPrice, _newPrice] =
+				calculateTradeSlippage(direction, childAmount, market);
+			if (priceImpact.gt(this.config.maxPriceImpact)) {
+				if (!paused) {
+					paused = true;
+					this.eventEmitter.emit('paused', priceImpact, execution);
+				}
+				await this.waitForMarketsUpdate();
+				continue;
+			}
+
+			if (paused) {
+				paused = false;
+				this.eventEmitter.emit('resumed', execution);
+			}
+
+			try {
+				const signature = await this.clearingHouse.openPosition(
+					direction,
+					childAmount,
+					marketIndex,
+					this.calculateLimitPrice(direction, marketIndex)
+				);
+				consecutiveFailures = 0;
+				execution.filledAmount = toQuoteAssetAmount(
+					execution.filledAmount.add(childAmount)
+				);
+				execution.signatures.push(signature);
+				this.eventEmitter.emit(
+					'childFilled',
+					signature,
+					childAmount,
+					execution
+				);
+			} catch (e) {
+				// emitting error without a listener throws, which would abandon the execution on the first failed child
+				if (this.eventEmitter.listenerCount('error') > 0) {
+					this.eventEmitter.emit('error', e, execution);
+				}
+				if (++consecutiveFailures >= this.config.maxConsecutiveFailures) {
+					break;
+				}
+			}
+
+			await new Promise((resolve) =>
+				setTimeout(resolve, this.config.childIntervalMs)
+			);
+		}
+
+		return execution;
+	}
+
+	/**
+	 * @returns worst acceptable entry price given the impact threshold : Precision MARK_PRICE_PRECISION
+	 */
+	private calculateLimitPrice(
+		direction: PositionDirection,
+		marketIndex: BN
+	): Price {
+		const markPrice = calculateMarkPrice(
+			this.clearingHouse.getMarket(marketIndex)
+		);
+		const multiplier =
+			direction === PositionDirection.LONG
+				? MARK_PRICE_PRECISION.add(this.config.maxPriceImpact)
+				: MARK_PRICE_PRECISION.sub(this.config.maxPriceImpact);
+		return toPrice(markPrice.mul(multiplier).div(MARK_PRICE_PRECISION));
+	}
+
+	private waitForMarketsUpdate(): Promise<void> {
+		return new Promise((resolve) => {
+			const onUpdate = () => {
+				clearTimeout(timeoutId);
+				resolve();
+			};
+			const timeoutId = setTimeout(() => {
+				this.clearingHouse.eventEmitter.removeListener(
+					'marketsAccountUpdate',
+					onUpdate
+				);
+				resolve();
+			}, this.config.resumeCheckMs);
+			this.clearingHouse.eventEmitter.once('marketsAccountUpdate', onUpdate);
+		});
+	}
+}
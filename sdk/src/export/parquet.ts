@@ -0,0 +1,197 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import {
+	FundingPaymentRecord,
+	FundingRateRecord,
+	LiquidationRecord,
+	TradeRecord,
+} from '../types';
+
+/**
+ * Bumped whenever a column is renamed, removed or changes type. Columns are only ever added within a version.
+ * Written to every file's key value metadata as drift_schema_version
+ */
+export const PARQUET_SCHEMA_VERSION = 1;
+
+type ParquetColumn = {
+	type: 'UTF8' | 'INT64' | 'BOOLEAN' | 'TIMESTAMP_MILLIS';
+};
+
+// amounts are exact integer strings at their on-chain precision: they overflow INT64 and doubles would lose precision
+const AMOUNT: ParquetColumn = { type: 'UTF8' };
+const PUBLIC_KEY: ParquetColumn = { type: 'UTF8' };
+const INDEX: ParquetColumn = { type: 'INT64' };
+const TIMESTAMP: ParquetColumn = { type: 'TIMESTAMP_MILLIS' };
+const BOOLEAN: ParquetColumn = { type: 'BOOLEAN' };
+
+export const PARQUET_SCHEMAS = {
+	trade: {
+		ts: TIMESTAMP,
+		recordId: INDEX,
+		userAuthority: PUBLIC_KEY,
+		user: PUBLIC_KEY,
+		/** long or short */
+		direction: { type: 'UTF8' },
+		baseAssetAmount: AMOUNT,
+		quoteAssetAmount: AMOUNT,
+		markPriceBefore: AMOUNT,
+		markPriceAfter: AMOUNT,
+		fee: AMOUNT,
+		referrerReward: AMOUNT,
+		refereeDiscount: AMOUNT,
+		tokenDiscount: AMOUNT,
+		marketIndex: INDEX,
+		liquidation: BOOLEAN,
+		oraclePrice: AMOUNT,
+	},
+	fundingRate: {
+		ts: TIMESTAMP,
+		recordId: INDEX,
+		marketIndex: INDEX,
+		fundingRate: AMOUNT,
+		cumulativeFundingRateLong: AMOUNT,
+		cumulativeFundingRateShort: AMOUNT,
+		oraclePriceTwap: AMOUNT,
+		markPriceTwap: AMOUNT,
+	},
+	fundingPayment: {
+		ts: TIMESTAMP,
+		recordId: INDEX,
+		userAuthority: PUBLIC_KEY,
+		user: PUBLIC_KEY,
+		marketIndex: INDEX,
+		fundingPayment: AMOUNT,
+		baseAssetAmount: AMOUNT,
+		userLastCumulativeFunding: AMOUNT,
+		userLastFundingRateTs: TIMESTAMP,
+		ammCumulativeFundingLong: AMOUNT,
+		ammCumulativeFundingShort: AMOUNT,
+	},
+	liquidation: {
+		ts: TIMESTAMP,
+		recordId: INDEX,
+		userAuthority: PUBLIC_KEY,
+		user: PUBLIC_KEY,
+		partial: BOOLEAN,
+		baseAssetValue: AMOUNT,
+		baseAssetValueClosed: AMOUNT,
+		liquidationFee: AMOUNT,
+		feeToLiquidator: AMOUNT,
+		feeToInsuranceFund: AMOUNT,
+		liquidator: PUBLIC_KEY,
+		totalCollateral: AMOUNT,
+		collateral: AMOUNT,
+		unrealizedPnl: AMOUNT,
+		marginRatio: AMOUNT,
+	},
+};
+
+export type ParquetRecordKind = keyof typeof PARQUET_SCHEMAS;
+
+type ParquetRecords = {
+	trade: TradeRecord;
+	fundingRate: FundingRateRecord;
+	fundingPayment: FundingPaymentRecord;
+	liquidation: LiquidationRecord;
+};
+
+/**
+ * Loads parquetjs-lite, an optional dependency installed only by users of the parquet export
+ */
+function loadParquet() {
+	try {
+		return require('parquetjs-lite');
+	} catch (e) {
+		throw new Error(
+			'Parquet export requires the optional dependency parquetjs-lite, install it with `yarn add parquetjs-lite`'
+		);
+	}
+}
+
+/**
+ * Appends history records of one kind to a parquet file as they stream in, e.g. from onFill or a history account subscription.
+ * Rows are buffered by row group and the file is only readable once closed
+ */
+export class ParquetRecordWriter<K extends ParquetRecordKind> {
+	kind: K;
+	private writer: any;
+
+	private constructor(kind: K, writer: any) {
+		this.kind = kind;
+		this.writer = writer;
+	}
+
+	/**
+	 * @param kind
+	 * @param path file to create, overwritten if it exists
+	 * @returns writer
+	 */
+	public static async open<K extends ParquetRecordKind>(
+		kind: K,
+		path: string
+	): Promise<ParquetRecordWriter<K>> {
+		const parquet = loadParquet();
+		const writer = await parquet.ParquetWriter.openFile(
+			new parquet.ParquetSchema(PARQUET_SCHEMAS[kind]),
+			path
+		);
+		writer.setMetadata(
+			'drift_schema_version',
+			PARQUET_SCHEMA_VERSION.toString()
+		);
+		writer.setMetadata('drift_record_kind', kind);
+		return new ParquetRecordWriter(kind, writer);
+	}
+
+	public async append(record: ParquetRecords[K]): Promise<void> {
+		await this.writer.appendRow(
+			toRow(this.kind, record as Record<string, unknown>)
+		);
+	}
+
+	public async close(): Promise<void> {
+		await this.writer.close();
+	}
+}
+
+/**
+ * Writes a batch of history records to a new parquet file
+ * @param kind
+ * @param path
+ * @param records
+ */
+export async function exportRecordsToParquet<K extends ParquetRecordKind>(
+	kind: K,
+	path: string,
+	records: ParquetRecords[K][]
+): Promise<void> {
+	const writer = await ParquetRecordWriter.open(kind, path);
+	for (const record of records) {
+		await writer.append(record);
+	}
+	await writer.close();
+}
+
+function toRow(
+	kind: ParquetRecordKind,
+	record: Record<string, unknown>
+): Record<string, unknown> {
+	const schema = PARQUET_SCHEMAS[kind];
+	const row: Record<string, unknown> = {};
+	for (const column of Object.keys(schema)) {
+		const value = record[column];
+		if (column === 'direction') {
+			row[column] =
+				'long' in (value as Record<string, unknown>) ? 'long' : 'short';
+		} else if (schema[column] === TIMESTAMP) {
+			row[column] = new Date((value as BN).toNumber() * 1000);
+		} else if (schema[column] === INDEX) {
+			row[column] = (value as BN).toNumber();
+		} else if (BN.isBN(value) || value instanceof PublicKey) {
+			row[column] = value.toString();
+		} else {
+			row[column] = value;
+		}
+	}
+	return row;
+}
@@ -0,0 +1,257 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import fs from 'fs';
+import {
+	AMM_RESERVE_PRECISION,
+	AMM_TO_QUOTE_PRECISION_RATIO,
+	QUOTE_PRECISION,
+	ZERO,
+} from '../constants/numericConstants';
+import { getOrderedRecords, isInTimeWindow } from '../history/utils';
+import { FundingPaymentRecord, TradeRecord } from '../types';
+import { formatBN, getMarketSymbol } from '../util/format';
+
+/**
+ * close: a realized gain of an opening trade matched against a closing trade, funding: a settled funding payment
+ */
+export type TaxRowType = 'close' | 'funding';
+
+/**
+ * Amounts at their on-chain precision, realized gains and funding are signed with positive being income
+ */
+export type TaxRow = {
+	type: TaxRowType;
+	/** when the gain was realized: the closing trade or the funding settlement */
+	ts: BN;
+	/** the closing trade or funding payment record */
+	recordId: BN;
+	marketIndex: BN;
+	/** direction of the closed position, undefined for funding */
+	direction?: 'long' | 'short';
+	/** when the closed position was opened, undefined for funding */
+	openTs?: BN;
+	/** base asset amount closed, zero for funding : Precision AMM_RESERVE_PRECISION */
+	baseAssetAmount: BN;
+	/** quote value of the position when opened : Precision QUOTE_PRECISION */
+	costBasis: BN;
+	/** quote value of the position when closed : Precision QUOTE_PRECISION */
+	proceeds: BN;
+	/** share of the opening and closing trades' fees : Precision QUOTE_PRECISION */
+	fees: BN;
+	/** Precision QUOTE_PRECISION */
+	funding: BN;
+	/** proceeds - costBasis (inverted for shorts) - fees + funding : Precision QUOTE_PRECISION */
+	realizedGain: BN;
+};
+
+/**
+ * Columns of the csv written by {@link formatTaxCsv}, in order. Dates are ISO 8601 in utc, amounts are exact decimals
+ * with base asset amounts in base units and every other amount in USDC
+ */
+export const TAX_CSV_COLUMNS = [
+	'type',
+	'date',
+	'record_id',
+	'market',
+	'direction',
+	'open_date',
+	'base_asset_amount',
+	'cost_basis',
+	'proceeds',
+	'fees',
+	'funding',
+	'realized_gain',
+];
+
+type Lot = {
+	ts: BN;
+	long: boolean;
+	/** Precision AMM_RESERVE_PRECISION */
+	baseAssetAmount: BN;
+	/** Precision QUOTE_PRECISION */
+	quoteAssetAmount: BN;
+	/** Precision QUOTE_PRECISION */
+	fee: BN;
+};
+
+/**
+ * Builds a user's realized gains for a calendar year. Positions are matched first in, first out: every opening trade is a lot and
+ * closing trades consume the oldest lots, one row per lot consumed. Fees are split across rows in proportion to base asset amount.
+ *
+ * This version of the program doesn't log records, so they can't be recovered from transactions. Pass every record since the user's
+ * first trade, e.g. persisted from the history account subscriptions, so lots opened in earlier years have a cost basis
+ * @param tradeRecords
+ * @param fundingPaymentRecords
+ * @param userAuthority
+ * @param year utc calendar year
+ * @returns rows ordered by ts
+ */
+export function calculateTaxRows(
+	tradeRecords: TradeRecord[],
+	fundingPaymentRecords: FundingPaymentRecord[],
+	userAuthority: PublicKey,
+	year: number
+): TaxRow[] {
+	const startTs = new BN(Date.UTC(year, 0, 1) / 1000);
+	const endTs = new BN(Date.UTC(year + 1, 0, 1) / 1000);
+	const rows: TaxRow[] = [];
+	const lotsByMarket = new Map<number, Lot[]>();
+
+	const userTradeRecords = getOrderedRecords(tradeRecords).filter((record) =>
+		record.userAuthority.equals(userAuthority)
+	);
+	for (const record of userTradeRecords) {
+		const lots = lotsByMarket.get(record.marketIndex.toNumber()) ?? [];
+		lotsByMarket.set(record.marketIndex.toNumber(), lots);
+		const long = !!record.direction.long;
+
+		let baseAssetAmountRemaining = record.baseAssetAmount;
+		let quoteAssetAmountRemaining = record.quoteAssetAmount;
+		let feeRemaining = record.fee;
+		while (
+			lots.length > 0 &&
+			lots[0].long !== long &&
+			baseAssetAmountRemaining.gt(ZERO)
+		) {
+			const lot = lots[0];
+			const baseAssetAmountClosed = BN.min(
+				lot.baseAssetAmount,
+				baseAssetAmountRemaining
+			);
+			const costBasis = lot.quoteAssetAmount
+				.mul(baseAssetAmountClosed)
+				.div(lot.baseAssetAmount);
+			const openFee = lot.fee
+				.mul(baseAssetAmountClosed)
+				.div(lot.baseAssetAmount);
+			const proceeds = record.quoteAssetAmount
+				.mul(baseAssetAmountClosed)
+				.div(record.baseAssetAmount);
+			const closeFee = record.fee
+				.mul(baseAssetAmountClosed)
+				.div(record.baseAssetAmount);
+
+			lot.baseAssetAmount = lot.baseAssetAmount.sub(baseAssetAmountClosed);
+			lot.quoteAssetAmount = lot.quoteAssetAmount.sub(costBasis);
+			lot.fee = lot.fee.sub(openFee);
+			if (lot.baseAssetAmount.eq(ZERO)) {
+				lots.shift();
+			}
+			baseAssetAmountRemaining = baseAssetAmountRemaining.sub(
+				baseAssetAmountClosed
+			);
+			quoteAssetAmountRemaining = quoteAssetAmountRemaining.sub(proceeds);
+			feeRemaining = feeRemaining.sub(closeFee);
+
+			if (!isInTimeWindow(record.ts, startTs, endTs)) {
+				continue;
+			}
+
+			const fees = openFee.add(closeFee);
+			const tradingGain = lot.long
+				? proceeds.sub(costBasis)
+				: costBasis.sub(proceeds);
+			rows.push({
+				type: 'close',
+				ts: record.ts,
+				recordId: record.recordId,
+				marketIndex: record.marketIndex,
+				direction: lot.long ? 'long' : 'short',
+				openTs: lot.ts,
+				baseAssetAmount: baseAssetAmountClosed,
+				costBasis,
+				proceeds,
+				fees,
+				funding: ZERO,
+				realizedGain: tradingGain.sub(fees),
+			});
+		}
+
+		// the rest of the trade, if any, opens or increases a position
+		if (baseAssetAmountRemaining.gt(ZERO)) {
+			lots.push({
+				ts: record.ts,
+				long,
+				baseAssetAmount: baseAssetAmountRemaining,
+				quoteAssetAmount: quoteAssetAmountRemaining,
+				fee: feeRemaining,
+			});
+		}
+	}
+
+	const userFundingPaymentRecords = getOrderedRecords(
+		fundingPaymentRecords
+	).filter(
+		(record) =>
+			record.userAuthority.equals(userAuthority) &&
+			isInTimeWindow(record.ts, startTs, endTs)
+	);
+	for (const record of userFundingPaymentRecords) {
+		// funding payments are recorded with AMM_RESERVE_PRECISION, positive means the user received funding
+		const funding = record.fundingPayment.div(AMM_TO_QUOTE_PRECISION_RATIO);
+		rows.push({
+			type: 'funding',
+			ts: record.ts,
+			recordId: record.recordId,
+			marketIndex: record.marketIndex,
+			baseAssetAmount: ZERO,
+			costBasis: ZERO,
+			proceeds: ZERO,
+			fees: ZERO,
+			funding,
+			realizedGain: funding,
+		});
+	}
+
+	return rows.sort((a, b) => a.ts.cmp(b.ts));
+}
+
+/**
+ * @param rows
+ * @returns csv with a header row, see {@link TAX_CSV_COLUMNS}
+ */
+export function formatTaxCsv(rows: TaxRow[]): string {
+	const formatDate = (ts?: BN) =>
+		ts ? new Date(ts.toNumber() * 1000).toISOString() : '';
+	const lines = rows.map((row) =>
+		[
+			row.type,
+			formatDate(row.ts),
+			row.recordId.toString(),
+			getMarketSymbol(row.marketIndex),
+			row.direction ?? '',
+			formatDate(row.openTs),
+			formatBN(row.baseAssetAmount, AMM_RESERVE_PRECISION),
+			formatBN(row.costBasis, QUOTE_PRECISION),
+			formatBN(row.proceeds, QUOTE_PRECISION),
+			formatBN(row.fees, QUOTE_PRECISION),
+			formatBN(row.funding, QUOTE_PRECISION),
+			formatBN(row.realizedGain, QUOTE_PRECISION),
+		].join(',')
+	);
+	return [TAX_CSV_COLUMNS.join(','), ...lines].join('\n') + '\n';
+}
+
+/**
+ * Writes a user's realized gains for a calendar year to a csv file, see {@link calculateTaxRows}
+ * @param path file to create, overwritten if it exists
+ * @param tradeRecords
+ * @param fundingPaymentRecords
+ * @param userAuthority
+ * @param year utc calendar year
+ */
+export async function exportTaxCsv(
+	path: string,
+	tradeRecords: TradeRecord[],
+	fundingPaymentRecords: FundingPaymentRecord[],
+	userAuthority: PublicKey,
+	year: number
+): Promise<void> {
+	const rows = calculateTaxRows(
+		tradeRecords,
+		fundingPaymentRecords,
+		userAuthority,
+		year
+	);
+	await fs.promises.writeFile(path, formatTaxCsv(rows));
+}
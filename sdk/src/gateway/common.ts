@@ -0,0 +1,132 @@
+import { BN } from '@project-serum/anchor';
+import { TransactionSignature } from '@solana/web3.js';
+import { ClearingHouse } from '../clearingHouse';
+import { toPrice, toQuoteAssetAmount } from '../math/conversion';
+import { calculateMarketStats, calculateNextFundingTime } from '../math/market';
+import { MarketStats, PositionDirection } from '../types';
+
+/**
+ * An order placed through a gateway. Amounts are integer strings at their on-chain precision
+ */
+export type OrderRequest =
+	| {
+			type: 'open';
+			marketIndex: string;
+			direction: 'long' | 'short';
+			/** Precision QUOTE_PRECISION */
+			quoteAssetAmount: string;
+			/** Precision MARK_PRICE_PRECISION, no limit if undefined */
+			limitPrice?: string;
+	  }
+	| {
+			type: 'close';
+			marketIndex: string;
+	  };
+
+export type MarketFunding = {
+	marketIndex: BN;
+	/** Precision MARK_PRICE_PRECISION * FUNDING_PAYMENT_PRECISION */
+	lastFundingRate: BN;
+	lastFundingRateTs: BN;
+	nextFundingTs: BN;
+	/** Precision MARK_PRICE_PRECISION * FUNDING_PAYMENT_PRECISION */
+	cumulativeFundingRateLong: BN;
+	/** Precision MARK_PRICE_PRECISION * FUNDING_PAYMENT_PRECISION */
+	cumulativeFundingRateShort: BN;
+	/** Precision MARK_PRICE_PRECISION */
+	markPriceTwap: BN;
+	/** Precision MARK_PRICE_PRECISION */
+	oraclePriceTwap: BN;
+};
+
+/**
+ * A malformed gateway request, as opposed to a failure placing it
+ */
+export class GatewayRequestError extends Error {
+	name = 'GatewayRequestError';
+}
+
+/**
+ * @param clearingHouse
+ * @returns stats of every initialized market
+ */
+export function getInitializedMarketStats(
+	clearingHouse: ClearingHouse
+): MarketStats[] {
+	return clearingHouse
+		.getMarketsAccount()
+		.markets.reduce((stats, market, i) => {
+			if (market.initialized) {
+				stats.push(calculateMarketStats(market, new BN(i)));
+			}
+			return stats;
+		}, [] as MarketStats[]);
+}
+
+/**
+ * @param clearingHouse
+ * @returns funding of every initialized market
+ */
+export function getInitializedMarketFunding(
+	clearingHouse: ClearingHouse
+): MarketFunding[] {
+	return clearingHouse
+		.getMarketsAccount()
+		.markets.reduce((funding, market, i) => {
+			if (market.initialized) {
+				const amm = market.amm;
+				funding.push({
+					marketIndex: new BN(i),
+					lastFundingRate: amm.lastFundingRate,
+					lastFundingRateTs: amm.lastFundingRateTs,
+					nextFundingTs: calculateNextFundingTime(market),
+					cumulativeFundingRateLong: amm.cumulativeFundingRateLong,
+					cumulativeFundingRateShort: amm.cumulativeFundingRateShort,
+					markPriceTwap: amm.lastMarkPriceTwap,
+					oraclePriceTwap: amm.lastOraclePriceTwap,
+				});
+			}
+			return funding;
+		}, [] as MarketFunding[]);
+}
+
+/**
+ * Validates an order from an untyped request body and sends it with the clearing house's wallet
+ * @param clearingHouse
+ * @param order
+ * @returns transaction signature
+ * @throws GatewayRequestError
+ */
+export async function placeOrder(
+	clearingHouse: ClearingHouse,
+	order: OrderRequest
+): Promise<TransactionSignature> {
+	if (!order || order.marketIndex === undefined) {
+		throw new GatewayRequestError('marketIndex is required');
+	}
+	const marketIndex = new BN(order.marketIndex);
+
+	if (order.type === 'close') {
+		return await clearingHouse.closePosition(marketIndex);
+	}
+	if (order.type !== 'open') {
+		throw new GatewayRequestError(
+			`Unknown order type ${(order as { type: string }).type}`
+		);
+	}
+	if (order.direction !== 'long' && order.direction !== 'short') {
+		throw new GatewayRequestError('direction must be long or short');
+	}
+	if (!order.quoteAssetAmount) {
+		throw new GatewayRequestError('quoteAssetAmount is required');
+	}
+
+	return await clearingHouse.openPosition(
+		order.direction === 'long'
+			? PositionDirection.LONG
+			: PositionDirection.SHORT,
+		toQuoteAssetAmount(new BN(order.quoteAssetAmount)),
+		marketIndex,
+		order.limitPrice ? toPrice(new BN(order.limitPrice)) : undefined
+	);
+}
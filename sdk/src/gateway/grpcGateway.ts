@@ -0,0 +1,192 @@
+import path from 'path';
+import { ClearingHouse } from '../clearingHouse';
+import { ClearingHouseUser } from '../clearingHouseUser';
+import { toJson } from '../util/json';
+import {
+	GatewayRequestError,
+	getInitializedMarketFunding,
+	getInitializedMarketStats,
+	OrderRequest,
+	placeOrder,
+} from './common';
+
+/** proto of the DriftGateway service, shipped with the package */
+export const GRPC_GATEWAY_PROTO_PATH = path.join(
+	__dirname,
+	'../../proto/drift_gateway.proto'
+);
+
+export type GrpcGatewayConfig = {
+	port: number;
+	/** interface to listen on. Defaults to loopback, since the gateway trades with the server's wallet */
+	host: string;
+	/** if set, every call must carry it in the x-api-key metadata */
+	apiKey?: string;
+};
+
+export const DEFAULT_GRPC_GATEWAY_CONFIG: GrpcGatewayConfig = {
+	port: 50051,
+	host: '127.0.0.1',
+};
+
+/**
+ * Loads @grpc/grpc-js and @grpc/proto-loader, optional dependencies installed only by users of the grpc gateway
+ */
+function loadGrpc() {
+	try {
+		return {
+			grpc: require('@grpc/grpc-js'),
+			protoLoader: require('@grpc/proto-loader'),
+		};
+	} catch (e) {
+		throw new Error(
+			'The grpc gateway requires the optional dependencies @grpc/grpc-js and @grpc/proto-loader, install them with `yarn add @grpc/grpc-js @grpc/proto-loader`'
+		);
+	}
+}
+
+/**
+ * # GrpcGateway
+ * Serves the DriftGateway service of proto/drift_gateway.proto for a subscribed ClearingHouse and its wallet's ClearingHouseUser,
+ * the low latency counterpart of {@link RestGateway} for internal services. StreamAccountUpdates pushes the portfolio and markets
+ * on every account update instead of having clients poll
+ */
+export class GrpcGateway {
+	clearingHouse: ClearingHouse;
+	user: ClearingHouseUser;
+	config: GrpcGatewayConfig;
+
+	private grpc: any;
+	private server?: any;
+
+	public constructor(
+		clearingHouse: ClearingHouse,
+		user: ClearingHouseUser,
+		config: Partial<GrpcGatewayConfig> = {}
+	) {
+		if (!user.authority.equals(clearingHouse.wallet.publicKey)) {
+			throw new Error('GrpcGateway user must belong to the wallet');
+		}
+
+		this.clearingHouse = clearingHouse;
+		this.user = user;
+		this.config = { ...DEFAULT_GRPC_GATEWAY_CONFIG, ...config };
+	}
+
+	public async start(): Promise<void> {
+		if (this.server) {
+			return;
+		}
+
+		const { grpc, protoLoader } = loadGrpc();
+		this.grpc = grpc;
+		const packageDefinition = protoLoader.loadSync(GRPC_GATEWAY_PROTO_PATH, {
+			defaults: true,
+			oneofs: true,
+		});
+		const proto = grpc.loadPackageDefinition(packageDefinition).drift.gateway
+			.v1;
+
+		const server = new grpc.Server();
+		server.addService(proto.DriftGateway.service, {
+			getPortfolio: this.unary(() => this.getPortfolio()),
+			getMarkets: this.unary(() => this.getMarkets()),
+			getFunding: this.unary(() =>
+				toJson({ markets: getInitializedMarketFunding(this.clearingHouse) })
+			),
+			placeOrder: this.unary(async (request: OrderRequest) => {
+				return { txSig: await placeOrder(this.clearingHouse, request) };
+			}),
+			streamAccountUpdates: (call) => this.streamAccountUpdates(call),
+		});
+
+		await new Promise<void>((resolve, reject) =>
+			server.bindAsync(
+				`${this.config.host}:${this.config.port}`,
+				grpc.ServerCredentials.createInsecure(),
+				(error: Error | null) => (error ? reject(error) : resolve())
+			)
+		);
+		server.start();
+		this.server = server;
+	}
+
+	public async stop(): Promise<void> {
+		if (!this.server) {
+			return;
+		}
+
+		const server = this.server;
+		this.server = undefined;
+		await new Promise<void>((resolve) => server.tryShutdown(() => resolve()));
+	}
+
+	private getPortfolio() {
+		return toJson(this.user.getPortfolioSummary());
+	}
+
+	private getMarkets() {
+		return toJson({ markets: getInitializedMarketStats(this.clearingHouse) });
+	}
+
+	private streamAccountUpdates(call: any): void {
+		if (!this.isAuthorized(call)) {
+			call.emit('error', {
+				code: this.grpc.status.UNAUTHENTICATED,
+				details: 'Invalid api key',
+			});
+			return;
+		}
+
+		const onUserUpdate = () => call.write({ portfolio: this.getPortfolio() });
+		const onMarketsUpdate = () => call.write({ markets: this.getMarkets() });
+		this.user.eventEmitter.on('update', onUserUpdate);
+		this.clearingHouse.eventEmitter.on('marketsAccountUpdate', onMarketsUpdate);
+		call.on('cancelled', () => {
+			this.user.eventEmitter.removeListener('update', onUserUpdate);
+			this.clearingHouse.eventEmitter.removeListener(
+				'marketsAccountUpdate',
+				onMarketsUpdate
+			);
+		});
+
+		onUserUpdate();
+		onMarketsUpdate();
+	}
+
+	/**
+	 * Wraps a handler into a grpc unary call handler, checking the api key and mapping errors to grpc statuses
+	 */
+	private unary<Request>(
+		handler: (request: Request) => unknown | Promise<unknown>
+	) {
+		return async (call: any, callback: any): Promise<void> => {
+			if (!this.isAuthorized(call)) {
+				callback({
+					code: this.grpc.status.UNAUTHENTICATED,
+					details: 'Invalid api key',
+				});
+				return;
+			}
+
+			try {
+				callback(null, await handler(call.request as Request));
+			} catch (e) {
+				callback({
+					code:
+						e instanceof GatewayRequestError
+							? this.grpc.status.INVALID_ARGUMENT
+							: this.grpc.status.INTERNAL,
+					details: e.message,
+				});
+			}
+		};
+	}
+
+	private isAuthorized(call: any): boolean {
+		return (
+			!this.config.apiKey ||
+			call.metadata.get('x-api-key')[0] === this.config.apiKey
+		);
+	}
+}
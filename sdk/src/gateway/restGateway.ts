@@ -0,0 +1,150 @@
+import http from 'http';
+import { ClearingHouse } from '../clearingHouse';
+import { ClearingHouseUser } from '../clearingHouseUser';
+import { toJson } from '../util/json';
+import {
+	GatewayRequestError,
+	getInitializedMarketFunding,
+	getInitializedMarketStats,
+	OrderRequest,
+	placeOrder,
+} from './common';
+
+export type RestGatewayConfig = {
+	port: number;
+	/** interface to listen on. Defaults to loopback, since the gateway trades with the server's wallet */
+	host: string;
+	/** if set, every request must carry it in the x-api-key header */
+	apiKey?: string;
+};
+
+export const DEFAULT_REST_GATEWAY_CONFIG: RestGatewayConfig = {
+	port: 8080,
+	host: '127.0.0.1',
+};
+
+class HttpError extends Error {
+	status: number;
+
+	constructor(status: number, message: string) {
+		super(message);
+		this.status = status;
+	}
+}
+
+/**
+ * # RestGateway
+ * Exposes a subscribed ClearingHouse and its wallet's ClearingHouseUser over HTTP, for services that can't use the typescript sdk.
+ * Responses are json with BNs and public keys as strings, amounts at their on-chain precision.
+ *
+ * - GET /positions: the user's {@link PortfolioSummary}
+ * - GET /markets: {@link MarketStats} of every initialized market
+ * - GET /funding: {@link MarketFunding} of every initialized market
+ * - POST /orders: an {@link OrderRequest}, responds with the transaction signature
+ */
+export class RestGateway {
+	clearingHouse: ClearingHouse;
+	user: ClearingHouseUser;
+	config: RestGatewayConfig;
+	server?: http.Server;
+
+	public constructor(
+		clearingHouse: ClearingHouse,
+		user: ClearingHouseUser,
+		config: Partial<RestGatewayConfig> = {}
+	) {
+		if (!user.authority.equals(clearingHouse.wallet.publicKey)) {
+			throw new Error('RestGateway user must belong to the wallet');
+		}
+
+		this.clearingHouse = clearingHouse;
+		this.user = user;
+		this.config = { ...DEFAULT_REST_GATEWAY_CONFIG, ...config };
+	}
+
+	public async start(): Promise<void> {
+		if (this.server) {
+			return;
+		}
+
+		this.server = http.createServer((req, res) => {
+			this.handle(req, res);
+		});
+		await new Promise<void>((resolve) =>
+			this.server.listen(this.config.port, this.config.host, resolve)
+		);
+	}
+
+	public async stop(): Promise<void> {
+		if (!this.server) {
+			return;
+		}
+
+		const server = this.server;
+		this.server = undefined;
+		await new Promise<void>((resolve) => server.close(() => resolve()));
+	}
+
+	private async handle(
+		req: http.IncomingMessage,
+		res: http.ServerResponse
+	): Promise<void> {
+		try {
+			if (
+				this.config.apiKey &&
+				req.headers['x-api-key'] !== this.config.apiKey
+			) {
+				throw new HttpError(401, 'Invalid api key');
+			}
+
+			const route = `${req.method} ${(req.url ?? '').split('?')[0]}`;
+			switch (route) {
+				case 'GET /positions':
+					return send(res, 200, this.user.getPortfolioSummary());
+				case 'GET /markets':
+					return send(
+						res,
+						200,
+						getInitializedMarketStats(this.clearingHouse)
+					);
+				case 'GET /funding':
+					return send(
+						res,
+						200,
+						getInitializedMarketFunding(this.clearingHouse)
+					);
+				case 'POST /orders': {
+					const txSig = await placeOrder(
+						this.clearingHouse,
+						JSON.parse(await readBody(req)) as OrderRequest
+					);
+					return send(res, 200, { txSig });
+				}
+				default:
+					throw new HttpError(404, `No route ${route}`);
+			}
+		} catch (e) {
+			const status =
+				e instanceof HttpError
+					? e.status
+					: e instanceof GatewayRequestError || e instanceof SyntaxError
+					? 400
+					: 500;
+			send(res, status, { error: e.message });
+		}
+	}
+}
+
+function readBody(req: http.IncomingMessage): Promise<string> {
+	return new Promise((resolve, reject) => {
+		let body = '';
+		req.on('data', (chunk) => (body += chunk));
+		req.on('end', () => resolve(body));
+		req.on('error', reject);
+	});
+}
+
+function send(res: http.ServerResponse, status: number, body: unknown): void {
+	res.writeHead(status, { 'Content-Type': 'application/json' });
+	res.end(JSON.stringify(toJson(body)));
+}
@@ -0,0 +1,105 @@
+import { Program } from '@project-serum/anchor';
+import {
+	ConfirmedSignatureInfo,
+	Connection,
+	Finality,
+	PublicKey,
+	TransactionError,
+	TransactionSignature,
+} from '@solana/web3.js';
+import { DecodedIx, decodeTransaction } from '../decode/instructionDecoder';
+import { DriftEvent, parseLogs } from '../decode/logParser';
+
+export type BackfilledTx = {
+	signature: TransactionSignature;
+	slot: number;
+	/** unix timestamp in seconds, if the node knows it */
+	blockTime?: number;
+	/** failed transactions are included, their instructions didn't take effect */
+	err: TransactionError | null;
+	instructions: DecodedIx[];
+	events: DriftEvent[];
+};
+
+export type BackfillOptions = {
+	/** stop at this signature, exclusive, e.g. the newest signature of the previous backfill. Goes back to the first transaction if undefined */
+	untilSignature?: TransactionSignature;
+	/** stop at transactions in or before this slot, e.g. the slot of a snapshot the transactions are replayed on top of */
+	untilSlot?: number;
+	/** start below this signature, exclusive. Starts at the newest transaction if undefined */
+	beforeSignature?: TransactionSignature;
+	/** signatures per getSignaturesForAddress page, at most 1000 */
+	pageSize: number;
+	commitment: Finality;
+};
+
+export const DEFAULT_BACKFILL_OPTIONS: BackfillOptions = {
+	pageSize: 1000,
+	commitment: 'confirmed',
+};
+
+/**
+ * Replays the clearing house activity of an address from its transaction history, beyond the window the history ring buffers retain.
+ * Pages through getSignaturesForAddress, then fetches and decodes every transaction's instructions and logs, oldest to newest
+ * @param connection
+ * @param program
+ * @param address a user account, user authority or the program itself
+ * @param onTransaction called in order for each transaction, awaited before the next is fetched
+ * @param options
+ * @returns the newest signature processed, to pass as untilSignature to resume later. Undefined if there were no new transactions
+ */
+export async function backfill(
+	connection: Connection,
+	program: Program,
+	address: PublicKey,
+	onTransaction: (tx: BackfilledTx) => void | Promise<void>,
+	options: Partial<BackfillOptions> = {}
+): Promise<TransactionSignature | undefined> {
+	const { untilSignature, untilSlot, beforeSignature, pageSize, commitment } = {
+		...DEFAULT_BACKFILL_OPTIONS,
+		...options,
+	};
+
+	// signatures come newest first, collect them all so transactions can be replayed in order
+	const signatureInfos: ConfirmedSignatureInfo[] = [];
+	let before = beforeSignature;
+	for (;;) {
+		const page = await connection.getSignaturesForAddress(
+			address,
+			{ before, until: untilSignature, limit: pageSize },
+			commitment
+		);
+		const newer =
+			untilSlot === undefined
+				? page
+				: page.filter((signatureInfo) => signatureInfo.slot > untilSlot);
+		signatureInfos.push(...newer);
+		if (page.length < pageSize || newer.length < page.length) {
+			break;
+		}
+		before = page[page.length - 1].signature;
+	}
+
+	signatureInfos.reverse();
+	for (const { signature } of signatureInfos) {
+		const response = await connection.getTransaction(signature, {
+			commitment,
+		});
+		if (!response) {
+			continue;
+		}
+
+		await onTransaction({
+			signature,
+			slot: response.slot,
+			blockTime: response.blockTime ?? undefined,
+			err: response.meta?.err ?? null,
+			instructions: decodeTransaction(program, response.transaction.message),
+			events: parseLogs(program, response.meta?.logMessages ?? []),
+		});
+	}
+
+	return signatureInfos.length > 0
+		? signatureInfos[signatureInfos.length - 1].signature
+		: undefined;
+}
@@ -0,0 +1,89 @@
+import { BN } from '@project-serum/anchor';
+import { AMM, CurveHistoryAccount, CurveRecord } from '../types';
+import { getOrderedRecords } from './utils';
+
+/**
+ * Finds the most recent curve record for the market that left the amm with the given peg multiplier and sqrtK.
+ * Used to reconcile a curve change detected with {@link hasCurveChanged} against the repeg or k update that caused it
+ * @param curveHistory
+ * @param marketIndex
+ * @param amm
+ * @returns the matching record, undefined if it isn't in the curve history
+ */
+export function findCurveRecordForAmm(
+	curveHistory: CurveHistoryAccount,
+	marketIndex: BN,
+	amm: AMM
+): CurveRecord | undefined {
+	return getOrderedRecords(curveHistory.depositRecords)
+		.reverse()
+		.find(
+			(record) =>
+				record.marketIndex.eq(marketIndex) &&
+				record.pegMultiplierAfter.eq(amm.pegMultiplier) &&
+				record.sqrtKAfter.eq(amm.sqrtK)
+		);
+}
+
+/**
+ * repeg: the admin moved the peg multiplier, updateK: the admin scaled the reserves
+ */
+export type CurveEventType = 'repeg' | 'updateK';
+
+/**
+ * A curve change of one market, amounts at their on-chain precision
+ */
+export type CurveEvent = {
+	type: CurveEventType;
+	ts: BN;
+	recordId: BN;
+	marketIndex: BN;
+	pegMultiplierBefore: BN;
+	pegMultiplierAfter: BN;
+	sqrtKBefore: BN;
+	sqrtKAfter: BN;
+	baseAssetReserveBefore: BN;
+	baseAssetReserveAfter: BN;
+	quoteAssetReserveBefore: BN;
+	quoteAssetReserveAfter: BN;
+	/** paid by the clearing house's fee pool if positive : Precision QUOTE_PRECISION */
+	adjustmentCost: BN;
+};
+
+/**
+ * @param record
+ * @returns the curve change the record describes. A repeg leaves k unchanged, so any record moving the peg is a repeg
+ */
+export function getCurveEvent(record: CurveRecord): CurveEvent {
+	return {
+		type: record.pegMultiplierBefore.eq(record.pegMultiplierAfter)
+			? 'updateK'
+			: 'repeg',
+		ts: record.ts,
+		recordId: record.recordId,
+		marketIndex: record.marketIndex,
+		pegMultiplierBefore: record.pegMultiplierBefore,
+		pegMultiplierAfter: record.pegMultiplierAfter,
+		sqrtKBefore: record.sqrtKBefore,
+		sqrtKAfter: record.sqrtKAfter,
+		baseAssetReserveBefore: record.baseAssetReserveBefore,
+		baseAssetReserveAfter: record.baseAssetReserveAfter,
+		quoteAssetReserveBefore: record.quoteAssetReserveBefore,
+		quoteAssetReserveAfter: record.quoteAssetReserveAfter,
+		adjustmentCost: record.adjustmentCost,
+	};
+}
+
+/**
+ * @param curveHistory
+ * @param sinceRecordId exclusive, all records if undefined
+ * @returns curve events ordered oldest to newest
+ */
+export function getCurveEvents(
+	curveHistory: CurveHistoryAccount,
+	sinceRecordId?: BN
+): CurveEvent[] {
+	return getOrderedRecords(curveHistory.depositRecords)
+		.filter((record) => !sinceRecordId || record.recordId.gt(sinceRecordId))
+		.map(getCurveEvent);
+}
@@ -0,0 +1,149 @@
+import { BN } from '@project-serum/anchor';
+import { Connection } from '@solana/web3.js';
+import { ZERO } from '../constants/numericConstants';
+import { toCollateralAmount } from '../math/conversion';
+import {
+	CollateralAmount,
+	DepositHistoryAccount,
+	DepositRecord,
+	StateAccount,
+	UserAccount,
+} from '../types';
+import { getOrderedRecords } from './utils';
+
+export type DepositDiscrepancyType =
+	| 'recordGap'
+	| 'cumulativeDepositsMismatch'
+	| 'depositSumMismatch'
+	| 'collateralVaultShortfall';
+
+export type DepositDiscrepancy = {
+	type: DepositDiscrepancyType;
+	message: string;
+	/** the record the discrepancy was found at, if any */
+	recordId?: BN;
+	/** Precision QUOTE_PRECISION */
+	expected: BN;
+	/** Precision QUOTE_PRECISION */
+	actual: BN;
+};
+
+export type DepositReconciliation = {
+	/** Precision QUOTE_PRECISION */
+	cumulativeDeposits: CollateralAmount;
+	/** sum of deposit records : Precision QUOTE_PRECISION */
+	totalDeposited: CollateralAmount;
+	/** sum of withdrawal records : Precision QUOTE_PRECISION */
+	totalWithdrawn: CollateralAmount;
+	recordCount: number;
+	/** whether the deposit history still holds the user's first deposit, needed to check the record sums */
+	historyComplete: boolean;
+	/** Precision QUOTE_PRECISION */
+	collateralVaultBalance: BN;
+	discrepancies: DepositDiscrepancy[];
+};
+
+function getCumulativeDepositsAfter(record: DepositRecord): BN {
+	return record.direction.deposit
+		? record.cumulativeDepositsBefore.add(record.amount)
+		: record.cumulativeDepositsBefore.sub(record.amount);
+}
+
+/**
+ * Cross-checks a user's cumulative deposits against their deposit history records and the collateral vault balance.
+ * Consecutive records must chain, the latest record must end at the user's cumulative deposits, and if the history holds the
+ * first deposit the records must sum to it. The vault must hold at least the user's collateral
+ * @param connection
+ * @param state
+ * @param depositHistory
+ * @param userAccount
+ * @returns reconciliation with any discrepancies found
+ */
+export async function reconcileDeposits(
+	connection: Connection,
+	state: StateAccount,
+	depositHistory: DepositHistoryAccount,
+	userAccount: UserAccount
+): Promise<DepositReconciliation> {
+	const collateralVaultBalance = new BN(
+		(
+			await connection.getTokenAccountBalance(state.collateralVault)
+		).value.amount
+	);
+
+	const records = getOrderedRecords(depositHistory.depositRecords).filter(
+		(record) => record.userAuthority.equals(userAccount.authority)
+	);
+
+	const discrepancies: DepositDiscrepancy[] = [];
+	let totalDeposited = ZERO;
+	let totalWithdrawn = ZERO;
+	let previousRecord: DepositRecord | undefined;
+	for (const record of records) {
+		if (record.direction.deposit) {
+			totalDeposited = totalDeposited.add(record.amount);
+		} else {
+			totalWithdrawn = totalWithdrawn.add(record.amount);
+		}
+
+		if (previousRecord) {
+			const expected = getCumulativeDepositsAfter(previousRecord);
+			if (!expected.eq(record.cumulativeDepositsBefore)) {
+				discrepancies.push({
+					type: 'recordGap',
+					message: `Record ${record.recordId.toString()} starts at cumulative deposits ${record.cumulativeDepositsBefore.toString()} but the previous record ended at ${expected.toString()}`,
+					recordId: record.recordId,
+					expected,
+					actual: record.cumulativeDepositsBefore,
+				});
+			}
+		}
+		previousRecord = record;
+	}
+
+	if (previousRecord) {
+		const expected = getCumulativeDepositsAfter(previousRecord);
+		if (!expected.eq(userAccount.cumulativeDeposits)) {
+			discrepancies.push({
+				type: 'cumulativeDepositsMismatch',
+				message: `User cumulative deposits ${userAccount.cumulativeDeposits.toString()} don't match the latest deposit record ${previousRecord.recordId.toString()}, which ended at ${expected.toString()}`,
+				recordId: previousRecord.recordId,
+				expected,
+				actual: userAccount.cumulativeDeposits,
+			});
+		}
+	}
+
+	const historyComplete =
+		records.length > 0 && records[0].cumulativeDepositsBefore.eq(ZERO);
+	if (historyComplete) {
+		const expected = totalDeposited.sub(totalWithdrawn);
+		if (!expected.eq(userAccount.cumulativeDeposits)) {
+			discrepancies.push({
+				type: 'depositSumMismatch',
+				message: `Deposit records sum to ${expected.toString()} but user cumulative deposits are ${userAccount.cumulativeDeposits.toString()}`,
+				expected,
+				actual: userAccount.cumulativeDeposits,
+			});
+		}
+	}
+
+	if (collateralVaultBalance.lt(userAccount.collateral)) {
+		discrepancies.push({
+			type: 'collateralVaultShortfall',
+			message: `Collateral vault balance ${collateralVaultBalance.toString()} is below the user's collateral ${userAccount.collateral.toString()}`,
+			expected: userAccount.collateral,
+			actual: collateralVaultBalance,
+		});
+	}
+
+	return {
+		cumulativeDeposits: toCollateralAmount(userAccount.cumulativeDeposits),
+		totalDeposited: toCollateralAmount(totalDeposited),
+		totalWithdrawn: toCollateralAmount(totalWithdrawn),
+		recordCount: records.length,
+		historyComplete,
+		collateralVaultBalance,
+		discrepancies,
+	};
+}
@@ -0,0 +1,239 @@
+import { BN } from '@project-serum/anchor';
+import { ZERO } from '../constants/numericConstants';
+import { toQuoteAssetAmount } from '../math/conversion';
+import {
+	CurveHistoryAccount,
+	MarketsAccount,
+	QuoteAssetAmount,
+} from '../types';
+import { getOrderedRecords, isInTimeWindow } from './utils';
+
+/**
+ * The program only lets the admin withdraw this share of a market's fees, the rest stays in the market to pay for repegs and k updates
+ */
+const SHARE_OF_FEES_ALLOCATED_TO_CLEARING_HOUSE_NUMERATOR = new BN(1);
+const SHARE_OF_FEES_ALLOCATED_TO_CLEARING_HOUSE_DENOMINATOR = new BN(2);
+
+export type MarketFeeRevenue = {
+	marketIndex: BN;
+	/** all fees collected, including those moved in from the insurance vault : Precision QUOTE_PRECISION */
+	totalFee: QuoteAssetAmount;
+	/** fees left after paying for funding imbalances, repegs and k updates : Precision QUOTE_PRECISION */
+	totalFeeMinusDistributions: QuoteAssetAmount;
+	/** fees spent on funding imbalances, repegs and k updates : Precision QUOTE_PRECISION */
+	distributions: QuoteAssetAmount;
+	/** fees the admin has withdrawn : Precision QUOTE_PRECISION */
+	totalFeeWithdrawn: QuoteAssetAmount;
+	/** fees the admin can still withdraw : Precision QUOTE_PRECISION */
+	withdrawableFee: QuoteAssetAmount;
+};
+
+export type FeeRevenueSummary = {
+	/** Precision QUOTE_PRECISION */
+	totalFee: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	totalFeeMinusDistributions: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	distributions: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	totalFeeWithdrawn: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	withdrawableFee: QuoteAssetAmount;
+	markets: MarketFeeRevenue[];
+};
+
+export type FeeRevenueSnapshot = {
+	/** unix timestamp in seconds */
+	ts: BN;
+	summary: FeeRevenueSummary;
+};
+
+export type CurveFeeRecord = {
+	ts: BN;
+	recordId: BN;
+	marketIndex: BN;
+	/** Precision QUOTE_PRECISION */
+	totalFee: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	totalFeeMinusDistributions: QuoteAssetAmount;
+	/** cost of the repeg or k update paid from fees, negative if it earned fees : Precision QUOTE_PRECISION */
+	adjustmentCost: QuoteAssetAmount;
+};
+
+/**
+ * Summarizes fee revenue across the initialized markets
+ * @param marketsAccount
+ * @returns summary : Precision QUOTE_PRECISION
+ */
+export function calculateFeeRevenueSummary(
+	marketsAccount: MarketsAccount
+): FeeRevenueSummary {
+	const markets: MarketFeeRevenue[] = [];
+	marketsAccount.markets.forEach((market, marketIndex) => {
+		if (!market.initialized) {
+			return;
+		}
+
+		const amm = market.amm;
+		const maxWithdraw = amm.totalFee
+			.mul(SHARE_OF_FEES_ALLOCATED_TO_CLEARING_HOUSE_NUMERATOR)
+			.div(SHARE_OF_FEES_ALLOCATED_TO_CLEARING_HOUSE_DENOMINATOR);
+		markets.push({
+			marketIndex: new BN(marketIndex),
+			totalFee: toQuoteAssetAmount(amm.totalFee),
+			totalFeeMinusDistributions: toQuoteAssetAmount(
+				amm.totalFeeMinusDistributions
+			),
+			distributions: toQuoteAssetAmount(
+				amm.totalFee.sub(amm.totalFeeMinusDistributions)
+			),
+			totalFeeWithdrawn: toQuoteAssetAmount(amm.totalFeeWithdrawn),
+			withdrawableFee: toQuoteAssetAmount(
+				BN.max(maxWithdraw.sub(amm.totalFeeWithdrawn), ZERO)
+			),
+		});
+	});
+
+	return markets.reduce(
+		(summary, market) => {
+			summary.totalFee = toQuoteAssetAmount(
+				summary.totalFee.add(market.totalFee)
+			);
+			summary.totalFeeMinusDistributions = toQuoteAssetAmount(
+				summary.totalFeeMinusDistributions.add(
+					market.totalFeeMinusDistributions
+				)
+			);
+			summary.distributions = toQuoteAssetAmount(
+				summary.distributions.add(market.distributions)
+			);
+			summary.totalFeeWithdrawn = toQuoteAssetAmount(
+				summary.totalFeeWithdrawn.add(market.totalFeeWithdrawn)
+			);
+			summary.withdrawableFee = toQuoteAssetAmount(
+				summary.withdrawableFee.add(market.withdrawableFee)
+			);
+			return summary;
+		},
+		{
+			totalFee: toQuoteAssetAmount(ZERO),
+			totalFeeMinusDistributions: toQuoteAssetAmount(ZERO),
+			distributions: toQuoteAssetAmount(ZERO),
+			totalFeeWithdrawn: toQuoteAssetAmount(ZERO),
+			withdrawableFee: toQuoteAssetAmount(ZERO),
+			markets,
+		} as FeeRevenueSummary
+	);
+}
+
+/**
+ * Records a fee revenue snapshot. Store snapshots periodically and compare them with {@link calculateFeeRevenueChange}
+ * @param marketsAccount
+ * @param ts unix timestamp in seconds
+ * @returns snapshot
+ */
+export function takeFeeRevenueSnapshot(
+	marketsAccount: MarketsAccount,
+	ts: BN = new BN((Date.now() / 1000).toFixed(0))
+): FeeRevenueSnapshot {
+	return {
+		ts,
+		summary: calculateFeeRevenueSummary(marketsAccount),
+	};
+}
+
+/**
+ * Calculates the fee flows between two snapshots. Markets initialized after the first snapshot count from zero
+ * @param before
+ * @param after
+ * @returns the change in each total : Precision QUOTE_PRECISION
+ */
+export function calculateFeeRevenueChange(
+	before: FeeRevenueSnapshot,
+	after: FeeRevenueSnapshot
+): FeeRevenueSummary {
+	const markets = after.summary.markets.map((marketAfter) => {
+		const marketBefore = before.summary.markets.find((market) =>
+			market.marketIndex.eq(marketAfter.marketIndex)
+		);
+		if (!marketBefore) {
+			return marketAfter;
+		}
+
+		return {
+			marketIndex: marketAfter.marketIndex,
+			totalFee: toQuoteAssetAmount(
+				marketAfter.totalFee.sub(marketBefore.totalFee)
+			),
+			totalFeeMinusDistributions: toQuoteAssetAmount(
+				marketAfter.totalFeeMinusDistributions.sub(
+					marketBefore.totalFeeMinusDistributions
+				)
+			),
+			distributions: toQuoteAssetAmount(
+				marketAfter.distributions.sub(marketBefore.distributions)
+			),
+			totalFeeWithdrawn: toQuoteAssetAmount(
+				marketAfter.totalFeeWithdrawn.sub(marketBefore.totalFeeWithdrawn)
+			),
+			withdrawableFee: toQuoteAssetAmount(
+				marketAfter.withdrawableFee.sub(marketBefore.withdrawableFee)
+			),
+		};
+	});
+
+	return {
+		totalFee: toQuoteAssetAmount(
+			after.summary.totalFee.sub(before.summary.totalFee)
+		),
+		totalFeeMinusDistributions: toQuoteAssetAmount(
+			after.summary.totalFeeMinusDistributions.sub(
+				before.summary.totalFeeMinusDistributions
+			)
+		),
+		distributions: toQuoteAssetAmount(
+			after.summary.distributions.sub(before.summary.distributions)
+		),
+		totalFeeWithdrawn: toQuoteAssetAmount(
+			after.summary.totalFeeWithdrawn.sub(before.summary.totalFeeWithdrawn)
+		),
+		withdrawableFee: toQuoteAssetAmount(
+			after.summary.withdrawableFee.sub(before.summary.withdrawableFee)
+		),
+		markets,
+	};
+}
+
+/**
+ * Every repeg and k update records the market's fee totals, giving a fee history between snapshots
+ * @param curveHistory
+ * @param marketIndex
+ * @param startTs inclusive, unbounded if undefined
+ * @param endTs exclusive, unbounded if undefined
+ * @returns fee records ordered oldest to newest
+ */
+export function getCurveFeeHistory(
+	curveHistory: CurveHistoryAccount,
+	marketIndex: BN,
+	startTs?: BN,
+	endTs?: BN
+): CurveFeeRecord[] {
+	return getOrderedRecords(curveHistory.depositRecords)
+		.filter(
+			(record) =>
+				record.marketIndex.eq(marketIndex) &&
+				isInTimeWindow(record.ts, startTs, endTs)
+		)
+		.map((record) => {
+			return {
+				ts: record.ts,
+				recordId: record.recordId,
+				marketIndex: record.marketIndex,
+				totalFee: toQuoteAssetAmount(record.totalFee),
+				totalFeeMinusDistributions: toQuoteAssetAmount(
+					record.totalFeeMinusDistributions
+				),
+				adjustmentCost: toQuoteAssetAmount(record.adjustmentCost),
+			};
+		});
+}
@@ -0,0 +1,165 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import {
+	AMM_TO_QUOTE_PRECISION_RATIO,
+	ZERO,
+} from '../constants/numericConstants';
+import {
+	FundingPaymentHistoryAccount,
+	FundingPaymentRecord,
+	FundingRateHistoryAccount,
+	FundingRateRecord,
+} from '../types';
+import { getOrderedRecords, isInTimeWindow } from './utils';
+
+const SECONDS_PER_DAY = new BN(60 * 60 * 24);
+
+export type FundingAttributionRow = {
+	/** unix timestamp of the utc day's start */
+	day: BN;
+	marketIndex: BN;
+	/** Precision QUOTE_PRECISION */
+	received: BN;
+	/** Precision QUOTE_PRECISION */
+	paid: BN;
+	/** received - paid : Precision QUOTE_PRECISION */
+	net: BN;
+	/** number of funding rate updates the user's position was exposed to, a payment attributed to its settlement day counts as one */
+	fundingRateUpdateCount: number;
+};
+
+/**
+ * Attributes a user's funding payments to the day and market they accrued in. Funding is only settled when the user's position changes,
+ * so a payment covers every funding rate update since the previous settlement, during which the position was constant. Each payment is
+ * split across those updates in proportion to their change of the cumulative funding rate of the position's side.
+ *
+ * A payment whose funding rate updates are no longer held in the funding rate history ring buffer is attributed to the day it was settled
+ * @param fundingPaymentHistory
+ * @param fundingRateHistory
+ * @param userAuthority
+ * @param startTs inclusive, unbounded if undefined. Applies to the day funding accrued in
+ * @param endTs exclusive, unbounded if undefined. Applies to the day funding accrued in
+ * @returns rows ordered by day then market index
+ */
+export function calculateFundingAttribution(
+	fundingPaymentHistory: FundingPaymentHistoryAccount,
+	fundingRateHistory: FundingRateHistoryAccount,
+	userAuthority: PublicKey,
+	startTs?: BN,
+	endTs?: BN
+): FundingAttributionRow[] {
+	const fundingRateRecords = getOrderedRecords(
+		fundingRateHistory.fundingRateRecords
+	);
+	const rows = new Map<string, FundingAttributionRow>();
+	const attribute = (ts: BN, marketIndex: BN, payment: BN) => {
+		if (!isInTimeWindow(ts, startTs, endTs)) {
+			return;
+		}
+
+		const day = ts.sub(ts.mod(SECONDS_PER_DAY));
+		const key = `${day.toString()}-${marketIndex.toString()}`;
+		const row = rows.get(key) ?? {
+			day,
+			marketIndex,
+			received: ZERO,
+			paid: ZERO,
+			net: ZERO,
+			fundingRateUpdateCount: 0,
+		};
+
+		if (payment.isNeg()) {
+			row.paid = row.paid.add(payment.abs());
+		} else {
+			row.received = row.received.add(payment);
+		}
+		row.net = row.net.add(payment);
+		row.fundingRateUpdateCount += 1;
+		rows.set(key, row);
+	};
+
+	const paymentRecords = getOrderedRecords(
+		fundingPaymentHistory.fundingPaymentRecords
+	).filter((record) => record.userAuthority.equals(userAuthority));
+	for (const paymentRecord of paymentRecords) {
+		// funding payments are recorded with AMM_RESERVE_PRECISION, positive means the user received funding
+		const payment = paymentRecord.fundingPayment.div(
+			AMM_TO_QUOTE_PRECISION_RATIO
+		);
+		const shares = splitFundingPayment(
+			paymentRecord,
+			payment,
+			fundingRateRecords
+		);
+		if (shares.length === 0) {
+			attribute(paymentRecord.ts, paymentRecord.marketIndex, payment);
+			continue;
+		}
+
+		for (const [ts, share] of shares) {
+			attribute(ts, paymentRecord.marketIndex, share);
+		}
+	}
+
+	return Array.from(rows.values()).sort(
+		(a, b) => a.day.cmp(b.day) || a.marketIndex.cmp(b.marketIndex)
+	);
+}
+
+/**
+ * @returns the payment's share of each funding rate update it covers, empty if they aren't all in the funding rate history
+ */
+function splitFundingPayment(
+	paymentRecord: FundingPaymentRecord,
+	payment: BN,
+	fundingRateRecords: FundingRateRecord[]
+): [BN, BN][] {
+	const isLong = paymentRecord.baseAssetAmount.gt(ZERO);
+	const getCumulativeFundingRate = (record: FundingRateRecord) =>
+		isLong
+			? record.cumulativeFundingRateLong
+			: record.cumulativeFundingRateShort;
+
+	const updates = fundingRateRecords.filter(
+		(record) =>
+			record.marketIndex.eq(paymentRecord.marketIndex) &&
+			record.ts.gt(paymentRecord.userLastFundingRateTs) &&
+			record.ts.lte(paymentRecord.ts)
+	);
+	const ammCumulativeFunding = isLong
+		? paymentRecord.ammCumulativeFundingLong
+		: paymentRecord.ammCumulativeFundingShort;
+	if (
+		updates.length === 0 ||
+		!getCumulativeFundingRate(updates[updates.length - 1]).eq(
+			ammCumulativeFunding
+		)
+	) {
+		return [];
+	}
+
+	const totalDelta = ammCumulativeFunding.sub(
+		paymentRecord.userLastCumulativeFunding
+	);
+	if (totalDelta.eq(ZERO)) {
+		return [];
+	}
+
+	const shares: [BN, BN][] = [];
+	let previousCumulativeFunding = paymentRecord.userLastCumulativeFunding;
+	let attributed = ZERO;
+	updates.forEach((record, i) => {
+		const cumulativeFunding = getCumulativeFundingRate(record);
+		// the last update takes the rounding remainder so the shares sum to the payment
+		const share =
+			i === updates.length - 1
+				? payment.sub(attributed)
+				: payment
+						.mul(cumulativeFunding.sub(previousCumulativeFunding))
+						.div(totalDelta);
+		shares.push([record.ts, share]);
+		attributed = attributed.add(share);
+		previousCumulativeFunding = cumulativeFunding;
+	});
+	return shares;
+}
@@ -0,0 +1,94 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import {
+	AMM_TO_QUOTE_PRECISION_RATIO,
+	ZERO,
+} from '../constants/numericConstants';
+import { FundingPaymentHistoryAccount } from '../types';
+import { getOrderedRecords, isInTimeWindow } from './utils';
+
+export type MarketFundingPaymentSummary = {
+	marketIndex: BN;
+	/** Precision QUOTE_PRECISION */
+	received: BN;
+	/** Precision QUOTE_PRECISION */
+	paid: BN;
+	/** received - paid : Precision QUOTE_PRECISION */
+	net: BN;
+	paymentCount: number;
+};
+
+export type FundingPaymentReport = {
+	userAuthority: PublicKey;
+	startTs?: BN;
+	endTs?: BN;
+	/** Precision QUOTE_PRECISION */
+	received: BN;
+	/** Precision QUOTE_PRECISION */
+	paid: BN;
+	/** received - paid : Precision QUOTE_PRECISION */
+	net: BN;
+	markets: MarketFundingPaymentSummary[];
+};
+
+/**
+ * Aggregates the settled funding payments of a user by market. Only covers the records still held in the funding payment history ring buffer
+ * @param fundingPaymentHistory
+ * @param userAuthority
+ * @param startTs inclusive, unbounded if undefined
+ * @param endTs exclusive, unbounded if undefined
+ * @returns report with markets ordered by market index
+ */
+export function calculateFundingPaymentReport(
+	fundingPaymentHistory: FundingPaymentHistoryAccount,
+	userAuthority: PublicKey,
+	startTs?: BN,
+	endTs?: BN
+): FundingPaymentReport {
+	const markets = new Map<number, MarketFundingPaymentSummary>();
+
+	const records = getOrderedRecords(
+		fundingPaymentHistory.fundingPaymentRecords
+	).filter(
+		(record) =>
+			record.userAuthority.equals(userAuthority) &&
+			isInTimeWindow(record.ts, startTs, endTs)
+	);
+
+	for (const record of records) {
+		const marketIndex = record.marketIndex.toNumber();
+		const summary = markets.get(marketIndex) ?? {
+			marketIndex: record.marketIndex,
+			received: ZERO,
+			paid: ZERO,
+			net: ZERO,
+			paymentCount: 0,
+		};
+
+		// funding payments are recorded with AMM_RESERVE_PRECISION, positive means the user received funding
+		const payment = record.fundingPayment.div(AMM_TO_QUOTE_PRECISION_RATIO);
+		if (payment.isNeg()) {
+			summary.paid = summary.paid.add(payment.abs());
+		} else {
+			summary.received = summary.received.add(payment);
+		}
+		summary.net = summary.net.add(payment);
+		summary.paymentCount += 1;
+
+		markets.set(marketIndex, summary);
+	}
+
+	const marketSummaries = Array.from(markets.values()).sort((a, b) =>
+		a.marketIndex.cmp(b.marketIndex)
+	);
+
+	return {
+		userAuthority,
+		startTs,
+		endTs,
+		received: marketSummaries.reduce((sum, m) => sum.add(m.received), ZERO),
+		paid: marketSummaries.reduce((sum, m) => sum.add(m.paid), ZERO),
+		net: marketSummaries.reduce((sum, m) => sum.add(m.net), ZERO),
+		markets: marketSummaries,
+	};
+}
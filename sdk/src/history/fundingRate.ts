@@ -0,0 +1,68 @@
+import { BN } from '@project-serum/anchor';
+import { FUNDING_RATE_PRECISION } from '../constants/numericConstants';
+import { convertToNumber } from '../math/conversion';
+import { FundingRateHistoryAccount } from '../types';
+import { getOrderedRecords, isInTimeWindow } from './utils';
+
+export type FundingRateHistoryEntry = {
+	/** unix timestamp in seconds */
+	ts: number;
+	recordId: number;
+	marketIndex: number;
+	/** quote asset paid per unit of base asset for the period, positive when longs pay shorts */
+	fundingRate: number;
+	/** funding rate as a percentage of the oracle price twap */
+	fundingRatePct: number;
+	/** quote asset per unit of base asset */
+	cumulativeFundingRateLong: number;
+	/** quote asset per unit of base asset */
+	cumulativeFundingRateShort: number;
+	markPriceTwap: number;
+	oraclePriceTwap: number;
+};
+
+/**
+ * Returns a market's funding rate updates, oldest to newest, with rates and prices in ui units.
+ * Only covers the records still held in the funding rate history ring buffer
+ * @param fundingRateHistory
+ * @param marketIndex
+ * @param sinceTs inclusive, unbounded if undefined
+ * @returns funding rate history entries
+ */
+export function getFundingRateHistoryForMarket(
+	fundingRateHistory: FundingRateHistoryAccount,
+	marketIndex: BN,
+	sinceTs?: BN
+): FundingRateHistoryEntry[] {
+	return getOrderedRecords(fundingRateHistory.fundingRateRecords)
+		.filter(
+			(record) =>
+				record.marketIndex.eq(marketIndex) &&
+				isInTimeWindow(record.ts, sinceTs)
+		)
+		.map((record) => {
+			const fundingRate = convertToNumber(
+				record.fundingRate,
+				FUNDING_RATE_PRECISION
+			);
+			const oraclePriceTwap = convertToNumber(record.oraclePriceTwap);
+			return {
+				ts: record.ts.toNumber(),
+				recordId: record.recordId.toNumber(),
+				marketIndex: record.marketIndex.toNumber(),
+				fundingRate,
+				fundingRatePct:
+					oraclePriceTwap !== 0 ? (fundingRate / oraclePriceTwap) * 100 : 0,
+				cumulativeFundingRateLong: convertToNumber(
+					record.cumulativeFundingRateLong,
+					FUNDING_RATE_PRECISION
+				),
+				cumulativeFundingRateShort: convertToNumber(
+					record.cumulativeFundingRateShort,
+					FUNDING_RATE_PRECISION
+				),
+				markPriceTwap: convertToNumber(record.markPriceTwap),
+				oraclePriceTwap,
+			};
+		});
+}
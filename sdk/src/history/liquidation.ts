@@ -0,0 +1,69 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import { LiquidationHistoryAccount, LiquidationRecord } from '../types';
+import { getOrderedRecords } from './utils';
+
+/**
+ * A liquidation of one user, amounts at their on-chain precision
+ */
+export type LiquidationEvent = {
+	/** partial liquidations close a share of the positions, full liquidations close all of them */
+	type: 'partial' | 'full';
+	ts: BN;
+	recordId: BN;
+	userAuthority: PublicKey;
+	user: PublicKey;
+	liquidator: PublicKey;
+	/** value of the closed positions : Precision QUOTE_PRECISION */
+	baseAssetValueClosed: BN;
+	/** total fee taken from the user's collateral : Precision QUOTE_PRECISION */
+	liquidationFee: BN;
+	/** Precision QUOTE_PRECISION */
+	feeToLiquidator: BN;
+	/** Precision QUOTE_PRECISION */
+	feeToInsuranceFund: BN;
+	/** the user's margin ratio when liquidated : Precision TEN_THOUSAND */
+	marginRatio: BN;
+};
+
+/**
+ * @param record
+ * @returns the liquidation the record describes
+ */
+export function getLiquidationEvent(
+	record: LiquidationRecord
+): LiquidationEvent {
+	return {
+		type: record.partial ? 'partial' : 'full',
+		ts: record.ts,
+		recordId: record.recordId,
+		userAuthority: record.userAuthority,
+		user: record.user,
+		liquidator: record.liquidator,
+		baseAssetValueClosed: record.baseAssetValueClosed,
+		liquidationFee: record.liquidationFee,
+		feeToLiquidator: record.feeToLiquidator,
+		feeToInsuranceFund: record.feeToInsuranceFund,
+		marginRatio: record.marginRatio,
+	};
+}
+
+/**
+ * @param liquidationHistory
+ * @param user the user account, NOT the user's wallet address
+ * @param sinceRecordId exclusive, all records if undefined
+ * @returns the user's liquidations ordered oldest to newest
+ */
+export function getLiquidationEventsForUser(
+	liquidationHistory: LiquidationHistoryAccount,
+	user: PublicKey,
+	sinceRecordId?: BN
+): LiquidationEvent[] {
+	return getOrderedRecords(liquidationHistory.liquidationRecords)
+		.filter(
+			(record) =>
+				record.user.equals(user) &&
+				(!sinceRecordId || record.recordId.gt(sinceRecordId))
+		)
+		.map(getLiquidationEvent);
+}
@@ -0,0 +1,189 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import { ZERO } from '../constants/numericConstants';
+import {
+	FundingPaymentHistoryAccount,
+	QuoteAssetAmount,
+	TradeHistoryAccount,
+} from '../types';
+import { toQuoteAssetAmount } from '../math/conversion';
+import { calculateFundingPaymentReport } from './fundingPayment';
+import { getOrderedRecords, isInTimeWindow } from './utils';
+
+export type MarketRealizedPnl = {
+	marketIndex: BN;
+	/** pnl from reducing and closing positions, before fees : Precision QUOTE_PRECISION */
+	tradingPnl: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	fees: QuoteAssetAmount;
+	/** funding received minus funding paid : Precision QUOTE_PRECISION */
+	funding: QuoteAssetAmount;
+	/** tradingPnl - fees + funding : Precision QUOTE_PRECISION */
+	net: QuoteAssetAmount;
+	/** number of trades that reduced, closed or flipped a position */
+	closingTradeCount: number;
+};
+
+export type RealizedPnlReport = {
+	userAuthority: PublicKey;
+	sinceTs?: BN;
+	/** Precision QUOTE_PRECISION */
+	tradingPnl: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	fees: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	funding: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	net: QuoteAssetAmount;
+	markets: MarketRealizedPnl[];
+};
+
+type ReplayedPosition = {
+	baseAssetAmount: BN;
+	quoteAssetAmount: BN;
+};
+
+/**
+ * Reconstructs a user's realized pnl by replaying their trades the way the program settles them: reducing a position realizes pnl against
+ * the proportional share of its entry value, closing realizes it against all of it. A trade that flips a position is split between the close
+ * and the new position in proportion to base asset amount, which is an approximation of how the program prices the close.
+ *
+ * Cost basis is rebuilt from the trades still held in the trade history ring buffer, so positions opened before the oldest record are misvalued.
+ * Trades before sinceTs are replayed for cost basis but don't count toward the report
+ * @param tradeHistory
+ * @param fundingPaymentHistory
+ * @param userAuthority
+ * @param sinceTs inclusive, unbounded if undefined
+ * @returns report with markets ordered by market index
+ */
+export function calculateRealizedPnlReport(
+	tradeHistory: TradeHistoryAccount,
+	fundingPaymentHistory: FundingPaymentHistoryAccount,
+	userAuthority: PublicKey,
+	sinceTs?: BN
+): RealizedPnlReport {
+	const positions = new Map<number, ReplayedPosition>();
+	const markets = new Map<number, MarketRealizedPnl>();
+	const getMarketSummary = (marketIndex: BN) => {
+		const key = marketIndex.toNumber();
+		let summary = markets.get(key);
+		if (!summary) {
+			summary = {
+				marketIndex,
+				tradingPnl: toQuoteAssetAmount(ZERO),
+				fees: toQuoteAssetAmount(ZERO),
+				funding: toQuoteAssetAmount(ZERO),
+				net: toQuoteAssetAmount(ZERO),
+				closingTradeCount: 0,
+			};
+			markets.set(key, summary);
+		}
+		return summary;
+	};
+
+	const tradeRecords = getOrderedRecords(tradeHistory.tradeRecords).filter(
+		(record) => record.userAuthority.equals(userAuthority)
+	);
+	for (const record of tradeRecords) {
+		const key = record.marketIndex.toNumber();
+		const position = positions.get(key) ?? {
+			baseAssetAmount: ZERO,
+			quoteAssetAmount: ZERO,
+		};
+
+		const baseAssetAmountChange = record.direction.long
+			? record.baseAssetAmount
+			: record.baseAssetAmount.neg();
+
+		let pnl = ZERO;
+		let closing = false;
+		if (
+			position.baseAssetAmount.eq(ZERO) ||
+			position.baseAssetAmount.isNeg() === baseAssetAmountChange.isNeg()
+		) {
+			position.baseAssetAmount = position.baseAssetAmount.add(
+				baseAssetAmountChange
+			);
+			position.quoteAssetAmount = position.quoteAssetAmount.add(
+				record.quoteAssetAmount
+			);
+		} else {
+			closing = true;
+			const wasLong = position.baseAssetAmount.gt(ZERO);
+			const baseAssetAmountClosed = BN.min(
+				record.baseAssetAmount,
+				position.baseAssetAmount.abs()
+			);
+			const entryValueClosed = position.quoteAssetAmount
+				.mul(baseAssetAmountClosed)
+				.div(position.baseAssetAmount.abs());
+			const exitValue = record.quoteAssetAmount
+				.mul(baseAssetAmountClosed)
+				.div(record.baseAssetAmount);
+
+			pnl = wasLong
+				? exitValue.sub(entryValueClosed)
+				: entryValueClosed.sub(exitValue);
+
+			position.baseAssetAmount = position.baseAssetAmount.add(
+				baseAssetAmountChange
+			);
+			if (record.baseAssetAmount.gt(baseAssetAmountClosed)) {
+				// flipped, the rest of the trade opened the new position
+				position.quoteAssetAmount = record.quoteAssetAmount.sub(exitValue);
+			} else {
+				position.quoteAssetAmount =
+					position.quoteAssetAmount.sub(entryValueClosed);
+			}
+		}
+		positions.set(key, position);
+
+		if (!isInTimeWindow(record.ts, sinceTs)) {
+			continue;
+		}
+
+		const summary = getMarketSummary(record.marketIndex);
+		summary.tradingPnl = toQuoteAssetAmount(summary.tradingPnl.add(pnl));
+		summary.fees = toQuoteAssetAmount(summary.fees.add(record.fee));
+		if (closing) {
+			summary.closingTradeCount += 1;
+		}
+	}
+
+	const fundingReport = calculateFundingPaymentReport(
+		fundingPaymentHistory,
+		userAuthority,
+		sinceTs
+	);
+	for (const fundingSummary of fundingReport.markets) {
+		const summary = getMarketSummary(fundingSummary.marketIndex);
+		summary.funding = toQuoteAssetAmount(fundingSummary.net);
+	}
+
+	const marketSummaries = Array.from(markets.values()).sort((a, b) =>
+		a.marketIndex.cmp(b.marketIndex)
+	);
+	for (const summary of marketSummaries) {
+		summary.net = toQuoteAssetAmount(
+			summary.tradingPnl.sub(summary.fees).add(summary.funding)
+		);
+	}
+
+	return {
+		userAuthority,
+		sinceTs,
+		tradingPnl: toQuoteAssetAmount(
+			marketSummaries.reduce((sum, m) => sum.add(m.tradingPnl), ZERO)
+		),
+		fees: toQuoteAssetAmount(
+			marketSummaries.reduce((sum, m) => sum.add(m.fees), ZERO)
+		),
+		funding: toQuoteAssetAmount(
+			marketSummaries.reduce((sum, m) => sum.add(m.funding), ZERO)
+		),
+		net: toQuoteAssetAmount(
+			marketSummaries.reduce((sum, m) => sum.add(m.net), ZERO)
+		),
+		markets: marketSummaries,
+	};
+}
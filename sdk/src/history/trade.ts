@@ -0,0 +1,91 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import {
+	AMM_TO_QUOTE_PRECISION_RATIO,
+	MARK_PRICE_PRECISION,
+	ZERO,
+} from '../constants/numericConstants';
+import {
+	toBaseAssetAmount,
+	toPrice,
+	toQuoteAssetAmount,
+} from '../math/conversion';
+import {
+	BaseAssetAmount,
+	PositionDirection,
+	Price,
+	QuoteAssetAmount,
+	TradeHistoryAccount,
+	TradeRecord,
+} from '../types';
+import { getOrderedRecords } from './utils';
+
+export type UserTrade = {
+	ts: BN;
+	recordId: BN;
+	marketIndex: BN;
+	direction: PositionDirection;
+	/** Precision AMM_RESERVE_PRECISION */
+	baseAssetAmount: BaseAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	quoteAssetAmount: QuoteAssetAmount;
+	/** average fill price : Precision MARK_PRICE_PRECISION */
+	price: Price;
+	/** fee paid after discounts : Precision QUOTE_PRECISION */
+	fee: QuoteAssetAmount;
+	/** whether the trade closed the position in a liquidation */
+	liquidation: boolean;
+};
+
+/**
+ * Calculates the average fill price of a trade record
+ * @param record
+ * @returns price : Precision MARK_PRICE_PRECISION
+ */
+export function calculateTradeRecordPrice(record: TradeRecord): Price {
+	if (record.baseAssetAmount.eq(ZERO)) {
+		return toPrice(ZERO);
+	}
+
+	return toPrice(
+		record.quoteAssetAmount
+			.mul(MARK_PRICE_PRECISION)
+			.mul(AMM_TO_QUOTE_PRECISION_RATIO)
+			.div(record.baseAssetAmount)
+	);
+}
+
+/**
+ * Returns a user's fills, oldest to newest. Only covers the records still held in the trade history ring buffer
+ * @param tradeHistory
+ * @param userAuthority
+ * @param sinceRecordId exclusive, returns every fill if undefined
+ * @returns trades
+ */
+export function getTradeHistoryForUser(
+	tradeHistory: TradeHistoryAccount,
+	userAuthority: PublicKey,
+	sinceRecordId?: BN
+): UserTrade[] {
+	return getOrderedRecords(tradeHistory.tradeRecords)
+		.filter(
+			(record) =>
+				record.userAuthority.equals(userAuthority) &&
+				(!sinceRecordId || record.recordId.gt(sinceRecordId))
+		)
+		.map((record) => {
+			return {
+				ts: record.ts,
+				recordId: record.recordId,
+				marketIndex: record.marketIndex,
+				direction: record.direction.long
+					? PositionDirection.LONG
+					: PositionDirection.SHORT,
+				baseAssetAmount: toBaseAssetAmount(record.baseAssetAmount),
+				quoteAssetAmount: toQuoteAssetAmount(record.quoteAssetAmount),
+				price: calculateTradeRecordPrice(record),
+				fee: toQuoteAssetAmount(record.fee),
+				liquidation: record.liquidation,
+			};
+		});
+}
@@ -0,0 +1,37 @@
+import { BN } from '@project-serum/anchor';
+import { ZERO } from '../constants/numericConstants';
+
+/**
+ * History accounts are fixed size ring buffers, so records aren't stored in order and slots that haven't been written yet are zeroed.
+ * Returns only the written records, ordered oldest to newest
+ * @param records
+ * @returns records ordered by recordId
+ */
+export function getOrderedRecords<T extends { recordId: BN }>(
+	records: T[]
+): T[] {
+	return records
+		.filter((record) => record.recordId.gt(ZERO))
+		.sort((a, b) => a.recordId.cmp(b.recordId));
+}
+
+/**
+ * @param records
+ * @returns the highest record id written to the ring buffer, zero if none
+ */
+export function getLatestRecordId(records: { recordId: BN }[]): BN {
+	return records.reduce(
+		(latest, record) => BN.max(latest, record.recordId),
+		ZERO
+	);
+}
+
+/**
+ * @param ts
+ * @param startTs inclusive, unbounded if undefined
+ * @param endTs exclusive, unbounded if undefined
+ * @returns whether ts is in the window
+ */
+export function isInTimeWindow(ts: BN, startTs?: BN, endTs?: BN): boolean {
+	return (!startTs || ts.gte(startTs)) && (!endTs || ts.lt(endTs));
+}
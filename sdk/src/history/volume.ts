@@ -0,0 +1,104 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import { ZERO } from '../constants/numericConstants';
+import { toQuoteAssetAmount } from '../math/conversion';
+import { QuoteAssetAmount, TradeRecord } from '../types';
+import { getOrderedRecords } from './utils';
+
+export type MarketVolume = {
+	marketIndex: BN;
+	/** Precision QUOTE_PRECISION */
+	volume: QuoteAssetAmount;
+	tradeCount: number;
+};
+
+export type UserVolume = {
+	userAuthority: PublicKey;
+	/** Precision QUOTE_PRECISION */
+	volume: QuoteAssetAmount;
+	tradeCount: number;
+};
+
+export type VolumeStats = {
+	startTs: BN;
+	endTs: BN;
+	/** Precision QUOTE_PRECISION */
+	totalVolume: QuoteAssetAmount;
+	tradeCount: number;
+	/** ordered by market index */
+	markets: MarketVolume[];
+	/** ordered by volume, highest first */
+	users: UserVolume[];
+};
+
+/**
+ * Sums traded quote volume per market and per user over a trailing window. Takes any set of trade records,
+ * e.g. the trade history account's records merged with backfilled ones; records sharing a record id are only counted once
+ * @param tradeRecords
+ * @param window length in seconds, defaults to 24 hours
+ * @param now unix timestamp in seconds the window ends at
+ * @returns volumeStats : Precision QUOTE_PRECISION
+ */
+export function calculateVolumeStats(
+	tradeRecords: TradeRecord[],
+	window: BN = new BN(24 * 60 * 60),
+	now: BN = new BN((Date.now() / 1000).toFixed(0))
+): VolumeStats {
+	const startTs = now.sub(window);
+
+	const marketVolumes = new Map<number, MarketVolume>();
+	const userVolumes = new Map<string, UserVolume>();
+	let totalVolume = ZERO;
+	let tradeCount = 0;
+	let lastRecordId = ZERO;
+	for (const record of getOrderedRecords(tradeRecords)) {
+		if (
+			record.recordId.eq(lastRecordId) ||
+			record.ts.lt(startTs) ||
+			record.ts.gt(now)
+		) {
+			continue;
+		}
+		lastRecordId = record.recordId;
+
+		totalVolume = totalVolume.add(record.quoteAssetAmount);
+		tradeCount++;
+
+		const marketKey = record.marketIndex.toNumber();
+		const marketVolume = marketVolumes.get(marketKey) ?? {
+			marketIndex: record.marketIndex,
+			volume: toQuoteAssetAmount(ZERO),
+			tradeCount: 0,
+		};
+		marketVolume.volume = toQuoteAssetAmount(
+			marketVolume.volume.add(record.quoteAssetAmount)
+		);
+		marketVolume.tradeCount++;
+		marketVolumes.set(marketKey, marketVolume);
+
+		const userKey = record.userAuthority.toString();
+		const userVolume = userVolumes.get(userKey) ?? {
+			userAuthority: record.userAuthority,
+			volume: toQuoteAssetAmount(ZERO),
+			tradeCount: 0,
+		};
+		userVolume.volume = toQuoteAssetAmount(
+			userVolume.volume.add(record.quoteAssetAmount)
+		);
+		userVolume.tradeCount++;
+		userVolumes.set(userKey, userVolume);
+	}
+
+	return {
+		startTs,
+		endTs: now,
+		totalVolume: toQuoteAssetAmount(totalVolume),
+		tradeCount,
+		markets: Array.from(marketVolumes.values()).sort((a, b) =>
+			a.marketIndex.cmp(b.marketIndex)
+		),
+		users: Array.from(userVolumes.values()).sort((a, b) =>
+			b.volume.cmp(a.volume)
+		),
+	};
+}
@@ -3,20 +3,68 @@ import { BN } from '@project-serum/anchor';
 export * from './mockUSDCFaucet';
 export * from './pythClient';
 export * from './types';
+export * from './errors';
 export * from './constants/markets';
 export * from './accounts/defaultClearingHouseAccountSubscriber';
 export * from './accounts/types';
+export * from './accounts/pollingAccountSubscriber';
+export * from './accounts/staticAccountSubscriber';
+export * from './accounts/historyAccountSubscriber';
+export * from './accounts/historyAccountView';
+export * from './accounts/marketsAccountView';
+export * from './accounts/zeroCopyLayout';
 export * from './addresses';
 export * from './admin';
 export * from './clearingHouseUser';
 export * from './clearingHouse';
+export * from './riskEngine';
+export * from './userScanner';
+export * from './leaderboard';
+export * from './referral';
+export * from './keepers/fundingCrank';
+export * from './keepers/liquidatorBot';
+export * from './paper/paperClearingHouse';
+export * from './paper/paperUserAccountSubscriber';
 export * from './math/conversion';
+export * from './math/fees';
+export * from './math/sizing';
 export * from './math/funding';
 export * from './math/insuranceFund';
+export * from './math/oracle';
+export * from './history/utils';
+export * from './history/fundingPayment';
+export * from './history/fundingAttribution';
+export * from './history/fundingRate';
+export * from './history/trade';
+export * from './history/volume';
+export * from './history/feeRevenue';
+export * from './history/realizedPnl';
+export * from './history/deposits';
+export * from './history/backfill';
+export * from './candles/types';
+export * from './candles/candleAggregator';
+export * from './triggers/types';
+export * from './triggers/triggerEngine';
+export * from './execution/icebergExecutor';
+export * from './strategies/rebalancer';
+export * from './strategies/deadMansSwitch';
+export * from './monitoring/healthMonitor';
+export * from './monitoring/exchangeStatusWatcher';
+export * from './monitoring/balanceWatcher';
+export * from './alerts/types';
+export * from './alerts/webhookAlertSink';
+export * from './alerts/attach';
+export * from './metrics/registry';
+export * from './metrics/sdkMetrics';
+export * from './decode/instructionDecoder';
+export * from './decode/logParser';
+export * from './history/curve';
+export * from './history/liquidation';
 export * from './math/market';
 export * from './math/position';
 export * from './math/amm';
 export * from './math/trade';
+export * from './math/fill';
 export * from './wallet';
 export * from './types';
 export * from './math/utils';
@@ -24,5 +72,36 @@ export * from './config';
 export * from './constants/numericConstants';
 export * from './util/computeUnits';
 export * from './util/tps';
+export * from './util/clock';
+export * from './util/computeBudget';
+export * from './util/memo';
+export * from './util/json';
+export * from './util/format';
+export * from './util/dynamicIx';
+export * from './util/compatibility';
+export * from './util/token';
+export * from './util/rent';
+export * from './util/accountSlice';
+export * from './util/rpcRequest';
+export * from './util/wrappedSol';
+export * from './util/airdrop';
+export * from './snapshot/types';
+export * from './snapshot/dump';
+export * from './snapshot/reader';
+export * from './snapshot/snapshotClearingHouseAccountSubscriber';
+export * from './snapshot/snapshotUserAccountSubscriber';
+export * from './replay/replayer';
+export * from './export/parquet';
+export * from './export/tax';
+export * from './ingest/webhookIngestor';
+export * from './sinks/databaseSink';
+export * from './gateway/common';
+export * from './gateway/restGateway';
+export * from './gateway/grpcGateway';
+export * from './tx/txPipeline';
+export * from './tx/multiRpcTxSender';
+export * from './tx/txTracker';
+export * from './tx/feePreflight';
+export * from './tx/partialSign';
 
 export { BN };
@@ -0,0 +1,268 @@
+import { AccountsCoder, Program, utils } from '@project-serum/anchor';
+import {
+	Message,
+	PublicKey,
+	TransactionError,
+	TransactionInstruction,
+} from '@solana/web3.js';
+import { EventEmitter } from 'events';
+import http from 'http';
+import StrictEventEmitter from 'strict-event-emitter-types';
+import {
+	decodeInstruction,
+	decodeTransaction,
+	DecodedIx,
+} from '../decode/instructionDecoder';
+import { parseLogs } from '../decode/logParser';
+import { BackfilledTx } from '../history/backfill';
+
+/**
+ * A transaction as returned by getTransaction with json encoding, the payload of raw transaction webhooks
+ */
+export type RawTransactionPayload = {
+	slot: number;
+	blockTime?: number | null;
+	meta: { err: TransactionError | null; logMessages?: string[] | null };
+	transaction: {
+		signatures: string[];
+		message: ConstructorParameters<typeof Message>[0];
+	};
+};
+
+/**
+ * A parsed transaction of enhanced transaction webhooks. Enhanced payloads don't carry logs, so their transactions have no events
+ */
+export type EnhancedTransactionPayload = {
+	signature: string;
+	slot: number;
+	/** unix timestamp in seconds */
+	timestamp?: number;
+	transactionError?: TransactionError | null;
+	instructions: { programId: string; accounts: string[]; data: string }[];
+};
+
+/**
+ * An account notification: the account's address, its data base64 encoded (or as [data, 'base64']) and the slot it was written in
+ */
+export type AccountPayload = {
+	pubkey: string;
+	data: string | [string, string];
+	slot: number;
+};
+
+export type WebhookAccountUpdate = {
+	publicKey: PublicKey;
+	/** idl name of the account, e.g. Markets or User */
+	name: string;
+	account: any;
+	slot: number;
+};
+
+export interface WebhookIngestorEvents {
+	transaction: (tx: BackfilledTx) => void;
+	accountUpdate: (update: WebhookAccountUpdate) => void;
+	error: (e: Error) => void;
+}
+
+export type WebhookIngestorConfig = {
+	port: number;
+	/** interface to listen on, anything but loopback requires authHeader */
+	host: string;
+	/** if set, every request must carry it in the authorization header, e.g. the auth header configured on the webhook */
+	authHeader?: string;
+	/** requests with a larger body are rejected with 413 */
+	maxBodyBytes: number;
+};
+
+export const DEFAULT_WEBHOOK_INGESTOR_CONFIG: WebhookIngestorConfig = {
+	port: 8081,
+	host: '127.0.0.1',
+	maxBodyBytes: 10 * 1024 * 1024,
+};
+
+const LOOPBACK_HOSTS = ['127.0.0.1', '::1', 'localhost'];
+
+/**
+ * # WebhookIngestor
+ * Converts webhook payloads pushed by rpc providers like Helius into the streams the sdk produces itself, so event driven services
+ * don't have to hold websocket connections: transactions become {@link BackfilledTx}s with decoded instructions and {@link DriftEvent}s,
+ * clearing house accounts become typed account updates.
+ *
+ * Payloads can be passed to {@link ingest} from an existing web server, or received by the ingestor's own http server with {@link start}.
+ * A payload is a single item or an array of raw transactions, enhanced transactions and account notifications, in any mix
+ */
+export class WebhookIngestor {
+	program: Program;
+	config: WebhookIngestorConfig;
+	eventEmitter: StrictEventEmitter<EventEmitter, WebhookIngestorEvents>;
+	server?: http.Server;
+
+	private accountNames: Map<string, string>;
+
+	public constructor(
+		program: Program,
+		config: Partial<WebhookIngestorConfig> = {}
+	) {
+		this.program = program;
+		this.config = { ...DEFAULT_WEBHOOK_INGESTOR_CONFIG, ...config };
+		this.eventEmitter = new EventEmitter();
+		this.accountNames = new Map(
+			program.idl.accounts.map((account) => [
+				AccountsCoder.accountDiscriminator(account.name).toString('hex'),
+				account.name,
+			])
+		);
+	}
+
+	/**
+	 * Emits the transactions and account updates of a webhook payload. Items that aren't clearing house accounts are skipped
+	 * @param payload the parsed json body of a webhook request
+	 */
+	public ingest(payload: unknown): void {
+		const items: any[] = Array.isArray(payload) ? payload : [payload];
+		for (const item of items) {
+			if (item.transaction && item.meta) {
+				this.ingestRawTransaction(item as RawTransactionPayload);
+			} else if (item.signature && item.instructions) {
+				this.ingestEnhancedTransaction(item as EnhancedTransactionPayload);
+			} else if (item.pubkey && item.data) {
+				this.ingestAccount(item as AccountPayload);
+			} else {
+				throw new Error('Unrecognized webhook payload');
+			}
+		}
+	}
+
+	/**
+	 * @throws Error if listening on a non loopback interface without an authHeader
+	 */
+	public async start(): Promise<void> {
+		if (this.server) {
+			return;
+		}
+		if (
+			!this.config.authHeader &&
+			!LOOPBACK_HOSTS.includes(this.config.host)
+		) {
+			throw new Error(
+				`Refusing to listen on ${this.config.host} without an authHeader`
+			);
+		}
+
+		this.server = http.createServer((req, res) => {
+			this.handle(req, res);
+		});
+		await new Promise<void>((resolve) =>
+			this.server.listen(this.config.port, this.config.host, resolve)
+		);
+	}
+
+	public async stop(): Promise<void> {
+		if (!this.server) {
+			return;
+		}
+
+		const server = this.server;
+		this.server = undefined;
+		await new Promise<void>((resolve) => server.close(() => resolve()));
+	}
+
+	private handle(req: http.IncomingMessage, res: http.ServerResponse): void {
+		if (
+			this.config.authHeader &&
+			req.headers['authorization'] !== this.config.authHeader
+		) {
+			res.writeHead(401).end();
+			return;
+		}
+		if (req.method !== 'POST') {
+			res.writeHead(405).end();
+			return;
+		}
+
+		let body = '';
+		let bodyBytes = 0;
+		req.on('data', (chunk: Buffer) => {
+			bodyBytes += chunk.length;
+			if (bodyBytes > this.config.maxBodyBytes) {
+				res.writeHead(413).end();
+				req.destroy();
+				return;
+			}
+			body += chunk;
+		});
+		req.on('end', () => {
+			try {
+				this.ingest(JSON.parse(body));
+				res.writeHead(200).end();
+			} catch (e) {
+				// emitting error without a listener throws, which would let any client crash the process
+				if (this.eventEmitter.listenerCount('error') > 0) {
+					this.eventEmitter.emit('error', e);
+				}
+				res.writeHead(400).end();
+			}
+		});
+	}
+
+	private ingestRawTransaction(payload: RawTransactionPayload): void {
+		this.eventEmitter.emit('transaction', {
+			signature: payload.transaction.signatures[0],
+			slot: payload.slot,
+			blockTime: payload.blockTime ?? undefined,
+			err: payload.meta.err,
+			instructions: decodeTransaction(
+				this.program,
+				new Message(payload.transaction.message)
+			),
+			events: parseLogs(this.program, payload.meta.logMessages ?? []),
+		});
+	}
+
+	private ingestEnhancedTransaction(payload: EnhancedTransactionPayload): void {
+		const instructions: DecodedIx[] = payload.instructions
+			.map((ix) =>
+				decodeInstruction(
+					this.program,
+					new TransactionInstruction({
+						programId: new PublicKey(ix.programId),
+						// enhanced payloads don't say which accounts are signers or writable, decoding doesn't need it
+						keys: ix.accounts.map((account) => ({
+							pubkey: new PublicKey(account),
+							isSigner: false,
+							isWritable: false,
+						})),
+						data: Buffer.from(utils.bytes.bs58.decode(ix.data)),
+					})
+				)
+			)
+			.filter((decoded) => decoded !== undefined);
+
+		this.eventEmitter.emit('transaction', {
+			signature: payload.signature,
+			slot: payload.slot,
+			blockTime: payload.timestamp,
+			err: payload.transactionError ?? null,
+			instructions,
+			events: [],
+		});
+	}
+
+	private ingestAccount(payload: AccountPayload): void {
+		const data = Buffer.from(
+			typeof payload.data === 'string' ? payload.data : payload.data[0],
+			'base64'
+		);
+		const name = this.accountNames.get(data.slice(0, 8).toString('hex'));
+		if (!name) {
+			return;
+		}
+
+		this.eventEmitter.emit('accountUpdate', {
+			publicKey: new PublicKey(payload.pubkey),
+			name,
+			account: this.program.coder.accounts.decode(name, data),
+			slot: payload.slot,
+		});
+	}
+}
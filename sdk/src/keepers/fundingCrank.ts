@@ -0,0 +1,162 @@
+import { BN } from '@project-serum/anchor';
+import { TransactionSignature } from '@solana/web3.js';
+import { EventEmitter } from 'events';
+import StrictEventEmitter from 'strict-event-emitter-types';
+import { ClearingHouse } from '../clearingHouse';
+import { ZERO } from '../constants/numericConstants';
+import { calculateTimeUntilNextFunding } from '../math/market';
+
+export type FundingCrankConfig = {
+	/** how often to check the markets, in ms */
+	intervalMs: number;
+	/** attempts per market update before giving up until the next check */
+	maxAttempts: number;
+	/** delay before the first retry, doubled for each one after, in ms */
+	retryDelayMs: number;
+};
+
+export const DEFAULT_FUNDING_CRANK_CONFIG: FundingCrankConfig = {
+	intervalMs: 10000,
+	maxAttempts: 3,
+	retryDelayMs: 1000,
+};
+
+export type FundingCrankMetrics = {
+	checks: number;
+	updatesSent: number;
+	updatesFailed: number;
+	retries: number;
+	/** failed checks and updates, counted whether or not anything listens for error */
+	errors: number;
+	/** unix timestamp in seconds of the last check */
+	lastCheckTs?: BN;
+	lastSignature?: TransactionSignature;
+};
+
+export interface FundingCrankEvents {
+	fundingRateUpdated: (
+		marketIndex: BN,
+		signature: TransactionSignature
+	) => void;
+	error: (marketIndex: BN | undefined, error: Error) => void;
+}
+
+/**
+ * # FundingCrank
+ * Keeper that updates each initialized market's funding rate as soon as its funding period has elapsed.
+ * The ClearingHouse must be subscribed so the markets account reflects the previous update.
+ */
+export class FundingCrank {
+	clearingHouse: ClearingHouse;
+	config: FundingCrankConfig;
+	metrics: FundingCrankMetrics;
+	eventEmitter: StrictEventEmitter<EventEmitter, FundingCrankEvents>;
+
+	private intervalId?: ReturnType<typeof setInterval>;
+	private running = false;
+
+	public constructor(
+		clearingHouse: ClearingHouse,
+		config: Partial<FundingCrankConfig> = {}
+	) {
+		this.clearingHouse = clearingHouse;
+		this.config = { ...DEFAULT_FUNDING_CRANK_CONFIG, ...config };
+		this.metrics = {
+			checks: 0,
+			updatesSent: 0,
+			updatesFailed: 0,
+			retries: 0,
+			errors: 0,
+		};
+		this.eventEmitter = new EventEmitter();
+	}
+
+	public start(): void {
+		if (this.intervalId) {
+			return;
+		}
+
+		this.intervalId = setInterval(async () => {
+			await this.crank();
+		}, this.config.intervalMs);
+	}
+
+	public stop(): void {
+		if (this.intervalId) {
+			clearInterval(this.intervalId);
+			this.intervalId = undefined;
+		}
+	}
+
+	/**
+	 * Checks every initialized market once and sends funding rate updates for the ones that are due.
+	 * Skipped if the previous check is still running or funding is paused
+	 */
+	public async crank(): Promise<void> {
+		if (this.running) {
+			return;
+		}
+		this.running = true;
+
+		try {
+			if (this.clearingHouse.getStateAccount().fundingPaused) {
+				return;
+			}
+
+			const now = await this.clearingHouse.getChainTime();
+			this.metrics.checks++;
+			this.metrics.lastCheckTs = now;
+
+			const markets = this.clearingHouse.getMarketsAccount().markets;
+			for (const [index, market] of markets.entries()) {
+				if (
+					!market.initialized ||
+					calculateTimeUntilNextFunding(market, now).gt(ZERO)
+				) {
+					continue;
+				}
+
+				await this.updateFundingRate(new BN(index));
+			}
+		} catch (e) {
+			this.emitError(undefined, e);
+		} finally {
+			this.running = false;
+		}
+	}
+
+	private async updateFundingRate(marketIndex: BN): Promise<void> {
+		const oracle = this.clearingHouse.getMarket(marketIndex).amm.oracle;
+		for (let attempt = 1; attempt <= this.config.maxAttempts; attempt++) {
+			try {
+				const signature = await this.clearingHouse.updateFundingRate(
+					oracle,
+					marketIndex
+				);
+				this.metrics.updatesSent++;
+				this.metrics.lastSignature = signature;
+				this.eventEmitter.emit('fundingRateUpdated', marketIndex, signature);
+				return;
+			} catch (e) {
+				if (attempt === this.config.maxAttempts) {
+					this.metrics.updatesFailed++;
+					this.emitError(marketIndex, e);
+					return;
+				}
+
+				this.metrics.retries++;
+				await new Promise((resolve) =>
+					setTimeout(resolve, this.config.retryDelayMs * 2 ** (attempt - 1))
+				);
+			}
+		}
+	}
+
+	private emitError(marketIndex: BN | undefined, error: Error): void {
+		this.metrics.errors++;
+		// emitting error without a listener throws, which would crash the process from the interval
+		if (this.eventEmitter.listenerCount('error') > 0) {
+			this.eventEmitter.emit('error', marketIndex, error);
+		}
+	}
+}
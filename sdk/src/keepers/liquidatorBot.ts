@@ -0,0 +1,270 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey, Transaction, TransactionSignature } from '@solana/web3.js';
+import { EventEmitter } from 'events';
+import StrictEventEmitter from 'strict-event-emitter-types';
+import { ClearingHouse } from '../clearingHouse';
+import { toQuoteAssetAmount } from '../math/conversion';
+import { QuoteAssetAmount, StateAccount } from '../types';
+import {
+	calculateUserAccountValue,
+	calculateUserMarginRatio,
+	fetchAllUsers,
+} from '../userScanner';
+import {
+	estimatePriorityFee,
+	getComputeUnitPriceIx,
+	getWritableAccounts,
+} from '../util/computeBudget';
+
+export type LiquidatorBotConfig = {
+	/** how often to scan every user, in ms */
+	scanIntervalMs: number;
+	/** skip liquidations whose expected fee to the liquidator is below this : Precision QUOTE_PRECISION */
+	minProfit: QuoteAssetAmount;
+	/** liquidations sent at once */
+	concurrency: number;
+	/** priority fee per compute unit in micro-lamports, none if zero */
+	priorityFeeMicroLamports: number;
+	/** if set, pay at least this percentile of the recent priority fees on the accounts a liquidation write locks */
+	priorityFeePercentile?: number;
+};
+
+export const DEFAULT_LIQUIDATOR_BOT_CONFIG: LiquidatorBotConfig = {
+	scanIntervalMs: 5000,
+	minProfit: toQuoteAssetAmount(new BN(0)),
+	concurrency: 4,
+	priorityFeeMicroLamports: 0,
+};
+
+export type LiquidationCandidate = {
+	userAccountPublicKey: PublicKey;
+	authority: PublicKey;
+	/** Precision TEN_THOUSAND */
+	marginRatio: BN;
+	/** full if at or below the maintenance margin ratio, partial otherwise */
+	full: boolean;
+	/** Precision QUOTE_PRECISION */
+	expectedFeeToLiquidator: QuoteAssetAmount;
+};
+
+export type LiquidatorBotMetrics = {
+	scans: number;
+	usersScanned: number;
+	candidatesFound: number;
+	liquidationsSent: number;
+	liquidationsFailed: number;
+	/** failed scans and liquidations, counted whether or not anything listens for error */
+	errors: number;
+	/** Precision QUOTE_PRECISION */
+	expectedFeesEarned: BN;
+};
+
+export interface LiquidatorBotEvents {
+	liquidation: (
+		candidate: LiquidationCandidate,
+		signature: TransactionSignature
+	) => void;
+	error: (candidate: LiquidationCandidate | undefined, error: Error) => void;
+}
+
+/**
+ * Estimates the liquidator's share of the liquidation fee the way the program charges it
+ * @param state
+ * @param accountValue collateral + unrealized pnl : Precision QUOTE_PRECISION
+ * @param full
+ * @returns fee to liquidator : Precision QUOTE_PRECISION
+ */
+export function calculateExpectedFeeToLiquidator(
+	state: StateAccount,
+	accountValue: BN,
+	full: boolean
+): QuoteAssetAmount {
+	if (full) {
+		return toQuoteAssetAmount(
+			accountValue
+				.mul(state.fullLiquidationPenaltyPercentageNumerator)
+				.div(state.fullLiquidationPenaltyPercentageDenominator)
+				.div(state.fullLiquidationLiquidatorShareDenominator)
+		);
+	}
+
+	return toQuoteAssetAmount(
+		accountValue
+			.mul(state.partialLiquidationPenaltyPercentageNumerator)
+			.div(state.partialLiquidationPenaltyPercentageDenominator)
+			.div(state.partialLiquidationLiquidatorShareDenominator)
+	);
+}
+
+/**
+ * # LiquidatorBot
+ * Scans every user on an interval and liquidates the ones below the partial liquidation margin ratio, most profitable first.
+ * The wallet's user account receives the liquidator fees, so it must be initialized, and the ClearingHouse must be subscribed.
+ */
+export class LiquidatorBot {
+	clearingHouse: ClearingHouse;
+	config: LiquidatorBotConfig;
+	metrics: LiquidatorBotMetrics;
+	eventEmitter: StrictEventEmitter<EventEmitter, LiquidatorBotEvents>;
+
+	private intervalId?: ReturnType<typeof setInterval>;
+	private running = false;
+
+	public constructor(
+		clearingHouse: ClearingHouse,
+		config: Partial<LiquidatorBotConfig> = {}
+	) {
+		this.clearingHouse = clearingHouse;
+		this.config = { ...DEFAULT_LIQUIDATOR_BOT_CONFIG, ...config };
+		this.metrics = {
+			scans: 0,
+			usersScanned: 0,
+			candidatesFound: 0,
+			liquidationsSent: 0,
+			liquidationsFailed: 0,
+			errors: 0,
+			expectedFeesEarned: new BN(0),
+		};
+		this.eventEmitter = new EventEmitter();
+	}
+
+	public start(): void {
+		if (this.intervalId) {
+			return;
+		}
+
+		this.intervalId = setInterval(async () => {
+			await this.scanAndLiquidate();
+		}, this.config.scanIntervalMs);
+	}
+
+	public stop(): void {
+		if (this.intervalId) {
+			clearInterval(this.intervalId);
+			this.intervalId = undefined;
+		}
+	}
+
+	/**
+	 * @returns users that can be liquidated for at least the configured min profit, most profitable first
+	 */
+	public async findCandidates(): Promise<LiquidationCandidate[]> {
+		const users = await fetchAllUsers(this.clearingHouse.program);
+		const marketsAccount = this.clearingHouse.getMarketsAccount();
+		const state = this.clearingHouse.getStateAccount();
+		const liquidator = this.clearingHouse.wallet.publicKey;
+
+		this.metrics.scans++;
+		this.metrics.usersScanned += users.length;
+
+		const candidates: LiquidationCandidate[] = [];
+		for (const user of users) {
+			if (user.userAccount.authority.equals(liquidator)) {
+				continue;
+			}
+
+			const marginRatio = calculateUserMarginRatio(marketsAccount, user);
+			if (marginRatio.gt(state.marginRatioPartial)) {
+				continue;
+			}
+
+			const full = marginRatio.lte(state.marginRatioMaintenance);
+			const expectedFeeToLiquidator = calculateExpectedFeeToLiquidator(
+				state,
+				calculateUserAccountValue(marketsAccount, user),
+				full
+			);
+			if (expectedFeeToLiquidator.lt(this.config.minProfit)) {
+				continue;
+			}
+
+			candidates.push({
+				userAccountPublicKey: user.userAccountPublicKey,
+				authority: user.userAccount.authority,
+				marginRatio,
+				full,
+				expectedFeeToLiquidator,
+			});
+		}
+
+		this.metrics.candidatesFound += candidates.length;
+		return candidates.sort((a, b) =>
+			b.expectedFeeToLiquidator.cmp(a.expectedFeeToLiquidator)
+		);
+	}
+
+	/**
+	 * Runs one scan and liquidates the candidates found. Skipped if the previous scan is still running
+	 */
+	public async scanAndLiquidate(): Promise<void> {
+		if (this.running) {
+			return;
+		}
+		this.running = true;
+
+		try {
+			const candidates = await this.findCandidates();
+			for (let i = 0; i < candidates.length; i += this.config.concurrency) {
+				await Promise.all(
+					candidates
+						.slice(i, i + this.config.concurrency)
+						.map((candidate) => this.liquidate(candidate))
+				);
+			}
+		} catch (e) {
+			this.emitError(undefined, e);
+		} finally {
+			this.running = false;
+		}
+	}
+
+	private async liquidate(candidate: LiquidationCandidate): Promise<void> {
+		try {
+			const liquidateIx = await this.clearingHouse.getLiquidateIx(
+				candidate.userAccountPublicKey
+			);
+			let priorityFeeMicroLamports = this.config.priorityFeeMicroLamports;
+			if (this.config.priorityFeePercentile !== undefined) {
+				priorityFeeMicroLamports = Math.max(
+					priorityFeeMicroLamports,
+					await estimatePriorityFee(
+						this.clearingHouse.connection,
+						getWritableAccounts([liquidateIx]),
+						this.config.priorityFeePercentile
+					)
+				);
+			}
+
+			const tx = new Transaction();
+			if (priorityFeeMicroLamports > 0) {
+				tx.add(getComputeUnitPriceIx(priorityFeeMicroLamports));
+			}
+			tx.add(liquidateIx);
+
+			const signature = await this.clearingHouse.txSender.send(
+				tx,
+				[],
+				this.clearingHouse.opts
+			);
+			this.metrics.liquidationsSent++;
+			this.metrics.expectedFeesEarned = this.metrics.expectedFeesEarned.add(
+				candidate.expectedFeeToLiquidator
+			);
+			this.eventEmitter.emit('liquidation', candidate, signature);
+		} catch (e) {
+			this.metrics.liquidationsFailed++;
+			this.emitError(candidate, e);
+		}
+	}
+
+	private emitError(
+		candidate: LiquidationCandidate | undefined,
+		error: Error
+	): void {
+		this.metrics.errors++;
+		// emitting error without a listener throws, which would crash the process from the interval
+		if (this.eventEmitter.listenerCount('error') > 0) {
+			this.eventEmitter.emit('error', candidate, error);
+		}
+	}
+}
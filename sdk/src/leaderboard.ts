@@ -0,0 +1,58 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import { ClearingHouse } from './clearingHouse';
+import { toQuoteAssetAmount } from './math/conversion';
+import { QuoteAssetAmount } from './types';
+import { calculateUserAccountValue, fetchAllUsers } from './userScanner';
+
+export type LeaderboardSortBy = 'collateral' | 'accountValue' | 'pnl';
+
+export type LeaderboardEntry = {
+	rank: number;
+	authority: PublicKey;
+	userAccountPublicKey: PublicKey;
+	/** Precision QUOTE_PRECISION */
+	collateral: QuoteAssetAmount;
+	/** collateral + unrealized pnl including funding : Precision QUOTE_PRECISION */
+	accountValue: QuoteAssetAmount;
+	/** accountValue - cumulative deposits : Precision QUOTE_PRECISION */
+	pnl: QuoteAssetAmount;
+};
+
+/**
+ * Ranks every user. Scans all user accounts, so it should be cached rather than called per page view
+ * @param clearingHouse subscribed ClearingHouse, used for market data
+ * @param sortBy
+ * @param limit number of entries to return, all users if undefined
+ * @returns entries ordered best first
+ */
+export async function fetchLeaderboard(
+	clearingHouse: ClearingHouse,
+	sortBy: LeaderboardSortBy = 'pnl',
+	limit?: number
+): Promise<LeaderboardEntry[]> {
+	const users = await fetchAllUsers(clearingHouse.program);
+	const marketsAccount = clearingHouse.getMarketsAccount();
+
+	const entries = users.map((user) => {
+		const accountValue = calculateUserAccountValue(marketsAccount, user);
+		return {
+			rank: 0,
+			authority: user.userAccount.authority,
+			userAccountPublicKey: user.userAccountPublicKey,
+			collateral: toQuoteAssetAmount(user.userAccount.collateral),
+			accountValue,
+			pnl: toQuoteAssetAmount(
+				accountValue.sub(user.userAccount.cumulativeDeposits)
+			),
+		};
+	});
+
+	const sortKey = (entry: LeaderboardEntry): BN => entry[sortBy];
+	entries.sort((a, b) => sortKey(b).cmp(sortKey(a)));
+
+	return entries.slice(0, limit).map((entry, index) => {
+		entry.rank = index + 1;
+		return entry;
+	});
+}
@@ -36,6 +36,73 @@ export function calculatePrice(
 
 export type AssetType = 'quote' | 'base';
 
+/**
+ * The default tolerance for {@link checkAmmInvariant}. Reserves are recomputed with integer division after every swap and k adjustment,
+ * so their product drifts slightly from sqrtK^2
+ */
+export const DEFAULT_INVARIANT_TOLERANCE = MARK_PRICE_PRECISION.div(
+	new BN(1000000)
+);
+
+/**
+ * Calculates the constant product invariant (k) the amm prices swaps against
+ *
+ * @param amm
+ * @returns k : Precision AMM_RESERVE_PRECISION^2
+ */
+export function calculateInvariant(amm: AMM): BN {
+	return amm.sqrtK.mul(amm.sqrtK);
+}
+
+/**
+ * Calculates the relative difference between the product of the reserves and the invariant
+ *
+ * @param amm
+ * @returns deviation : Precision MARK_PRICE_PRECISION
+ */
+export function calculateInvariantDeviation(amm: AMM): BN {
+	const invariant = calculateInvariant(amm);
+	if (invariant.eq(ZERO)) {
+		return ZERO;
+	}
+
+	return amm.baseAssetReserve
+		.mul(amm.quoteAssetReserve)
+		.sub(invariant)
+		.abs()
+		.mul(MARK_PRICE_PRECISION)
+		.div(invariant);
+}
+
+/**
+ * Checks that the reserves still lie on the curve defined by sqrtK
+ *
+ * @param amm
+ * @param tolerance max relative deviation : Precision MARK_PRICE_PRECISION
+ * @returns whether the invariant holds
+ */
+export function checkAmmInvariant(
+	amm: AMM,
+	tolerance = DEFAULT_INVARIANT_TOLERANCE
+): boolean {
+	return calculateInvariantDeviation(amm).lte(tolerance);
+}
+
+/**
+ * Swaps only move the reserves along the curve. A change in the peg multiplier or sqrtK means the admin repegged or updated k,
+ * which should have a matching record in the curve history
+ *
+ * @param ammBefore
+ * @param ammAfter
+ * @returns whether the curve changed between the two snapshots
+ */
+export function hasCurveChanged(ammBefore: AMM, ammAfter: AMM): boolean {
+	return (
+		!ammBefore.pegMultiplier.eq(ammAfter.pegMultiplier) ||
+		!ammBefore.sqrtK.eq(ammAfter.sqrtK)
+	);
+}
+
 /**
  * Calculates what the amm reserves would be after swapping a quote or base asset amount.
  *
@@ -1,8 +1,16 @@
 import BN from 'bn.js';
 import {
+	AMM_RESERVE_PRECISION,
 	MARK_PRICE_PRECISION,
 	PEG_PRECISION,
+	QUOTE_PRECISION,
 } from '../constants/numericConstants';
+import {
+	BaseAssetAmount,
+	CollateralAmount,
+	Price,
+	QuoteAssetAmount,
+} from '../types';
 
 export const convertToNumber = (
 	bigNumber: BN,
@@ -21,3 +29,54 @@ export const convertBaseAssetAmountToNumber = (baseAssetAmount: BN) => {
 		MARK_PRICE_PRECISION.mul(PEG_PRECISION)
 	);
 };
+
+/**
+ * Converts a ui number (e.g. 10.5) into an integer BN with the given precision, without going through floating point multiplication
+ * @param amount
+ * @param precision
+ * @returns : Precision precision
+ */
+export const convertToBN = (amount: number, precision: BN): BN => {
+	const decimals = precision.toString().length - 1;
+	const [whole, fraction = ''] = amount.toFixed(decimals).split('.');
+	return new BN(whole + fraction);
+};
+
+/**
+ * ui numbers are scaled to the precision, BNs are taken as already scaled
+ */
+const toPrecision = (amount: number | BN, precision: BN): BN => {
+	return BN.isBN(amount) ? amount : convertToBN(amount, precision);
+};
+
+/**
+ * @param amount ui dollar amount, e.g. 10.5, or a BN already at QUOTE_PRECISION
+ * @returns : Precision QUOTE_PRECISION
+ */
+export const toQuoteAssetAmount = (amount: number | BN): QuoteAssetAmount => {
+	return toPrecision(amount, QUOTE_PRECISION) as QuoteAssetAmount;
+};
+
+/**
+ * @param amount ui base amount, e.g. 0.25 (BTC), or a BN already at AMM_RESERVE_PRECISION. Negative for shorts
+ * @returns : Precision AMM_RESERVE_PRECISION
+ */
+export const toBaseAssetAmount = (amount: number | BN): BaseAssetAmount => {
+	return toPrecision(amount, AMM_RESERVE_PRECISION) as BaseAssetAmount;
+};
+
+/**
+ * @param amount ui collateral amount, e.g. 100 (USDC), or a BN already at QUOTE_PRECISION
+ * @returns : Precision QUOTE_PRECISION
+ */
+export const toCollateralAmount = (amount: number | BN): CollateralAmount => {
+	return toPrecision(amount, QUOTE_PRECISION) as CollateralAmount;
+};
+
+/**
+ * @param price ui price, e.g. 42000.5, or a BN already at MARK_PRICE_PRECISION
+ * @returns : Precision MARK_PRICE_PRECISION
+ */
+export const toPrice = (price: number | BN): Price => {
+	return toPrecision(price, MARK_PRICE_PRECISION) as Price;
+};
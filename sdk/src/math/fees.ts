@@ -0,0 +1,90 @@
+import { BN } from '@project-serum/anchor';
+import { ZERO } from '../constants/numericConstants';
+import { FeeStructure, QuoteAssetAmount } from '../types';
+import { toQuoteAssetAmount } from './conversion';
+
+export type FeeBreakdown = {
+	/** fee charged to the user after discounts : Precision QUOTE_PRECISION */
+	userFee: QuoteAssetAmount;
+	/** fee kept by the market after the referrer reward : Precision QUOTE_PRECISION */
+	feeToMarket: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	tokenDiscount: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	referrerReward: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	refereeDiscount: QuoteAssetAmount;
+};
+
+/**
+ * Calculates the fee for a trade exactly as the program does, including the discount token tiers and referral discount
+ * @param quoteAssetAmount
+ * @param feeStructure from the clearing house state account
+ * @param discountTokenBalance balance of the discount token account passed with the trade, undefined if none is passed
+ * @param hasReferrer whether a referrer is passed with the trade
+ * @returns feeBreakdown : Precision QUOTE_PRECISION
+ */
+export function calculateFee(
+	quoteAssetAmount: BN,
+	feeStructure: FeeStructure,
+	discountTokenBalance?: BN,
+	hasReferrer = false
+): FeeBreakdown {
+	const fee = quoteAssetAmount
+		.mul(feeStructure.feeNumerator)
+		.div(feeStructure.feeDenominator);
+
+	const tokenDiscount = calculateTokenDiscount(
+		fee,
+		feeStructure,
+		discountTokenBalance
+	);
+
+	let referrerReward = ZERO;
+	let refereeDiscount = ZERO;
+	if (hasReferrer) {
+		const referralDiscount = feeStructure.referralDiscount;
+		referrerReward = fee
+			.mul(referralDiscount.referrerRewardNumerator)
+			.div(referralDiscount.referrerRewardDenominator);
+		refereeDiscount = fee
+			.mul(referralDiscount.refereeDiscountNumerator)
+			.div(referralDiscount.refereeDiscountDenominator);
+	}
+
+	const userFee = fee.sub(tokenDiscount).sub(refereeDiscount);
+	const feeToMarket = userFee.sub(referrerReward);
+
+	return {
+		userFee: toQuoteAssetAmount(userFee),
+		feeToMarket: toQuoteAssetAmount(feeToMarket),
+		tokenDiscount: toQuoteAssetAmount(tokenDiscount),
+		referrerReward: toQuoteAssetAmount(referrerReward),
+		refereeDiscount: toQuoteAssetAmount(refereeDiscount),
+	};
+}
+
+function calculateTokenDiscount(
+	fee: BN,
+	feeStructure: FeeStructure,
+	discountTokenBalance?: BN
+): BN {
+	if (!discountTokenBalance) {
+		return ZERO;
+	}
+
+	const tiers = feeStructure.discountTokenTiers;
+	// the first tier has the highest minimum balance, so the first tier the balance qualifies for is the best discount
+	for (const tier of [
+		tiers.firstTier,
+		tiers.secondTier,
+		tiers.thirdTier,
+		tiers.fourthTier,
+	]) {
+		if (discountTokenBalance.gte(tier.minimumBalance)) {
+			return fee.mul(tier.discountNumerator).div(tier.discountDenominator);
+		}
+	}
+
+	return ZERO;
+}
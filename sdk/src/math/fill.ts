@@ -0,0 +1,469 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import {
+	AMM_TIMES_PEG_TO_QUOTE_PRECISION_RATIO,
+	PRICE_TO_QUOTE_PRECISION,
+	ZERO,
+} from '../constants/numericConstants';
+import { SlippageOutsideLimitError } from '../errors';
+import {
+	FeeStructure,
+	Market,
+	PositionDirection,
+	Price,
+	QuoteAssetAmount,
+	UserAccount,
+	UserPosition,
+	UserPositionsAccount,
+} from '../types';
+import {
+	calculateAmmReservesAfterSwap,
+	calculatePrice,
+	getSwapDirection,
+} from './amm';
+import { toPrice, toQuoteAssetAmount } from './conversion';
+import { calculateFee, FeeBreakdown } from './fees';
+import { calculateMarkPrice } from './market';
+import {
+	calculateBaseAssetValue,
+	calculatePositionFundingPNL,
+	calculatePositionPNL,
+} from './position';
+import { isTradeSizeTooSmall } from './trade';
+
+/** position slots of a user positions account */
+export const MAX_POSITIONS = 5;
+
+export type Fill = {
+	direction: PositionDirection;
+	/** Precision AMM_RESERVE_PRECISION */
+	baseAssetAmount: BN;
+	/** Precision QUOTE_PRECISION */
+	quoteAssetAmount: QuoteAssetAmount;
+	fee: FeeBreakdown;
+	/** Precision MARK_PRICE_PRECISION */
+	markPriceBefore: Price;
+	/** Precision MARK_PRICE_PRECISION */
+	markPriceAfter: Price;
+};
+
+/**
+ * Fills an open_position trade the way the program does: increase, reduce, close or flip the position, then charge the fee.
+ * Mutates the market's amm, the user account's collateral and fees, and the position
+ * @param market
+ * @param userAccount
+ * @param position position in the market, or an empty one to open
+ * @param direction
+ * @param amount : Precision QUOTE_PRECISION
+ * @param feeStructure
+ * @param limitPrice : Precision MARK_PRICE_PRECISION, none if zero or undefined
+ * @param discountTokenBalance balance of the discount token account passed with the trade, undefined if none is passed
+ * @param hasReferrer whether a referrer is passed with the trade
+ * @returns fill
+ * @throws SlippageOutsideLimitError
+ */
+export function fillOpenPosition(
+	market: Market,
+	userAccount: UserAccount,
+	position: UserPosition,
+	direction: PositionDirection,
+	amount: QuoteAssetAmount,
+	feeStructure: FeeStructure,
+	limitPrice?: Price,
+	discountTokenBalance?: BN,
+	hasReferrer = false
+): Fill {
+	const markPriceBefore = calculateMarkPrice(market);
+
+	let quoteAssetAmount: BN = amount;
+	let baseAssetAmount: BN;
+	const increasePosition =
+		position.baseAssetAmount.eq(ZERO) ||
+		position.baseAssetAmount.isNeg() ===
+			(direction === PositionDirection.SHORT);
+	if (increasePosition) {
+		baseAssetAmount = increase(
+			market,
+			position,
+			direction,
+			quoteAssetAmount
+		).abs();
+	} else {
+		const baseAssetValue = calculateBaseAssetValue(market, position);
+		// the program rounds trades within the minimum trade size of the position value to a full close
+		if (
+			isTradeSizeTooSmall(market, quoteAssetAmount.sub(baseAssetValue).abs())
+		) {
+			quoteAssetAmount = baseAssetValue;
+		}
+
+		if (baseAssetValue.gt(quoteAssetAmount)) {
+			baseAssetAmount = reduce(
+				market,
+				userAccount,
+				position,
+				direction,
+				quoteAssetAmount
+			).abs();
+		} else {
+			const baseAssetAmountClosed = close(market, userAccount, position).abs();
+			const baseAssetAmountOpened = increase(
+				market,
+				position,
+				direction,
+				quoteAssetAmount.sub(baseAssetValue)
+			).abs();
+			baseAssetAmount = baseAssetAmountClosed.add(baseAssetAmountOpened);
+		}
+	}
+
+	if (limitPrice && !limitPrice.eq(ZERO)) {
+		const entryPrice = calculatePrice(
+			quoteAssetAmount
+				.mul(AMM_TIMES_PEG_TO_QUOTE_PRECISION_RATIO)
+				.div(market.amm.pegMultiplier),
+			baseAssetAmount,
+			market.amm.pegMultiplier
+		);
+		if (
+			direction === PositionDirection.LONG
+				? entryPrice.gt(limitPrice)
+				: entryPrice.lt(limitPrice)
+		) {
+			throw new SlippageOutsideLimitError(
+				`Entry price ${entryPrice.toString()} is outside the limit price ${limitPrice.toString()} (MARK_PRICE_PRECISION)`
+			);
+		}
+	}
+
+	return chargeFee(
+		market,
+		userAccount,
+		direction,
+		baseAssetAmount,
+		quoteAssetAmount,
+		markPriceBefore,
+		feeStructure,
+		discountTokenBalance,
+		hasReferrer
+	);
+}
+
+/**
+ * Fills a close_position the way the program does. Mutates the market's amm, the user account and the position
+ * @param market
+ * @param userAccount
+ * @param position open position to close
+ * @param feeStructure
+ * @param discountTokenBalance balance of the discount token account passed with the trade, undefined if none is passed
+ * @param hasReferrer whether a referrer is passed with the trade
+ * @returns fill
+ */
+export function fillClosePosition(
+	market: Market,
+	userAccount: UserAccount,
+	position: UserPosition,
+	feeStructure: FeeStructure,
+	discountTokenBalance?: BN,
+	hasReferrer = false
+): Fill {
+	const markPriceBefore = calculateMarkPrice(market);
+	const direction = position.baseAssetAmount.isNeg()
+		? PositionDirection.LONG
+		: PositionDirection.SHORT;
+	const quoteAssetAmount = calculateBaseAssetValue(market, position);
+	const baseAssetAmount = close(market, userAccount, position).abs();
+
+	return chargeFee(
+		market,
+		userAccount,
+		direction,
+		baseAssetAmount,
+		quoteAssetAmount,
+		markPriceBefore,
+		feeStructure,
+		discountTokenBalance,
+		hasReferrer
+	);
+}
+
+/**
+ * Finds the position a trade in a market fills the way the program does: the open position in the market, otherwise the first
+ * empty slot, which is reset for the market
+ * @param positions the user positions account's positions
+ * @param marketIndex
+ * @returns position, undefined if every slot holds a position in another market
+ */
+export function getOrCreatePosition(
+	positions: UserPosition[],
+	marketIndex: BN
+): UserPosition | undefined {
+	let position = positions.find(
+		(position) =>
+			position.marketIndex.eq(marketIndex) &&
+			!position.baseAssetAmount.eq(ZERO)
+	);
+	if (!position) {
+		position = positions.find((position) => position.baseAssetAmount.eq(ZERO));
+		if (!position) {
+			return undefined;
+		}
+		position.marketIndex = marketIndex;
+		position.quoteAssetAmount = ZERO;
+		position.lastCumulativeFundingRate = ZERO;
+	}
+	return position;
+}
+
+/**
+ * A user account and user positions account as the program initializes them, without collateral or positions
+ * @param authority
+ * @param userAccountPublicKey
+ * @param userPositionsPublicKey
+ * @returns userAccount and userPositionsAccount
+ */
+export function getEmptyUserAccounts(
+	authority: PublicKey,
+	userAccountPublicKey: PublicKey,
+	userPositionsPublicKey: PublicKey
+): {
+	userAccount: UserAccount;
+	userPositionsAccount: UserPositionsAccount;
+} {
+	return {
+		userAccount: {
+			authority,
+			collateral: ZERO,
+			cumulativeDeposits: ZERO,
+			positions: userPositionsPublicKey,
+			totalFeePaid: ZERO,
+			totalTokenDiscount: ZERO,
+			totalReferralReward: ZERO,
+			totalRefereeDiscount: ZERO,
+		},
+		userPositionsAccount: {
+			user: userAccountPublicKey,
+			positions: Array.from({ length: MAX_POSITIONS }, () => {
+				return {
+					baseAssetAmount: ZERO,
+					lastCumulativeFundingRate: ZERO,
+					marketIndex: ZERO,
+					quoteAssetAmount: ZERO,
+				};
+			}),
+		},
+	};
+}
+
+/**
+ * Settles the position's pending funding payment into the user's collateral, like the program does before every trade
+ * @param market
+ * @param userAccount
+ * @param position
+ */
+export function settlePositionFunding(
+	market: Market,
+	userAccount: UserAccount,
+	position: UserPosition
+): void {
+	if (position.baseAssetAmount.eq(ZERO)) {
+		return;
+	}
+
+	const fundingPayment = calculatePositionFundingPNL(market, position).div(
+		PRICE_TO_QUOTE_PRECISION
+	);
+	updateCollateral(userAccount, fundingPayment);
+	position.lastCumulativeFundingRate = position.baseAssetAmount.gt(ZERO)
+		? market.amm.cumulativeFundingRateLong
+		: market.amm.cumulativeFundingRateShort;
+}
+
+/**
+ * @returns signed base asset amount swapped : Precision AMM_RESERVE_PRECISION
+ */
+function swapQuote(
+	market: Market,
+	direction: PositionDirection,
+	quoteAssetAmount: BN
+): BN {
+	const [newQuoteAssetReserve, newBaseAssetReserve] =
+		calculateAmmReservesAfterSwap(
+			market.amm,
+			'quote',
+			quoteAssetAmount,
+			getSwapDirection('quote', direction)
+		);
+	const baseAssetAmountSwapped = market.amm.baseAssetReserve.sub(
+		newBaseAssetReserve
+	);
+	market.amm.quoteAssetReserve = newQuoteAssetReserve;
+	market.amm.baseAssetReserve = newBaseAssetReserve;
+	return baseAssetAmountSwapped;
+}
+
+function updateMarketBaseAssetAmount(
+	market: Market,
+	baseAssetAmountBefore: BN,
+	baseAssetAmountSwapped: BN,
+	baseAssetAmountAfter: BN
+): void {
+	market.baseAssetAmount = market.baseAssetAmount.add(baseAssetAmountSwapped);
+	const side = baseAssetAmountBefore.eq(ZERO)
+		? baseAssetAmountAfter
+		: baseAssetAmountBefore;
+	if (side.gt(ZERO)) {
+		market.baseAssetAmountLong = market.baseAssetAmountLong.add(
+			baseAssetAmountSwapped
+		);
+	} else {
+		market.baseAssetAmountShort = market.baseAssetAmountShort.add(
+			baseAssetAmountSwapped
+		);
+	}
+
+	if (baseAssetAmountBefore.eq(ZERO) && !baseAssetAmountAfter.eq(ZERO)) {
+		market.openInterest = market.openInterest.add(new BN(1));
+	} else if (!baseAssetAmountBefore.eq(ZERO) && baseAssetAmountAfter.eq(ZERO)) {
+		market.openInterest = market.openInterest.sub(new BN(1));
+	}
+}
+
+function increase(
+	market: Market,
+	position: UserPosition,
+	direction: PositionDirection,
+	quoteAssetAmount: BN
+): BN {
+	if (quoteAssetAmount.eq(ZERO)) {
+		return ZERO;
+	}
+
+	const baseAssetAmountBefore = position.baseAssetAmount;
+	if (baseAssetAmountBefore.eq(ZERO)) {
+		position.lastCumulativeFundingRate =
+			direction === PositionDirection.LONG
+				? market.amm.cumulativeFundingRateLong
+				: market.amm.cumulativeFundingRateShort;
+	}
+
+	const baseAssetAmountSwapped = swapQuote(market, direction, quoteAssetAmount);
+	position.baseAssetAmount = baseAssetAmountBefore.add(baseAssetAmountSwapped);
+	position.quoteAssetAmount = position.quoteAssetAmount.add(quoteAssetAmount);
+	updateMarketBaseAssetAmount(
+		market,
+		baseAssetAmountBefore,
+		baseAssetAmountSwapped,
+		position.baseAssetAmount
+	);
+	return baseAssetAmountSwapped;
+}
+
+function reduce(
+	market: Market,
+	userAccount: UserAccount,
+	position: UserPosition,
+	direction: PositionDirection,
+	quoteAssetAmount: BN
+): BN {
+	const baseAssetAmountBefore = position.baseAssetAmount;
+	const baseAssetAmountSwapped = swapQuote(market, direction, quoteAssetAmount);
+	position.baseAssetAmount = baseAssetAmountBefore.add(baseAssetAmountSwapped);
+	updateMarketBaseAssetAmount(
+		market,
+		baseAssetAmountBefore,
+		baseAssetAmountSwapped,
+		position.baseAssetAmount
+	);
+
+	const initialQuoteAssetAmountClosed = position.quoteAssetAmount
+		.mul(baseAssetAmountSwapped.abs())
+		.div(baseAssetAmountBefore.abs());
+	position.quoteAssetAmount = position.quoteAssetAmount.sub(
+		initialQuoteAssetAmountClosed
+	);
+
+	const pnl = position.baseAssetAmount.gt(ZERO)
+		? quoteAssetAmount.sub(initialQuoteAssetAmountClosed)
+		: initialQuoteAssetAmountClosed.sub(quoteAssetAmount);
+	updateCollateral(userAccount, pnl);
+
+	return baseAssetAmountSwapped;
+}
+
+function close(
+	market: Market,
+	userAccount: UserAccount,
+	position: UserPosition
+): BN {
+	const baseAssetAmountBefore = position.baseAssetAmount;
+	updateCollateral(userAccount, calculatePositionPNL(market, position));
+
+	const closeDirection = baseAssetAmountBefore.gt(ZERO)
+		? PositionDirection.SHORT
+		: PositionDirection.LONG;
+	const [newQuoteAssetReserve, newBaseAssetReserve] =
+		calculateAmmReservesAfterSwap(
+			market.amm,
+			'base',
+			baseAssetAmountBefore.abs(),
+			getSwapDirection('base', closeDirection)
+		);
+	market.amm.quoteAssetReserve = newQuoteAssetReserve;
+	market.amm.baseAssetReserve = newBaseAssetReserve;
+
+	const baseAssetAmountSwapped = baseAssetAmountBefore.neg();
+	position.baseAssetAmount = ZERO;
+	position.quoteAssetAmount = ZERO;
+	position.lastCumulativeFundingRate = ZERO;
+	updateMarketBaseAssetAmount(
+		market,
+		baseAssetAmountBefore,
+		baseAssetAmountSwapped,
+		ZERO
+	);
+	return baseAssetAmountSwapped;
+}
+
+function chargeFee(
+	market: Market,
+	userAccount: UserAccount,
+	direction: PositionDirection,
+	baseAssetAmount: BN,
+	quoteAssetAmount: BN,
+	markPriceBefore: BN,
+	feeStructure: FeeStructure,
+	discountTokenBalance: BN | undefined,
+	hasReferrer: boolean
+): Fill {
+	const fee = calculateFee(
+		quoteAssetAmount,
+		feeStructure,
+		discountTokenBalance,
+		hasReferrer
+	);
+	market.amm.totalFee = market.amm.totalFee.add(fee.feeToMarket);
+	market.amm.totalFeeMinusDistributions =
+		market.amm.totalFeeMinusDistributions.add(fee.feeToMarket);
+	updateCollateral(userAccount, fee.userFee.neg());
+	userAccount.totalFeePaid = userAccount.totalFeePaid.add(fee.userFee);
+	userAccount.totalTokenDiscount = userAccount.totalTokenDiscount.add(
+		fee.tokenDiscount
+	);
+	userAccount.totalRefereeDiscount = userAccount.totalRefereeDiscount.add(
+		fee.refereeDiscount
+	);
+
+	return {
+		direction,
+		baseAssetAmount,
+		quoteAssetAmount: toQuoteAssetAmount(quoteAssetAmount),
+		fee,
+		markPriceBefore: toPrice(markPriceBefore),
+		markPriceAfter: toPrice(calculateMarkPrice(market)),
+	};
+}
+
+function updateCollateral(userAccount: UserAccount, change: BN): void {
+	userAccount.collateral = BN.max(userAccount.collateral.add(change), ZERO);
+}
@@ -2,11 +2,15 @@ import { BN } from '@project-serum/anchor';
 import { PriceData } from '@pythnetwork/client';
 import {
 	AMM_RESERVE_PRECISION,
+	FUNDING_PAYMENT_PRECISION,
+	FUNDING_RATE_PRECISION,
 	MARK_PRICE_PRECISION,
+	ONE,
 	QUOTE_PRECISION,
 	ZERO,
 } from '../constants/numericConstants';
-import { Market } from '../types';
+import { FundingDashboardEntry, Market, Price } from '../types';
+import { convertToNumber } from './conversion';
 import { calculateMarkPrice } from './market';
 
 /**
@@ -292,3 +296,126 @@ export function calculateFundingPool(market: Market): BN {
 	const feePool = market.amm.totalFeeMinusDistributions.sub(totalFeeLB);
 	return feePool;
 }
+
+/**
+ * Calculates a twap the same way the program does when it updates the amm's mark and oracle twaps:
+ * the new price is weighted by the time since the last update and the last twap by the remainder of the funding period
+ * @param newPrice
+ * @param lastTwap
+ * @param lastTwapTs
+ * @param fundingPeriod
+ * @param now
+ * @returns twap : Precision MARK_PRICE_PRECISION
+ */
+export function calculateNewTwap(
+	newPrice: BN,
+	lastTwap: BN,
+	lastTwapTs: BN,
+	fundingPeriod: BN,
+	now: BN
+): BN {
+	const sinceLast = BN.max(ONE, now.sub(lastTwapTs));
+	const fromStart = BN.max(ONE, fundingPeriod.sub(sinceLast));
+	return newPrice
+		.mul(sinceLast)
+		.add(lastTwap.mul(fromStart))
+		.div(sinceLast.add(fromStart));
+}
+
+/**
+ * Estimates the funding rate the program would set if update_funding_rate was called now,
+ * i.e. the spread between the mark twap and oracle twap scaled down to the funding period.
+ * This is the uncapped rate; when the clearing house can't cover the imbalance between longs and shorts, the receiving side's rate is capped
+ * @param market
+ * @param oraclePrice
+ * @param now unix timestamp in seconds, defaults to the local clock
+ * @returns funding rate : Precision MARK_PRICE_PRECISION * FUNDING_PAYMENT_PRECISION (same as amm.lastFundingRate)
+ */
+export function estimateNextFundingRate(
+	market: Market,
+	oraclePrice: Price,
+	now: BN = new BN((Date.now() / 1000).toFixed(0))
+): BN {
+	if (!market.initialized) {
+		return ZERO;
+	}
+
+	const markPriceTwap = calculateNewTwap(
+		calculateMarkPrice(market),
+		market.amm.lastMarkPriceTwap,
+		market.amm.lastMarkPriceTwapTs,
+		market.amm.fundingPeriod,
+		now
+	);
+	const oraclePriceTwap = calculateNewTwap(
+		oraclePrice,
+		market.amm.lastOraclePriceTwap,
+		market.amm.lastOraclePriceTwapTs,
+		market.amm.fundingPeriod,
+		now
+	);
+
+	const secondsInHour = new BN(3600);
+	const periodAdjustment = new BN(24)
+		.mul(secondsInHour)
+		.div(BN.max(secondsInHour, market.amm.fundingPeriod));
+
+	return markPriceTwap
+		.sub(oraclePriceTwap)
+		.mul(FUNDING_PAYMENT_PRECISION)
+		.div(periodAdjustment);
+}
+
+const SECONDS_PER_YEAR = 365.25 * 24 * 60 * 60;
+
+/**
+ * Summarizes a market's last and predicted funding rate, as rates, percentages of the oracle price and annualized percentages
+ * @param market
+ * @param marketIndex
+ * @param oraclePrice : Precision MARK_PRICE_PRECISION
+ * @param now unix timestamp in seconds, defaults to the local clock
+ * @returns funding dashboard entry
+ */
+export function calculateFundingDashboardEntry(
+	market: Market,
+	marketIndex: BN,
+	oraclePrice: Price,
+	now?: BN
+): FundingDashboardEntry {
+	const fundingPeriod = market.amm.fundingPeriod.toNumber();
+	const periodsPerYear =
+		fundingPeriod > 0 ? SECONDS_PER_YEAR / fundingPeriod : 0;
+
+	const currentFundingRate = convertToNumber(
+		market.amm.lastFundingRate,
+		FUNDING_RATE_PRECISION
+	);
+	const oraclePriceTwap = convertToNumber(market.amm.lastOraclePriceTwap);
+	const currentFundingRatePct =
+		oraclePriceTwap !== 0 ? (currentFundingRate / oraclePriceTwap) * 100 : 0;
+
+	const predictedFundingRate = convertToNumber(
+		estimateNextFundingRate(market, oraclePrice, now),
+		FUNDING_RATE_PRECISION
+	);
+	const oraclePriceNumber = convertToNumber(oraclePrice);
+	const predictedFundingRatePct =
+		oraclePriceNumber !== 0
+			? (predictedFundingRate / oraclePriceNumber) * 100
+			: 0;
+
+	return {
+		marketIndex: marketIndex.toNumber(),
+		fundingPeriod,
+		lastFundingRateTs: market.amm.lastFundingRateTs.toNumber(),
+		currentFundingRate,
+		currentFundingRatePct,
+		currentFundingRateAnnualizedPct: currentFundingRatePct * periodsPerYear,
+		predictedFundingRate,
+		predictedFundingRatePct,
+		predictedFundingRateAnnualizedPct:
+			predictedFundingRatePct * periodsPerYear,
+		markPrice: convertToNumber(calculateMarkPrice(market)),
+		oraclePrice: oraclePriceNumber,
+	};
+}
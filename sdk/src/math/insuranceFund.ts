@@ -1,6 +1,12 @@
-import { MarketsAccount, StateAccount } from '../types';
+import {
+	LiquidationHistoryAccount,
+	MarketsAccount,
+	StateAccount,
+} from '../types';
 import BN from 'bn.js';
 import { Connection } from '@solana/web3.js';
+import { getOrderedRecords, isInTimeWindow } from '../history/utils';
+import { fetchClockUnixTimestamp } from '../util/clock';
 
 /**
  * In the case of a levered loss, the exchange first pays out undistributed fees and then the insurance fund.
@@ -27,3 +33,107 @@ export async function calculateInsuranceFundSize(
 		return insuranceVaultAmount.add(market.amm.totalFee.div(new BN(2)));
 	}, insuranceVaultAmount);
 }
+
+export type InsuranceFundStats = {
+	/** unix timestamp in seconds the stats were fetched at */
+	ts: BN;
+	/** Precision QUOTE_PRECISION */
+	vaultBalance: BN;
+	/** vault balance plus undistributed market fees, see {@link calculateInsuranceFundSize} : Precision QUOTE_PRECISION */
+	effectiveSize: BN;
+	/** liquidation fees paid into the vault within the window : Precision QUOTE_PRECISION */
+	liquidationInflows: BN;
+	liquidationCount: number;
+	startTs?: BN;
+	endTs?: BN;
+};
+
+export type InsuranceFundFlows = {
+	startTs: BN;
+	endTs: BN;
+	/** Precision QUOTE_PRECISION */
+	balanceChange: BN;
+	/** liquidation fees paid into the vault between the snapshots : Precision QUOTE_PRECISION */
+	liquidationInflows: BN;
+	/** balance change not explained by liquidations, i.e. admin withdrawals (negative) or transfers into the vault : Precision QUOTE_PRECISION */
+	otherFlows: BN;
+};
+
+/**
+ * Fetches the insurance vault balance and sums the liquidation fees paid into it from the liquidation history.
+ * Only covers the records still held in the liquidation history ring buffer
+ * @param connection
+ * @param state
+ * @param marketsAccount
+ * @param liquidationHistory
+ * @param startTs inclusive, unbounded if undefined
+ * @param endTs exclusive, unbounded if undefined
+ * @returns stats : Precision QUOTE_PRECISION
+ */
+export async function fetchInsuranceFundStats(
+	connection: Connection,
+	state: StateAccount,
+	marketsAccount: MarketsAccount,
+	liquidationHistory: LiquidationHistoryAccount,
+	startTs?: BN,
+	endTs?: BN
+): Promise<InsuranceFundStats> {
+	const [vaultBalance, ts] = await Promise.all([
+		connection
+			.getTokenAccountBalance(state.insuranceVault)
+			.then((balance) => new BN(balance.value.amount)),
+		fetchClockUnixTimestamp(connection),
+	]);
+
+	const liquidationRecords = getOrderedRecords(
+		liquidationHistory.liquidationRecords
+	).filter((record) => isInTimeWindow(record.ts, startTs, endTs));
+
+	return {
+		ts,
+		vaultBalance,
+		effectiveSize: marketsAccount.markets.reduce(
+			(size, market) => size.add(market.amm.totalFee.div(new BN(2))),
+			vaultBalance
+		),
+		liquidationInflows: liquidationRecords.reduce(
+			(inflows, record) => inflows.add(record.feeToInsuranceFund),
+			new BN(0)
+		),
+		liquidationCount: liquidationRecords.length,
+		startTs,
+		endTs,
+	};
+}
+
+/**
+ * Breaks the change in vault balance between two stats snapshots into liquidation inflows and other flows.
+ * Withdrawals from the insurance vault aren't recorded on chain, so they show up in otherFlows
+ * @param before
+ * @param after
+ * @param liquidationHistory
+ * @returns flows : Precision QUOTE_PRECISION
+ */
+export function calculateInsuranceFundFlows(
+	before: InsuranceFundStats,
+	after: InsuranceFundStats,
+	liquidationHistory: LiquidationHistoryAccount
+): InsuranceFundFlows {
+	const balanceChange = after.vaultBalance.sub(before.vaultBalance);
+	const liquidationInflows = getOrderedRecords(
+		liquidationHistory.liquidationRecords
+	)
+		.filter((record) => isInTimeWindow(record.ts, before.ts, after.ts))
+		.reduce(
+			(inflows, record) => inflows.add(record.feeToInsuranceFund),
+			new BN(0)
+		);
+
+	return {
+		startTs: before.ts,
+		endTs: after.ts,
+		balanceChange,
+		liquidationInflows,
+		otherFlows: balanceChange.sub(liquidationInflows),
+	};
+}
@@ -1,6 +1,17 @@
 import { BN } from '@project-serum/anchor';
-import { Market } from '../types';
+import {
+	AMM_TO_QUOTE_PRECISION_RATIO,
+	MARK_PRICE_PRECISION,
+	ZERO,
+} from '../constants/numericConstants';
+import { Market, MarketStats, MarketTwaps } from '../types';
 import { calculatePrice } from './amm';
+import {
+	convertToNumber,
+	toBaseAssetAmount,
+	toPrice,
+	toQuoteAssetAmount,
+} from './conversion';
 
 /**
  * Calculates market mark price
@@ -15,3 +26,94 @@ export function calculateMarkPrice(market: Market): BN {
 		market.amm.pegMultiplier
 	);
 }
+
+/**
+ * Calculates the earliest time the market's funding rate can next be updated. Mirrors the program, which rounds updates to land on the funding period boundary
+ * unless the last update was more than a third of a period late
+ *
+ * @param market
+ * @return next funding time : unix timestamp in seconds
+ */
+export function calculateNextFundingTime(market: Market): BN {
+	const fundingPeriod = market.amm.fundingPeriod;
+	const lastFundingRateTs = market.amm.lastFundingRateTs;
+
+	let nextUpdateWait = fundingPeriod;
+	if (fundingPeriod.gt(new BN(1))) {
+		const lastUpdateDelay = lastFundingRateTs.umod(fundingPeriod);
+		if (!lastUpdateDelay.eq(ZERO)) {
+			const maxDelayForNextPeriod = fundingPeriod.div(new BN(3));
+			if (lastUpdateDelay.gt(maxDelayForNextPeriod)) {
+				// too late for on the hour next period, delay to following period
+				nextUpdateWait = fundingPeriod.mul(new BN(2)).sub(lastUpdateDelay);
+			} else {
+				nextUpdateWait = fundingPeriod.sub(lastUpdateDelay);
+			}
+		}
+	}
+
+	return lastFundingRateTs.add(nextUpdateWait);
+}
+
+/**
+ * Calculates how long until the market's funding rate can next be updated. Use {@link fetchClockUnixTimestamp} for `now` to avoid local clock drift
+ *
+ * @param market
+ * @param now unix timestamp in seconds
+ * @return seconds until next funding, zero if an update is already due
+ */
+export function calculateTimeUntilNextFunding(market: Market, now: BN): BN {
+	return BN.max(calculateNextFundingTime(market).sub(now), ZERO);
+}
+
+/**
+ * Summarizes a market's open interest, fees and funding from the markets account
+ *
+ * @param market
+ * @param marketIndex
+ * @return marketStats
+ */
+export function calculateMarketStats(
+	market: Market,
+	marketIndex: BN
+): MarketStats {
+	const markPrice = calculateMarkPrice(market);
+	const openInterestNotional = market.baseAssetAmountLong
+		.add(market.baseAssetAmountShort.abs())
+		.mul(markPrice)
+		.div(MARK_PRICE_PRECISION)
+		.div(AMM_TO_QUOTE_PRECISION_RATIO);
+
+	return {
+		marketIndex,
+		openInterest: market.openInterest,
+		baseAssetAmountLong: toBaseAssetAmount(market.baseAssetAmountLong),
+		baseAssetAmountShort: toBaseAssetAmount(market.baseAssetAmountShort),
+		baseAssetAmount: toBaseAssetAmount(market.baseAssetAmount),
+		openInterestNotional: toQuoteAssetAmount(openInterestNotional),
+		markPrice: toPrice(markPrice),
+		totalFee: toQuoteAssetAmount(market.amm.totalFee),
+		totalFeeMinusDistributions: toQuoteAssetAmount(
+			market.amm.totalFeeMinusDistributions
+		),
+		totalFeeWithdrawn: toQuoteAssetAmount(market.amm.totalFeeWithdrawn),
+		cumulativeFundingRateLong: market.amm.cumulativeFundingRateLong,
+		cumulativeFundingRateShort: market.amm.cumulativeFundingRateShort,
+		lastFundingRate: market.amm.lastFundingRate,
+	};
+}
+
+/**
+ * Returns the market's last mark and oracle price twaps converted from MARK_PRICE_PRECISION to ui units
+ *
+ * @param market
+ * @return twaps and the unix timestamps they were last updated at
+ */
+export function getMarketTwaps(market: Market): MarketTwaps {
+	return {
+		markPriceTwap: convertToNumber(market.amm.lastMarkPriceTwap),
+		markPriceTwapTs: market.amm.lastMarkPriceTwapTs.toNumber(),
+		oraclePriceTwap: convertToNumber(market.amm.lastOraclePriceTwap),
+		oraclePriceTwapTs: market.amm.lastOraclePriceTwapTs.toNumber(),
+	};
+}
@@ -0,0 +1,124 @@
+import { BN } from '@project-serum/anchor';
+import { PriceData } from '@pythnetwork/client';
+import { MARK_PRICE_PRECISION, ONE, ZERO } from '../constants/numericConstants';
+import { OracleGuardRails } from '../types';
+import { toPrice } from './conversion';
+
+export type OracleValidity = {
+	/** Precision MARK_PRICE_PRECISION */
+	oraclePrice: BN;
+	/** slots since the oracle was last updated */
+	delay: number;
+	isStale: boolean;
+	isConfidenceTooLarge: boolean;
+	isNonPositive: boolean;
+	isTooVolatile: boolean;
+	/** the program ignores the oracle for twaps and divergence checks if this is false */
+	isValid: boolean;
+};
+
+/**
+ * Scales a pyth integer component to MARK_PRICE_PRECISION like the program does (get_pyth_price), truncating any finer precision
+ * @param component
+ * @param exponent
+ * @returns : Precision MARK_PRICE_PRECISION
+ */
+function scaleOracleComponent(component: bigint, exponent: number): BN {
+	const value = new BN(component.toString());
+	const oraclePrecision = new BN(10).pow(new BN(Math.abs(exponent)));
+	return oraclePrecision.gt(MARK_PRICE_PRECISION)
+		? value.div(oraclePrecision.div(MARK_PRICE_PRECISION))
+		: value.mul(MARK_PRICE_PRECISION.div(oraclePrecision));
+}
+
+/**
+ * Applies the program's oracle validity guard rails (is_oracle_valid) to a pyth price, with the same integer math
+ * @param priceData
+ * @param currentSlot
+ * @param guardRails
+ * @returns oracle validity
+ */
+export function calculateOracleValidity(
+	priceData: PriceData,
+	currentSlot: number,
+	guardRails: OracleGuardRails
+): OracleValidity {
+	const price = scaleOracleComponent(
+		priceData.aggregate.priceComponent,
+		priceData.exponent
+	);
+	const twap = scaleOracleComponent(
+		priceData.twap.valueComponent,
+		priceData.exponent
+	);
+	const confidence = scaleOracleComponent(
+		priceData.aggregate.confidenceComponent,
+		priceData.exponent
+	);
+	const twac = scaleOracleComponent(
+		priceData.twac.valueComponent,
+		priceData.exponent
+	);
+	const delay = currentSlot - Number(priceData.validSlot);
+
+	const isNonPositive = price.lte(ZERO) || twap.lte(ZERO);
+
+	const tooVolatileRatio = guardRails.validity.tooVolatileRatio;
+	const isTooVolatile =
+		price.div(BN.max(ONE, twap)).gt(tooVolatileRatio) ||
+		twap.div(BN.max(ONE, price)).gt(tooVolatileRatio);
+
+	const confidenceIntervalMaxSize =
+		guardRails.validity.confidenceIntervalMaxSize;
+	const isConfidenceTooLarge =
+		price.div(BN.max(ONE, confidence)).lt(confidenceIntervalMaxSize) ||
+		twap.div(BN.max(ONE, twac)).lt(confidenceIntervalMaxSize);
+
+	const isStale = new BN(delay).gt(guardRails.validity.slotsBeforeStale);
+
+	return {
+		oraclePrice: toPrice(price),
+		delay,
+		isStale,
+		isConfidenceTooLarge,
+		isNonPositive,
+		isTooVolatile,
+		isValid: !(
+			isStale ||
+			isConfidenceTooLarge ||
+			isNonPositive ||
+			isTooVolatile
+		),
+	};
+}
+
+/**
+ * @param markPrice : Precision MARK_PRICE_PRECISION
+ * @param oraclePrice : Precision MARK_PRICE_PRECISION
+ * @returns (mark - oracle) / oracle : Precision MARK_PRICE_PRECISION
+ */
+export function calculateMarkOracleSpreadPct(
+	markPrice: BN,
+	oraclePrice: BN
+): BN {
+	if (oraclePrice.lte(ZERO)) {
+		return ZERO;
+	}
+	return markPrice.sub(oraclePrice).mul(MARK_PRICE_PRECISION).div(oraclePrice);
+}
+
+/**
+ * Applies the program's mark/oracle divergence guard rail (is_oracle_mark_too_divergent)
+ * @param markOracleSpreadPct : Precision MARK_PRICE_PRECISION
+ * @param guardRails
+ * @returns true if the spread is beyond the allowed divergence
+ */
+export function isOracleMarkTooDivergent(
+	markOracleSpreadPct: BN,
+	guardRails: OracleGuardRails
+): boolean {
+	const maxDivergence = guardRails.priceDivergence.markOracleDivergenceNumerator
+		.mul(MARK_PRICE_PRECISION)
+		.div(guardRails.priceDivergence.markOracleDivergenceDenominator);
+	return markOracleSpreadPct.abs().gt(maxDivergence);
+}
@@ -0,0 +1,93 @@
+import { BN } from '@project-serum/anchor';
+import { TEN_THOUSAND, ZERO } from '../constants/numericConstants';
+import { PortfolioSummary, QuoteAssetAmount } from '../types';
+import { convertToBN, toQuoteAssetAmount } from './conversion';
+
+const FRACTION_PRECISION = new BN(10 ** 6);
+
+/**
+ * Scales the account's total collateral by a ui fraction and caps the result at the account's buying power,
+ * so the size can be passed straight to openPosition
+ * @param portfolio from {@link ClearingHouseUser.getPortfolioSummary}
+ * @param fraction e.g. 0.5 for half of total collateral, above 1 for leveraged sizes
+ * @param maxLeverage from {@link ClearingHouse.getMaxLeverage} : Precision TEN_THOUSAND
+ * @returns quoteAssetAmount : Precision QUOTE_PRECISION
+ */
+function sizeFromFraction(
+	portfolio: PortfolioSummary,
+	fraction: number,
+	maxLeverage: BN
+): QuoteAssetAmount {
+	if (!(fraction > 0) || !isFinite(fraction)) {
+		return toQuoteAssetAmount(ZERO);
+	}
+
+	const size = portfolio.totalCollateral
+		.mul(convertToBN(fraction, FRACTION_PRECISION))
+		.div(FRACTION_PRECISION);
+	const buyingPower = BN.max(
+		portfolio.freeCollateral.mul(maxLeverage).div(TEN_THOUSAND),
+		ZERO
+	);
+	return toQuoteAssetAmount(BN.min(size, buyingPower));
+}
+
+/**
+ * Sizes a position as a fixed fraction of total collateral
+ * @param portfolio from {@link ClearingHouseUser.getPortfolioSummary}
+ * @param fraction e.g. 0.1 to put 10% of total collateral into the position
+ * @param maxLeverage from {@link ClearingHouse.getMaxLeverage} : Precision TEN_THOUSAND
+ * @returns quoteAssetAmount capped at buying power : Precision QUOTE_PRECISION
+ */
+export function calculateFixedFractionSize(
+	portfolio: PortfolioSummary,
+	fraction: number,
+	maxLeverage: BN
+): QuoteAssetAmount {
+	return sizeFromFraction(portfolio, fraction, maxLeverage);
+}
+
+/**
+ * Sizes a position so that its expected volatility, as a share of total collateral, matches a target
+ * @param portfolio from {@link ClearingHouseUser.getPortfolioSummary}
+ * @param targetVolatility e.g. 0.2 for 20% annualized volatility of the account
+ * @param assetVolatility the market's volatility over the same horizon, e.g. 0.8
+ * @param maxLeverage from {@link ClearingHouse.getMaxLeverage} : Precision TEN_THOUSAND
+ * @returns quoteAssetAmount capped at buying power : Precision QUOTE_PRECISION
+ */
+export function calculateVolatilityTargetSize(
+	portfolio: PortfolioSummary,
+	targetVolatility: number,
+	assetVolatility: number,
+	maxLeverage: BN
+): QuoteAssetAmount {
+	return sizeFromFraction(
+		portfolio,
+		targetVolatility / assetVolatility,
+		maxLeverage
+	);
+}
+
+/**
+ * Sizes a position with the continuous Kelly criterion, leverage = expected return / variance, scaled down by kellyFraction.
+ * Full Kelly is very aggressive on estimated inputs, half Kelly or less is the usual choice
+ * @param portfolio from {@link ClearingHouseUser.getPortfolioSummary}
+ * @param expectedReturn expected return of the position over the horizon, e.g. 0.05, net of funding and fees
+ * @param volatility volatility of the market over the same horizon, e.g. 0.3
+ * @param maxLeverage from {@link ClearingHouse.getMaxLeverage} : Precision TEN_THOUSAND
+ * @param kellyFraction share of the full Kelly size to take
+ * @returns quoteAssetAmount capped at buying power, zero if the edge is negative : Precision QUOTE_PRECISION
+ */
+export function calculateKellyFractionSize(
+	portfolio: PortfolioSummary,
+	expectedReturn: number,
+	volatility: number,
+	maxLeverage: BN,
+	kellyFraction = 0.5
+): QuoteAssetAmount {
+	return sizeFromFraction(
+		portfolio,
+		(kellyFraction * expectedReturn) / (volatility * volatility),
+		maxLeverage
+	);
+}
@@ -5,6 +5,7 @@ import {
 	MARK_PRICE_PRECISION,
 	PEG_PRECISION,
 	AMM_TO_QUOTE_PRECISION_RATIO,
+	AMM_TIMES_PEG_TO_QUOTE_PRECISION_RATIO,
 	ZERO,
 } from '../constants/numericConstants';
 import { calculateMarkPrice } from './market';
@@ -261,3 +262,32 @@ export function calculateTargetPriceTrade(
 		return [direction, baseSize, entryPrice, targetPrice];
 	}
 }
+
+/**
+ * Checks a quote amount against the market's minimum trade size, which the program enforces on the amount converted to quote asset reserves
+ * @param market
+ * @param quoteAssetAmount : Precision QUOTE_PRECISION
+ * @returns whether the program would reject the swap as too small
+ */
+export function isTradeSizeTooSmall(
+	market: Market,
+	quoteAssetAmount: BN
+): boolean {
+	const quoteAssetReserveAmount = quoteAssetAmount
+		.mul(AMM_TIMES_PEG_TO_QUOTE_PRECISION_RATIO)
+		.div(market.amm.pegMultiplier);
+	return quoteAssetReserveAmount.lt(market.amm.minimumTradeSize);
+}
+
+/**
+ * Calculates the smallest quote amount the program accepts for a swap in the market
+ * @param market
+ * @returns minimum quote amount : Precision QUOTE_PRECISION
+ */
+export function calculateMinimumQuoteAssetAmount(market: Market): BN {
+	const amount = market.amm.minimumTradeSize
+		.mul(market.amm.pegMultiplier)
+		.div(AMM_TIMES_PEG_TO_QUOTE_PRECISION_RATIO);
+	// round up so the amount converts back to at least the minimum trade size
+	return isTradeSizeTooSmall(market, amount) ? amount.addn(1) : amount;
+}
@@ -0,0 +1,240 @@
+export type Labels = Record<string, string>;
+
+export type MetricType = 'counter' | 'gauge' | 'histogram';
+
+export interface Metric {
+	name: string;
+	help: string;
+	type: MetricType;
+	/** lines in the prometheus text exposition format, without HELP and TYPE */
+	render(): string[];
+}
+
+/**
+ * Minimal prometheus registry, so the sdk can be scraped without depending on a metrics library.
+ * Serve {@link MetricsRegistry.metrics} with {@link MetricsRegistry.contentType} from the host application's /metrics endpoint
+ */
+export class MetricsRegistry {
+	contentType = 'text/plain; version=0.0.4; charset=utf-8';
+
+	private registered = new Map<string, Metric>();
+
+	public counter(name: string, help: string): Counter {
+		return this.register(new Counter(name, help));
+	}
+
+	public gauge(name: string, help: string): Gauge {
+		return this.register(new Gauge(name, help));
+	}
+
+	/**
+	 * @param buckets upper bounds, in ascending order
+	 */
+	public histogram(
+		name: string,
+		help: string,
+		buckets: number[] = DEFAULT_BUCKETS
+	): Histogram {
+		return this.register(new Histogram(name, help, buckets));
+	}
+
+	public getMetric(name: string): Metric | undefined {
+		return this.registered.get(name);
+	}
+
+	/**
+	 * @returns all metrics in the prometheus text exposition format
+	 */
+	public metrics(): string {
+		const lines: string[] = [];
+		for (const metric of Array.from(this.registered.values())) {
+			lines.push(`# HELP ${metric.name} ${escapeHelp(metric.help)}`);
+			lines.push(`# TYPE ${metric.name} ${metric.type}`);
+			lines.push(...metric.render());
+		}
+		return lines.join('\n') + '\n';
+	}
+
+	private register<T extends Metric>(metric: T): T {
+		const existing = this.registered.get(metric.name);
+		if (existing) {
+			if (existing.type !== metric.type) {
+				throw new Error(
+					`Metric ${metric.name} is already registered as a ${existing.type}`
+				);
+			}
+			return existing as T;
+		}
+		this.registered.set(metric.name, metric);
+		return metric;
+	}
+}
+
+/** seconds, tuned for rpc requests and transaction confirmations */
+export const DEFAULT_BUCKETS = [
+	0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1, 2.5, 5, 10, 30, 60,
+];
+
+export class Counter implements Metric {
+	name: string;
+	help: string;
+	type: MetricType = 'counter';
+	private values = new Map<string, number>();
+
+	public constructor(name: string, help: string) {
+		this.name = name;
+		this.help = help;
+	}
+
+	public inc(labels: Labels = {}, value = 1): void {
+		const key = labelsKey(labels);
+		this.values.set(key, (this.values.get(key) ?? 0) + value);
+	}
+
+	public get(labels: Labels = {}): number {
+		return this.values.get(labelsKey(labels)) ?? 0;
+	}
+
+	public render(): string[] {
+		return Array.from(this.values.entries()).map(
+			([key, value]) => `${this.name}${key} ${value}`
+		);
+	}
+}
+
+export class Gauge implements Metric {
+	name: string;
+	help: string;
+	type: MetricType = 'gauge';
+	private values = new Map<string, number>();
+	private collectors: Array<() => void> = [];
+
+	public constructor(name: string, help: string) {
+		this.name = name;
+		this.help = help;
+	}
+
+	public set(labels: Labels, value: number): void {
+		this.values.set(labelsKey(labels), value);
+	}
+
+	public get(labels: Labels = {}): number | undefined {
+		return this.values.get(labelsKey(labels));
+	}
+
+	/**
+	 * Registers a callback run before every render, for values computed at scrape time
+	 */
+	public onCollect(collector: () => void): void {
+		this.collectors.push(collector);
+	}
+
+	public render(): string[] {
+		for (const collector of this.collectors) {
+			collector();
+		}
+		return Array.from(this.values.entries()).map(
+			([key, value]) => `${this.name}${key} ${value}`
+		);
+	}
+}
+
+type HistogramSeries = {
+	labels: Labels;
+	bucketCounts: number[];
+	sum: number;
+	count: number;
+};
+
+export class Histogram implements Metric {
+	name: string;
+	help: string;
+	type: MetricType = 'histogram';
+	private buckets: number[];
+	private series = new Map<string, HistogramSeries>();
+
+	public constructor(name: string, help: string, buckets: number[]) {
+		this.name = name;
+		this.help = help;
+		this.buckets = buckets;
+	}
+
+	public observe(labels: Labels, value: number): void {
+		const key = labelsKey(labels);
+		let series = this.series.get(key);
+		if (!series) {
+			series = {
+				labels,
+				bucketCounts: this.buckets.map(() => 0),
+				sum: 0,
+				count: 0,
+			};
+			this.series.set(key, series);
+		}
+
+		for (let i = 0; i < this.buckets.length; i++) {
+			if (value <= this.buckets[i]) {
+				series.bucketCounts[i]++;
+			}
+		}
+		series.sum += value;
+		series.count++;
+	}
+
+	/**
+	 * Starts a timer, observing the elapsed seconds when the returned function is called
+	 */
+	public startTimer(labels: Labels = {}): (extraLabels?: Labels) => number {
+		const start = Date.now();
+		return (extraLabels: Labels = {}) => {
+			const seconds = (Date.now() - start) / 1000;
+			this.observe({ ...labels, ...extraLabels }, seconds);
+			return seconds;
+		};
+	}
+
+	public render(): string[] {
+		const lines: string[] = [];
+		for (const series of Array.from(this.series.values())) {
+			this.buckets.forEach((bucket, i) => {
+				lines.push(
+					`${this.name}_bucket${labelsKey({
+						...series.labels,
+						le: bucket.toString(),
+					})} ${series.bucketCounts[i]}`
+				);
+			});
+			lines.push(
+				`${this.name}_bucket${labelsKey({ ...series.labels, le: '+Inf' })} ${
+					series.count
+				}`
+			);
+			lines.push(`${this.name}_sum${labelsKey(series.labels)} ${series.sum}`);
+			lines.push(
+				`${this.name}_count${labelsKey(series.labels)} ${series.count}`
+			);
+		}
+		return lines;
+	}
+}
+
+function labelsKey(labels: Labels): string {
+	const names = Object.keys(labels).sort();
+	if (names.length === 0) {
+		return '';
+	}
+	return `{${names
+		.map((name) => `${name}="${escapeLabelValue(labels[name])}"`)
+		.join(',')}}`;
+}
+
+function escapeLabelValue(value: string): string {
+	return value
+		.replace(/\\/g, '\\\\')
+		.replace(/\n/g, '\\n')
+		.replace(/"/g, '\\"');
+}
+
+function escapeHelp(help: string): string {
+	return help.replace(/\\/g, '\\\\').replace(/\n/g, '\\n');
+}
@@ -0,0 +1,180 @@
+import {
+	ConfirmOptions,
+	Connection,
+	Signer,
+	Transaction,
+	TransactionSignature,
+} from '@solana/web3.js';
+import { ClearingHouseAccountEvents } from '../accounts/types';
+import { ClearingHouse } from '../clearingHouse';
+import { ClearingHouseUser } from '../clearingHouseUser';
+import { TxSender } from '../tx/types';
+import { Counter, Gauge, Histogram, MetricsRegistry } from './registry';
+
+const CLEARING_HOUSE_ACCOUNT_EVENTS: Array<keyof ClearingHouseAccountEvents> =
+	[
+		'stateAccountUpdate',
+		'marketsAccountUpdate',
+		'fundingPaymentHistoryAccountUpdate',
+		'fundingRateHistoryAccountUpdate',
+		'tradeHistoryAccountUpdate',
+		'liquidationHistoryAccountUpdate',
+		'depositHistoryAccountUpdate',
+		'curveHistoryAccountUpdate',
+	];
+
+/**
+ * # SdkMetrics
+ * Opt-in instrumentation of rpc request latency, transaction outcomes, subscription lag and cache hit rates,
+ * collected in a {@link MetricsRegistry} the host application exposes for prometheus to scrape.
+ *
+ * ```ts
+ * const metrics = new SdkMetrics();
+ * metrics.instrumentClearingHouse(clearingHouse);
+ * metrics.instrumentUser(user);
+ * // serve metrics.registry.metrics() on /metrics
+ * ```
+ */
+export class SdkMetrics {
+	registry: MetricsRegistry;
+	rpcRequestDuration: Histogram;
+	rpcRequestErrors: Counter;
+	txs: Counter;
+	txDuration: Histogram;
+	subscriptionUpdateAge: Gauge;
+	cacheLookups: Counter;
+
+	private lastUpdateTs = new Map<string, number>();
+	private instrumentedConnections = new WeakSet<Connection>();
+
+	/**
+	 * @param registry shared with other metrics of the host application, a new one by default
+	 * @param prefix prepended to every metric name
+	 */
+	public constructor(
+		registry: MetricsRegistry = new MetricsRegistry(),
+		prefix = 'drift'
+	) {
+		this.registry = registry;
+		this.rpcRequestDuration = registry.histogram(
+			`${prefix}_rpc_request_duration_seconds`,
+			'Latency of json rpc requests by method'
+		);
+		this.rpcRequestErrors = registry.counter(
+			`${prefix}_rpc_request_errors_total`,
+			'Json rpc requests that failed or returned an error, by method'
+		);
+		this.txs = registry.counter(
+			`${prefix}_tx_total`,
+			'Transactions sent, by result (success or failure)'
+		);
+		this.txDuration = registry.histogram(
+			`${prefix}_tx_duration_seconds`,
+			'Time from sending a transaction to its confirmation or failure'
+		);
+		this.subscriptionUpdateAge = registry.gauge(
+			`${prefix}_subscription_update_age_seconds`,
+			'Seconds since the subscription last delivered the account'
+		);
+		this.cacheLookups = registry.counter(
+			`${prefix}_cache_lookups_total`,
+			'Lookups of sdk caches, by cache and result (hit or miss)'
+		);
+
+		this.subscriptionUpdateAge.onCollect(() => {
+			const now = Date.now();
+			for (const [account, ts] of Array.from(this.lastUpdateTs.entries())) {
+				this.subscriptionUpdateAge.set({ account }, (now - ts) / 1000);
+			}
+		});
+	}
+
+	/**
+	 * Times every json rpc request the connection makes. Instrumenting a connection twice is a no-op
+	 */
+	public instrumentConnection(connection: Connection): void {
+		if (this.instrumentedConnections.has(connection)) {
+			return;
+		}
+		this.instrumentedConnections.add(connection);
+
+		// every Connection method goes through _rpcRequest
+		const rpcRequest = connection['_rpcRequest'];
+		connection['_rpcRequest'] = async (method: string, args: unknown[]) => {
+			const endTimer = this.rpcRequestDuration.startTimer({ method });
+			try {
+				const response = await rpcRequest(method, args);
+				if (response?.error) {
+					this.rpcRequestErrors.inc({ method });
+				}
+				return response;
+			} catch (e) {
+				this.rpcRequestErrors.inc({ method });
+				throw e;
+			} finally {
+				endTimer();
+			}
+		};
+	}
+
+	/**
+	 * @returns a TxSender recording the outcome and duration of every transaction sent through txSender
+	 */
+	public instrumentTxSender(txSender: TxSender): TxSender {
+		return {
+			send: async (
+				tx: Transaction,
+				additionalSigners?: Array<Signer>,
+				opts?: ConfirmOptions
+			): Promise<TransactionSignature> => {
+				const endTimer = this.txDuration.startTimer();
+				try {
+					const txSig = await txSender.send(tx, additionalSigners, opts);
+					this.txs.inc({ result: 'success' });
+					endTimer({ result: 'success' });
+					return txSig;
+				} catch (e) {
+					this.txs.inc({ result: 'failure' });
+					endTimer({ result: 'failure' });
+					throw e;
+				}
+			},
+		};
+	}
+
+	/**
+	 * Instruments the clearing house's connection, transactions, account subscriptions and user account cache
+	 */
+	public instrumentClearingHouse(clearingHouse: ClearingHouse): void {
+		this.instrumentConnection(clearingHouse.connection);
+		clearingHouse.txSender = this.instrumentTxSender(clearingHouse.txSender);
+		clearingHouse.metrics = this;
+
+		for (const event of CLEARING_HOUSE_ACCOUNT_EVENTS) {
+			clearingHouse.eventEmitter.on(event, () => {
+				this.recordSubscriptionUpdate(event.replace(/Update$/, ''));
+			});
+		}
+	}
+
+	/**
+	 * Tracks the lag of the user's account subscriptions
+	 */
+	public instrumentUser(user: ClearingHouseUser): void {
+		const authority = user.authority.toString();
+		user.eventEmitter.on('userAccountData', () => {
+			this.recordSubscriptionUpdate(`userAccount:${authority}`);
+		});
+		user.eventEmitter.on('userPositionsData', () => {
+			this.recordSubscriptionUpdate(`userPositionsAccount:${authority}`);
+		});
+	}
+
+	public recordSubscriptionUpdate(account: string): void {
+		this.lastUpdateTs.set(account, Date.now());
+	}
+
+	public recordCacheLookup(cache: string, hit: boolean): void {
+		this.cacheLookups.inc({ cache, result: hit ? 'hit' : 'miss' });
+	}
+}
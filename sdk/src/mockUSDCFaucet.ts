@@ -20,6 +20,8 @@ import {
 import BN from 'bn.js';
 import mockUSDCFaucetIDL from './idl/mock_usdc_faucet.json';
 import { IWallet } from './types';
+import { configs } from './config';
+import { getOrCreateAssociatedTokenAccount } from './util/token';
 
 export class MockUSDCFaucet {
 	connection: Connection;
@@ -130,6 +132,42 @@ export class MockUSDCFaucet {
 		});
 	}
 
+	/**
+	 * Mints to the wallet's associated token account of the faucet's mint, creating the account in the same transaction if it
+	 * doesn't exist yet
+	 * @param amount
+	 * @returns the associated token account and the transaction signature
+	 */
+	public async mintToWallet(
+		amount: BN
+	): Promise<[PublicKey, TransactionSignature]> {
+		const state: any = await this.fetchState();
+		const [associatedTokenPublicKey, createAssociatedAccountIx] =
+			await getOrCreateAssociatedTokenAccount(
+				this.connection,
+				this.wallet.publicKey,
+				state.mint
+			);
+
+		const tx = new Transaction();
+		if (createAssociatedAccountIx) {
+			tx.add(createAssociatedAccountIx);
+		}
+		tx.add(
+			await this.program.instruction.mintToUser(amount, {
+				accounts: {
+					mockUsdcFaucetState: await this.getMockUSDCFaucetStatePublicKey(),
+					mintAccount: state.mint,
+					userTokenAccount: associatedTokenPublicKey,
+					mintAuthority: state.mintAuthority,
+					tokenProgram: TOKEN_PROGRAM_ID,
+				},
+			})
+		);
+		const txSig = await this.program.provider.send(tx, [], this.opts);
+		return [associatedTokenPublicKey, txSig];
+	}
+
 	public async createAssociatedTokenAccountAndMintTo(
 		userPublicKey: PublicKey,
 		amount: BN
@@ -236,3 +274,23 @@ export class MockUSDCFaucet {
 		}
 	}
 }
+
+/**
+ * Mints the devnet deployment's mock usdc collateral to the wallet, e.g. to fund a devnet trading bot
+ * @param connection a devnet connection
+ * @param wallet
+ * @param amount : Precision QUOTE_PRECISION
+ * @returns the wallet's associated token account and the transaction signature
+ */
+export async function mintDevnetUsdc(
+	connection: Connection,
+	wallet: IWallet,
+	amount: BN
+): Promise<[PublicKey, TransactionSignature]> {
+	const faucet = new MockUSDCFaucet(
+		connection,
+		wallet,
+		new PublicKey(configs.devnet.MOCK_USDC_FAUCET_PROGRAM_ID)
+	);
+	return await faucet.mintToWallet(amount);
+}
@@ -0,0 +1,101 @@
+import { Connection, LAMPORTS_PER_SOL, PublicKey } from '@solana/web3.js';
+import { EventEmitter } from 'events';
+import StrictEventEmitter from 'strict-event-emitter-types';
+
+export type BalanceWatcherConfig = {
+	/** lowBalance is emitted when the balance drops below this many lamports */
+	thresholdLamports: number;
+	/** how often to check the balance, in ms */
+	intervalMs: number;
+};
+
+export const DEFAULT_BALANCE_WATCHER_CONFIG: BalanceWatcherConfig = {
+	thresholdLamports: 0.1 * LAMPORTS_PER_SOL,
+	intervalMs: 60000,
+};
+
+export interface BalanceWatcherEvents {
+	/** the balance dropped below the threshold, e.g. to top up the account. Emitted once until the balance recovers */
+	lowBalance: (balanceLamports: number, thresholdLamports: number) => void;
+	/** the balance is back at or above the threshold */
+	recovered: (balanceLamports: number) => void;
+	error: (error: Error) => void;
+}
+
+/**
+ * # BalanceWatcher
+ * Polls the sol balance of an account, typically a keeper or liquidator bot's fee payer, and emits lowBalance when it drops
+ * below a threshold, so bots get topped up before they quietly stop landing transactions
+ */
+export class BalanceWatcher {
+	connection: Connection;
+	publicKey: PublicKey;
+	config: BalanceWatcherConfig;
+	eventEmitter: StrictEventEmitter<EventEmitter, BalanceWatcherEvents>;
+	/** last balance seen, in lamports */
+	balance?: number;
+
+	private intervalId?: ReturnType<typeof setInterval>;
+	private low = false;
+
+	public constructor(
+		connection: Connection,
+		publicKey: PublicKey,
+		config: Partial<BalanceWatcherConfig> = {}
+	) {
+		this.connection = connection;
+		this.publicKey = publicKey;
+		this.config = { ...DEFAULT_BALANCE_WATCHER_CONFIG, ...config };
+		this.eventEmitter = new EventEmitter();
+	}
+
+	public start(): void {
+		if (this.intervalId) {
+			return;
+		}
+
+		this.intervalId = setInterval(async () => {
+			await this.check();
+		}, this.config.intervalMs);
+		this.check();
+	}
+
+	public stop(): void {
+		if (!this.intervalId) {
+			return;
+		}
+
+		clearInterval(this.intervalId);
+		this.intervalId = undefined;
+	}
+
+	public async check(): Promise<void> {
+		try {
+			this.balance = await this.connection.getBalance(this.publicKey);
+		} catch (e) {
+			this.emitError(e);
+			return;
+		}
+
+		if (this.balance < this.config.thresholdLamports) {
+			if (!this.low) {
+				this.low = true;
+				this.eventEmitter.emit(
+					'lowBalance',
+					this.balance,
+					this.config.thresholdLamports
+				);
+			}
+		} else if (this.low) {
+			this.low = false;
+			this.eventEmitter.emit('recovered', this.balance);
+		}
+	}
+
+	private emitError(error: Error): void {
+		// emitting error without a listener throws, which would crash the process from the interval
+		if (this.eventEmitter.listenerCount('error') > 0) {
+			this.eventEmitter.emit('error', error);
+		}
+	}
+}
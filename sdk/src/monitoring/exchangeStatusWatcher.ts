@@ -0,0 +1,106 @@
+import { EventEmitter } from 'events';
+import StrictEventEmitter from 'strict-event-emitter-types';
+import { ClearingHouse } from '../clearingHouse';
+import { StateAccount } from '../types';
+
+export type ExchangeStatus = {
+	exchangePaused: boolean;
+	fundingPaused: boolean;
+	adminControlsPrices: boolean;
+};
+
+export interface ExchangeStatusEvents {
+	/** any flag changed */
+	statusChange: (
+		status: ExchangeStatus,
+		previousStatus: ExchangeStatus
+	) => void;
+	exchangePausedChange: (exchangePaused: boolean) => void;
+	fundingPausedChange: (fundingPaused: boolean) => void;
+	adminControlsPricesChange: (adminControlsPrices: boolean) => void;
+}
+
+export function getExchangeStatus(state: StateAccount): ExchangeStatus {
+	return {
+		exchangePaused: state.exchangePaused,
+		fundingPaused: state.fundingPaused,
+		adminControlsPrices: state.adminControlsPrices,
+	};
+}
+
+/**
+ * # ExchangeStatusWatcher
+ * Emits events when the exchange paused, funding paused or admin controls prices flags change in the state account,
+ * so services can gate their activity on the protocol's status. The ClearingHouse must be subscribed.
+ */
+export class ExchangeStatusWatcher {
+	clearingHouse: ClearingHouse;
+	eventEmitter: StrictEventEmitter<EventEmitter, ExchangeStatusEvents>;
+
+	private status?: ExchangeStatus;
+	private listener?: (state: StateAccount) => void;
+
+	public constructor(clearingHouse: ClearingHouse) {
+		this.clearingHouse = clearingHouse;
+		this.eventEmitter = new EventEmitter();
+	}
+
+	public start(): void {
+		if (this.listener) {
+			return;
+		}
+
+		this.status = getExchangeStatus(this.clearingHouse.getStateAccount());
+		this.listener = (state: StateAccount) => {
+			this.update(getExchangeStatus(state));
+		};
+		this.clearingHouse.eventEmitter.on('stateAccountUpdate', this.listener);
+	}
+
+	public stop(): void {
+		if (!this.listener) {
+			return;
+		}
+
+		this.clearingHouse.eventEmitter.removeListener(
+			'stateAccountUpdate',
+			this.listener
+		);
+		this.listener = undefined;
+	}
+
+	/**
+	 * @returns the last status seen, read from the state account if not started
+	 */
+	public getStatus(): ExchangeStatus {
+		return (
+			this.status ?? getExchangeStatus(this.clearingHouse.getStateAccount())
+		);
+	}
+
+	private update(status: ExchangeStatus): void {
+		const previousStatus = this.status;
+		this.status = status;
+
+		if (status.exchangePaused !== previousStatus.exchangePaused) {
+			this.eventEmitter.emit('exchangePausedChange', status.exchangePaused);
+		}
+		if (status.fundingPaused !== previousStatus.fundingPaused) {
+			this.eventEmitter.emit('fundingPausedChange', status.fundingPaused);
+		}
+		if (status.adminControlsPrices !== previousStatus.adminControlsPrices) {
+			this.eventEmitter.emit(
+				'adminControlsPricesChange',
+				status.adminControlsPrices
+			);
+		}
+
+		if (
+			status.exchangePaused !== previousStatus.exchangePaused ||
+			status.fundingPaused !== previousStatus.fundingPaused ||
+			status.adminControlsPrices !== previousStatus.adminControlsPrices
+		) {
+			this.eventEmitter.emit('statusChange', status, previousStatus);
+		}
+	}
+}
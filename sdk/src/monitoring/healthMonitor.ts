@@ -0,0 +1,194 @@
+import { BN } from '@project-serum/anchor';
+import { EventEmitter } from 'events';
+import StrictEventEmitter from 'strict-event-emitter-types';
+import { ClearingHouse } from '../clearingHouse';
+import { ClearingHouseUser } from '../clearingHouseUser';
+
+export type HealthStatus = 'healthy' | 'warning' | 'danger';
+
+/**
+ * A user is in a status once its margin ratio is at or below the threshold : Precision TEN_THOUSAND
+ */
+export type HealthThresholds = {
+	warning: BN;
+	danger: BN;
+};
+
+export type HealthUpdate = {
+	user: ClearingHouseUser;
+	status: HealthStatus;
+	previousStatus: HealthStatus;
+	/** Precision TEN_THOUSAND */
+	marginRatio: BN;
+};
+
+export interface HealthMonitorEvents {
+	/** any status change, including recoveries */
+	statusChange: (update: HealthUpdate) => void;
+	warning: (update: HealthUpdate) => void;
+	danger: (update: HealthUpdate) => void;
+	/** back to healthy after a warning or danger */
+	recovered: (update: HealthUpdate) => void;
+	error: (user: ClearingHouseUser, error: Error) => void;
+}
+
+type Watch = {
+	user: ClearingHouseUser;
+	thresholds: HealthThresholds;
+	status: HealthStatus;
+	listener: () => void;
+	subscribedByMonitor: boolean;
+};
+
+/**
+ * # HealthMonitor
+ * Recomputes users' margin ratios on every user account and markets update and emits an event when a user crosses
+ * a warning or danger threshold, the basis for margin call notifications. Events are only emitted on status changes.
+ */
+export class HealthMonitor {
+	clearingHouse: ClearingHouse;
+	eventEmitter: StrictEventEmitter<EventEmitter, HealthMonitorEvents>;
+
+	private watches = new Map<string, Watch>();
+
+	public constructor(clearingHouse: ClearingHouse) {
+		this.clearingHouse = clearingHouse;
+		this.eventEmitter = new EventEmitter();
+	}
+
+	/**
+	 * Starts watching a user, subscribing it if it isn't already. Watching a user again replaces its thresholds
+	 * @param user
+	 * @param thresholds
+	 * @returns the user's current status
+	 */
+	public async watch(
+		user: ClearingHouseUser,
+		thresholds: HealthThresholds
+	): Promise<HealthStatus> {
+		if (thresholds.danger.gt(thresholds.warning)) {
+			throw new Error('Danger threshold must not be above warning threshold');
+		}
+
+		const key = user.authority.toString();
+		const existingWatch = this.watches.get(key);
+		if (existingWatch) {
+			existingWatch.thresholds = thresholds;
+			this.check(existingWatch);
+			return existingWatch.status;
+		}
+
+		let subscribedByMonitor = false;
+		if (!user.isSubscribed) {
+			await user.subscribe();
+			subscribedByMonitor = true;
+		}
+
+		const watch: Watch = {
+			user,
+			thresholds,
+			status: 'healthy',
+			listener: () => {
+				this.check(watch);
+			},
+			subscribedByMonitor,
+		};
+		this.watches.set(key, watch);
+
+		user.eventEmitter.on('userAccountData', watch.listener);
+		user.eventEmitter.on('userPositionsData', watch.listener);
+		this.clearingHouse.eventEmitter.on('marketsAccountUpdate', watch.listener);
+
+		this.check(watch);
+		return watch.status;
+	}
+
+	/**
+	 * Stops watching a user, unsubscribing it if the monitor subscribed it
+	 * @param user
+	 */
+	public async unwatch(user: ClearingHouseUser): Promise<void> {
+		const key = user.authority.toString();
+		const watch = this.watches.get(key);
+		if (!watch) {
+			return;
+		}
+
+		user.eventEmitter.removeListener('userAccountData', watch.listener);
+		user.eventEmitter.removeListener('userPositionsData', watch.listener);
+		this.clearingHouse.eventEmitter.removeListener(
+			'marketsAccountUpdate',
+			watch.listener
+		);
+		this.watches.delete(key);
+
+		if (watch.subscribedByMonitor) {
+			await user.unsubscribe();
+		}
+	}
+
+	public async unwatchAll(): Promise<void> {
+		for (const { user } of Array.from(this.watches.values())) {
+			await this.unwatch(user);
+		}
+	}
+
+	public getStatus(user: ClearingHouseUser): HealthStatus | undefined {
+		return this.watches.get(user.authority.toString())?.status;
+	}
+
+	private check(watch: Watch): void {
+		let marginRatio: BN;
+		try {
+			marginRatio = watch.user.getMarginRatio();
+		} catch (e) {
+			this.emitError(watch.user, e);
+			return;
+		}
+
+		const status = getHealthStatus(marginRatio, watch.thresholds);
+		if (status === watch.status) {
+			return;
+		}
+
+		const update: HealthUpdate = {
+			user: watch.user,
+			status,
+			previousStatus: watch.status,
+			marginRatio,
+		};
+		watch.status = status;
+
+		this.eventEmitter.emit('statusChange', update);
+		if (status === 'healthy') {
+			this.eventEmitter.emit('recovered', update);
+		} else {
+			this.eventEmitter.emit(status, update);
+		}
+	}
+
+	private emitError(user: ClearingHouseUser, error: Error): void {
+		// emitting error without a listener throws, which would break the user's account update listeners and watch()
+		if (this.eventEmitter.listenerCount('error') > 0) {
+			this.eventEmitter.emit('error', user, error);
+		}
+	}
+}
+
+/**
+ * @param marginRatio : Precision TEN_THOUSAND
+ * @param thresholds
+ * @returns health status
+ */
+export function getHealthStatus(
+	marginRatio: BN,
+	thresholds: HealthThresholds
+): HealthStatus {
+	if (marginRatio.lte(thresholds.danger)) {
+		return 'danger';
+	} else if (marginRatio.lte(thresholds.warning)) {
+		return 'warning';
+	} else {
+		return 'healthy';
+	}
+}
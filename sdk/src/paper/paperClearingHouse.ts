@@ -0,0 +1,339 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey, TransactionSignature } from '@solana/web3.js';
+import { EventEmitter } from 'events';
+import { ClearingHouse } from '../clearingHouse';
+import { ClearingHouseUser } from '../clearingHouseUser';
+import { ZERO } from '../constants/numericConstants';
+import {
+	InsufficientCollateralError,
+	MaxNumberOfPositionsError,
+} from '../errors';
+import {
+	Fill,
+	fillClosePosition,
+	fillOpenPosition,
+	getEmptyUserAccounts,
+	getOrCreatePosition,
+	MAX_POSITIONS,
+	settlePositionFunding,
+} from '../math/fill';
+import {
+	CollateralAmount,
+	MarketsAccount,
+	OpenPositionOptions,
+	PositionDirection,
+	Price,
+	QuoteAssetAmount,
+	TradeHistoryAccount,
+	TradeRecord,
+	UserAccount,
+	UserPosition,
+	UserPositionsAccount,
+} from '../types';
+import { PaperUserAccountSubscriber } from './paperUserAccountSubscriber';
+
+/**
+ * # PaperClearingHouse
+ * A ClearingHouse that fills trades locally instead of sending transactions, for backtesting and forward-testing strategies with the same
+ * code that trades for real. Trades are applied with the amm math to an in-memory copy of the markets account and to a virtual user, and
+ * a {@link ClearingHouseUser} from {@link getPaperUser} reads that virtual user and copy of the markets.
+ *
+ * Fills follow the program's increase/reduce/close/flip, fee, minimum trade size, initial margin and limit price rules.
+ * Oracle guard rails are only checked with the validateOracle option and funding rate updates aren't simulated; call
+ * {@link resetMarkets} to pick up the live markets again.
+ */
+export class PaperClearingHouse extends ClearingHouse {
+	paperAuthority: PublicKey;
+
+	private paperMarketsAccount?: MarketsAccount;
+	private paperUserAccount: UserAccount;
+	private paperUserPositionsAccount: UserPositionsAccount;
+	private paperTradeRecords: TradeRecord[] = [];
+	private paperUserAccountSubscriber: PaperUserAccountSubscriber;
+	private paperUser?: ClearingHouseUser;
+
+	/**
+	 * @param clearingHouse the live ClearingHouse, used for the state account and the initial copy of the markets
+	 * @param initialCollateral : Precision QUOTE_PRECISION
+	 * @param paperAuthority authority recorded on the virtual user and its trades, defaults to the wallet
+	 */
+	public static fromClearingHouse(
+		clearingHouse: ClearingHouse,
+		initialCollateral: CollateralAmount,
+		paperAuthority: PublicKey = clearingHouse.wallet.publicKey
+	): PaperClearingHouse {
+		return new PaperClearingHouse(
+			clearingHouse,
+			initialCollateral,
+			paperAuthority
+		);
+	}
+
+	private constructor(
+		clearingHouse: ClearingHouse,
+		initialCollateral: CollateralAmount,
+		paperAuthority: PublicKey
+	) {
+		super(
+			clearingHouse.connection,
+			clearingHouse.wallet,
+			clearingHouse.program,
+			clearingHouse.accountSubscriber,
+			clearingHouse.txSender,
+			clearingHouse.opts
+		);
+		this.paperAuthority = paperAuthority;
+		const { userAccount, userPositionsAccount } = getEmptyUserAccounts(
+			paperAuthority,
+			paperAuthority,
+			paperAuthority
+		);
+		this.paperUserAccount = {
+			...userAccount,
+			collateral: initialCollateral,
+			cumulativeDeposits: initialCollateral,
+		};
+		this.paperUserPositionsAccount = userPositionsAccount;
+		this.paperUserAccountSubscriber = new PaperUserAccountSubscriber(
+			this.paperUserAccount,
+			this.paperUserPositionsAccount
+		);
+		// keep simulated updates away from listeners on the live clearing house
+		this.eventEmitter = new EventEmitter();
+	}
+
+	/**
+	 * @returns a ClearingHouseUser backed by the virtual user and this copy of the markets
+	 */
+	public getPaperUser(): ClearingHouseUser {
+		if (!this.paperUser) {
+			this.paperUser = new ClearingHouseUser(
+				this,
+				this.paperAuthority,
+				this.paperUserAccountSubscriber
+			);
+		}
+		return this.paperUser;
+	}
+
+	/**
+	 * Discards the simulated amm changes and copies the live markets account again
+	 */
+	public resetMarkets(): void {
+		this.paperMarketsAccount = undefined;
+	}
+
+	public getMarketsAccount(): MarketsAccount {
+		if (!this.paperMarketsAccount) {
+			const marketsAccount = this.accountSubscriber.getMarketsAccount();
+			this.paperMarketsAccount = {
+				...marketsAccount,
+				markets: marketsAccount.markets.map((market) => {
+					return { ...market, amm: { ...market.amm } };
+				}),
+			};
+		}
+		return this.paperMarketsAccount;
+	}
+
+	public getTradeHistoryAccount(): TradeHistoryAccount {
+		return {
+			head: new BN(this.paperTradeRecords.length),
+			tradeRecords: this.paperTradeRecords,
+		};
+	}
+
+	public async getUserAccount(): Promise<UserAccount> {
+		return this.paperUserAccount;
+	}
+
+	public async depositCollateral(
+		amount: CollateralAmount,
+		_collateralAccountPublicKey?: PublicKey,
+		_userPositionsAccountPublicKey?: PublicKey
+	): Promise<TransactionSignature> {
+		this.paperUserAccount.collateral =
+			this.paperUserAccount.collateral.add(amount);
+		this.paperUserAccount.cumulativeDeposits =
+			this.paperUserAccount.cumulativeDeposits.add(amount);
+		this.emitUserUpdate();
+		return this.nextSignature('deposit');
+	}
+
+	public async withdrawCollateral(
+		amount: CollateralAmount,
+		_collateralAccountPublicKey?: PublicKey
+	): Promise<TransactionSignature> {
+		this.settleFunding();
+
+		const collateralBefore = this.paperUserAccount.collateral;
+		const withdrawAmount = BN.min(amount, collateralBefore);
+		this.paperUserAccount.collateral = collateralBefore.sub(withdrawAmount);
+
+		const marginRatio = this.getPaperUser().getMarginRatio();
+		if (marginRatio.lt(this.getStateAccount().marginRatioInitial)) {
+			this.paperUserAccount.collateral = collateralBefore;
+			throw new InsufficientCollateralError(
+				`Withdrawing ${withdrawAmount.toString()} would bring the margin ratio to ${marginRatio.toString()} (TEN_THOUSAND)`
+			);
+		}
+
+		this.paperUserAccount.cumulativeDeposits =
+			this.paperUserAccount.cumulativeDeposits.sub(withdrawAmount);
+		this.emitUserUpdate();
+		return this.nextSignature('withdraw');
+	}
+
+	public async openPosition(
+		direction: PositionDirection,
+		amount: QuoteAssetAmount,
+		marketIndex: BN,
+		limitPrice?: Price,
+		_discountToken?: PublicKey,
+		_referrer?: PublicKey,
+		options: OpenPositionOptions = {}
+	): Promise<TransactionSignature> {
+		if (!options.skipValidation) {
+			this.getPaperUser().validateOpenPosition(direction, amount, marketIndex);
+		}
+		if (options.validateOracle) {
+			await this.validateOracleForTrade(direction, amount, marketIndex);
+		}
+
+		return this.simulate(() => {
+			this.settleFunding();
+
+			const fill = fillOpenPosition(
+				this.getMarket(marketIndex),
+				this.paperUserAccount,
+				this.getOrCreatePosition(marketIndex),
+				direction,
+				amount,
+				this.getStateAccount().feeStructure,
+				limitPrice
+			);
+			return this.recordTrade(marketIndex, fill);
+		});
+	}
+
+	public async closePosition(
+		marketIndex: BN,
+		_discountToken?: PublicKey,
+		_referrer?: PublicKey
+	): Promise<TransactionSignature> {
+		return this.simulate(() => {
+			this.settleFunding();
+
+			const market = this.getMarket(marketIndex);
+			const position = this.getOrCreatePosition(marketIndex);
+			if (position.baseAssetAmount.eq(ZERO)) {
+				throw new Error(
+					`User has no position in market ${marketIndex.toString()}`
+				);
+			}
+
+			const fill = fillClosePosition(
+				market,
+				this.paperUserAccount,
+				position,
+				this.getStateAccount().feeStructure
+			);
+			return this.recordTrade(marketIndex, fill);
+		});
+	}
+
+	/**
+	 * Runs a simulated instruction, rolling the virtual accounts back if it throws like a failed transaction would
+	 */
+	private simulate(fill: () => TransactionSignature): TransactionSignature {
+		const marketsBefore = this.getMarketsAccount().markets.map((market) => {
+			return { ...market, amm: { ...market.amm } };
+		});
+		const userAccountBefore = { ...this.paperUserAccount };
+		const positionsBefore = this.paperUserPositionsAccount.positions.map(
+			(position) => {
+				return { ...position };
+			}
+		);
+
+		let signature: TransactionSignature;
+		try {
+			signature = fill();
+		} catch (e) {
+			this.paperMarketsAccount.markets = marketsBefore;
+			Object.assign(this.paperUserAccount, userAccountBefore);
+			this.paperUserPositionsAccount.positions = positionsBefore;
+			throw e;
+		}
+
+		this.emitUserUpdate();
+		this.eventEmitter.emit('marketsAccountUpdate', this.paperMarketsAccount);
+		return signature;
+	}
+
+	private getOrCreatePosition(marketIndex: BN): UserPosition {
+		const position = getOrCreatePosition(
+			this.paperUserPositionsAccount.positions,
+			marketIndex
+		);
+		if (!position) {
+			throw new MaxNumberOfPositionsError(
+				`Max number of positions (${MAX_POSITIONS}) taken`
+			);
+		}
+		return position;
+	}
+
+	private settleFunding(): void {
+		for (const position of this.paperUserPositionsAccount.positions) {
+			if (position.baseAssetAmount.eq(ZERO)) {
+				continue;
+			}
+
+			settlePositionFunding(
+				this.getMarket(position.marketIndex),
+				this.paperUserAccount,
+				position
+			);
+		}
+	}
+
+	private recordTrade(marketIndex: BN, fill: Fill): TransactionSignature {
+		const recordId = new BN(this.paperTradeRecords.length + 1);
+		this.paperTradeRecords.push({
+			ts: new BN((Date.now() / 1000).toFixed(0)),
+			recordId,
+			userAuthority: this.paperAuthority,
+			user: this.paperAuthority,
+			direction: fill.direction,
+			baseAssetAmount: fill.baseAssetAmount,
+			quoteAssetAmount: fill.quoteAssetAmount,
+			markPriceBefore: fill.markPriceBefore,
+			markPriceAfter: fill.markPriceAfter,
+			fee: fill.fee.userFee,
+			referrerReward: fill.fee.referrerReward,
+			refereeDiscount: fill.fee.refereeDiscount,
+			tokenDiscount: fill.fee.tokenDiscount,
+			marketIndex,
+			liquidation: false,
+			oraclePrice: ZERO,
+		});
+		this.eventEmitter.emit(
+			'tradeHistoryAccountUpdate',
+			this.getTradeHistoryAccount()
+		);
+
+		return `paper-trade-${recordId.toString()}`;
+	}
+
+	private nextSignature(prefix: string): TransactionSignature {
+		return `paper-${prefix}-${Date.now().toString()}`;
+	}
+
+	private emitUserUpdate(): void {
+		this.paperUserAccountSubscriber.update(
+			this.paperUserAccount,
+			this.paperUserPositionsAccount
+		);
+	}
+}
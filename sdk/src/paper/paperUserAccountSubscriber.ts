@@ -0,0 +1,56 @@
+import StrictEventEmitter from 'strict-event-emitter-types';
+import { EventEmitter } from 'events';
+import { UserAccountEvents, UserAccountSubscriber } from '../accounts/types';
+import { UserAccount, UserPositionsAccount } from '../types';
+
+/**
+ * Serves the virtual user and positions accounts of a {@link PaperClearingHouse} to a ClearingHouseUser
+ */
+export class PaperUserAccountSubscriber implements UserAccountSubscriber {
+	isSubscribed = false;
+	eventEmitter: StrictEventEmitter<EventEmitter, UserAccountEvents>;
+
+	private userAccount: UserAccount;
+	private userPositionsAccount: UserPositionsAccount;
+
+	public constructor(
+		userAccount: UserAccount,
+		userPositionsAccount: UserPositionsAccount
+	) {
+		this.userAccount = userAccount;
+		this.userPositionsAccount = userPositionsAccount;
+		this.eventEmitter = new EventEmitter();
+	}
+
+	async subscribe(): Promise<boolean> {
+		this.isSubscribed = true;
+		return true;
+	}
+
+	async fetch(): Promise<void> {
+		return;
+	}
+
+	async unsubscribe(): Promise<void> {
+		this.isSubscribed = false;
+	}
+
+	public update(
+		userAccount: UserAccount,
+		userPositionsAccount: UserPositionsAccount
+	): void {
+		this.userAccount = userAccount;
+		this.userPositionsAccount = userPositionsAccount;
+		this.eventEmitter.emit('userAccountData', userAccount);
+		this.eventEmitter.emit('userPositionsData', userPositionsAccount);
+		this.eventEmitter.emit('update');
+	}
+
+	public getUserAccount(): UserAccount {
+		return this.userAccount;
+	}
+
+	public getUserPositionsAccount(): UserPositionsAccount {
+		return this.userPositionsAccount;
+	}
+}
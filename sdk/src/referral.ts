@@ -0,0 +1,105 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import { ClearingHouse } from './clearingHouse';
+import { ZERO } from './constants/numericConstants';
+import { getOrderedRecords, isInTimeWindow } from './history/utils';
+import { toQuoteAssetAmount } from './math/conversion';
+import { QuoteAssetAmount, TradeRecord } from './types';
+
+export type RefereeRebates = {
+	authority: PublicKey;
+	/** trades that paid a referrer reward */
+	tradeCount: number;
+	/** Precision QUOTE_PRECISION */
+	referrerReward: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	refereeDiscount: QuoteAssetAmount;
+};
+
+export type ReferralRebates = {
+	/** rewards earned by the referrer : Precision QUOTE_PRECISION */
+	totalReferrerReward: QuoteAssetAmount;
+	/** discounts the referees got : Precision QUOTE_PRECISION */
+	totalRefereeDiscount: QuoteAssetAmount;
+	/** ordered by referrer reward, highest first */
+	referees: RefereeRebates[];
+};
+
+/**
+ * Resolves the user account to pass as the referrer of trades, see {@link ClearingHouse.openPosition}
+ * @param clearingHouse
+ * @param referrerAuthority the referrer's wallet address
+ * @returns the referrer's user account address
+ * @throws UserAccountNotFoundError if the referrer hasn't initialized a user account, the program rejects trades referring it
+ */
+export async function resolveReferrer(
+	clearingHouse: ClearingHouse,
+	referrerAuthority: PublicKey
+): Promise<PublicKey> {
+	const [userAccountPublicKey] = await clearingHouse.getUserForAuthority(
+		referrerAuthority
+	);
+	return userAccountPublicKey;
+}
+
+/**
+ * Sums the referral rebates paid on the trades of a referrer's referees.
+ *
+ * Trade records don't name the referrer, so the referees have to be known, e.g. from the referral links that signed them up.
+ * The referrer's lifetime total is the totalReferralReward of its user account
+ * @param tradeRecords
+ * @param refereeAuthorities
+ * @param startTs
+ * @param endTs
+ * @returns rebates : Precision QUOTE_PRECISION
+ */
+export function calculateReferralRebates(
+	tradeRecords: TradeRecord[],
+	refereeAuthorities: PublicKey[],
+	startTs?: BN,
+	endTs?: BN
+): ReferralRebates {
+	const refereesByAuthority = new Map<string, RefereeRebates>(
+		refereeAuthorities.map((authority) => [
+			authority.toString(),
+			{
+				authority,
+				tradeCount: 0,
+				referrerReward: toQuoteAssetAmount(ZERO),
+				refereeDiscount: toQuoteAssetAmount(ZERO),
+			},
+		])
+	);
+
+	let totalReferrerReward = ZERO;
+	let totalRefereeDiscount = ZERO;
+	for (const record of getOrderedRecords(tradeRecords)) {
+		const referee = refereesByAuthority.get(record.userAuthority.toString());
+		if (
+			!referee ||
+			record.referrerReward.eq(ZERO) ||
+			!isInTimeWindow(record.ts, startTs, endTs)
+		) {
+			continue;
+		}
+
+		referee.tradeCount += 1;
+		referee.referrerReward = toQuoteAssetAmount(
+			referee.referrerReward.add(record.referrerReward)
+		);
+		referee.refereeDiscount = toQuoteAssetAmount(
+			referee.refereeDiscount.add(record.refereeDiscount)
+		);
+		totalReferrerReward = totalReferrerReward.add(record.referrerReward);
+		totalRefereeDiscount = totalRefereeDiscount.add(record.refereeDiscount);
+	}
+
+	const referees = Array.from(refereesByAuthority.values()).sort((a, b) =>
+		b.referrerReward.cmp(a.referrerReward)
+	);
+	return {
+		totalReferrerReward: toQuoteAssetAmount(totalReferrerReward),
+		totalRefereeDiscount: toQuoteAssetAmount(totalRefereeDiscount),
+		referees,
+	};
+}
@@ -0,0 +1,435 @@
+import { BN, Program } from '@project-serum/anchor';
+import { TransactionSignature } from '@solana/web3.js';
+import { EventEmitter } from 'events';
+import StrictEventEmitter from 'strict-event-emitter-types';
+import { ZERO } from '../constants/numericConstants';
+import { DecodedIx } from '../decode/instructionDecoder';
+import { BackfilledTx } from '../history/backfill';
+import {
+	Fill,
+	fillClosePosition,
+	fillOpenPosition,
+	getEmptyUserAccounts,
+	getOrCreatePosition,
+	MAX_POSITIONS,
+	settlePositionFunding,
+} from '../math/fill';
+import { toPrice, toQuoteAssetAmount } from '../math/conversion';
+import { calculateMarkPrice } from '../math/market';
+import { Snapshot } from '../snapshot/reader';
+import {
+	FundingRateRecord,
+	MarketsAccount,
+	Price,
+	StateAccount,
+	UserPosition,
+} from '../types';
+import {
+	calculateUserAccountValue,
+	UserAccountAndPositions,
+} from '../userScanner';
+
+export type ReplaySkippedIx = {
+	signature: TransactionSignature;
+	name: string;
+	reason: string;
+};
+
+export type ReplaySlot = {
+	slot: number;
+	/** block time of the slot's last transaction, in seconds */
+	blockTime?: number;
+	/** mark price of every market after the slot, indexed by market index. Zero for uninitialized markets : Precision MARK_PRICE_PRECISION */
+	markPrices: Price[];
+	/** cumulative funding rates of every market after the slot : Precision MARK_PRICE_PRECISION * FUNDING_PAYMENT_PRECISION */
+	cumulativeFundingRatesLong: BN[];
+	cumulativeFundingRatesShort: BN[];
+	/** account value less cumulative deposits, of the users the slot touched, keyed by user account public key : Precision QUOTE_PRECISION */
+	userPnl: Map<string, BN>;
+	/** successful instructions that changed on-chain state the replay couldn't reproduce, the replay diverges from the chain after these */
+	skipped: ReplaySkippedIx[];
+};
+
+export interface ReplayerEvents {
+	slot: (replaySlot: ReplaySlot) => void;
+}
+
+/** instructions that don't change the markets or any user, nothing to replay */
+const NO_OP_IXS = [
+	'initializeHistory',
+	'withdrawFees',
+	'withdrawFromInsuranceVault',
+	'updateAdmin',
+	'updateWhitelistMint',
+	'updateDiscountMint',
+	'updateMaxDeposit',
+	'updateExchangePaused',
+	'disableAdminControlsPrices',
+	'updateFundingPaused',
+	'updateOracleGuardRails',
+	'updateMarginRatio',
+	'updatePartialLiquidationClosePercentage',
+	'updatePartialLiquidationPenaltyPercentage',
+	'updateFullLiquidationPenaltyPercentage',
+	'updatePartialLiquidationLiquidatorShareDenominator',
+	'updateFullLiquidationLiquidatorShareDenominator',
+	'updateFundingRate',
+];
+
+/**
+ * # Replayer
+ * Replays the clearing house's transactions on top of a snapshot through the SDK's local AMM and fill math, producing the mark
+ * prices, funding rates and user pnl after every slot. Feed it the transactions after the snapshot's slot in order, e.g. with
+ * backfill(connection, program, program.programId, (tx) => replayer.apply(tx), { untilSlot: snapshot.manifest.slot }), then flush.
+ *
+ * Funding rates depend on oracle twaps the replay doesn't have, so they come from funding rate records instead: each record is
+ * applied after the last transaction whose block time reaches the record's timestamp.
+ * Trades, closes, deposits, withdrawals, funding settlements and new users are replayed. Liquidations, admin curve changes and
+ * instructions of users missing from the snapshot can't be, and are reported in {@link ReplaySlot.skipped}. So are trades passing
+ * a discount token, which are replayed without its discount.
+ */
+export class Replayer {
+	program: Program;
+	stateAccount: StateAccount;
+	marketsAccount: MarketsAccount;
+	/** keyed by user account public key */
+	users = new Map<string, UserAccountAndPositions>();
+	eventEmitter: StrictEventEmitter<EventEmitter, ReplayerEvents>;
+	/** last slot replayed */
+	slot: number;
+
+	private fundingRateRecords: FundingRateRecord[];
+	private pendingSlot?: {
+		slot: number;
+		blockTime?: number;
+		touchedUsers: Set<string>;
+		skipped: ReplaySkippedIx[];
+	};
+
+	/**
+	 * @param program
+	 * @param snapshot starting state, deep copied. Should be dumped with includeUsers
+	 * @param fundingRateRecords funding rate records covering the replayed period, e.g. from a later snapshot's funding rate history
+	 */
+	public constructor(
+		program: Program,
+		snapshot: Snapshot,
+		fundingRateRecords: FundingRateRecord[] = []
+	) {
+		this.program = program;
+		this.stateAccount = snapshot.stateAccount;
+		this.marketsAccount = {
+			...snapshot.marketsAccount,
+			markets: snapshot.marketsAccount.markets.map((market) => {
+				return { ...market, amm: { ...market.amm } };
+			}),
+		};
+		for (const user of snapshot.users) {
+			this.users.set(user.userAccountPublicKey.toString(), {
+				userAccountPublicKey: user.userAccountPublicKey,
+				userAccount: { ...user.userAccount },
+				userPositionsAccount: {
+					...user.userPositionsAccount,
+					positions: user.userPositionsAccount.positions.map((position) => {
+						return { ...position };
+					}),
+				},
+			});
+		}
+		this.eventEmitter = new EventEmitter();
+		this.slot = snapshot.manifest.slot;
+
+		// records already reflected in the snapshot's markets are dropped
+		const lastRecordTs =
+			snapshot.fundingRateHistoryAccount.fundingRateRecords.reduce(
+				(ts, record) => BN.max(ts, record.ts),
+				ZERO
+			);
+		this.fundingRateRecords = fundingRateRecords
+			.filter((record) => record.ts.gt(lastRecordTs))
+			.sort((a, b) => a.recordId.cmp(b.recordId));
+	}
+
+	/**
+	 * Replays a transaction. Transactions must come in order, those in or before the last replayed slot and failed ones are ignored.
+	 * Emits the previous slot once a transaction of a later slot arrives
+	 * @param tx
+	 */
+	public apply(tx: BackfilledTx): void {
+		if (tx.slot <= this.slot) {
+			return;
+		}
+		if (this.pendingSlot && tx.slot > this.pendingSlot.slot) {
+			this.flush();
+		}
+		if (!this.pendingSlot) {
+			this.pendingSlot = {
+				slot: tx.slot,
+				touchedUsers: new Set(),
+				skipped: [],
+			};
+		}
+		this.pendingSlot.blockTime = tx.blockTime ?? this.pendingSlot.blockTime;
+
+		if (!tx.err) {
+			for (const ix of tx.instructions) {
+				const reason = this.applyIx(ix);
+				if (reason) {
+					this.pendingSlot.skipped.push({
+						signature: tx.signature,
+						name: ix.name,
+						reason,
+					});
+				}
+			}
+		}
+
+		if (tx.blockTime !== undefined) {
+			this.applyFundingRateRecords(new BN(tx.blockTime));
+		}
+	}
+
+	/**
+	 * Emits the slot in progress. Call it after the last transaction
+	 */
+	public flush(): void {
+		if (!this.pendingSlot) {
+			return;
+		}
+
+		const { slot, blockTime, touchedUsers, skipped } = this.pendingSlot;
+		this.pendingSlot = undefined;
+		this.slot = slot;
+
+		const userPnl = new Map<string, BN>();
+		touchedUsers.forEach((key) => {
+			const user = this.users.get(key);
+			if (user) {
+				userPnl.set(key, this.getUserPnl(user));
+			}
+		});
+
+		const markets = this.marketsAccount.markets;
+		this.eventEmitter.emit('slot', {
+			slot,
+			blockTime,
+			markPrices: markets.map((market) =>
+				toPrice(market.initialized ? calculateMarkPrice(market) : ZERO)
+			),
+			cumulativeFundingRatesLong: markets.map(
+				(market) => market.amm.cumulativeFundingRateLong
+			),
+			cumulativeFundingRatesShort: markets.map(
+				(market) => market.amm.cumulativeFundingRateShort
+			),
+			userPnl,
+			skipped,
+		});
+	}
+
+	/**
+	 * @param user
+	 * @returns account value less cumulative deposits : Precision QUOTE_PRECISION
+	 */
+	public getUserPnl(user: UserAccountAndPositions): BN {
+		return calculateUserAccountValue(this.marketsAccount, user).sub(
+			user.userAccount.cumulativeDeposits
+		);
+	}
+
+	/**
+	 * @returns why the instruction couldn't be replayed, undefined if it was
+	 */
+	private applyIx(ix: DecodedIx): string | undefined {
+		if (NO_OP_IXS.indexOf(ix.name) !== -1) {
+			return undefined;
+		}
+
+		if (
+			ix.name === 'initializeUser' ||
+			ix.name === 'initializeUserWithExplicitPayer'
+		) {
+			this.initializeUser(ix);
+			return undefined;
+		}
+
+		if (ix.name === 'deleteUser') {
+			this.users.delete(ix.accounts.user.toString());
+			return undefined;
+		}
+
+		const userKey = ix.accounts.user?.toString();
+		const user = userKey ? this.users.get(userKey) : undefined;
+		if (!user) {
+			return userKey
+				? `user ${userKey} is not in the snapshot`
+				: 'changes the markets in a way the sdk cannot replay';
+		}
+		this.pendingSlot.touchedUsers.add(userKey);
+
+		const marketsBefore = this.marketsAccount.markets.map((market) => {
+			return { ...market, amm: { ...market.amm } };
+		});
+		const userAccountBefore = { ...user.userAccount };
+		const positionsBefore = user.userPositionsAccount.positions.map(
+			(position) => {
+				return { ...position };
+			}
+		);
+
+		try {
+			return this.applyUserIx(ix, user);
+		} catch (e) {
+			// the instruction succeeded on-chain, so the local math diverged from the program's
+			this.marketsAccount.markets = marketsBefore;
+			user.userAccount = userAccountBefore;
+			user.userPositionsAccount.positions = positionsBefore;
+			return e.message;
+		}
+	}
+
+	private applyUserIx(
+		ix: DecodedIx,
+		user: UserAccountAndPositions
+	): string | undefined {
+		const feeStructure = this.stateAccount.feeStructure;
+		switch (ix.name) {
+			case 'depositCollateral':
+				this.settleFunding(user);
+				user.userAccount.collateral = user.userAccount.collateral.add(
+					ix.args.amount
+				);
+				user.userAccount.cumulativeDeposits =
+					user.userAccount.cumulativeDeposits.add(ix.args.amount);
+				return undefined;
+			case 'withdrawCollateral':
+				this.settleFunding(user);
+				user.userAccount.collateral = user.userAccount.collateral.sub(
+					ix.args.amount
+				);
+				user.userAccount.cumulativeDeposits =
+					user.userAccount.cumulativeDeposits.sub(ix.args.amount);
+				return undefined;
+			case 'settleFundingPayment':
+				this.settleFunding(user);
+				return undefined;
+			case 'openPosition': {
+				this.settleFunding(user);
+				const fill = fillOpenPosition(
+					this.marketsAccount.markets[ix.args.marketIndex.toNumber()],
+					user.userAccount,
+					this.getOrCreatePosition(user, ix.args.marketIndex),
+					ix.args.direction,
+					toQuoteAssetAmount(ix.args.quoteAssetAmount),
+					feeStructure,
+					toPrice(ix.args.limitPrice),
+					undefined,
+					ix.args.optionalAccounts.referrer
+				);
+				return this.applyOptionalAccounts(ix, fill);
+			}
+			case 'closePosition': {
+				this.settleFunding(user);
+				const position = user.userPositionsAccount.positions.find(
+					(position) =>
+						position.marketIndex.eq(ix.args.marketIndex) &&
+						!position.baseAssetAmount.eq(ZERO)
+				);
+				if (!position) {
+					return undefined;
+				}
+				const fill = fillClosePosition(
+					this.marketsAccount.markets[ix.args.marketIndex.toNumber()],
+					user.userAccount,
+					position,
+					feeStructure,
+					undefined,
+					ix.args.optionalAccounts.referrer
+				);
+				return this.applyOptionalAccounts(ix, fill);
+			}
+			default:
+				return 'not supported by the replay';
+		}
+	}
+
+	/**
+	 * Credits the referrer's reward of a trade. The discount token balance at the time of the trade isn't known, so trades
+	 * passing one are filled without its discount
+	 * @returns why the trade's fee couldn't be reproduced, undefined if it was
+	 */
+	private applyOptionalAccounts(
+		ix: DecodedIx & { name: 'openPosition' | 'closePosition' },
+		fill: Fill
+	): string | undefined {
+		const optionalAccounts = ix.args.optionalAccounts;
+		if (optionalAccounts.referrer) {
+			const referrerPublicKey =
+				ix.remainingAccounts[optionalAccounts.discountToken ? 1 : 0];
+			const referrer = this.users.get(referrerPublicKey.toString());
+			if (referrer) {
+				referrer.userAccount.totalReferralReward =
+					referrer.userAccount.totalReferralReward.add(
+						fill.fee.referrerReward
+					);
+			}
+		}
+
+		return optionalAccounts.discountToken
+			? 'discount token balance unknown, fee replayed without its discount'
+			: undefined;
+	}
+
+	private initializeUser(ix: DecodedIx): void {
+		const userAccountPublicKey = ix.accounts.user;
+		this.users.set(userAccountPublicKey.toString(), {
+			userAccountPublicKey,
+			...getEmptyUserAccounts(
+				ix.accounts.authority,
+				userAccountPublicKey,
+				ix.accounts.userPositions
+			),
+		});
+	}
+
+	private settleFunding(user: UserAccountAndPositions): void {
+		for (const position of user.userPositionsAccount.positions) {
+			settlePositionFunding(
+				this.marketsAccount.markets[position.marketIndex.toNumber()],
+				user.userAccount,
+				position
+			);
+		}
+	}
+
+	private getOrCreatePosition(
+		user: UserAccountAndPositions,
+		marketIndex: BN
+	): UserPosition {
+		const position = getOrCreatePosition(
+			user.userPositionsAccount.positions,
+			marketIndex
+		);
+		if (!position) {
+			throw new Error(`Max number of positions (${MAX_POSITIONS}) taken`);
+		}
+		return position;
+	}
+
+	private applyFundingRateRecords(ts: BN): void {
+		while (
+			this.fundingRateRecords.length > 0 &&
+			this.fundingRateRecords[0].ts.lte(ts)
+		) {
+			const record = this.fundingRateRecords.shift();
+			const amm =
+				this.marketsAccount.markets[record.marketIndex.toNumber()].amm;
+			amm.cumulativeFundingRateLong = record.cumulativeFundingRateLong;
+			amm.cumulativeFundingRateShort = record.cumulativeFundingRateShort;
+			amm.lastFundingRate = record.fundingRate;
+			amm.lastFundingRateTs = record.ts;
+			amm.lastMarkPriceTwap = record.markPriceTwap;
+			amm.lastOraclePriceTwap = record.oraclePriceTwap;
+		}
+	}
+}
@@ -0,0 +1,144 @@
+import { BN } from '@project-serum/anchor';
+import { ClearingHouseUser } from './clearingHouseUser';
+import { BN_MAX, TEN_THOUSAND, ZERO } from './constants/numericConstants';
+import {
+	BaseAssetAmount,
+	PositionDirection,
+	Price,
+	QuoteAssetAmount,
+} from './types';
+import {
+	toBaseAssetAmount,
+	toPrice,
+	toQuoteAssetAmount,
+} from './math/conversion';
+import {
+	calculateTradeAcquiredAmounts,
+	calculateTradeSlippage,
+} from './math/trade';
+
+export type ProposedTrade = {
+	direction: PositionDirection;
+	/** Precision QUOTE_PRECISION */
+	amount: QuoteAssetAmount;
+	marketIndex: BN;
+};
+
+export type RiskReport = {
+	/** Precision AMM_RESERVE_PRECISION */
+	baseAssetAmountAcquired: BaseAssetAmount;
+	/** average fill price : Precision MARK_PRICE_PRECISION */
+	entryPrice: Price;
+	/** mark price after the trade : Precision MARK_PRICE_PRECISION */
+	newMarkPrice: Price;
+	/** Precision MARK_PRICE_PRECISION */
+	priceImpact: BN;
+	/** Precision QUOTE_PRECISION */
+	fee: QuoteAssetAmount;
+	/** Precision TEN_THOUSAND */
+	marginRatioBefore: BN;
+	/** Precision TEN_THOUSAND */
+	marginRatioAfter: BN;
+	/** Precision TEN_THOUSAND */
+	leverageBefore: BN;
+	/** Precision TEN_THOUSAND */
+	leverageAfter: BN;
+	/** -1 if the position can't be liquidated : Precision MARK_PRICE_PRECISION */
+	liquidationPriceBefore: Price;
+	/** -1 if the position can't be liquidated : Precision MARK_PRICE_PRECISION */
+	liquidationPriceAfter: Price;
+	/** whether the program would reject the trade */
+	rejected: boolean;
+	rejectionReason?: string;
+};
+
+/**
+ * # RiskEngine
+ * Evaluates what a trade would do to an account before it's sent, entirely from the subscribed account data.
+ * Useful for order tickets that show the account's margin, leverage and liquidation price after the trade
+ */
+export class RiskEngine {
+	/**
+	 * @param user subscribed ClearingHouseUser
+	 * @param trade
+	 * @returns riskReport
+	 */
+	public static evaluate(
+		user: ClearingHouseUser,
+		trade: ProposedTrade
+	): RiskReport {
+		const market = user.clearingHouse.getMarket(trade.marketIndex);
+
+		const [baseAssetAmountAcquired] = calculateTradeAcquiredAmounts(
+			trade.direction,
+			trade.amount,
+			market
+		);
+		const [priceImpact, , entryPrice, newMarkPrice] =
+			calculateTradeSlippage(trade.direction, trade.amount, market);
+
+		const fee = user.calculateFeeForQuoteAmount(trade.amount);
+
+		// fees are taken from collateral once the trade is filled
+		const totalCollateralAfter = BN.max(
+			user.getTotalCollateral().sub(fee),
+			ZERO
+		);
+		const totalPositionValueAfter = user.getTotalPositionValueAfterTrade(
+			trade.marketIndex,
+			trade.amount,
+			trade.direction
+		);
+
+		let marginRatioAfter = BN_MAX;
+		let leverageAfter = ZERO;
+		if (totalPositionValueAfter.gt(ZERO)) {
+			marginRatioAfter = totalCollateralAfter
+				.mul(TEN_THOUSAND)
+				.div(totalPositionValueAfter);
+		}
+		if (totalCollateralAfter.gt(ZERO)) {
+			leverageAfter = totalPositionValueAfter
+				.mul(TEN_THOUSAND)
+				.div(totalCollateralAfter);
+		}
+
+		let rejected = false;
+		let rejectionReason: string;
+		try {
+			user.validateOpenPosition(
+				trade.direction,
+				trade.amount,
+				trade.marketIndex
+			);
+		} catch (e) {
+			rejected = true;
+			rejectionReason = e.message;
+		}
+
+		return {
+			baseAssetAmountAcquired: toBaseAssetAmount(baseAssetAmountAcquired),
+			entryPrice: toPrice(entryPrice),
+			newMarkPrice: toPrice(newMarkPrice),
+			priceImpact,
+			fee: toQuoteAssetAmount(fee),
+			marginRatioBefore: user.getMarginRatio(),
+			marginRatioAfter,
+			leverageBefore: user.getLeverage(),
+			leverageAfter,
+			liquidationPriceBefore: toPrice(
+				user.liquidationPrice({
+					marketIndex: trade.marketIndex,
+				})
+			),
+			liquidationPriceAfter: toPrice(
+				user.liquidationPrice(
+					{ marketIndex: trade.marketIndex },
+					baseAssetAmountAcquired
+				)
+			),
+			rejected,
+			rejectionReason,
+		};
+	}
+}
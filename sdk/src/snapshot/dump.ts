@@ -0,0 +1,231 @@
+import { AccountsCoder, Program } from '@project-serum/anchor';
+import { Commitment, PublicKey } from '@solana/web3.js';
+import fs from 'fs';
+import path from 'path';
+import { getClearingHouseStateAccountPublicKey } from '../addresses';
+import { StateAccount } from '../types';
+import { toJson } from '../util/json';
+import { rpcRequest } from '../util/rpcRequest';
+import {
+	SNAPSHOT_ACCOUNT_NAMES,
+	SNAPSHOT_MANIFEST_FILE,
+	SNAPSHOT_USERS_FILE,
+	SNAPSHOT_VERSION,
+	SnapshotAccount,
+	SnapshotAccountName,
+	SnapshotManifest,
+	SnapshotUser,
+} from './types';
+
+export type DumpOptions = {
+	/** also write every user and positions account, which costs a getProgramAccounts over the whole program */
+	includeUsers: boolean;
+	/** the rpc must have processed at least this slot, e.g. the slot of a transaction the snapshot has to include */
+	minContextSlot?: number;
+	commitment: Commitment;
+};
+
+export const DEFAULT_DUMP_OPTIONS: DumpOptions = {
+	includeUsers: false,
+	commitment: 'confirmed',
+};
+
+type RawAccount = {
+	publicKey: PublicKey;
+	data: Buffer;
+};
+
+/**
+ * Writes the state, markets and history accounts, and optionally every user, to versioned json files in a directory.
+ * All accounts are fetched in a single rpc request, so they are consistent with each other at the slot recorded in the manifest
+ * @param program
+ * @param dir created if it doesn't exist, existing snapshot files are overwritten
+ * @param options
+ * @returns the manifest written
+ */
+export async function dump(
+	program: Program,
+	dir: string,
+	options: Partial<DumpOptions> = {}
+): Promise<SnapshotManifest> {
+	const { includeUsers, minContextSlot, commitment } = {
+		...DEFAULT_DUMP_OPTIONS,
+		...options,
+	};
+
+	const statePublicKey = await getClearingHouseStateAccountPublicKey(
+		program.programId
+	);
+	const state = (await program.account.state.fetch(
+		statePublicKey
+	)) as StateAccount;
+	const publicKeys: Record<SnapshotAccountName, PublicKey> = {
+		state: statePublicKey,
+		markets: state.markets,
+		tradeHistory: state.tradeHistory,
+		depositHistory: state.depositHistory,
+		fundingPaymentHistory: state.fundingPaymentHistory,
+		fundingRateHistory: state.fundingRateHistory,
+		curveHistory: state.curveHistory,
+		liquidationHistory: state.liquidationHistory,
+	};
+
+	const { slot, accounts } = includeUsers
+		? await fetchProgramAccounts(program, commitment, minContextSlot)
+		: await fetchMultipleAccounts(
+				program,
+				SNAPSHOT_ACCOUNT_NAMES.map((name) => publicKeys[name]),
+				commitment,
+				minContextSlot
+		  );
+	const accountMap = new Map<string, RawAccount>();
+	for (const account of accounts) {
+		accountMap.set(account.publicKey.toString(), account);
+	}
+
+	await fs.promises.mkdir(dir, { recursive: true });
+
+	const files = {} as Record<SnapshotAccountName, string>;
+	for (const name of SNAPSHOT_ACCOUNT_NAMES) {
+		const account = accountMap.get(publicKeys[name].toString());
+		if (!account) {
+			throw new Error(
+				`Account ${name} ${publicKeys[name].toString()} not found at slot ${slot}`
+			);
+		}
+		files[name] = `${name}.json`;
+		await writeJson(
+			path.join(dir, files[name]),
+			toSnapshotAccount(program, name, account)
+		);
+	}
+
+	if (includeUsers) {
+		await writeJson(
+			path.join(dir, SNAPSHOT_USERS_FILE),
+			toSnapshotUsers(program, accounts)
+		);
+	}
+
+	const manifest: SnapshotManifest = {
+		version: SNAPSHOT_VERSION,
+		programId: program.programId.toString(),
+		slot,
+		createdTs: Date.now(),
+		includesUsers: includeUsers,
+		files,
+	};
+	await writeJson(path.join(dir, SNAPSHOT_MANIFEST_FILE), manifest);
+	return manifest;
+}
+
+function toSnapshotAccount(
+	program: Program,
+	name: string,
+	account: RawAccount
+): SnapshotAccount {
+	return {
+		publicKey: account.publicKey.toString(),
+		data: account.data.toString('base64'),
+		decoded: toJson(
+			program.coder.accounts.decode(capitalize(name), account.data)
+		),
+	};
+}
+
+function toSnapshotUsers(
+	program: Program,
+	accounts: RawAccount[]
+): SnapshotUser[] {
+	const userDiscriminator = AccountsCoder.accountDiscriminator('User');
+	const userPositionsDiscriminator =
+		AccountsCoder.accountDiscriminator('UserPositions');
+
+	const userPositionsMap = new Map<string, RawAccount>();
+	for (const account of accounts) {
+		if (account.data.slice(0, 8).equals(userPositionsDiscriminator)) {
+			userPositionsMap.set(account.publicKey.toString(), account);
+		}
+	}
+
+	const users: SnapshotUser[] = [];
+	for (const account of accounts) {
+		if (!account.data.slice(0, 8).equals(userDiscriminator)) {
+			continue;
+		}
+		const user = toSnapshotAccount(program, 'user', account);
+		const userPositions = userPositionsMap.get(
+			(user.decoded as { positions: string }).positions
+		);
+		if (userPositions) {
+			users.push({
+				user,
+				userPositions: toSnapshotAccount(
+					program,
+					'userPositions',
+					userPositions
+				),
+			});
+		}
+	}
+	return users;
+}
+
+/**
+ * Every account the program owns, from a single getProgramAccounts
+ */
+async function fetchProgramAccounts(
+	program: Program,
+	commitment: Commitment,
+	minContextSlot?: number
+): Promise<{ slot: number; accounts: RawAccount[] }> {
+	const result = await rpcRequest<{
+		context: { slot: number };
+		value: { pubkey: string; account: { data: [string, string] } }[];
+	}>(program.provider.connection, 'getProgramAccounts', [
+		program.programId.toBase58(),
+		{ encoding: 'base64', commitment, withContext: true, minContextSlot },
+	]);
+	return {
+		slot: result.context.slot,
+		accounts: result.value.map(({ pubkey, account }) => ({
+			publicKey: new PublicKey(pubkey),
+			data: Buffer.from(account.data[0], 'base64'),
+		})),
+	};
+}
+
+async function fetchMultipleAccounts(
+	program: Program,
+	publicKeys: PublicKey[],
+	commitment: Commitment,
+	minContextSlot?: number
+): Promise<{ slot: number; accounts: RawAccount[] }> {
+	const result = await rpcRequest<{
+		context: { slot: number };
+		value: ({ data: [string, string] } | null)[];
+	}>(program.provider.connection, 'getMultipleAccounts', [
+		publicKeys.map((publicKey) => publicKey.toBase58()),
+		{ encoding: 'base64', commitment, minContextSlot },
+	]);
+	return {
+		slot: result.context.slot,
+		accounts: result.value.reduce((accounts, account, i) => {
+			if (account) {
+				accounts.push({
+					publicKey: publicKeys[i],
+					data: Buffer.from(account.data[0], 'base64'),
+				});
+			}
+			return accounts;
+		}, [] as RawAccount[]),
+	};
+}
+
+function capitalize(name: string): string {
+	return name.charAt(0).toUpperCase() + name.slice(1);
+}
+
+async function writeJson(file: string, value: unknown): Promise<void> {
+	await fs.promises.writeFile(file, JSON.stringify(value, null, 2));
+}
@@ -0,0 +1,130 @@
+import { Program } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import fs from 'fs';
+import path from 'path';
+import {
+	CurveHistoryAccount,
+	DepositHistoryAccount,
+	FundingPaymentHistoryAccount,
+	FundingRateHistoryAccount,
+	LiquidationHistoryAccount,
+	MarketsAccount,
+	StateAccount,
+	TradeHistoryAccount,
+	UserAccount,
+	UserPositionsAccount,
+} from '../types';
+import { UserAccountAndPositions } from '../userScanner';
+import {
+	SNAPSHOT_MANIFEST_FILE,
+	SNAPSHOT_USERS_FILE,
+	SNAPSHOT_VERSION,
+	SnapshotAccount,
+	SnapshotAccountName,
+	SnapshotManifest,
+	SnapshotUser,
+} from './types';
+
+export type Snapshot = {
+	manifest: SnapshotManifest;
+	stateAccount: StateAccount;
+	marketsAccount: MarketsAccount;
+	tradeHistoryAccount: TradeHistoryAccount;
+	depositHistoryAccount: DepositHistoryAccount;
+	fundingPaymentHistoryAccount: FundingPaymentHistoryAccount;
+	fundingRateHistoryAccount: FundingRateHistoryAccount;
+	curveHistoryAccount: CurveHistoryAccount;
+	liquidationHistoryAccount: LiquidationHistoryAccount;
+	/** empty if the snapshot was dumped without users */
+	users: UserAccountAndPositions[];
+};
+
+/**
+ * Reads a snapshot written by {@link dump}. Accounts are decoded from their raw data with the program's coder, so no rpc is needed
+ * and the program may be built on a provider whose connection is never used
+ * @param program
+ * @param dir
+ * @returns the decoded accounts
+ */
+export async function readSnapshot(
+	program: Program,
+	dir: string
+): Promise<Snapshot> {
+	const manifest = await readJson<SnapshotManifest>(
+		path.join(dir, SNAPSHOT_MANIFEST_FILE)
+	);
+	if (manifest.version !== SNAPSHOT_VERSION) {
+		throw new Error(
+			`Snapshot version ${manifest.version} is not supported, expected ${SNAPSHOT_VERSION}`
+		);
+	}
+	if (manifest.programId !== program.programId.toString()) {
+		throw new Error(
+			`Snapshot is of program ${manifest.programId}, not ${program.programId.toString()}`
+		);
+	}
+
+	const readAccount = async <T>(name: SnapshotAccountName): Promise<T> =>
+		decodeAccount<T>(
+			program,
+			name,
+			await readJson<SnapshotAccount>(path.join(dir, manifest.files[name]))
+		);
+
+	const users: UserAccountAndPositions[] = [];
+	if (manifest.includesUsers) {
+		const snapshotUsers = await readJson<SnapshotUser[]>(
+			path.join(dir, SNAPSHOT_USERS_FILE)
+		);
+		for (const { user, userPositions } of snapshotUsers) {
+			users.push({
+				userAccountPublicKey: new PublicKey(user.publicKey),
+				userAccount: decodeAccount<UserAccount>(program, 'user', user),
+				userPositionsAccount: decodeAccount<UserPositionsAccount>(
+					program,
+					'userPositions',
+					userPositions
+				),
+			});
+		}
+	}
+
+	return {
+		manifest,
+		stateAccount: await readAccount<StateAccount>('state'),
+		marketsAccount: await readAccount<MarketsAccount>('markets'),
+		tradeHistoryAccount: await readAccount<TradeHistoryAccount>(
+			'tradeHistory'
+		),
+		depositHistoryAccount: await readAccount<DepositHistoryAccount>(
+			'depositHistory'
+		),
+		fundingPaymentHistoryAccount:
+			await readAccount<FundingPaymentHistoryAccount>('fundingPaymentHistory'),
+		fundingRateHistoryAccount: await readAccount<FundingRateHistoryAccount>(
+			'fundingRateHistory'
+		),
+		curveHistoryAccount: await readAccount<CurveHistoryAccount>(
+			'curveHistory'
+		),
+		liquidationHistoryAccount: await readAccount<LiquidationHistoryAccount>(
+			'liquidationHistory'
+		),
+		users,
+	};
+}
+
+function decodeAccount<T>(
+	program: Program,
+	name: string,
+	account: SnapshotAccount
+): T {
+	return program.coder.accounts.decode(
+		name.charAt(0).toUpperCase() + name.slice(1),
+		Buffer.from(account.data, 'base64')
+	) as T;
+}
+
+async function readJson<T>(file: string): Promise<T> {
+	return JSON.parse(await fs.promises.readFile(file, 'utf8')) as T;
+}
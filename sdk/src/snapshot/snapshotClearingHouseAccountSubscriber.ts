@@ -0,0 +1,155 @@
+import { Program } from '@project-serum/anchor';
+import { EventEmitter } from 'events';
+import StrictEventEmitter from 'strict-event-emitter-types';
+import {
+	ClearingHouseAccountEvents,
+	ClearingHouseAccountSubscriber,
+	ClearingHouseAccountTypes,
+	NotSubscribedError,
+} from '../accounts/types';
+import {
+	CurveHistoryAccount,
+	DepositHistoryAccount,
+	FundingPaymentHistoryAccount,
+	FundingRateHistoryAccount,
+	LiquidationHistoryAccount,
+	MarketsAccount,
+	StateAccount,
+	TradeHistoryAccount,
+} from '../types';
+import { readSnapshot, Snapshot } from './reader';
+
+/**
+ * Serves the accounts of a snapshot directory written by {@link dump} to a ClearingHouse, so analytics, backtests and tests run the
+ * same code paths as against a cluster without any rpc access. Every history account is available whether or not it was subscribed to.
+ * fetch re-reads the directory, so pointing it at a snapshot that is dumped again picks up the new state
+ */
+export class SnapshotClearingHouseAccountSubscriber
+	implements ClearingHouseAccountSubscriber
+{
+	isSubscribed = false;
+	program: Program;
+	dir: string;
+	eventEmitter: StrictEventEmitter<EventEmitter, ClearingHouseAccountEvents>;
+	snapshot?: Snapshot;
+
+	optionalExtraSubscriptions: ClearingHouseAccountTypes[] = [];
+
+	public constructor(program: Program, dir: string) {
+		this.program = program;
+		this.dir = dir;
+		this.eventEmitter = new EventEmitter();
+	}
+
+	public async subscribe(
+		optionalSubscriptions?: ClearingHouseAccountTypes[]
+	): Promise<boolean> {
+		if (this.isSubscribed) {
+			return true;
+		}
+
+		this.optionalExtraSubscriptions = optionalSubscriptions ?? [];
+		await this.load();
+		this.isSubscribed = true;
+		return true;
+	}
+
+	public async fetch(): Promise<void> {
+		if (!this.isSubscribed) {
+			return;
+		}
+
+		await this.load();
+	}
+
+	public async unsubscribe(): Promise<void> {
+		this.isSubscribed = false;
+	}
+
+	private async load(): Promise<void> {
+		const snapshot = await readSnapshot(this.program, this.dir);
+		if (
+			this.snapshot &&
+			this.snapshot.manifest.slot === snapshot.manifest.slot
+		) {
+			return;
+		}
+
+		this.snapshot = snapshot;
+		this.eventEmitter.emit('stateAccountUpdate', snapshot.stateAccount);
+		this.eventEmitter.emit('marketsAccountUpdate', snapshot.marketsAccount);
+		this.eventEmitter.emit(
+			'tradeHistoryAccountUpdate',
+			snapshot.tradeHistoryAccount
+		);
+		this.eventEmitter.emit(
+			'depositHistoryAccountUpdate',
+			snapshot.depositHistoryAccount
+		);
+		this.eventEmitter.emit(
+			'fundingPaymentHistoryAccountUpdate',
+			snapshot.fundingPaymentHistoryAccount
+		);
+		this.eventEmitter.emit(
+			'fundingRateHistoryAccountUpdate',
+			snapshot.fundingRateHistoryAccount
+		);
+		this.eventEmitter.emit(
+			'curveHistoryAccountUpdate',
+			snapshot.curveHistoryAccount
+		);
+		this.eventEmitter.emit(
+			'liquidationHistoryAccountUpdate',
+			snapshot.liquidationHistoryAccount
+		);
+		this.eventEmitter.emit('update');
+	}
+
+	assertIsSubscribed(): void {
+		if (!this.isSubscribed) {
+			throw new NotSubscribedError(
+				'You must call `subscribe` before using this function'
+			);
+		}
+	}
+
+	public getStateAccount(): StateAccount {
+		this.assertIsSubscribed();
+		return this.snapshot.stateAccount;
+	}
+
+	public getMarketsAccount(): MarketsAccount {
+		this.assertIsSubscribed();
+		return this.snapshot.marketsAccount;
+	}
+
+	public getTradeHistoryAccount(): TradeHistoryAccount {
+		this.assertIsSubscribed();
+		return this.snapshot.tradeHistoryAccount;
+	}
+
+	public getDepositHistoryAccount(): DepositHistoryAccount {
+		this.assertIsSubscribed();
+		return this.snapshot.depositHistoryAccount;
+	}
+
+	public getFundingPaymentHistoryAccount(): FundingPaymentHistoryAccount {
+		this.assertIsSubscribed();
+		return this.snapshot.fundingPaymentHistoryAccount;
+	}
+
+	public getFundingRateHistoryAccount(): FundingRateHistoryAccount {
+		this.assertIsSubscribed();
+		return this.snapshot.fundingRateHistoryAccount;
+	}
+
+	public getCurveHistoryAccount(): CurveHistoryAccount {
+		this.assertIsSubscribed();
+		return this.snapshot.curveHistoryAccount;
+	}
+
+	public getLiquidationHistoryAccount(): LiquidationHistoryAccount {
+		this.assertIsSubscribed();
+		return this.snapshot.liquidationHistoryAccount;
+	}
+}
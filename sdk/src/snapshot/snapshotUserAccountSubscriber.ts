@@ -0,0 +1,95 @@
+import { Program } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import { EventEmitter } from 'events';
+import StrictEventEmitter from 'strict-event-emitter-types';
+import {
+	NotSubscribedError,
+	UserAccountEvents,
+	UserAccountSubscriber,
+} from '../accounts/types';
+import { UserAccount, UserPositionsAccount } from '../types';
+import { readSnapshot } from './reader';
+
+/**
+ * Serves a user's accounts from a snapshot directory dumped with includeUsers to a ClearingHouseUser, alongside a
+ * {@link SnapshotClearingHouseAccountSubscriber} reading the same directory
+ */
+export class SnapshotUserAccountSubscriber implements UserAccountSubscriber {
+	isSubscribed = false;
+	program: Program;
+	dir: string;
+	authority: PublicKey;
+	eventEmitter: StrictEventEmitter<EventEmitter, UserAccountEvents>;
+
+	private userAccount?: UserAccount;
+	private userPositionsAccount?: UserPositionsAccount;
+
+	public constructor(program: Program, dir: string, authority: PublicKey) {
+		this.program = program;
+		this.dir = dir;
+		this.authority = authority;
+		this.eventEmitter = new EventEmitter();
+	}
+
+	async subscribe(): Promise<boolean> {
+		if (this.isSubscribed) {
+			return true;
+		}
+
+		await this.load();
+		this.isSubscribed = true;
+		return true;
+	}
+
+	async fetch(): Promise<void> {
+		if (!this.isSubscribed) {
+			return;
+		}
+
+		await this.load();
+	}
+
+	async unsubscribe(): Promise<void> {
+		this.isSubscribed = false;
+	}
+
+	private async load(): Promise<void> {
+		const snapshot = await readSnapshot(this.program, this.dir);
+		if (!snapshot.manifest.includesUsers) {
+			throw new Error(`Snapshot ${this.dir} was dumped without users`);
+		}
+
+		const user = snapshot.users.find(({ userAccount }) =>
+			userAccount.authority.equals(this.authority)
+		);
+		if (!user) {
+			throw new Error(
+				`Snapshot ${this.dir} has no user for ${this.authority.toString()}`
+			);
+		}
+
+		this.userAccount = user.userAccount;
+		this.userPositionsAccount = user.userPositionsAccount;
+		this.eventEmitter.emit('userAccountData', user.userAccount);
+		this.eventEmitter.emit('userPositionsData', user.userPositionsAccount);
+		this.eventEmitter.emit('update');
+	}
+
+	assertIsSubscribed(): void {
+		if (!this.isSubscribed) {
+			throw new NotSubscribedError(
+				'You must call `subscribe` before using this function'
+			);
+		}
+	}
+
+	public getUserAccount(): UserAccount {
+		this.assertIsSubscribed();
+		return this.userAccount;
+	}
+
+	public getUserPositionsAccount(): UserPositionsAccount {
+		this.assertIsSubscribed();
+		return this.userPositionsAccount;
+	}
+}
@@ -0,0 +1,56 @@
+/**
+ * Bumped whenever the layout of the snapshot files changes, readers reject versions they don't know
+ */
+export const SNAPSHOT_VERSION = 1;
+
+export const SNAPSHOT_MANIFEST_FILE = 'manifest.json';
+export const SNAPSHOT_USERS_FILE = 'users.json';
+
+export type SnapshotAccountName =
+	| 'state'
+	| 'markets'
+	| 'tradeHistory'
+	| 'depositHistory'
+	| 'fundingPaymentHistory'
+	| 'fundingRateHistory'
+	| 'curveHistory'
+	| 'liquidationHistory';
+
+export const SNAPSHOT_ACCOUNT_NAMES: SnapshotAccountName[] = [
+	'state',
+	'markets',
+	'tradeHistory',
+	'depositHistory',
+	'fundingPaymentHistory',
+	'fundingRateHistory',
+	'curveHistory',
+	'liquidationHistory',
+];
+
+/**
+ * An account as stored in a snapshot file. The raw data is what readers decode, the decoded mirror is there for people reading the file
+ */
+export type SnapshotAccount = {
+	publicKey: string;
+	/** base64 account data, discriminator included */
+	data: string;
+	decoded: unknown;
+};
+
+export type SnapshotUser = {
+	user: SnapshotAccount;
+	userPositions: SnapshotAccount;
+};
+
+export type SnapshotManifest = {
+	version: number;
+	programId: string;
+	/** slot the rpc served every account in the snapshot at */
+	slot: number;
+	/** unix timestamp in ms the snapshot was taken at */
+	createdTs: number;
+	/** whether users.json was written */
+	includesUsers: boolean;
+	/** file name of each account, relative to the snapshot directory */
+	files: Record<SnapshotAccountName, string>;
+};
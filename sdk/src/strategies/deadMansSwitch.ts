@@ -0,0 +1,313 @@
+import { BN } from '@project-serum/anchor';
+import {
+	PublicKey,
+	Transaction,
+	TransactionInstruction,
+	TransactionSignature,
+} from '@solana/web3.js';
+import { EventEmitter } from 'events';
+import StrictEventEmitter from 'strict-event-emitter-types';
+import { ClearingHouse } from '../clearingHouse';
+import { ClearingHouseUser } from '../clearingHouseUser';
+import { ZERO } from '../constants/numericConstants';
+import { toCollateralAmount } from '../math/conversion';
+import { calculateFee } from '../math/fees';
+import { calculateBaseAssetValue } from '../math/position';
+import { CollateralAmount, UserPosition } from '../types';
+
+export type DeadMansSwitchConfig = {
+	/** flatten once no heartbeat has arrived for this long, in ms */
+	timeoutMs: number;
+	/** how often to check the last heartbeat, in ms */
+	checkIntervalMs: number;
+	/** how often to re-sign the flattening transactions, must be well within a blockhash's lifetime, in ms */
+	presignIntervalMs: number;
+	/** token account to withdraw all collateral to after flattening, no withdrawal if undefined */
+	withdrawTo?: PublicKey;
+};
+
+export const DEFAULT_DEAD_MANS_SWITCH_CONFIG: DeadMansSwitchConfig = {
+	timeoutMs: 60000,
+	checkIntervalMs: 1000,
+	presignIntervalMs: 20000,
+};
+
+export interface DeadMansSwitchEvents {
+	/** no heartbeat for sinceHeartbeatMs, flattening */
+	triggered: (sinceHeartbeatMs: number) => void;
+	positionClosed: (marketIndex: BN, signature: TransactionSignature) => void;
+	collateralWithdrawn: (signature: TransactionSignature) => void;
+	error: (error: Error) => void;
+}
+
+type PresignedTx = {
+	/** undefined for the withdrawal */
+	marketIndex?: BN;
+	rawTx: Buffer;
+};
+
+/**
+ * # DeadMansSwitch
+ * Flattens all of the wallet's positions, and optionally withdraws its collateral, when the strategy stops calling {@link heartbeat}
+ * (e.g. the process hangs or its price feed dies). On trigger, the positions are fetched again and closed, so positions opened since
+ * the last presign are closed too. Flattening transactions are also signed ahead of time and re-signed with a fresh blockhash every
+ * presignIntervalMs, as a fallback needing only that the rpc accepts raw transactions, e.g. when the wallet can't sign anymore.
+ * The ClearingHouse and user must be subscribed, and the user must belong to the clearing house's wallet.
+ */
+export class DeadMansSwitch {
+	clearingHouse: ClearingHouse;
+	user: ClearingHouseUser;
+	config: DeadMansSwitchConfig;
+	eventEmitter: StrictEventEmitter<EventEmitter, DeadMansSwitchEvents>;
+	lastHeartbeatTs = 0;
+	triggered = false;
+
+	private presigned: PresignedTx[] = [];
+	private checkIntervalId?: ReturnType<typeof setInterval>;
+	private presignIntervalId?: ReturnType<typeof setInterval>;
+	private presigning = false;
+
+	public constructor(
+		clearingHouse: ClearingHouse,
+		user: ClearingHouseUser,
+		config: Partial<DeadMansSwitchConfig> = {}
+	) {
+		if (!user.authority.equals(clearingHouse.wallet.publicKey)) {
+			throw new Error('DeadMansSwitch user must belong to the wallet');
+		}
+
+		this.clearingHouse = clearingHouse;
+		this.user = user;
+		this.config = { ...DEFAULT_DEAD_MANS_SWITCH_CONFIG, ...config };
+		this.eventEmitter = new EventEmitter();
+	}
+
+	public async start(): Promise<void> {
+		if (this.checkIntervalId) {
+			return;
+		}
+
+		this.triggered = false;
+		this.heartbeat();
+		await this.presign();
+
+		this.presignIntervalId = setInterval(async () => {
+			await this.presign();
+		}, this.config.presignIntervalMs);
+		this.checkIntervalId = setInterval(async () => {
+			await this.check();
+		}, this.config.checkIntervalMs);
+	}
+
+	public stop(): void {
+		if (this.checkIntervalId) {
+			clearInterval(this.checkIntervalId);
+			this.checkIntervalId = undefined;
+		}
+		if (this.presignIntervalId) {
+			clearInterval(this.presignIntervalId);
+			this.presignIntervalId = undefined;
+		}
+	}
+
+	/**
+	 * Signals that the strategy is alive. Call it more often than timeoutMs
+	 */
+	public heartbeat(): void {
+		this.lastHeartbeatTs = Date.now();
+	}
+
+	/**
+	 * Flattens if the last heartbeat is older than timeoutMs. Only triggers once per start
+	 */
+	public async check(): Promise<void> {
+		const sinceHeartbeatMs = Date.now() - this.lastHeartbeatTs;
+		if (this.triggered || sinceHeartbeatMs < this.config.timeoutMs) {
+			return;
+		}
+
+		this.triggered = true;
+		this.stop();
+		this.eventEmitter.emit('triggered', sinceHeartbeatMs);
+		await this.flatten();
+	}
+
+	/**
+	 * Builds and signs a close for every open position, plus the withdrawal if configured, with a fresh blockhash
+	 */
+	public async presign(): Promise<void> {
+		if (this.presigning) {
+			return;
+		}
+		this.presigning = true;
+
+		try {
+			const txs: Array<[BN | undefined, TransactionInstruction]> = [];
+			for (const position of this.getOpenPositions()) {
+				txs.push([
+					position.marketIndex,
+					await this.clearingHouse.getClosePositionIx(position.marketIndex),
+				]);
+			}
+			if (this.config.withdrawTo) {
+				txs.push([
+					undefined,
+					await this.clearingHouse.getWithdrawCollateralIx(
+						this.estimateCollateralAfterFlattening(),
+						this.config.withdrawTo
+					),
+				]);
+			}
+
+			const { blockhash } =
+				await this.clearingHouse.connection.getRecentBlockhash();
+			const presigned: PresignedTx[] = [];
+			for (const [marketIndex, ix] of txs) {
+				const tx = new Transaction({
+					feePayer: this.clearingHouse.wallet.publicKey,
+					recentBlockhash: blockhash,
+				}).add(ix);
+				const signedTx = await this.clearingHouse.wallet.signTransaction(tx);
+				presigned.push({ marketIndex, rawTx: signedTx.serialize() });
+			}
+			this.presigned = presigned;
+		} catch (e) {
+			this.emitError(e);
+		} finally {
+			this.presigning = false;
+		}
+	}
+
+	/**
+	 * Closes the positions open now and withdraws the collateral left, if configured. A close or withdrawal that can't be built or
+	 * sent falls back to its presigned transaction
+	 */
+	private async flatten(): Promise<void> {
+		let openPositions: UserPosition[];
+		try {
+			await this.user.fetchAccounts();
+			openPositions = this.getOpenPositions();
+		} catch (e) {
+			this.emitError(e);
+			for (const presignedTx of this.presigned) {
+				await this.sendPresignedTx(presignedTx);
+			}
+			return;
+		}
+
+		for (const position of openPositions) {
+			try {
+				const signature = await this.clearingHouse.closePosition(
+					position.marketIndex
+				);
+				this.eventEmitter.emit(
+					'positionClosed',
+					position.marketIndex,
+					signature
+				);
+			} catch (e) {
+				this.emitError(e);
+				const presignedTx = this.presigned.find((presignedTx) =>
+					presignedTx.marketIndex?.eq(position.marketIndex)
+				);
+				if (presignedTx) {
+					await this.sendPresignedTx(presignedTx);
+				}
+			}
+		}
+
+		if (this.config.withdrawTo && !(await this.withdrawRemainingCollateral())) {
+			const presignedTx = this.presigned.find(
+				(presignedTx) => presignedTx.marketIndex === undefined
+			);
+			if (presignedTx) {
+				await this.sendPresignedTx(presignedTx);
+			}
+		}
+	}
+
+	/**
+	 * @returns whether the transaction landed
+	 */
+	private async sendPresignedTx({
+		marketIndex,
+		rawTx,
+	}: PresignedTx): Promise<boolean> {
+		const connection = this.clearingHouse.connection;
+		try {
+			const signature = await connection.sendRawTransaction(rawTx, {
+				skipPreflight: true,
+			});
+			const { value } = await connection.confirmTransaction(
+				signature,
+				this.clearingHouse.opts?.commitment
+			);
+			if (value.err) {
+				throw new Error(
+					`Transaction ${signature} failed: ${JSON.stringify(value.err)}`
+				);
+			}
+
+			if (marketIndex !== undefined) {
+				this.eventEmitter.emit('positionClosed', marketIndex, signature);
+			} else {
+				this.eventEmitter.emit('collateralWithdrawn', signature);
+			}
+			return true;
+		} catch (e) {
+			this.emitError(e);
+			return false;
+		}
+	}
+
+	/**
+	 * @returns whether the collateral was withdrawn, or there was none
+	 */
+	private async withdrawRemainingCollateral(): Promise<boolean> {
+		try {
+			await this.user.fetchAccounts();
+			const collateral = this.user.getUserAccount().collateral;
+			if (collateral.gt(ZERO)) {
+				const signature = await this.clearingHouse.withdrawCollateral(
+					toCollateralAmount(collateral),
+					this.config.withdrawTo
+				);
+				this.eventEmitter.emit('collateralWithdrawn', signature);
+			}
+			return true;
+		} catch (e) {
+			this.emitError(e);
+			return false;
+		}
+	}
+
+	private emitError(error: Error): void {
+		// emitting error without a listener throws, which would skip the fallbacks and leave positions open
+		if (this.eventEmitter.listenerCount('error') > 0) {
+			this.eventEmitter.emit('error', error);
+		}
+	}
+
+	private getOpenPositions(): UserPosition[] {
+		return this.user
+			.getUserPositionsAccount()
+			.positions.filter((position) => !position.baseAssetAmount.eq(ZERO));
+	}
+
+	/**
+	 * @returns collateral plus unrealized pnl, less the fees of closing every position : Precision QUOTE_PRECISION
+	 */
+	private estimateCollateralAfterFlattening(): CollateralAmount {
+		const feeStructure = this.clearingHouse.getStateAccount().feeStructure;
+		let collateral = this.user.getTotalCollateral();
+		for (const position of this.getOpenPositions()) {
+			const market = this.clearingHouse.getMarket(position.marketIndex);
+			const fee = calculateFee(
+				calculateBaseAssetValue(market, position),
+				feeStructure
+			);
+			collateral = collateral.sub(fee.userFee);
+		}
+		return toCollateralAmount(BN.max(collateral, ZERO));
+	}
+}
@@ -0,0 +1,185 @@
+import { BN } from '@project-serum/anchor';
+import { TransactionSignature } from '@solana/web3.js';
+import { EventEmitter } from 'events';
+import StrictEventEmitter from 'strict-event-emitter-types';
+import { ClearingHouse } from '../clearingHouse';
+import { ClearingHouseUser } from '../clearingHouseUser';
+import {
+	AMM_RESERVE_PRECISION,
+	PRICE_TO_QUOTE_PRECISION,
+	ZERO,
+} from '../constants/numericConstants';
+import { toQuoteAssetAmount } from '../math/conversion';
+import { calculateBaseAssetValue } from '../math/position';
+import { isTradeSizeTooSmall } from '../math/trade';
+import { PositionDirection, QuoteAssetAmount } from '../types';
+
+/**
+ * Inventory held outside the clearing house, e.g. spot holdings being hedged
+ */
+export type ExternalInventory = {
+	/** signed, positive if long : Precision AMM_RESERVE_PRECISION */
+	baseAssetAmount: BN;
+	/** Precision MARK_PRICE_PRECISION */
+	price: BN;
+};
+
+export type RebalancerConfig = {
+	/** market the hedging trades are sent to */
+	hedgeMarketIndex: BN;
+	/** net exposure the rebalancer steers towards : Precision QUOTE_PRECISION */
+	targetExposure: QuoteAssetAmount;
+	/** no trade is sent while the net exposure is within this distance of the target : Precision QUOTE_PRECISION */
+	band: QuoteAssetAmount;
+	/** how often to check the exposure, in ms */
+	intervalMs: number;
+	/** external hedge feed, only the clearing house positions count if undefined */
+	fetchExternalInventory?: () => Promise<ExternalInventory>;
+};
+
+export const DEFAULT_REBALANCER_CONFIG: Omit<
+	RebalancerConfig,
+	'hedgeMarketIndex' | 'band'
+> = {
+	targetExposure: toQuoteAssetAmount(ZERO),
+	intervalMs: 10000,
+};
+
+export interface RebalancerEvents {
+	rebalanced: (
+		direction: PositionDirection,
+		amount: QuoteAssetAmount,
+		netExposure: QuoteAssetAmount,
+		signature: TransactionSignature
+	) => void;
+	error: (error: Error) => void;
+}
+
+/**
+ * # Rebalancer
+ * Keeps the user's net exposure within a band of a target, a building block for market-neutral strategies.
+ * Net exposure is the signed value of every open position plus any external inventory at the hedge feed's price;
+ * once it leaves the band, a trade in the hedge market brings it back to the target.
+ * The ClearingHouse and ClearingHouseUser must be subscribed.
+ */
+export class Rebalancer {
+	clearingHouse: ClearingHouse;
+	user: ClearingHouseUser;
+	config: RebalancerConfig;
+	eventEmitter: StrictEventEmitter<EventEmitter, RebalancerEvents>;
+
+	private intervalId?: ReturnType<typeof setInterval>;
+	private running = false;
+
+	public constructor(
+		clearingHouse: ClearingHouse,
+		user: ClearingHouseUser,
+		config: Partial<RebalancerConfig> &
+			Pick<RebalancerConfig, 'hedgeMarketIndex' | 'band'>
+	) {
+		this.clearingHouse = clearingHouse;
+		this.user = user;
+		this.config = { ...DEFAULT_REBALANCER_CONFIG, ...config };
+		this.eventEmitter = new EventEmitter();
+	}
+
+	public start(): void {
+		if (this.intervalId) {
+			return;
+		}
+
+		this.intervalId = setInterval(async () => {
+			await this.rebalance();
+		}, this.config.intervalMs);
+	}
+
+	public stop(): void {
+		if (this.intervalId) {
+			clearInterval(this.intervalId);
+			this.intervalId = undefined;
+		}
+	}
+
+	/**
+	 * @returns signed value of every open position plus the external inventory : Precision QUOTE_PRECISION
+	 */
+	public async calculateNetExposure(): Promise<QuoteAssetAmount> {
+		let netExposure = ZERO;
+		for (const position of this.user.getUserPositionsAccount().positions) {
+			if (position.baseAssetAmount.eq(ZERO)) {
+				continue;
+			}
+
+			const market = this.clearingHouse.getMarket(position.marketIndex);
+			const value = calculateBaseAssetValue(market, position);
+			netExposure = position.baseAssetAmount.gt(ZERO)
+				? netExposure.add(value)
+				: netExposure.sub(value);
+		}
+
+		if (this.config.fetchExternalInventory) {
+			const inventory = await this.config.fetchExternalInventory();
+			netExposure = netExposure.add(
+				inventory.baseAssetAmount
+					.mul(inventory.price)
+					.div(AMM_RESERVE_PRECISION.mul(PRICE_TO_QUOTE_PRECISION))
+			);
+		}
+
+		return toQuoteAssetAmount(netExposure);
+	}
+
+	/**
+	 * Checks the net exposure once and trades back to the target if it is outside the band.
+	 * Skipped if the previous check is still running
+	 */
+	public async rebalance(): Promise<void> {
+		if (this.running) {
+			return;
+		}
+		this.running = true;
+
+		try {
+			const netExposure = await this.calculateNetExposure();
+			const deviation = netExposure.sub(this.config.targetExposure);
+			if (deviation.abs().lte(this.config.band)) {
+				return;
+			}
+
+			const amount = toQuoteAssetAmount(deviation.abs());
+			const market = this.clearingHouse.getMarket(
+				this.config.hedgeMarketIndex
+			);
+			if (isTradeSizeTooSmall(market, amount)) {
+				return;
+			}
+
+			const direction = deviation.gt(ZERO)
+				? PositionDirection.SHORT
+				: PositionDirection.LONG;
+			const signature = await this.clearingHouse.openPosition(
+				direction,
+				amount,
+				this.config.hedgeMarketIndex
+			);
+			this.eventEmitter.emit(
+				'rebalanced',
+				direction,
+				amount,
+				netExposure,
+				signature
+			);
+		} catch (e) {
+			this.emitError(e);
+		} finally {
+			this.running = false;
+		}
+	}
+
+	private emitError(error: Error): void {
+		// emitting error without a listener throws, which would crash the process from the interval
+		if (this.eventEmitter.listenerCount('error') > 0) {
+			this.eventEmitter.emit('error', error);
+		}
+	}
+}
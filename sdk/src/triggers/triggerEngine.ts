@@ -0,0 +1,249 @@
+import { BN } from '@project-serum/anchor';
+import { TransactionSignature } from '@solana/web3.js';
+import { EventEmitter } from 'events';
+import StrictEventEmitter from 'strict-event-emitter-types';
+import { ClearingHouse } from '../clearingHouse';
+import { ClearingHouseUser } from '../clearingHouseUser';
+import {
+	MARK_PRICE_PRECISION,
+	TEN_THOUSAND,
+	ZERO,
+} from '../constants/numericConstants';
+import { toQuoteAssetAmount } from '../math/conversion';
+import { calculateMarkPrice } from '../math/market';
+import { PythClient } from '../pythClient';
+import { PositionDirection } from '../types';
+import {
+	Trigger,
+	TriggerAction,
+	TriggerCondition,
+	TriggerCrossing,
+	TriggerEvents,
+} from './types';
+
+export type TriggerEngineConfig = {
+	/** how often to fetch the oracle prices used by oraclePrice conditions, in ms */
+	oraclePollMs: number;
+};
+
+export const DEFAULT_TRIGGER_ENGINE_CONFIG: TriggerEngineConfig = {
+	oraclePollMs: 1000,
+};
+
+/**
+ * # TriggerEngine
+ * Client-side stop-loss / take-profit. Protocol v1 has no resting orders, so conditions on mark price, oracle price
+ * or margin ratio are watched here and the tied action is sent once a condition is met.
+ *
+ * Triggers are one-shot: a trigger is removed after its action lands, and kept to be retried on the next update if it fails.
+ * The ClearingHouse and ClearingHouseUser must be subscribed.
+ */
+export class TriggerEngine {
+	clearingHouse: ClearingHouse;
+	user: ClearingHouseUser;
+	config: TriggerEngineConfig;
+	eventEmitter: StrictEventEmitter<EventEmitter, TriggerEvents>;
+
+	private pythClient: PythClient;
+	private triggers = new Map<number, Trigger>();
+	private pending = new Set<number>();
+	private nextTriggerId = 0;
+	/** latest oracle price by market index : Precision MARK_PRICE_PRECISION */
+	private oraclePrices = new Map<number, BN>();
+	private oracleIntervalId?: ReturnType<typeof setInterval>;
+	private listener?: () => void;
+
+	public constructor(
+		clearingHouse: ClearingHouse,
+		user: ClearingHouseUser,
+		config: Partial<TriggerEngineConfig> = {}
+	) {
+		this.clearingHouse = clearingHouse;
+		this.user = user;
+		this.config = { ...DEFAULT_TRIGGER_ENGINE_CONFIG, ...config };
+		this.pythClient = new PythClient(clearingHouse.connection);
+		this.eventEmitter = new EventEmitter();
+	}
+
+	/**
+	 * @param condition
+	 * @param action
+	 * @returns id of the trigger, to remove it
+	 */
+	public register(condition: TriggerCondition, action: TriggerAction): number {
+		const id = this.nextTriggerId++;
+		this.triggers.set(id, { id, condition, action });
+		return id;
+	}
+
+	public remove(id: number): boolean {
+		return this.triggers.delete(id);
+	}
+
+	public getTriggers(): Trigger[] {
+		return Array.from(this.triggers.values());
+	}
+
+	public start(): void {
+		if (this.listener) {
+			return;
+		}
+
+		this.listener = () => {
+			this.evaluate();
+		};
+		this.clearingHouse.eventEmitter.on('marketsAccountUpdate', this.listener);
+		this.user.eventEmitter.on('userAccountData', this.listener);
+		this.user.eventEmitter.on('userPositionsData', this.listener);
+
+		this.oracleIntervalId = setInterval(async () => {
+			await this.updateOraclePrices();
+			this.evaluate();
+		}, this.config.oraclePollMs);
+	}
+
+	public stop(): void {
+		if (!this.listener) {
+			return;
+		}
+
+		this.clearingHouse.eventEmitter.removeListener(
+			'marketsAccountUpdate',
+			this.listener
+		);
+		this.user.eventEmitter.removeListener('userAccountData', this.listener);
+		this.user.eventEmitter.removeListener('userPositionsData', this.listener);
+		this.listener = undefined;
+
+		clearInterval(this.oracleIntervalId);
+		this.oracleIntervalId = undefined;
+	}
+
+	/**
+	 * Checks every trigger against the current markets, oracle prices and user account and fires the ones that are met
+	 */
+	public evaluate(): void {
+		for (const trigger of this.triggers.values()) {
+			if (this.pending.has(trigger.id)) {
+				continue;
+			}
+
+			try {
+				if (this.isConditionMet(trigger.condition)) {
+					this.fire(trigger);
+				}
+			} catch (e) {
+				this.emitError(trigger, e);
+			}
+		}
+	}
+
+	private isConditionMet(condition: TriggerCondition): boolean {
+		switch (condition.type) {
+			case 'markPrice': {
+				const market = this.clearingHouse.getMarket(condition.marketIndex);
+				return hasCrossed(
+					calculateMarkPrice(market),
+					condition.price,
+					condition.crossing
+				);
+			}
+			case 'oraclePrice': {
+				const oraclePrice = this.oraclePrices.get(
+					condition.marketIndex.toNumber()
+				);
+				return (
+					oraclePrice !== undefined &&
+					hasCrossed(oraclePrice, condition.price, condition.crossing)
+				);
+			}
+			case 'marginRatio':
+				return hasCrossed(
+					this.user.getMarginRatio(),
+					condition.marginRatio,
+					condition.crossing
+				);
+		}
+	}
+
+	private async fire(trigger: Trigger): Promise<void> {
+		this.pending.add(trigger.id);
+		try {
+			const signature = await this.executeAction(trigger.action);
+			this.triggers.delete(trigger.id);
+			if (signature) {
+				this.eventEmitter.emit('triggerFired', trigger, signature);
+			}
+		} catch (e) {
+			this.emitError(trigger, e);
+		} finally {
+			this.pending.delete(trigger.id);
+		}
+	}
+
+	/**
+	 * @returns undefined if there is no position left to act on
+	 */
+	private async executeAction(
+		action: TriggerAction
+	): Promise<TransactionSignature | undefined> {
+		const position = this.user.getUserPosition(action.marketIndex);
+		if (!position || position.baseAssetAmount.eq(ZERO)) {
+			return undefined;
+		}
+
+		if (action.type === 'closePosition') {
+			return await this.clearingHouse.closePosition(action.marketIndex);
+		}
+
+		const amount = toQuoteAssetAmount(
+			this.user
+				.getPositionValue(action.marketIndex)
+				.mul(action.fraction)
+				.div(TEN_THOUSAND)
+		);
+		const direction = position.baseAssetAmount.gt(ZERO)
+			? PositionDirection.SHORT
+			: PositionDirection.LONG;
+		return await this.clearingHouse.openPosition(
+			direction,
+			amount,
+			action.marketIndex
+		);
+	}
+
+	private async updateOraclePrices(): Promise<void> {
+		const marketIndexes = new Set<number>();
+		for (const { condition } of this.triggers.values()) {
+			if (condition.type === 'oraclePrice') {
+				marketIndexes.add(condition.marketIndex.toNumber());
+			}
+		}
+
+		for (const marketIndex of marketIndexes) {
+			try {
+				const oracle = this.clearingHouse.getMarket(marketIndex).amm.oracle;
+				const priceData = await this.pythClient.getPriceData(oracle);
+				this.oraclePrices.set(
+					marketIndex,
+					new BN(
+						Math.round(priceData.price * MARK_PRICE_PRECISION.toNumber())
+					)
+				);
+			} catch (e) {
+				this.emitError(undefined, e);
+			}
+		}
+	}
+
+	private emitError(trigger: Trigger | undefined, error: Error): void {
+		// emitting error without a listener throws, which would crash the process from the interval
+		if (this.eventEmitter.listenerCount('error') > 0) {
+			this.eventEmitter.emit('error', trigger, error);
+		}
+	}
+}
+
+function hasCrossed(value: BN, level: BN, crossing: TriggerCrossing): boolean {
+	return crossing === 'above' ? value.gte(level) : value.lte(level);
+}
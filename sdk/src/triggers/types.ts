@@ -0,0 +1,49 @@
+import { BN } from '@project-serum/anchor';
+import { TransactionSignature } from '@solana/web3.js';
+
+export type TriggerCrossing = 'above' | 'below';
+
+export type TriggerCondition =
+	| {
+			type: 'markPrice';
+			marketIndex: BN;
+			crossing: TriggerCrossing;
+			/** Precision MARK_PRICE_PRECISION */
+			price: BN;
+	  }
+	| {
+			type: 'oraclePrice';
+			marketIndex: BN;
+			crossing: TriggerCrossing;
+			/** Precision MARK_PRICE_PRECISION */
+			price: BN;
+	  }
+	| {
+			type: 'marginRatio';
+			crossing: TriggerCrossing;
+			/** Precision TEN_THOUSAND */
+			marginRatio: BN;
+	  };
+
+export type TriggerAction =
+	| {
+			type: 'closePosition';
+			marketIndex: BN;
+	  }
+	| {
+			type: 'reducePosition';
+			marketIndex: BN;
+			/** share of the position value to close : Precision TEN_THOUSAND */
+			fraction: BN;
+	  };
+
+export type Trigger = {
+	id: number;
+	condition: TriggerCondition;
+	action: TriggerAction;
+};
+
+export interface TriggerEvents {
+	triggerFired: (trigger: Trigger, signature: TransactionSignature) => void;
+	error: (trigger: Trigger | undefined, error: Error) => void;
+}
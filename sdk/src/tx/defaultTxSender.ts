@@ -6,19 +6,43 @@ import {
 	TransactionSignature,
 } from '@solana/web3.js';
 import { Provider } from '@project-serum/anchor';
+import { isBlockhashExpiredError } from './utils';
+import { TxExpiredError } from '../errors';
 
 export class DefaultTxSender implements TxSender {
 	provider: Provider;
+	/** sends with a fresh blockhash, before giving up, when the blockhash expires */
+	maxBlockhashAttempts: number;
 
-	public constructor(provider: Provider) {
+	public constructor(provider: Provider, maxBlockhashAttempts = 3) {
 		this.provider = provider;
+		this.maxBlockhashAttempts = maxBlockhashAttempts;
 	}
 
-	send(
+	/**
+	 * Sends and confirms the transaction. If the blockhash expires before it lands, it is resigned with a fresh blockhash
+	 * and resent, up to maxBlockhashAttempts times
+	 * @throws TxExpiredError if every attempt's blockhash expired
+	 */
+	async send(
 		tx: Transaction,
 		additionalSigners?: Array<Signer>,
 		opts?: ConfirmOptions
 	): Promise<TransactionSignature> {
-		return this.provider.send(tx, additionalSigners, opts);
+		for (let attempt = 1; ; attempt++) {
+			try {
+				// the provider sets a fresh blockhash and signs on every call
+				return await this.provider.send(tx, additionalSigners, opts);
+			} catch (e) {
+				if (!isBlockhashExpiredError(e)) {
+					throw e;
+				}
+				if (attempt >= this.maxBlockhashAttempts) {
+					throw new TxExpiredError(
+						`Blockhash expired on all ${attempt} attempts: ${e.message}`
+					);
+				}
+			}
+		}
 	}
 }
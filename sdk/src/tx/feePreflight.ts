@@ -0,0 +1,121 @@
+import { ASSOCIATED_TOKEN_PROGRAM_ID, Token } from '@solana/spl-token';
+import {
+	Connection,
+	PublicKey,
+	SystemInstruction,
+	SystemProgram,
+	Transaction,
+} from '@solana/web3.js';
+import { InsufficientSolForFeesError } from '../errors';
+import { rpcRequest } from '../util/rpcRequest';
+
+export type TxCostEstimate = {
+	/** signature and priority fees */
+	feeLamports: number;
+	/** rent the fee payer funds for accounts the transaction creates */
+	rentLamports: number;
+	totalLamports: number;
+};
+
+/**
+ * Estimates what a transaction costs its fee payer. Rent is counted for system program account creations and associated token
+ * account creations the fee payer funds. Accounts a program creates itself, e.g. the user account on initializeUser, aren't counted
+ * @param connection
+ * @param tx
+ * @param feePayer
+ * @returns estimate in lamports
+ */
+export async function estimateTxCost(
+	connection: Connection,
+	tx: Transaction,
+	feePayer: PublicKey
+): Promise<TxCostEstimate> {
+	const feeLamports = await getFeeForTransaction(connection, tx, feePayer);
+
+	let rentLamports = 0;
+	for (const ix of tx.instructions) {
+		if (
+			ix.programId.equals(SystemProgram.programId) &&
+			SystemInstruction.decodeInstructionType(ix) === 'Create'
+		) {
+			const { fromPubkey, lamports } =
+				SystemInstruction.decodeCreateAccount(ix);
+			if (fromPubkey.equals(feePayer)) {
+				rentLamports += lamports;
+			}
+		} else if (
+			ix.programId.equals(ASSOCIATED_TOKEN_PROGRAM_ID) &&
+			ix.keys[0].pubkey.equals(feePayer)
+		) {
+			rentLamports += await Token.getMinBalanceRentForExemptAccount(
+				connection
+			);
+		}
+	}
+
+	return {
+		feeLamports,
+		rentLamports,
+		totalLamports: feeLamports + rentLamports,
+	};
+}
+
+/**
+ * Checks that the fee payer's sol balance covers the transaction's estimated cost, see {@link estimateTxCost}
+ * @param connection
+ * @param tx
+ * @param feePayer
+ * @throws InsufficientSolForFeesError if it doesn't
+ */
+export async function assertSufficientSolForTx(
+	connection: Connection,
+	tx: Transaction,
+	feePayer: PublicKey
+): Promise<void> {
+	const [estimate, balance] = await Promise.all([
+		estimateTxCost(connection, tx, feePayer),
+		connection.getBalance(feePayer),
+	]);
+	if (balance < estimate.totalLamports) {
+		throw new InsufficientSolForFeesError(
+			feePayer,
+			estimate.totalLamports,
+			balance
+		);
+	}
+}
+
+/**
+ * Asks the node for the fee of the transaction's message, which includes priority fees. Falls back to the blockhash's
+ * fee per signature on nodes without getFeeForMessage, which the pinned web3.js doesn't wrap
+ */
+async function getFeeForTransaction(
+	connection: Connection,
+	tx: Transaction,
+	feePayer: PublicKey
+): Promise<number> {
+	const { blockhash, feeCalculator } = await connection.getRecentBlockhash();
+	const message = new Transaction({ recentBlockhash: blockhash, feePayer })
+		.add(...tx.instructions)
+		.compileMessage();
+
+	try {
+		const result = await rpcRequest<{ value: number | null }>(
+			connection,
+			'getFeeForMessage',
+			[
+				message.serialize().toString('base64'),
+				{ commitment: connection.commitment },
+			]
+		);
+		if (typeof result.value === 'number') {
+			return result.value;
+		}
+	} catch (e) {
+		// fall back to the fee calculator
+	}
+
+	return (
+		feeCalculator.lamportsPerSignature * message.header.numRequiredSignatures
+	);
+}
@@ -0,0 +1,91 @@
+import { TxSender } from './types';
+import {
+	ConfirmOptions,
+	Connection,
+	Signer,
+	Transaction,
+	TransactionSignature,
+} from '@solana/web3.js';
+import { Provider } from '@project-serum/anchor';
+import { IWallet } from '../types';
+
+/**
+ * # MultiRpcTxSender
+ * Signs a transaction once and broadcasts it to every connection concurrently. Resolves as soon as any
+ * connection confirms it, which improves landing rates for time-critical liquidations and closes.
+ *
+ * Pass it as the txSender to the ClearingHouse constructor. The first connection is used to fetch the blockhash.
+ */
+export class MultiRpcTxSender implements TxSender {
+	connections: Connection[];
+	wallet: IWallet;
+	opts: ConfirmOptions;
+
+	public constructor(
+		connections: Connection[],
+		wallet: IWallet,
+		opts: ConfirmOptions = Provider.defaultOptions()
+	) {
+		if (connections.length === 0) {
+			throw new Error('MultiRpcTxSender needs at least one connection');
+		}
+		this.connections = connections;
+		this.wallet = wallet;
+		this.opts = opts;
+	}
+
+	async send(
+		tx: Transaction,
+		additionalSigners: Array<Signer> = [],
+		opts: ConfirmOptions = this.opts
+	): Promise<TransactionSignature> {
+		tx.feePayer = this.wallet.publicKey;
+		tx.recentBlockhash = (
+			await this.connections[0].getRecentBlockhash(opts.preflightCommitment)
+		).blockhash;
+		if (additionalSigners.length > 0) {
+			tx.partialSign(...additionalSigners);
+		}
+		const signedTx = await this.wallet.signTransaction(tx);
+		const rawTx = signedTx.serialize();
+
+		const signature = await firstResolved(
+			this.connections.map((connection) =>
+				connection.sendRawTransaction(rawTx, opts)
+			)
+		);
+
+		await firstResolved(
+			this.connections.map(async (connection) => {
+				const { value } = await connection.confirmTransaction(
+					signature,
+					opts.commitment
+				);
+				if (value.err) {
+					throw new Error(
+						`Transaction ${signature} failed: ${JSON.stringify(value.err)}`
+					);
+				}
+			})
+		);
+
+		return signature;
+	}
+}
+
+/**
+ * Resolves with the first promise to resolve, rejects with the last error if all of them reject
+ */
+function firstResolved<T>(promises: Promise<T>[]): Promise<T> {
+	return new Promise((resolve, reject) => {
+		let rejected = 0;
+		for (const promise of promises) {
+			promise.then(resolve, (e) => {
+				rejected++;
+				if (rejected === promises.length) {
+					reject(e);
+				}
+			});
+		}
+	});
+}
@@ -0,0 +1,114 @@
+import {
+	Commitment,
+	Connection,
+	PublicKey,
+	Signer,
+	Transaction,
+	TransactionSignature,
+} from '@solana/web3.js';
+import { IWallet } from '../types';
+
+/**
+ * @param tx
+ * @returns the required signers that haven't signed yet
+ */
+export function getMissingSigners(tx: Transaction): PublicKey[] {
+	return tx.signatures
+		.filter(({ signature }) => signature === null)
+		.map(({ publicKey }) => publicKey);
+}
+
+/**
+ * Signs a transaction with the signers at hand, leaving the signatures of absent signers, e.g. the other members of a multisig, for
+ * {@link countersignTx}. The blockhash is set here, so countersigners have until it expires, about a minute, to sign and send it
+ * @param connection
+ * @param wallet fee payer, signs if it's a required signer and not absent
+ * @param tx
+ * @param availableSigners
+ * @param absentSigners signers expected to countersign later
+ * @returns the partially signed transaction, serialize it with requireAllSignatures false to hand it on
+ * @throws Error if a required signer is neither available nor absent, or an absent signer isn't required
+ */
+export async function partiallySignTx(
+	connection: Connection,
+	wallet: IWallet,
+	tx: Transaction,
+	availableSigners: Signer[],
+	absentSigners: PublicKey[]
+): Promise<Transaction> {
+	tx.feePayer = wallet.publicKey;
+	tx.recentBlockhash = (
+		await connection.getRecentBlockhash(connection.commitment)
+	).blockhash;
+
+	const isAbsent = (publicKey: PublicKey) =>
+		absentSigners.some((absent) => absent.equals(publicKey));
+	const message = tx.compileMessage();
+	const requiredSigners = message.accountKeys.slice(
+		0,
+		message.header.numRequiredSignatures
+	);
+	for (const absent of absentSigners) {
+		if (!requiredSigners.some((required) => required.equals(absent))) {
+			throw new Error(
+				`Absent signer ${absent.toBase58()} isn't a signer of the transaction`
+			);
+		}
+	}
+	for (const required of requiredSigners) {
+		const available =
+			required.equals(wallet.publicKey) ||
+			availableSigners.some((signer) => signer.publicKey.equals(required));
+		if (!available && !isAbsent(required)) {
+			throw new Error(`Missing signer ${required.toBase58()}`);
+		}
+	}
+
+	const signers = availableSigners.filter(
+		(signer) => !isAbsent(signer.publicKey)
+	);
+	if (signers.length > 0) {
+		tx.partialSign(...signers);
+	}
+	if (!isAbsent(wallet.publicKey)) {
+		tx = await wallet.signTransaction(tx);
+	}
+	return tx;
+}
+
+/**
+ * Adds the signatures of signers that were absent when the transaction was built, see {@link partiallySignTx}
+ * @param tx e.g. deserialized with Transaction.from
+ * @param signers
+ * @returns the transaction, fully signed if no signers are missing anymore
+ */
+export function countersignTx(tx: Transaction, signers: Signer[]): Transaction {
+	tx.partialSign(...signers);
+	return tx;
+}
+
+/**
+ * Sends a transaction once every signer has signed and confirms it
+ * @param connection
+ * @param tx
+ * @param commitment
+ * @throws Error if signers are still missing
+ */
+export async function sendCountersignedTx(
+	connection: Connection,
+	tx: Transaction,
+	commitment: Commitment = connection.commitment
+): Promise<TransactionSignature> {
+	const missingSigners = getMissingSigners(tx);
+	if (missingSigners.length > 0) {
+		throw new Error(
+			`Transaction is missing signatures of ${missingSigners
+				.map((signer) => signer.toBase58())
+				.join(', ')}`
+		);
+	}
+
+	const txSig = await connection.sendRawTransaction(tx.serialize());
+	await connection.confirmTransaction(txSig, commitment);
+	return txSig;
+}
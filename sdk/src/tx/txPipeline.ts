@@ -0,0 +1,224 @@
+import {
+	Commitment,
+	Connection,
+	Signer,
+	Transaction,
+	TransactionInstruction,
+	TransactionSignature,
+} from '@solana/web3.js';
+import { TxExpiredError, TxSimulationError } from '../errors';
+import { IWallet } from '../types';
+import {
+	estimatePriorityFee,
+	getComputeUnitLimitIx,
+	getComputeUnitPriceIx,
+	getWritableAccounts,
+} from '../util/computeBudget';
+
+export type TxPipelineConfig = {
+	/** compute unit limit to request, the default limit if undefined */
+	computeUnitLimit?: number;
+	/** priority fee of the first attempt, in micro-lamports per compute unit */
+	priorityFeeMicroLamports: number;
+	/** if set, the first attempt pays at least this percentile of the recent priority fees on the accounts it write locks, see {@link estimatePriorityFee} */
+	priorityFeePercentile?: number;
+	/** priority fee multiplier applied on every resubmission */
+	priorityFeeMultiplier: number;
+	/** priority fee is never escalated above this, in micro-lamports per compute unit */
+	maxPriorityFeeMicroLamports: number;
+	/** attempts, each with a fresh blockhash, before giving up */
+	maxAttempts: number;
+	/** skip simulating before the first send */
+	skipSimulation: boolean;
+	/** how often to poll for confirmation, in ms */
+	confirmPollMs: number;
+	/** how long to wait before checking whether the blockhash expired, in ms */
+	blockhashCheckMs: number;
+	commitment: Commitment;
+};
+
+export const DEFAULT_TX_PIPELINE_CONFIG: TxPipelineConfig = {
+	priorityFeeMicroLamports: 0,
+	priorityFeeMultiplier: 2,
+	maxPriorityFeeMicroLamports: 1000000,
+	maxAttempts: 3,
+	skipSimulation: false,
+	confirmPollMs: 1000,
+	blockhashCheckMs: 30000,
+	commitment: 'confirmed',
+};
+
+export type TxPipelineResult = {
+	signature: TransactionSignature;
+	attempts: number;
+	/** priority fee of the attempt that landed, in micro-lamports per compute unit */
+	priorityFeeMicroLamports: number;
+};
+
+/**
+ * Builds the instructions for an attempt. Called again on every resubmission so instructions that depend on
+ * account state are rebuilt against fresh data
+ */
+export type InstructionBuilder = () => Promise<TransactionInstruction[]>;
+
+/**
+ * # TxPipeline
+ * Resilient transaction sending shared by keepers and trading bots: build instructions → simulate → attach compute budget → sign → send → confirm.
+ * If the blockhash expires before the transaction confirms, the instructions are rebuilt and resent with a fresh blockhash and a higher priority fee.
+ */
+export class TxPipeline {
+	connection: Connection;
+	wallet: IWallet;
+	config: TxPipelineConfig;
+
+	public constructor(
+		connection: Connection,
+		wallet: IWallet,
+		config: Partial<TxPipelineConfig> = {}
+	) {
+		this.connection = connection;
+		this.wallet = wallet;
+		this.config = { ...DEFAULT_TX_PIPELINE_CONFIG, ...config };
+	}
+
+	/**
+	 * @param buildInstructions
+	 * @param additionalSigners signers besides the wallet
+	 * @returns the signature that confirmed
+	 * @throws TxSimulationError if the simulation fails, nothing is sent
+	 * @throws TxExpiredError if no attempt confirmed
+	 */
+	public async send(
+		buildInstructions: InstructionBuilder,
+		additionalSigners: Signer[] = []
+	): Promise<TxPipelineResult> {
+		let priorityFeeMicroLamports = this.config.priorityFeeMicroLamports;
+		for (let attempt = 1; attempt <= this.config.maxAttempts; attempt++) {
+			const instructions = await buildInstructions();
+			if (attempt === 1 && this.config.priorityFeePercentile !== undefined) {
+				priorityFeeMicroLamports = Math.min(
+					Math.max(
+						priorityFeeMicroLamports,
+						await estimatePriorityFee(
+							this.connection,
+							getWritableAccounts(instructions),
+							this.config.priorityFeePercentile
+						)
+					),
+					this.config.maxPriorityFeeMicroLamports
+				);
+			}
+			const tx = await this.buildAndSign(
+				instructions,
+				priorityFeeMicroLamports,
+				additionalSigners
+			);
+
+			if (attempt === 1 && !this.config.skipSimulation) {
+				await this.simulate(tx);
+			}
+
+			const signature = await this.connection.sendRawTransaction(
+				tx.serialize(),
+				{ skipPreflight: true }
+			);
+
+			if (await this.confirm(signature, tx.recentBlockhash)) {
+				return { signature, attempts: attempt, priorityFeeMicroLamports };
+			}
+
+			priorityFeeMicroLamports = Math.min(
+				Math.ceil(
+					Math.max(priorityFeeMicroLamports, 1) *
+						this.config.priorityFeeMultiplier
+				),
+				this.config.maxPriorityFeeMicroLamports
+			);
+		}
+
+		throw new TxExpiredError(
+			`Transaction didn't confirm after ${this.config.maxAttempts} attempts`
+		);
+	}
+
+	private async buildAndSign(
+		instructions: TransactionInstruction[],
+		priorityFeeMicroLamports: number,
+		additionalSigners: Signer[]
+	): Promise<Transaction> {
+		const tx = new Transaction();
+		if (this.config.computeUnitLimit) {
+			tx.add(getComputeUnitLimitIx(this.config.computeUnitLimit));
+		}
+		if (priorityFeeMicroLamports > 0) {
+			tx.add(getComputeUnitPriceIx(priorityFeeMicroLamports));
+		}
+		tx.add(...instructions);
+
+		tx.feePayer = this.wallet.publicKey;
+		tx.recentBlockhash = (
+			await this.connection.getRecentBlockhash(this.config.commitment)
+		).blockhash;
+		if (additionalSigners.length > 0) {
+			tx.partialSign(...additionalSigners);
+		}
+		return await this.wallet.signTransaction(tx);
+	}
+
+	private async simulate(tx: Transaction): Promise<void> {
+		const { value } = await this.connection.simulateTransaction(tx);
+		if (value.err) {
+			throw new TxSimulationError(
+				`Transaction simulation failed: ${JSON.stringify(value.err)}`,
+				value.logs ?? []
+			);
+		}
+	}
+
+	/**
+	 * @returns true once confirmed, false if the blockhash expired first
+	 * @throws if the transaction landed with an error
+	 */
+	private async confirm(
+		signature: TransactionSignature,
+		blockhash: string
+	): Promise<boolean> {
+		let lastBlockhashCheck = Date.now();
+		// eslint-disable-next-line no-constant-condition
+		while (true) {
+			const { value } = await this.connection.getSignatureStatuses([
+				signature,
+			]);
+			const status = value[0];
+			if (status) {
+				if (status.err) {
+					throw new Error(
+						`Transaction ${signature} failed: ${JSON.stringify(status.err)}`
+					);
+				}
+				if (
+					status.confirmationStatus === this.config.commitment ||
+					status.confirmationStatus === 'finalized'
+				) {
+					return true;
+				}
+			}
+
+			if (Date.now() - lastBlockhashCheck >= this.config.blockhashCheckMs) {
+				lastBlockhashCheck = Date.now();
+				const { value: feeCalculator } =
+					await this.connection.getFeeCalculatorForBlockhash(
+						blockhash,
+						this.config.commitment
+					);
+				if (!feeCalculator) {
+					return false;
+				}
+			}
+
+			await new Promise((resolve) =>
+				setTimeout(resolve, this.config.confirmPollMs)
+			);
+		}
+	}
+}
@@ -0,0 +1,222 @@
+import {
+	Connection,
+	TransactionError,
+	TransactionSignature,
+} from '@solana/web3.js';
+import { EventEmitter } from 'events';
+import StrictEventEmitter from 'strict-event-emitter-types';
+
+export type TrackedTxStatus =
+	| 'pending'
+	| 'processed'
+	| 'confirmed'
+	| 'finalized'
+	| 'failed'
+	| 'dropped';
+
+export type TrackedTx = {
+	signature: TransactionSignature;
+	status: TrackedTxStatus;
+	/** blockhash the transaction was signed with, lets it be marked dropped as soon as the blockhash expires */
+	blockhash?: string;
+	/** unix timestamp in ms */
+	submittedTs: number;
+	/** unix timestamp in ms of the last status change */
+	updatedTs: number;
+	slot?: number;
+	err?: TransactionError;
+};
+
+export type TxTrackerConfig = {
+	/** how often to poll signature statuses, in ms */
+	pollMs: number;
+	/** a transaction the cluster hasn't seen after this long is marked dropped, in ms */
+	dropAfterMs: number;
+};
+
+export const DEFAULT_TX_TRACKER_CONFIG: TxTrackerConfig = {
+	pollMs: 2000,
+	dropAfterMs: 90000,
+};
+
+export interface TxTrackerEvents {
+	statusChange: (tx: TrackedTx, previousStatus: TrackedTxStatus) => void;
+	error: (error: Error) => void;
+}
+
+const TERMINAL_STATUSES: TrackedTxStatus[] = ['finalized', 'failed', 'dropped'];
+
+// getSignatureStatuses accepts at most this many signatures per request
+const MAX_SIGNATURES_PER_REQUEST = 256;
+
+/**
+ * # TxTracker
+ * Follows submitted transactions until they're finalized, fail or are dropped, polling their statuses in the background.
+ * Bots can use a transaction's status to decide whether it is safe to resubmit instead of sending duplicates.
+ */
+export class TxTracker {
+	connection: Connection;
+	config: TxTrackerConfig;
+	eventEmitter: StrictEventEmitter<EventEmitter, TxTrackerEvents>;
+
+	private txs = new Map<TransactionSignature, TrackedTx>();
+	private intervalId?: ReturnType<typeof setInterval>;
+	private polling = false;
+
+	public constructor(
+		connection: Connection,
+		config: Partial<TxTrackerConfig> = {}
+	) {
+		this.connection = connection;
+		this.config = { ...DEFAULT_TX_TRACKER_CONFIG, ...config };
+		this.eventEmitter = new EventEmitter();
+	}
+
+	public start(): void {
+		if (this.intervalId) {
+			return;
+		}
+
+		this.intervalId = setInterval(async () => {
+			await this.poll();
+		}, this.config.pollMs);
+	}
+
+	public stop(): void {
+		if (this.intervalId) {
+			clearInterval(this.intervalId);
+			this.intervalId = undefined;
+		}
+	}
+
+	/**
+	 * @param signature
+	 * @param blockhash the transaction's recent blockhash, if known
+	 * @returns the tracked transaction
+	 */
+	public track(signature: TransactionSignature, blockhash?: string): TrackedTx {
+		const existing = this.txs.get(signature);
+		if (existing) {
+			return existing;
+		}
+
+		const now = Date.now();
+		const tx: TrackedTx = {
+			signature,
+			status: 'pending',
+			blockhash,
+			submittedTs: now,
+			updatedTs: now,
+		};
+		this.txs.set(signature, tx);
+		return tx;
+	}
+
+	public untrack(signature: TransactionSignature): boolean {
+		return this.txs.delete(signature);
+	}
+
+	public get(signature: TransactionSignature): TrackedTx | undefined {
+		return this.txs.get(signature);
+	}
+
+	public getAll(): TrackedTx[] {
+		return Array.from(this.txs.values());
+	}
+
+	/**
+	 * @returns transactions that haven't been finalized, failed or been dropped yet
+	 */
+	public getInFlight(): TrackedTx[] {
+		return this.getAll().filter((tx) => !isTerminal(tx.status));
+	}
+
+	/**
+	 * Removes finalized, failed and dropped transactions
+	 */
+	public prune(): void {
+		for (const tx of this.getAll()) {
+			if (isTerminal(tx.status)) {
+				this.txs.delete(tx.signature);
+			}
+		}
+	}
+
+	/**
+	 * Fetches the statuses of all in flight transactions once. Skipped if the previous poll is still running
+	 */
+	public async poll(): Promise<void> {
+		if (this.polling) {
+			return;
+		}
+		this.polling = true;
+
+		try {
+			const inFlight = this.getInFlight();
+			for (let i = 0; i < inFlight.length; i += MAX_SIGNATURES_PER_REQUEST) {
+				const batch = inFlight.slice(i, i + MAX_SIGNATURES_PER_REQUEST);
+				const { value: statuses } = await this.connection.getSignatureStatuses(
+					batch.map((tx) => tx.signature),
+					{ searchTransactionHistory: false }
+				);
+
+				for (const [j, tx] of batch.entries()) {
+					const status = statuses[j];
+					if (status) {
+						tx.slot = status.slot;
+						if (status.err) {
+							tx.err = status.err;
+							this.setStatus(tx, 'failed');
+						} else {
+							this.setStatus(tx, status.confirmationStatus ?? 'processed');
+						}
+					} else if (tx.status === 'pending' && (await this.isDropped(tx))) {
+						this.setStatus(tx, 'dropped');
+					}
+				}
+			}
+		} catch (e) {
+			this.emitError(e);
+		} finally {
+			this.polling = false;
+		}
+	}
+
+	private emitError(error: Error): void {
+		// emitting error without a listener throws, which would crash the process from the interval
+		if (this.eventEmitter.listenerCount('error') > 0) {
+			this.eventEmitter.emit('error', error);
+		}
+	}
+
+	private async isDropped(tx: TrackedTx): Promise<boolean> {
+		if (Date.now() - tx.submittedTs >= this.config.dropAfterMs) {
+			return true;
+		}
+
+		if (tx.blockhash) {
+			const { value } = await this.connection.getFeeCalculatorForBlockhash(
+				tx.blockhash,
+				'processed'
+			);
+			return value === null;
+		}
+
+		return false;
+	}
+
+	private setStatus(tx: TrackedTx, status: TrackedTxStatus): void {
+		if (tx.status === status) {
+			return;
+		}
+
+		const previousStatus = tx.status;
+		tx.status = status;
+		tx.updatedTs = Date.now();
+		this.eventEmitter.emit('statusChange', tx, previousStatus);
+	}
+}
+
+function isTerminal(status: TrackedTxStatus): boolean {
+	return TERMINAL_STATUSES.indexOf(status) !== -1;
+}
@@ -12,3 +12,28 @@ export interface TxSender {
 		opts?: ConfirmOptions
 	): Promise<TransactionSignature>;
 }
+
+export type TxContext = {
+	tx: Transaction;
+	additionalSigners: Array<Signer>;
+	opts?: ConfirmOptions;
+	/** unix timestamp in ms when the transaction was built */
+	builtTs: number;
+};
+
+/**
+ * Hooks into the lifecycle of every transaction the ClearingHouse sends, e.g. for APM tracing or custom logging.
+ * Implement only the callbacks needed. Errors thrown by observers are swallowed so they can't fail the transaction
+ */
+export interface TxObserver {
+	/** instructions are assembled, the tx sender hasn't set the blockhash or signed yet */
+	onBuilt?(ctx: TxContext): void;
+	/** handed to the tx sender for signing, submission and confirmation */
+	onSent?(ctx: TxContext): void;
+	onConfirmed?(
+		ctx: TxContext,
+		txSig: TransactionSignature,
+		durationMs: number
+	): void;
+	onFailed?(ctx: TxContext, error: Error, durationMs: number): void;
+}
@@ -3,3 +3,14 @@ import { Transaction, TransactionInstruction } from '@solana/web3.js';
 export function wrapInTx(instruction: TransactionInstruction): Transaction {
 	return new Transaction().add(instruction);
 }
+
+const BLOCKHASH_EXPIRED_PATTERN = /blockhash ?not ?found|block height exceeded/i;
+
+/**
+ * @returns true if the transaction failed because its blockhash expired or was never seen by the node,
+ * in which case it is safe to resend with a fresh blockhash
+ */
+export function isBlockhashExpiredError(e: unknown): boolean {
+	const message = e instanceof Error ? e.message : String(e);
+	return BLOCKHASH_EXPIRED_PATTERN.test(message);
+}
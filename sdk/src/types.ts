@@ -22,6 +22,31 @@ export enum TradeSide {
 	Sell = 2,
 }
 
+// # Typed Amounts
+// Each amount carries a unit brand, so the compiler rejects a plain BN or an amount of another unit where one is expected
+// (e.g. a price passed to openPosition as its quote amount). Build them with the conversions in math/conversion.ts.
+// BN arithmetic returns a plain BN, so convert results back before passing them on.
+
+/**
+ * Precision QUOTE_PRECISION
+ */
+export type QuoteAssetAmount = BN & { readonly __unit: 'QuoteAssetAmount' };
+
+/**
+ * Precision AMM_RESERVE_PRECISION
+ */
+export type BaseAssetAmount = BN & { readonly __unit: 'BaseAssetAmount' };
+
+/**
+ * Precision QUOTE_PRECISION
+ */
+export type CollateralAmount = BN & { readonly __unit: 'CollateralAmount' };
+
+/**
+ * Precision MARK_PRICE_PRECISION
+ */
+export type Price = BN & { readonly __unit: 'Price' };
+
 export type CandleResolution =
 	| '1'
 	| '5'
@@ -45,7 +70,8 @@ export type DepositHistoryAccount = {
 
 export type CurveHistoryAccount = {
 	head: BN;
-	curveRecords: CurveRecord[];
+	// named deposit_records on-chain
+	depositRecords: CurveRecord[];
 };
 
 export type FundingRateHistoryAccount = {
@@ -93,6 +119,9 @@ export type CurveRecord = {
 	baseAssetAmountShort: BN;
 	baseAssetAmount: BN;
 	openInterest: BN;
+	totalFee: BN;
+	totalFeeMinusDistributions: BN;
+	adjustmentCost: BN;
 };
 
 export type TradeRecord = {
@@ -238,6 +267,67 @@ export type AMM = {
 	minimumTradeSize: BN;
 };
 
+export type MarketStats = {
+	marketIndex: BN;
+	/** number of users with a position in the market */
+	openInterest: BN;
+	/** Precision AMM_RESERVE_PRECISION */
+	baseAssetAmountLong: BaseAssetAmount;
+	/** Precision AMM_RESERVE_PRECISION */
+	baseAssetAmountShort: BaseAssetAmount;
+	/** net market bias : Precision AMM_RESERVE_PRECISION */
+	baseAssetAmount: BaseAssetAmount;
+	/** long and short base asset valued at the mark price : Precision QUOTE_PRECISION */
+	openInterestNotional: QuoteAssetAmount;
+	/** Precision MARK_PRICE_PRECISION */
+	markPrice: Price;
+	/** Precision QUOTE_PRECISION */
+	totalFee: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	totalFeeMinusDistributions: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	totalFeeWithdrawn: QuoteAssetAmount;
+	/** Precision MARK_PRICE_PRECISION * FUNDING_PAYMENT_PRECISION */
+	cumulativeFundingRateLong: BN;
+	/** Precision MARK_PRICE_PRECISION * FUNDING_PAYMENT_PRECISION */
+	cumulativeFundingRateShort: BN;
+	/** Precision MARK_PRICE_PRECISION * FUNDING_PAYMENT_PRECISION */
+	lastFundingRate: BN;
+};
+
+export type MarketTwaps = {
+	/** price in quote asset units, e.g. 100.5 */
+	markPriceTwap: number;
+	/** unix timestamp in seconds */
+	markPriceTwapTs: number;
+	/** price in quote asset units, e.g. 100.5 */
+	oraclePriceTwap: number;
+	/** unix timestamp in seconds */
+	oraclePriceTwapTs: number;
+};
+
+export type FundingDashboardEntry = {
+	marketIndex: number;
+	/** seconds between funding rate updates */
+	fundingPeriod: number;
+	/** unix timestamp in seconds of the last funding rate update */
+	lastFundingRateTs: number;
+	/** last funding rate set, quote asset per unit of base asset for the period. Positive when longs pay shorts */
+	currentFundingRate: number;
+	/** last funding rate as a percentage of the oracle price twap */
+	currentFundingRatePct: number;
+	currentFundingRateAnnualizedPct: number;
+	/** funding rate if updated now, quote asset per unit of base asset for the period */
+	predictedFundingRate: number;
+	/** predicted funding rate as a percentage of the oracle price */
+	predictedFundingRatePct: number;
+	predictedFundingRateAnnualizedPct: number;
+	/** price in quote asset units, e.g. 100.5 */
+	markPrice: number;
+	/** price in quote asset units, e.g. 100.5 */
+	oraclePrice: number;
+};
+
 // # User Account Types
 export type UserPosition = {
 	baseAssetAmount: BN;
@@ -257,6 +347,60 @@ export type UserAccount = {
 	cumulativeDeposits: BN;
 	positions: PublicKey;
 	totalFeePaid: BN;
+	totalTokenDiscount: BN;
+	/** rewards earned referring other users' trades */
+	totalReferralReward: BN;
+	totalRefereeDiscount: BN;
+};
+
+export type MarginCategory = 'Initial' | 'Partial' | 'Maintenance';
+
+/**
+ * Margin ratios required by the clearing house : Precision TEN_THOUSAND (e.g. 2000 = 20%)
+ */
+export type MarginRequirements = {
+	initial: BN;
+	partial: BN;
+	maintenance: BN;
+};
+
+// # Portfolio Types
+export type PositionSummary = {
+	marketIndex: BN;
+	/** Precision AMM_RESERVE_PRECISION */
+	baseAssetAmount: BaseAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	quoteAssetAmount: QuoteAssetAmount;
+	/** Precision MARK_PRICE_PRECISION */
+	entryPrice: Price;
+	/** Precision MARK_PRICE_PRECISION */
+	markPrice: Price;
+	/** Precision QUOTE_PRECISION */
+	positionValue: QuoteAssetAmount;
+	/** excludes funding : Precision QUOTE_PRECISION */
+	unrealizedPnl: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	pendingFundingPayment: QuoteAssetAmount;
+	/** -1 if the position can't be liquidated : Precision MARK_PRICE_PRECISION */
+	liquidationPrice: Price;
+};
+
+export type PortfolioSummary = {
+	/** Precision QUOTE_PRECISION */
+	collateral: CollateralAmount;
+	/** Precision QUOTE_PRECISION */
+	cumulativeDeposits: CollateralAmount;
+	/** collateral + unrealized pnl including funding : Precision QUOTE_PRECISION */
+	totalCollateral: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	freeCollateral: QuoteAssetAmount;
+	/** Precision QUOTE_PRECISION */
+	totalPositionValue: QuoteAssetAmount;
+	/** Precision TEN_THOUSAND */
+	leverage: BN;
+	/** Precision TEN_THOUSAND */
+	marginRatio: BN;
+	positions: PositionSummary[];
 };
 
 // # Misc Types
@@ -311,3 +455,10 @@ export type OracleGuardRails = {
 	};
 	useForLiquidations: boolean;
 };
+
+export type OpenPositionOptions = {
+	/** send the trade without checking it against the minimum trade size and the attached user's collateral first */
+	skipValidation?: boolean;
+	/** check the market's oracle against the oracle guard rails before sending the trade, see {@link ClearingHouse.validateOracleForTrade} */
+	validateOracle?: boolean;
+};
@@ -0,0 +1,129 @@
+import { BN, Program } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import { BN_MAX, TEN_THOUSAND, ZERO } from './constants/numericConstants';
+import { toQuoteAssetAmount } from './math/conversion';
+import { calculateBaseAssetValue, calculatePositionPNL } from './math/position';
+import {
+	MarketsAccount,
+	QuoteAssetAmount,
+	UserAccount,
+	UserPositionsAccount,
+} from './types';
+
+export type UserAccountAndPositions = {
+	userAccountPublicKey: PublicKey;
+	userAccount: UserAccount;
+	userPositionsAccount: UserPositionsAccount;
+};
+
+/**
+ * Fetches every user account and its positions account with two getProgramAccounts calls.
+ * This is a heavy rpc request, use it for periodic scans rather than per-user lookups
+ * @param program
+ * @returns users
+ */
+export async function fetchAllUsers(
+	program: Program
+): Promise<UserAccountAndPositions[]> {
+	const [userAccounts, userPositionsAccounts] = await Promise.all([
+		program.account.user.all(),
+		program.account.userPositions.all(),
+	]);
+
+	const userPositionsAccountMap = new Map<string, UserPositionsAccount>();
+	for (const userPositionsAccount of userPositionsAccounts) {
+		userPositionsAccountMap.set(
+			userPositionsAccount.publicKey.toString(),
+			userPositionsAccount.account as UserPositionsAccount
+		);
+	}
+
+	return userAccounts.reduce((users, programAccount) => {
+		const userAccount = programAccount.account as UserAccount;
+		const userPositionsAccount = userPositionsAccountMap.get(
+			userAccount.positions.toString()
+		);
+		if (userPositionsAccount) {
+			users.push({
+				userAccountPublicKey: programAccount.publicKey,
+				userAccount,
+				userPositionsAccount,
+			});
+		}
+		return users;
+	}, [] as UserAccountAndPositions[]);
+}
+
+/**
+ * Calculates a user's account value the way {@link ClearingHouseUser.getAccountValue} does, without a subscription
+ * @param marketsAccount
+ * @param user
+ * @param withFunding
+ * @returns accountValue : Precision QUOTE_PRECISION
+ */
+export function calculateUserAccountValue(
+	marketsAccount: MarketsAccount,
+	user: Omit<UserAccountAndPositions, 'userAccountPublicKey'>,
+	withFunding = true
+): QuoteAssetAmount {
+	const unrealizedPnl = user.userPositionsAccount.positions.reduce(
+		(pnl, position) =>
+			pnl.add(
+				calculatePositionPNL(
+					marketsAccount.markets[position.marketIndex.toNumber()],
+					position,
+					withFunding
+				)
+			),
+		ZERO
+	);
+	return toQuoteAssetAmount(
+		BN.max(user.userAccount.collateral.add(unrealizedPnl), ZERO)
+	);
+}
+
+/**
+ * @param marketsAccount
+ * @param user
+ * @returns totalPositionValue : Precision QUOTE_PRECISION
+ */
+export function calculateUserTotalPositionValue(
+	marketsAccount: MarketsAccount,
+	user: Omit<UserAccountAndPositions, 'userAccountPublicKey'>
+): QuoteAssetAmount {
+	return toQuoteAssetAmount(
+		user.userPositionsAccount.positions.reduce(
+			(positionValue, position) =>
+				positionValue.add(
+					calculateBaseAssetValue(
+						marketsAccount.markets[position.marketIndex.toNumber()],
+						position
+					)
+				),
+			ZERO
+		)
+	);
+}
+
+/**
+ * Calculates a user's margin ratio the way {@link ClearingHouseUser.getMarginRatio} does, without a subscription
+ * @param marketsAccount
+ * @param user
+ * @returns marginRatio : Precision TEN_THOUSAND, BN_MAX if the user has no positions
+ */
+export function calculateUserMarginRatio(
+	marketsAccount: MarketsAccount,
+	user: Omit<UserAccountAndPositions, 'userAccountPublicKey'>
+): BN {
+	const totalPositionValue = calculateUserTotalPositionValue(
+		marketsAccount,
+		user
+	);
+	if (totalPositionValue.eq(ZERO)) {
+		return BN_MAX;
+	}
+
+	return calculateUserAccountValue(marketsAccount, user)
+		.mul(TEN_THOUSAND)
+		.div(totalPositionValue);
+}
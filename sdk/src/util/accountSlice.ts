@@ -0,0 +1,31 @@
+import { Commitment, Connection, PublicKey } from '@solana/web3.js';
+import { rpcRequest } from './rpcRequest';
+
+/**
+ * Fetches part of an account's data, so the rpc only sends those bytes. Uses getAccountInfo's dataSlice, which the pinned web3.js doesn't expose
+ * @param connection
+ * @param publicKey
+ * @param offset
+ * @param length
+ * @param commitment defaults to the connection's
+ * @returns the requested bytes, null if the account doesn't exist
+ */
+export async function fetchAccountSlice(
+	connection: Connection,
+	publicKey: PublicKey,
+	offset: number,
+	length: number,
+	commitment: Commitment = connection.commitment
+): Promise<Buffer | null> {
+	const result = await rpcRequest<{
+		value: { data: [string, string] } | null;
+	}>(connection, 'getAccountInfo', [
+		publicKey.toBase58(),
+		{ encoding: 'base64', commitment, dataSlice: { offset, length } },
+	]);
+	if (!result.value) {
+		return null;
+	}
+
+	return Buffer.from(result.value.data[0], 'base64');
+}
@@ -0,0 +1,62 @@
+import {
+	Commitment,
+	Connection,
+	PublicKey,
+	TransactionSignature,
+} from '@solana/web3.js';
+
+export type AirdropOptions = {
+	/** attempts after the first failed one, the devnet faucet rate limits aggressively */
+	maxRetries: number;
+	/** delay before the first retry, doubled after every attempt */
+	retryDelayMs: number;
+	commitment: Commitment;
+};
+
+export const DEFAULT_AIRDROP_OPTIONS: AirdropOptions = {
+	maxRetries: 5,
+	retryDelayMs: 1000,
+	commitment: 'confirmed',
+};
+
+/**
+ * Requests an airdrop from the cluster's faucet and waits for it to confirm, retrying with backoff when the faucet
+ * rejects or drops the request. Only localnet, devnet and testnet have a faucet
+ * @param connection
+ * @param publicKey
+ * @param lamports
+ * @param options
+ * @returns the airdrop's signature
+ */
+export async function airdropSol(
+	connection: Connection,
+	publicKey: PublicKey,
+	lamports: number,
+	options: Partial<AirdropOptions> = {}
+): Promise<TransactionSignature> {
+	const { maxRetries, retryDelayMs, commitment } = {
+		...DEFAULT_AIRDROP_OPTIONS,
+		...options,
+	};
+
+	let delayMs = retryDelayMs;
+	for (let attempt = 0; ; attempt++) {
+		try {
+			const signature = await connection.requestAirdrop(publicKey, lamports);
+			const { value } = await connection.confirmTransaction(
+				signature,
+				commitment
+			);
+			if (value.err) {
+				throw new Error(`Airdrop ${signature} failed: ${value.err}`);
+			}
+			return signature;
+		} catch (e) {
+			if (attempt >= maxRetries) {
+				throw e;
+			}
+			await new Promise((resolve) => setTimeout(resolve, delayMs));
+			delayMs *= 2;
+		}
+	}
+}
@@ -0,0 +1,77 @@
+import { Connection, SYSVAR_CLOCK_PUBKEY } from '@solana/web3.js';
+import BN from 'bn.js';
+
+export type ClockSysvar = {
+	slot: number;
+	/** Precision seconds */
+	unixTimestamp: BN;
+};
+
+/**
+ * Fetches the Clock sysvar
+ * @param connection
+ * @returns clock
+ */
+export async function fetchClock(connection: Connection): Promise<ClockSysvar> {
+	const account = await connection.getAccountInfo(SYSVAR_CLOCK_PUBKEY);
+	// Clock layout: slot u64, epoch_start_timestamp i64, epoch u64, leader_schedule_epoch u64, unix_timestamp i64
+	return {
+		slot: new BN(account.data.slice(0, 8), 'le').toNumber(),
+		unixTimestamp: new BN(account.data.slice(32, 40), 'le').fromTwos(64),
+	};
+}
+
+/**
+ * Fetches the cluster's unix timestamp from the Clock sysvar. Prefer this over the local clock when comparing against on-chain timestamps
+ * @param connection
+ * @returns unix timestamp in seconds
+ */
+export async function fetchClockUnixTimestamp(
+	connection: Connection
+): Promise<BN> {
+	return (await fetchClock(connection)).unixTimestamp;
+}
+
+/**
+ * # ChainClock
+ * Serves the Clock sysvar from cache, refetching it once it is older than maxAgeMs, so funding countdowns, oracle staleness
+ * checks and keeper loops can read the chain's time and slot as often as they like. Concurrent reads share one request
+ */
+export class ChainClock {
+	connection: Connection;
+	/** how long a fetched clock is served, a slot is about 400ms */
+	maxAgeMs: number;
+
+	private cached?: { clock: Promise<ClockSysvar>; fetchedTs: number };
+
+	public constructor(connection: Connection, maxAgeMs = 1000) {
+		this.connection = connection;
+		this.maxAgeMs = maxAgeMs;
+	}
+
+	public async getClock(): Promise<ClockSysvar> {
+		const now = Date.now();
+		if (!this.cached || now - this.cached.fetchedTs >= this.maxAgeMs) {
+			const cached = { clock: fetchClock(this.connection), fetchedTs: now };
+			this.cached = cached;
+			// failed fetches aren't served to later reads
+			cached.clock.catch(() => {
+				if (this.cached === cached) {
+					this.cached = undefined;
+				}
+			});
+		}
+		return await this.cached.clock;
+	}
+
+	/**
+	 * @returns the cluster's unix timestamp in seconds
+	 */
+	public async getChainTime(): Promise<BN> {
+		return (await this.getClock()).unixTimestamp;
+	}
+
+	public async getChainSlot(): Promise<number> {
+		return (await this.getClock()).slot;
+	}
+}
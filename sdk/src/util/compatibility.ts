@@ -0,0 +1,101 @@
+import { AccountsCoder, Program } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import { getClearingHouseStateAccountPublicKey } from '../addresses';
+import { IncompatibleProgramVersionError } from '../errors';
+import { StateAccount } from '../types';
+
+const STATE_ACCOUNTS: Array<[string, keyof StateAccount]> = [
+	['markets', 'markets'],
+	['tradeHistory', 'tradeHistory'],
+	['depositHistory', 'depositHistory'],
+	['fundingPaymentHistory', 'fundingPaymentHistory'],
+	['fundingRateHistory', 'fundingRateHistory'],
+	['curveHistory', 'curveHistory'],
+	['liquidationHistory', 'liquidationHistory'],
+];
+
+/**
+ * Checks that the deployed program's accounts have the discriminators and sizes of the idl the sdk was built with, so a program
+ * upgrade the sdk doesn't know about fails fast instead of producing garbage deserializations. Call it once at startup
+ * @param program
+ * @throws IncompatibleProgramVersionError listing every mismatch
+ */
+export async function checkCompatibility(program: Program): Promise<void> {
+	const connection = program.provider.connection;
+	const programAccount = await connection.getAccountInfo(program.programId);
+	if (!programAccount || !programAccount.executable) {
+		throw new IncompatibleProgramVersionError(
+			`No program deployed at ${program.programId.toString()}`
+		);
+	}
+
+	const statePublicKey = await getClearingHouseStateAccountPublicKey(
+		program.programId
+	);
+	const stateAccountInfo = await connection.getAccountInfo(statePublicKey);
+	const mismatches = checkAccount(
+		program,
+		'state',
+		statePublicKey,
+		stateAccountInfo?.data
+	);
+	if (mismatches.length > 0) {
+		throw new IncompatibleProgramVersionError(mismatches.join('\n'));
+	}
+
+	const state = program.coder.accounts.decode(
+		'State',
+		stateAccountInfo.data
+	) as StateAccount;
+	const publicKeys = STATE_ACCOUNTS.map(
+		([, field]) => state[field] as PublicKey
+	);
+	const accountInfos = await connection.getMultipleAccountsInfo(publicKeys);
+	STATE_ACCOUNTS.forEach(([accountName], i) => {
+		mismatches.push(
+			...checkAccount(
+				program,
+				accountName,
+				publicKeys[i],
+				accountInfos[i]?.data
+			)
+		);
+	});
+
+	if (mismatches.length > 0) {
+		throw new IncompatibleProgramVersionError(mismatches.join('\n'));
+	}
+}
+
+/**
+ * @returns a description of every way the account doesn't match the idl
+ */
+function checkAccount(
+	program: Program,
+	accountName: string,
+	publicKey: PublicKey,
+	data?: Buffer
+): string[] {
+	const idlName = accountName.charAt(0).toUpperCase() + accountName.slice(1);
+	if (!data) {
+		return [`${idlName} account ${publicKey.toString()} not found`];
+	}
+
+	const mismatches: string[] = [];
+	const discriminator = data.slice(0, 8);
+	const expectedDiscriminator = AccountsCoder.accountDiscriminator(idlName);
+	if (!discriminator.equals(expectedDiscriminator)) {
+		mismatches.push(
+			`${idlName} account ${publicKey.toString()} discriminator ${discriminator.toString(
+				'hex'
+			)} is not the idl's ${expectedDiscriminator.toString('hex')}`
+		);
+	}
+	const expectedSize = program.account[accountName].size;
+	if (data.length !== expectedSize) {
+		mismatches.push(
+			`${idlName} account ${publicKey.toString()} is ${data.length} bytes, the idl expects ${expectedSize}`
+		);
+	}
+	return mismatches;
+}
@@ -0,0 +1,106 @@
+import {
+	Connection,
+	PublicKey,
+	Transaction,
+	TransactionInstruction,
+} from '@solana/web3.js';
+import BN from 'bn.js';
+import { rpcRequest } from './rpcRequest';
+
+export const COMPUTE_BUDGET_PROGRAM_ID = new PublicKey(
+	'ComputeBudget111111111111111111111111111111'
+);
+
+/**
+ * Builds a compute budget SetComputeUnitPrice instruction, which sets the priority fee paid per compute unit.
+ * Built by hand since the pinned web3.js predates ComputeBudgetProgram
+ * @param microLamports price per compute unit in micro-lamports
+ * @returns instruction to prepend to a transaction
+ */
+export function getComputeUnitPriceIx(
+	microLamports: number | BN
+): TransactionInstruction {
+	const data = Buffer.alloc(9);
+	data.writeUInt8(3, 0);
+	new BN(microLamports).toArrayLike(Buffer, 'le', 8).copy(data, 1);
+	return new TransactionInstruction({
+		programId: COMPUTE_BUDGET_PROGRAM_ID,
+		keys: [],
+		data,
+	});
+}
+
+/**
+ * Builds a compute budget SetComputeUnitLimit instruction, which caps the compute units the transaction may use.
+ * Priority fees are charged on the limit, so it should be set close to the units actually consumed
+ * @param units
+ * @returns instruction to prepend to a transaction
+ */
+export function getComputeUnitLimitIx(units: number): TransactionInstruction {
+	const data = Buffer.alloc(5);
+	data.writeUInt8(2, 0);
+	data.writeUInt32LE(units, 1);
+	return new TransactionInstruction({
+		programId: COMPUTE_BUDGET_PROGRAM_ID,
+		keys: [],
+		data,
+	});
+}
+
+/**
+ * @param tx
+ * @returns true if the transaction already sets its compute unit limit
+ */
+export function hasComputeUnitLimitIx(tx: Transaction): boolean {
+	return tx.instructions.some(
+		(ix) => ix.programId.equals(COMPUTE_BUDGET_PROGRAM_ID) && ix.data[0] === 2
+	);
+}
+
+/**
+ * Estimates the priority fee needed to land a transaction from the fees paid in recent slots by transactions
+ * write locking the same accounts. Uses getRecentPrioritizationFees, which the pinned web3.js doesn't wrap
+ * @param connection
+ * @param writableAccounts accounts the transaction write locks, at most 128
+ * @param percentile of the recent fees to pay, between 0 and 100
+ * @returns priority fee in micro-lamports per compute unit
+ */
+export async function estimatePriorityFee(
+	connection: Connection,
+	writableAccounts: PublicKey[],
+	percentile = 75
+): Promise<number> {
+	const result = await rpcRequest<{ prioritizationFee: number }[]>(
+		connection,
+		'getRecentPrioritizationFees',
+		[writableAccounts.map((account) => account.toBase58())]
+	);
+
+	const fees = result
+		.map((entry) => entry.prioritizationFee)
+		.sort((a, b) => a - b);
+	if (fees.length === 0) {
+		return 0;
+	}
+
+	const index = Math.ceil((percentile / 100) * fees.length) - 1;
+	return fees[Math.min(Math.max(index, 0), fees.length - 1)];
+}
+
+/**
+ * @param instructions
+ * @returns the distinct accounts the instructions write lock
+ */
+export function getWritableAccounts(
+	instructions: TransactionInstruction[]
+): PublicKey[] {
+	const accounts = new Map<string, PublicKey>();
+	for (const ix of instructions) {
+		for (const key of ix.keys) {
+			if (key.isWritable) {
+				accounts.set(key.pubkey.toString(), key.pubkey);
+			}
+		}
+	}
+	return Array.from(accounts.values());
+}
@@ -1,4 +1,16 @@
-import { Connection, Finality, PublicKey } from '@solana/web3.js';
+import {
+	Connection,
+	Finality,
+	PublicKey,
+	Transaction,
+} from '@solana/web3.js';
+import { TxSimulationError } from '../errors';
+import { getComputeUnitLimitIx } from './computeBudget';
+
+/**
+ * Most compute units a transaction can request
+ */
+export const MAX_COMPUTE_UNIT_LIMIT = 1400000;
 
 export async function findComputeUnitConsumption(
 	programId: PublicKey,
@@ -19,3 +31,57 @@ export async function findComputeUnitConsumption(
 	});
 	return computeUnits;
 }
+
+/**
+ * Sums the compute units consumed by a transaction's top level instructions, which include the units of their inner invocations
+ * @param logs program logs of the transaction
+ */
+export function parseComputeUnitsConsumed(logs: string[]): number {
+	let depth = 0;
+	let unitsConsumed = 0;
+	for (const log of logs) {
+		const invoke = log.match(/^Program \w+ invoke \[(\d+)\]/);
+		if (invoke) {
+			depth = parseInt(invoke[1]);
+			continue;
+		}
+
+		const consumed = log.match(
+			/^Program \w+ consumed (\d+) of \d+ compute units/
+		);
+		if (consumed && depth === 1) {
+			unitsConsumed += parseInt(consumed[1]);
+		} else if (/^Program \w+ (success|failed)/.test(log)) {
+			depth--;
+		}
+	}
+	return unitsConsumed;
+}
+
+/**
+ * Simulates the transaction's instructions with the maximum compute unit limit, so large transactions don't fail
+ * the simulation on the default limit
+ * @param connection
+ * @param tx
+ * @param feePayer
+ * @returns compute units consumed
+ * @throws TxSimulationError if the simulation fails
+ */
+export async function simulateComputeUnits(
+	connection: Connection,
+	tx: Transaction,
+	feePayer: PublicKey
+): Promise<number> {
+	const simulationTx = new Transaction({ feePayer }).add(
+		getComputeUnitLimitIx(MAX_COMPUTE_UNIT_LIMIT),
+		...tx.instructions
+	);
+	const { value } = await connection.simulateTransaction(simulationTx);
+	if (value.err) {
+		throw new TxSimulationError(
+			`Transaction simulation failed: ${JSON.stringify(value.err)}`,
+			value.logs ?? []
+		);
+	}
+	return parseComputeUnitsConsumed(value.logs ?? []);
+}
@@ -0,0 +1,159 @@
+import { BN, Coder, Idl } from '@project-serum/anchor';
+import {
+	AccountMeta,
+	PublicKey,
+	TransactionInstruction,
+} from '@solana/web3.js';
+
+type IdlType = string | { [kind: string]: any };
+
+type IdlAccountItem = {
+	name: string;
+	isMut?: boolean;
+	isSigner?: boolean;
+	accounts?: IdlAccountItem[];
+};
+
+const BN_TYPES = ['u64', 'i64', 'u128', 'i128'];
+
+/**
+ * Builds an instruction from an idl at runtime, for instructions added by a program upgrade the typed sdk doesn't know yet.
+ * Args are plain json: integers of 64 bits or more as strings or numbers, public keys as base58 strings and enum variants
+ * as their name (e.g. 'long') or their anchor object ({ long: {} })
+ * @param idl e.g. the idl fetched with Program.fetchIdl
+ * @param programId
+ * @param ixName camelCase or snake_case, e.g. open_position
+ * @param args keyed by arg name
+ * @param accounts keyed by account name, nested account groups flattened
+ * @param remainingAccounts appended after the idl's accounts
+ * @returns instruction
+ */
+export function buildDynamicInstruction(
+	idl: Idl,
+	programId: PublicKey,
+	ixName: string,
+	args: Record<string, unknown>,
+	accounts: Record<string, PublicKey | string>,
+	remainingAccounts: AccountMeta[] = []
+): TransactionInstruction {
+	const name = toCamelCase(ixName);
+	const idlIx = idl.instructions.find((ix) => ix.name === name);
+	if (!idlIx) {
+		throw new Error(`Instruction ${ixName} is not in the idl`);
+	}
+
+	const convertedArgs: Record<string, unknown> = {};
+	for (const arg of idlIx.args) {
+		const value = args[arg.name] ?? args[toSnakeCase(arg.name)];
+		convertedArgs[arg.name] = convertArg(idl, arg.type, value, arg.name);
+	}
+
+	const keys = flattenAccounts(idlIx.accounts as IdlAccountItem[]).map(
+		(account) => {
+			const publicKey =
+				accounts[account.name] ?? accounts[toSnakeCase(account.name)];
+			if (publicKey === undefined) {
+				throw new Error(`Missing account ${account.name} for ${name}`);
+			}
+			return {
+				pubkey: new PublicKey(publicKey),
+				isWritable: !!account.isMut,
+				isSigner: !!account.isSigner,
+			};
+		}
+	);
+
+	return new TransactionInstruction({
+		programId,
+		keys: keys.concat(remainingAccounts),
+		data: new Coder(idl).instruction.encode(name, convertedArgs),
+	});
+}
+
+function convertArg(
+	idl: Idl,
+	type: IdlType,
+	value: any,
+	path: string
+): unknown {
+	if (typeof type === 'string') {
+		if (value === undefined || value === null) {
+			throw new Error(`Missing arg ${path}`);
+		}
+		if (BN_TYPES.indexOf(type) !== -1) {
+			return new BN(value.toString());
+		}
+		if (type === 'publicKey') {
+			return new PublicKey(value);
+		}
+		return value;
+	}
+
+	if (type.option !== undefined) {
+		return value === undefined || value === null
+			? null
+			: convertArg(idl, type.option, value, path);
+	}
+	if (type.vec !== undefined) {
+		return (value as unknown[]).map((item, i) =>
+			convertArg(idl, type.vec, item, `${path}[${i}]`)
+		);
+	}
+	if (type.array !== undefined) {
+		return (value as unknown[]).map((item, i) =>
+			convertArg(idl, type.array[0], item, `${path}[${i}]`)
+		);
+	}
+
+	const definedName = type.defined as string;
+	const definedType = (idl.types ?? []).find(
+		(idlType) => idlType.name === definedName
+	);
+	if (!definedType) {
+		throw new Error(`Type ${definedName} of ${path} is not in the idl`);
+	}
+
+	if (definedType.type.kind === 'enum') {
+		const variant = typeof value === 'string' ? value : Object.keys(value)[0];
+		const variantName = toCamelCase(variant);
+		const known = definedType.type.variants.some(
+			(idlVariant) => toCamelCase(idlVariant.name) === variantName
+		);
+		if (!known) {
+			throw new Error(`${variant} is not a variant of ${definedName}`);
+		}
+		return { [variantName]: {} };
+	}
+
+	const struct: Record<string, unknown> = {};
+	for (const field of definedType.type.fields ?? []) {
+		struct[field.name] = convertArg(
+			idl,
+			field.type as IdlType,
+			value?.[field.name] ?? value?.[toSnakeCase(field.name)],
+			`${path}.${field.name}`
+		);
+	}
+	return struct;
+}
+
+function flattenAccounts(accounts: IdlAccountItem[]): IdlAccountItem[] {
+	return accounts.reduce(
+		(flattened, account) =>
+			flattened.concat(
+				account.accounts ? flattenAccounts(account.accounts) : [account]
+			),
+		[] as IdlAccountItem[]
+	);
+}
+
+function toCamelCase(name: string): string {
+	const camelCase = name.replace(/_([a-z0-9])/g, (_, c: string) =>
+		c.toUpperCase()
+	);
+	return camelCase.charAt(0).toLowerCase() + camelCase.slice(1);
+}
+
+function toSnakeCase(name: string): string {
+	return name.replace(/[A-Z]/g, (c) => `_${c.toLowerCase()}`);
+}
@@ -0,0 +1,210 @@
+import { BN } from '@project-serum/anchor';
+import {
+	AMM_RESERVE_PRECISION,
+	FUNDING_RATE_PRECISION,
+	MARK_PRICE_PRECISION,
+	PEG_PRECISION,
+	QUOTE_PRECISION,
+	ZERO,
+} from '../constants/numericConstants';
+import { Markets } from '../constants/markets';
+import { calculateMarkPrice } from '../math/market';
+import {
+	calculateBaseAssetValue,
+	calculateEntryPrice,
+	calculatePositionPNL,
+} from '../math/position';
+import {
+	Market,
+	UserAccount,
+	UserPosition,
+	UserPositionsAccount,
+} from '../types';
+
+/**
+ * Renders a fixed point amount as a decimal string without going through floating point, e.g. 1500000 at QUOTE_PRECISION is 1.500000
+ * @param value
+ * @param precision
+ * @param decimals digits after the point, truncated. All of the precision's digits by default
+ * @returns decimal string
+ */
+export function formatBN(
+	value: BN,
+	precision: BN,
+	decimals = precision.toString().length - 1
+): string {
+	const precisionDigits = precision.toString().length - 1;
+	const abs = value.abs();
+	const whole = abs.div(precision).toString();
+	const fraction = (
+		'0'.repeat(precisionDigits) + abs.mod(precision).toString()
+	)
+		.slice(-precisionDigits)
+		.slice(0, decimals);
+	const sign = value.isNeg() ? '-' : '';
+	return fraction.length > 0
+		? `${sign}${whole}.${fraction}`
+		: `${sign}${whole}`;
+}
+
+function formatTs(ts: BN): string {
+	return ts.eq(ZERO) ? 'never' : new Date(ts.toNumber() * 1000).toISOString();
+}
+
+/**
+ * @param marketIndex
+ * @returns the market's symbol, e.g. SOL-PERP, or its index if the sdk doesn't know it
+ */
+export function getMarketSymbol(marketIndex: BN): string {
+	const market = Markets.find((market) => market.marketIndex.eq(marketIndex));
+	return market ? market.symbol : `market ${marketIndex.toString()}`;
+}
+
+function formatLines(title: string, fields: Array<[string, string]>): string {
+	const width = Math.max(...fields.map(([label]) => label.length));
+	return [
+		title,
+		...fields.map(
+			([label, value]) =>
+				`  ${label}${' '.repeat(width - label.length)}  ${value}`
+		),
+	].join('\n');
+}
+
+/**
+ * @param market
+ * @param marketIndex
+ * @returns the market's labeled fields with amounts at their decimal scale, one per line
+ */
+export function formatMarket(market: Market, marketIndex: BN): string {
+	const amm = market.amm;
+	return formatLines(getMarketSymbol(marketIndex), [
+		['initialized', market.initialized.toString()],
+		[
+			'mark price',
+			formatBN(calculateMarkPrice(market), MARK_PRICE_PRECISION, 4),
+		],
+		['oracle', amm.oracle.toString()],
+		[
+			'base asset reserve',
+			formatBN(amm.baseAssetReserve, AMM_RESERVE_PRECISION),
+		],
+		[
+			'quote asset reserve',
+			formatBN(amm.quoteAssetReserve, AMM_RESERVE_PRECISION),
+		],
+		['sqrt k', formatBN(amm.sqrtK, AMM_RESERVE_PRECISION)],
+		['peg multiplier', formatBN(amm.pegMultiplier, PEG_PRECISION)],
+		['open interest', market.openInterest.toString()],
+		[
+			'base asset amount',
+			formatBN(market.baseAssetAmount, AMM_RESERVE_PRECISION),
+		],
+		[
+			'base asset amount long',
+			formatBN(market.baseAssetAmountLong, AMM_RESERVE_PRECISION),
+		],
+		[
+			'base asset amount short',
+			formatBN(market.baseAssetAmountShort, AMM_RESERVE_PRECISION),
+		],
+		[
+			'last funding rate',
+			formatBN(amm.lastFundingRate, FUNDING_RATE_PRECISION),
+		],
+		['last funding ts', formatTs(amm.lastFundingRateTs)],
+		['funding period', `${amm.fundingPeriod.toString()}s`],
+		['mark twap', formatBN(amm.lastMarkPriceTwap, MARK_PRICE_PRECISION, 4)],
+		[
+			'oracle twap',
+			formatBN(amm.lastOraclePriceTwap, MARK_PRICE_PRECISION, 4),
+		],
+		['total fee', formatBN(amm.totalFee, QUOTE_PRECISION)],
+		[
+			'fee minus distributions',
+			formatBN(amm.totalFeeMinusDistributions, QUOTE_PRECISION),
+		],
+		['fee withdrawn', formatBN(amm.totalFeeWithdrawn, QUOTE_PRECISION)],
+		['minimum trade size', formatBN(amm.minimumTradeSize, QUOTE_PRECISION)],
+	]);
+}
+
+/**
+ * @param position
+ * @param market the position's market, adds its value, pnl and mark price
+ * @returns the position's labeled fields with amounts at their decimal scale, one per line
+ */
+export function formatPosition(
+	position: UserPosition,
+	market?: Market
+): string {
+	const fields: Array<[string, string]> = [
+		[
+			'base asset amount',
+			formatBN(position.baseAssetAmount, AMM_RESERVE_PRECISION),
+		],
+		[
+			'quote asset amount',
+			formatBN(position.quoteAssetAmount, QUOTE_PRECISION),
+		],
+		[
+			'entry price',
+			formatBN(calculateEntryPrice(position), MARK_PRICE_PRECISION, 4),
+		],
+	];
+	if (market) {
+		fields.push(
+			[
+				'mark price',
+				formatBN(calculateMarkPrice(market), MARK_PRICE_PRECISION, 4),
+			],
+			[
+				'value',
+				formatBN(calculateBaseAssetValue(market, position), QUOTE_PRECISION),
+			],
+			[
+				'unrealized pnl',
+				formatBN(calculatePositionPNL(market, position), QUOTE_PRECISION),
+			]
+		);
+	}
+	return formatLines(getMarketSymbol(position.marketIndex), fields);
+}
+
+/**
+ * @param userAccount
+ * @param userPositionsAccount adds the open positions
+ * @param markets the markets account's markets, adds each position's value and pnl
+ * @returns the user's labeled fields with amounts at their decimal scale, one per line
+ */
+export function formatUser(
+	userAccount: UserAccount,
+	userPositionsAccount?: UserPositionsAccount,
+	markets?: Market[]
+): string {
+	const sections = [
+		formatLines(`user ${userAccount.authority.toString()}`, [
+			['collateral', formatBN(userAccount.collateral, QUOTE_PRECISION)],
+			[
+				'cumulative deposits',
+				formatBN(userAccount.cumulativeDeposits, QUOTE_PRECISION),
+			],
+			['total fee paid', formatBN(userAccount.totalFeePaid, QUOTE_PRECISION)],
+			['positions account', userAccount.positions.toString()],
+		]),
+	];
+
+	for (const position of userPositionsAccount?.positions ?? []) {
+		if (position.baseAssetAmount.eq(ZERO)) {
+			continue;
+		}
+		sections.push(
+			formatPosition(
+				position,
+				markets ? markets[position.marketIndex.toNumber()] : undefined
+			)
+		);
+	}
+
+	return sections.join('\n');
+}
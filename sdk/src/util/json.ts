@@ -0,0 +1,69 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import {
+	CurveRecord,
+	DepositRecord,
+	FundingPaymentRecord,
+	FundingRateRecord,
+	LiquidationRecord,
+	MarketsAccount,
+	StateAccount,
+	TradeRecord,
+	UserAccount,
+	UserPositionsAccount,
+} from '../types';
+
+/**
+ * Mirror of an account type that JSON.stringify can serialize losslessly: BNs become decimal strings and public keys base58 strings
+ */
+export type Jsonified<T> = T extends BN
+	? string
+	: T extends PublicKey
+	? string
+	: T extends Array<infer U>
+	? Jsonified<U>[]
+	: T extends object
+	? { [K in keyof T]: Jsonified<T[K]> }
+	: T;
+
+export type StateAccountJson = Jsonified<StateAccount>;
+export type MarketsAccountJson = Jsonified<MarketsAccount>;
+export type UserAccountJson = Jsonified<UserAccount>;
+export type UserPositionsAccountJson = Jsonified<UserPositionsAccount>;
+export type TradeRecordJson = Jsonified<TradeRecord>;
+export type DepositRecordJson = Jsonified<DepositRecord>;
+export type FundingPaymentRecordJson = Jsonified<FundingPaymentRecord>;
+export type FundingRateRecordJson = Jsonified<FundingRateRecord>;
+export type LiquidationRecordJson = Jsonified<LiquidationRecord>;
+export type CurveRecordJson = Jsonified<CurveRecord>;
+
+/**
+ * Converts an account, a history record, or anything built from them, to its JSON-safe mirror.
+ * Amounts keep their on-chain precision, so they round-trip with new BN(value)
+ * @param value
+ * @returns mirror with BNs and public keys as strings
+ */
+export function toJson<T>(value: T): Jsonified<T> {
+	return convert(value) as Jsonified<T>;
+}
+
+function convert(value: unknown): unknown {
+	if (BN.isBN(value)) {
+		return (value as BN).toString();
+	}
+	if (value instanceof PublicKey) {
+		return value.toString();
+	}
+	if (Array.isArray(value)) {
+		return value.map(convert);
+	}
+	if (value !== null && typeof value === 'object') {
+		const record = value as Record<string, unknown>;
+		const result: Record<string, unknown> = {};
+		for (const key of Object.keys(record)) {
+			result[key] = convert(record[key]);
+		}
+		return result;
+	}
+	return value;
+}
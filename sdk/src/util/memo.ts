@@ -0,0 +1,19 @@
+import { PublicKey, TransactionInstruction } from '@solana/web3.js';
+
+export const MEMO_PROGRAM_ID = new PublicKey(
+	'MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr'
+);
+
+/**
+ * Builds an SPL Memo instruction. The memo is recorded in the transaction's logs, which lets on-chain history be attributed
+ * to the client or strategy that sent it
+ * @param memo utf-8 text
+ * @returns instruction to append to a transaction
+ */
+export function getMemoIx(memo: string): TransactionInstruction {
+	return new TransactionInstruction({
+		programId: MEMO_PROGRAM_ID,
+		keys: [],
+		data: Buffer.from(memo, 'utf8'),
+	});
+}
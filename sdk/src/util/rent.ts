@@ -0,0 +1,174 @@
+import { Program } from '@project-serum/anchor';
+import { AccountLayout } from '@solana/spl-token';
+import {
+	Connection,
+	PublicKey,
+	SystemProgram,
+	TransactionInstruction,
+} from '@solana/web3.js';
+
+/**
+ * Rent exemption minimums by account size, fetched once per size. Rent only changes with a feature activation,
+ * so a cache can live as long as the connection
+ */
+export class RentCache {
+	connection: Connection;
+
+	private lamportsBySpace = new Map<number, Promise<number>>();
+
+	public constructor(connection: Connection) {
+		this.connection = connection;
+	}
+
+	/**
+	 * @param space account size in bytes
+	 * @returns lamports an account of that size needs to be rent exempt
+	 */
+	public async getRentExemptLamports(space: number): Promise<number> {
+		let lamports = this.lamportsBySpace.get(space);
+		if (!lamports) {
+			lamports = this.connection.getMinimumBalanceForRentExemption(space);
+			this.lamportsBySpace.set(space, lamports);
+			// failed requests aren't cached
+			lamports.catch(() => this.lamportsBySpace.delete(space));
+		}
+		return await lamports;
+	}
+}
+
+/**
+ * Builds the instruction creating a rent exempt program account, like the account client's createInstruction
+ * but reading rent through the cache
+ * @param program
+ * @param rentCache
+ * @param accountName account client name, e.g. tradeHistory
+ * @param account the new account, signs the transaction
+ * @param payer
+ */
+export async function getCreateAccountIx(
+	program: Program,
+	rentCache: RentCache,
+	accountName: string,
+	account: PublicKey,
+	payer: PublicKey
+): Promise<TransactionInstruction> {
+	const space = program.account[accountName].size;
+	return SystemProgram.createAccount({
+		fromPubkey: payer,
+		newAccountPubkey: account,
+		space,
+		lamports: await rentCache.getRentExemptLamports(space),
+		programId: program.programId,
+	});
+}
+
+/**
+ * Builds the instructions creating several program accounts, fetching the rent of each distinct size once, see {@link getCreateAccountIx}
+ * @param program
+ * @param rentCache
+ * @param accounts account client names and new accounts
+ * @param payer
+ * @returns instructions in the order of accounts
+ */
+export async function getCreateAccountIxs(
+	program: Program,
+	rentCache: RentCache,
+	accounts: Array<[string, PublicKey]>,
+	payer: PublicKey
+): Promise<TransactionInstruction[]> {
+	return await Promise.all(
+		accounts.map(([accountName, account]) =>
+			getCreateAccountIx(program, rentCache, accountName, account, payer)
+		)
+	);
+}
+
+/**
+ * Accounts the clearing house creates, by account client name. tokenVault is the collateral and insurance vaults' token account
+ */
+export type DriftAccountKind =
+	| 'state'
+	| 'markets'
+	| 'tokenVault'
+	| 'user'
+	| 'userPositions'
+	| 'depositHistory'
+	| 'fundingRateHistory'
+	| 'fundingPaymentHistory'
+	| 'tradeHistory'
+	| 'liquidationHistory'
+	| 'curveHistory';
+
+/**
+ * Accounts the admin pays for in {@link Admin.initialize}, the vaults being two token accounts
+ */
+export const CLEARING_HOUSE_INITIALIZATION_ACCOUNTS: DriftAccountKind[] = [
+	'state',
+	'markets',
+	'tokenVault',
+	'tokenVault',
+	'depositHistory',
+	'fundingRateHistory',
+	'fundingPaymentHistory',
+	'tradeHistory',
+	'liquidationHistory',
+	'curveHistory',
+];
+
+/**
+ * Accounts a user pays for when initializing a user account
+ */
+export const USER_INITIALIZATION_ACCOUNTS: DriftAccountKind[] = [
+	'user',
+	'userPositions',
+];
+
+/**
+ * @param program
+ * @param kind
+ * @returns size of the account in bytes, including the account discriminator
+ */
+export function getAccountSpace(
+	program: Program,
+	kind: DriftAccountKind
+): number {
+	return kind === 'tokenVault'
+		? AccountLayout.span
+		: program.account[kind].size;
+}
+
+/**
+ * @param program
+ * @param rentCache
+ * @param kind
+ * @returns lamports an account of the kind needs to be rent exempt
+ */
+export async function getRequiredLamports(
+	program: Program,
+	rentCache: RentCache,
+	kind: DriftAccountKind
+): Promise<number> {
+	return await rentCache.getRentExemptLamports(getAccountSpace(program, kind));
+}
+
+/**
+ * Sums the rent of several accounts, e.g. {@link CLEARING_HOUSE_INITIALIZATION_ACCOUNTS} for deployment scripts
+ * to check the admin's balance before initializing. Transaction fees aren't included
+ * @param program
+ * @param rentCache
+ * @param kinds
+ * @returns lamports
+ */
+export async function getTotalRequiredLamports(
+	program: Program,
+	rentCache: RentCache,
+	kinds: DriftAccountKind[]
+): Promise<number> {
+	const lamports = await Promise.all(
+		kinds.map((kind) => getRequiredLamports(program, rentCache, kind))
+	);
+	return lamports.reduce(
+		(total, accountLamports) => total + accountLamports,
+		0
+	);
+}
@@ -0,0 +1,21 @@
+import { Connection } from '@solana/web3.js';
+
+/**
+ * Sends a raw json rpc request, for methods and params the pinned web3.js doesn't wrap (e.g. dataSlice, minContextSlot)
+ * @param connection
+ * @param method rpc method, e.g. getRecentPrioritizationFees
+ * @param args
+ * @returns the response's result
+ * @throws if the node responds with an error
+ */
+export async function rpcRequest<T>(
+	connection: Connection,
+	method: string,
+	args: unknown[]
+): Promise<T> {
+	const response = await connection['_rpcRequest'](method, args);
+	if (response.error) {
+		throw new Error(`${method} failed: ${response.error.message}`);
+	}
+	return response.result as T;
+}
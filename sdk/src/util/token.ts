@@ -0,0 +1,188 @@
+import { BN } from '@project-serum/anchor';
+import {
+	AccountLayout,
+	ASSOCIATED_TOKEN_PROGRAM_ID,
+	Token,
+	TOKEN_PROGRAM_ID,
+	u64,
+} from '@solana/spl-token';
+import {
+	AccountInfo,
+	Connection,
+	PublicKey,
+	TransactionInstruction,
+} from '@solana/web3.js';
+import { InvalidTokenAccountError, TokenAccountNotFoundError } from '../errors';
+
+/**
+ * Resolves the owner's associated token account for a mint, with the instruction creating it if it doesn't exist yet
+ * @param connection
+ * @param owner
+ * @param mint
+ * @param payer pays the account's rent, defaults to the owner
+ * @returns the associated token account and the create instruction, undefined if the account exists
+ */
+export async function getOrCreateAssociatedTokenAccount(
+	connection: Connection,
+	owner: PublicKey,
+	mint: PublicKey,
+	payer: PublicKey = owner
+): Promise<[PublicKey, TransactionInstruction | undefined]> {
+	const associatedTokenPublicKey = await Token.getAssociatedTokenAddress(
+		ASSOCIATED_TOKEN_PROGRAM_ID,
+		TOKEN_PROGRAM_ID,
+		mint,
+		owner
+	);
+
+	const accountInfo = await connection.getAccountInfo(associatedTokenPublicKey);
+	if (accountInfo !== null) {
+		return [associatedTokenPublicKey, undefined];
+	}
+
+	return [
+		associatedTokenPublicKey,
+		Token.createAssociatedTokenAccountInstruction(
+			ASSOCIATED_TOKEN_PROGRAM_ID,
+			TOKEN_PROGRAM_ID,
+			mint,
+			associatedTokenPublicKey,
+			owner,
+			payer
+		),
+	];
+}
+
+/**
+ * @param connection
+ * @param tokenAccount
+ * @returns the token account's balance in the mint's base units, undefined if the account doesn't exist or isn't a token account
+ */
+export async function getTokenAccountBalance(
+	connection: Connection,
+	tokenAccount: PublicKey
+): Promise<BN | undefined> {
+	const accountInfo = await connection.getAccountInfo(tokenAccount);
+	if (
+		accountInfo === null ||
+		!accountInfo.owner.equals(TOKEN_PROGRAM_ID) ||
+		accountInfo.data.length !== AccountLayout.span
+	) {
+		return undefined;
+	}
+
+	return u64.fromBuffer(AccountLayout.decode(accountInfo.data).amount);
+}
+
+export type TokenAccountCandidate = {
+	publicKey: PublicKey;
+	/** in the mint's base units */
+	balance: BN;
+	/** whether it's the owner's associated token account for the mint */
+	isAssociated: boolean;
+};
+
+/**
+ * Finds every token account the owner holds for a mint, e.g. to let users pick the account a deposit is paid from
+ * @param connection
+ * @param owner
+ * @param mint
+ * @returns the associated token account first, then the others by balance, highest first
+ */
+export async function findTokenAccounts(
+	connection: Connection,
+	owner: PublicKey,
+	mint: PublicKey
+): Promise<TokenAccountCandidate[]> {
+	const [associatedTokenPublicKey, response] = await Promise.all([
+		Token.getAssociatedTokenAddress(
+			ASSOCIATED_TOKEN_PROGRAM_ID,
+			TOKEN_PROGRAM_ID,
+			mint,
+			owner
+		),
+		connection.getTokenAccountsByOwner(owner, { mint }),
+	]);
+
+	const candidates = response.value.map(({ pubkey, account }) => ({
+		publicKey: pubkey,
+		balance: u64.fromBuffer(AccountLayout.decode(account.data).amount),
+		isAssociated: pubkey.equals(associatedTokenPublicKey),
+	}));
+	return candidates.sort((a, b) => {
+		if (a.isAssociated !== b.isAssociated) {
+			return a.isAssociated ? -1 : 1;
+		}
+		return b.balance.cmp(a.balance);
+	});
+}
+
+export type TokenAccount = {
+	publicKey: PublicKey;
+	mint: PublicKey;
+	owner: PublicKey;
+	/** in the mint's base units */
+	amount: BN;
+};
+
+/**
+ * @param publicKey
+ * @param accountInfo as returned by the connection, null if the account doesn't exist
+ * @throws TokenAccountNotFoundError if the account doesn't exist
+ * @throws InvalidTokenAccountError if the account isn't a token account
+ */
+export function decodeTokenAccount(
+	publicKey: PublicKey,
+	accountInfo: AccountInfo<Buffer> | null
+): TokenAccount {
+	if (accountInfo === null) {
+		throw new TokenAccountNotFoundError(publicKey);
+	}
+	if (
+		!accountInfo.owner.equals(TOKEN_PROGRAM_ID) ||
+		accountInfo.data.length !== AccountLayout.span
+	) {
+		throw new InvalidTokenAccountError(publicKey);
+	}
+
+	const decoded = AccountLayout.decode(accountInfo.data);
+	return {
+		publicKey,
+		mint: new PublicKey(decoded.mint),
+		owner: new PublicKey(decoded.owner),
+		amount: u64.fromBuffer(decoded.amount),
+	};
+}
+
+/**
+ * Rpc failures are thrown as the connection throws them
+ * @param connection
+ * @param publicKey
+ * @throws TokenAccountNotFoundError if the account doesn't exist
+ * @throws InvalidTokenAccountError if the account isn't a token account
+ */
+export async function fetchTokenAccount(
+	connection: Connection,
+	publicKey: PublicKey
+): Promise<TokenAccount> {
+	return decodeTokenAccount(
+		publicKey,
+		await connection.getAccountInfo(publicKey)
+	);
+}
+
+/**
+ * Fetches several token accounts in a single request, see {@link fetchTokenAccount}
+ * @param connection
+ * @param publicKeys
+ * @returns token accounts in the order of publicKeys
+ */
+export async function fetchTokenAccounts(
+	connection: Connection,
+	publicKeys: PublicKey[]
+): Promise<TokenAccount[]> {
+	const accountInfos = await connection.getMultipleAccountsInfo(publicKeys);
+	return publicKeys.map((publicKey, i) =>
+		decodeTokenAccount(publicKey, accountInfos[i])
+	);
+}
@@ -0,0 +1,66 @@
+import {
+	AccountLayout,
+	NATIVE_MINT,
+	Token,
+	TOKEN_PROGRAM_ID,
+} from '@solana/spl-token';
+import {
+	Connection,
+	Keypair,
+	PublicKey,
+	SystemProgram,
+	TransactionInstruction,
+} from '@solana/web3.js';
+import BN from 'bn.js';
+
+export type TemporaryWrappedSolAccount = {
+	/** signs the account's creation */
+	keypair: Keypair;
+	/** create and initialize the account, funded with the wrapped lamports */
+	createInstructions: TransactionInstruction[];
+	/** close the account, unwrapping its balance and rent back to the owner */
+	closeInstruction: TransactionInstruction;
+};
+
+/**
+ * Builds a wrapped sol token account that only lives for one transaction: created with the lamports to wrap on top of its rent,
+ * used as the source or destination of a token transfer, then closed so its whole balance returns to the owner as sol
+ * @param connection
+ * @param owner owns the account, pays its rent and receives its balance on close
+ * @param lamports to wrap, zero for an account that only receives
+ * @returns account
+ */
+export async function getTemporaryWrappedSolAccount(
+	connection: Connection,
+	owner: PublicKey,
+	lamports: BN
+): Promise<TemporaryWrappedSolAccount> {
+	const keypair = new Keypair();
+	const rent = await Token.getMinBalanceRentForExemptAccount(connection);
+
+	return {
+		keypair,
+		createInstructions: [
+			SystemProgram.createAccount({
+				fromPubkey: owner,
+				newAccountPubkey: keypair.publicKey,
+				lamports: lamports.addn(rent).toNumber(),
+				space: AccountLayout.span,
+				programId: TOKEN_PROGRAM_ID,
+			}),
+			Token.createInitAccountInstruction(
+				TOKEN_PROGRAM_ID,
+				NATIVE_MINT,
+				keypair.publicKey,
+				owner
+			),
+		],
+		closeInstruction: Token.createCloseAccountInstruction(
+			TOKEN_PROGRAM_ID,
+			keypair.publicKey,
+			owner,
+			owner,
+			[]
+		),
+	};
+}
@@ -0,0 +1,274 @@
+import * as anchor from '@project-serum/anchor';
+import { Connection, Keypair } from '@solana/web3.js';
+import BN from 'bn.js';
+import {
+	ClearingHouse,
+	ClearingHouseUser,
+	CollateralAmount,
+	PositionDirection,
+	QuoteAssetAmount,
+	toCollateralAmount,
+	toQuoteAssetAmount,
+	ZERO,
+} from '../sdk/src';
+import { initUserAccounts } from './stressUtils';
+
+export type LoadTestConfig = {
+	numUsers: number;
+	/** target transactions per second across all users */
+	tps: number;
+	durationSec: number;
+	/** collateral each user deposits : Precision QUOTE_PRECISION */
+	userCollateral: CollateralAmount;
+	/** quote size of each open : Precision QUOTE_PRECISION */
+	tradeSize: QuoteAssetAmount;
+	marketIndexes: BN[];
+	/** chance that a user with an open position closes it instead of trading again */
+	closeProbability: number;
+	/** forget each user's cached positions address before every trade, the trade path before it was cached, to measure what the cache saves */
+	uncachedPositionsAddress: boolean;
+	/** refetch the markets and user accounts after every trade, see {@link ClearingHouse.enableRefreshAfterTx} */
+	refreshAfterTx: boolean;
+};
+
+export const DEFAULT_LOAD_TEST_CONFIG: LoadTestConfig = {
+	numUsers: 10,
+	tps: 5,
+	durationSec: 60,
+	userCollateral: toCollateralAmount(10000),
+	tradeSize: toQuoteAssetAmount(100),
+	marketIndexes: [new BN(0)],
+	closeProbability: 0.3,
+	uncachedPositionsAddress: false,
+	refreshAfterTx: false,
+};
+
+export type LatencyDistribution = {
+	min: number;
+	p50: number;
+	p90: number;
+	p99: number;
+	max: number;
+	mean: number;
+};
+
+export type LoadTestReport = {
+	sent: number;
+	succeeded: number;
+	failed: number;
+	/** includes waiting for the transactions still in flight once sending stopped */
+	durationSec: number;
+	targetTps: number;
+	achievedTps: number;
+	/** confirmation latency of succeeded transactions, in ms */
+	latencyMs: LatencyDistribution;
+	/** json rpc requests the users' connections made per transaction sent, including background refreshes */
+	rpcRequestsPerTx: number;
+	/** failure counts keyed by error message */
+	errors: Record<string, number>;
+};
+
+type LoadTestUser = {
+	clearingHouse: ClearingHouse;
+	user: ClearingHouseUser;
+	busy: boolean;
+};
+
+type TxResult = {
+	latencyMs: number;
+	error?: string;
+};
+
+/**
+ * Spins up simulated users (mint, fund, initialize, deposit) and fires open/close traffic at a fixed rate against localnet,
+ * reporting latency and error distributions to validate rpc and keeper capacity. Markets must already be initialized.
+ */
+export async function loadTest(
+	usdcMint: Keypair,
+	provider: anchor.Provider,
+	config: Partial<LoadTestConfig> = {}
+): Promise<LoadTestReport> {
+	const fullConfig = { ...DEFAULT_LOAD_TEST_CONFIG, ...config };
+
+	const [, , clearingHouses, userAccountInfos] = await initUserAccounts(
+		fullConfig.numUsers,
+		usdcMint,
+		fullConfig.userCollateral,
+		provider
+	);
+	const users: LoadTestUser[] = clearingHouses.map((clearingHouse, i) => {
+		if (fullConfig.refreshAfterTx) {
+			clearingHouse.enableRefreshAfterTx(userAccountInfos[i]);
+		}
+		return { clearingHouse, user: userAccountInfos[i], busy: false };
+	});
+
+	const stopCounting = countRpcRequests(
+		clearingHouses.map((clearingHouse) => clearingHouse.connection)
+	);
+	const report = await runLoad(users, fullConfig);
+	report.rpcRequestsPerTx = stopCounting() / Math.max(report.sent, 1);
+
+	for (const { clearingHouse, user } of users) {
+		await user.unsubscribe();
+		await clearingHouse.unsubscribe();
+	}
+
+	return report;
+}
+
+async function runLoad(
+	users: LoadTestUser[],
+	config: LoadTestConfig
+): Promise<LoadTestReport> {
+	const inFlight: Promise<TxResult>[] = [];
+	const intervalMs = 1000 / config.tps;
+	const start = Date.now();
+	const end = start + config.durationSec * 1000;
+
+	let next = start;
+	while (next < end) {
+		const idleUsers = users.filter((user) => !user.busy);
+		if (idleUsers.length > 0) {
+			const user = idleUsers[Math.floor(Math.random() * idleUsers.length)];
+			inFlight.push(sendTrade(user, config));
+		}
+
+		next += intervalMs;
+		await sleep(Math.max(0, next - Date.now()));
+	}
+
+	const results = await Promise.all(inFlight);
+	const durationSec = (Date.now() - start) / 1000;
+
+	const latencies = results
+		.filter((result) => result.error === undefined)
+		.map((result) => result.latencyMs);
+	const errors: Record<string, number> = {};
+	for (const result of results) {
+		if (result.error !== undefined) {
+			errors[result.error] = (errors[result.error] ?? 0) + 1;
+		}
+	}
+
+	return {
+		sent: results.length,
+		succeeded: latencies.length,
+		failed: results.length - latencies.length,
+		durationSec,
+		targetTps: config.tps,
+		achievedTps: latencies.length / durationSec,
+		latencyMs: calculateLatencyDistribution(latencies),
+		rpcRequestsPerTx: 0,
+		errors,
+	};
+}
+
+async function sendTrade(
+	loadTestUser: LoadTestUser,
+	config: LoadTestConfig
+): Promise<TxResult> {
+	const { clearingHouse, user } = loadTestUser;
+	const marketIndex =
+		config.marketIndexes[
+			Math.floor(Math.random() * config.marketIndexes.length)
+		];
+
+	loadTestUser.busy = true;
+	if (config.uncachedPositionsAddress) {
+		clearingHouse.userPositionsPublicKey = undefined;
+	}
+	const start = Date.now();
+	try {
+		const position = user.getUserPosition(marketIndex);
+		if (
+			position &&
+			!position.baseAssetAmount.eq(ZERO) &&
+			Math.random() < config.closeProbability
+		) {
+			await clearingHouse.closePosition(marketIndex);
+		} else {
+			const direction =
+				Math.random() < 0.5 ? PositionDirection.LONG : PositionDirection.SHORT;
+			await clearingHouse.openPosition(
+				direction,
+				config.tradeSize,
+				marketIndex
+			);
+		}
+		return { latencyMs: Date.now() - start };
+	} catch (e) {
+		return { latencyMs: Date.now() - start, error: e.message ?? String(e) };
+	} finally {
+		loadTestUser.busy = false;
+	}
+}
+
+export function calculateLatencyDistribution(
+	latencies: number[]
+): LatencyDistribution {
+	if (latencies.length === 0) {
+		return { min: 0, p50: 0, p90: 0, p99: 0, max: 0, mean: 0 };
+	}
+
+	const sorted = [...latencies].sort((a, b) => a - b);
+	const percentile = (p: number) =>
+		sorted[Math.min(sorted.length - 1, Math.floor(p * sorted.length))];
+	return {
+		min: sorted[0],
+		p50: percentile(0.5),
+		p90: percentile(0.9),
+		p99: percentile(0.99),
+		max: sorted[sorted.length - 1],
+		mean: sorted.reduce((sum, latency) => sum + latency, 0) / sorted.length,
+	};
+}
+
+export function printLoadTestReport(report: LoadTestReport): void {
+	console.log(
+		`sent ${report.sent} txs in ${report.durationSec.toFixed(1)}s: ${
+			report.succeeded
+		} succeeded, ${report.failed} failed`
+	);
+	console.log(
+		`tps: target ${report.targetTps}, achieved ${report.achievedTps.toFixed(2)}`
+	);
+	const { min, p50, p90, p99, max, mean } = report.latencyMs;
+	console.log(
+		`latency ms: min ${min} p50 ${p50} p90 ${p90} p99 ${p99} max ${max} mean ${mean.toFixed(
+			0
+		)}`
+	);
+	console.log(`rpc requests per tx: ${report.rpcRequestsPerTx.toFixed(2)}`);
+	for (const error of Object.keys(report.errors)) {
+		console.log(`error x${report.errors[error]}: ${error}`);
+	}
+}
+
+/**
+ * Counts the json rpc requests of the connections from now on
+ * @returns function restoring the connections and returning the count
+ */
+function countRpcRequests(connections: Connection[]): () => number {
+	let count = 0;
+	const distinctConnections = Array.from(new Set(connections));
+	const rpcRequests = distinctConnections.map((connection) => {
+		const rpcRequest = connection['_rpcRequest'];
+		connection['_rpcRequest'] = (method: string, args: unknown[]) => {
+			count++;
+			return rpcRequest(method, args);
+		};
+		return rpcRequest;
+	});
+
+	return () => {
+		distinctConnections.forEach((connection, i) => {
+			connection['_rpcRequest'] = rpcRequests[i];
+		});
+		return count;
+	};
+}
+
+function sleep(ms: number): Promise<void> {
+	return new Promise((resolve) => setTimeout(resolve, ms));
+}
@@ -10,6 +10,8 @@ import {
 	MARK_PRICE_PRECISION,
 	MAX_LEVERAGE,
 	PositionDirection,
+	toCollateralAmount,
+	toQuoteAssetAmount,
 } from '../sdk/src';
 
 import { Markets } from '../sdk/src/constants/markets';
@@ -73,7 +75,7 @@ describe('admin withdraw', () => {
 		);
 
 		await clearingHouse.initializeUserAccountAndDepositCollateral(
-			usdcAmount,
+			toCollateralAmount(usdcAmount),
 			userUSDCAccount.publicKey
 		);
 
@@ -81,7 +83,7 @@ describe('admin withdraw', () => {
 		const incrementalUSDCNotionalAmount = calculateTradeAmount(usdcAmount);
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			incrementalUSDCNotionalAmount,
+			toQuoteAssetAmount(incrementalUSDCNotionalAmount),
 			marketIndex
 		);
 	});
@@ -23,6 +23,8 @@ import {
 	AMM_RESERVE_PRECISION,
 	calculateMarkPrice,
 	convertToNumber,
+	toCollateralAmount,
+	toQuoteAssetAmount,
 } from '../sdk';
 
 import { Program } from '@project-serum/anchor';
@@ -195,7 +197,7 @@ async function cappedSymFundingScenario(
 	if (longShortSizes[0] !== 0) {
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			QUOTE_PRECISION.mul(new BN(longShortSizes[0])),
+			toQuoteAssetAmount(QUOTE_PRECISION.mul(new BN(longShortSizes[0]))),
 			marketIndex
 		);
 	}
@@ -205,7 +207,7 @@ async function cappedSymFundingScenario(
 	if (longShortSizes[1] !== 0) {
 		await clearingHouse2.openPosition(
 			PositionDirection.SHORT,
-			QUOTE_PRECISION.mul(new BN(longShortSizes[1])),
+			toQuoteAssetAmount(QUOTE_PRECISION.mul(new BN(longShortSizes[1]))),
 			marketIndex
 		);
 	}
@@ -361,7 +363,7 @@ describe('capped funding', () => {
 		await userAccount.subscribe();
 
 		await clearingHouse.depositCollateral(
-			usdcAmount,
+			toCollateralAmount(usdcAmount),
 			userUSDCAccount.publicKey
 		);
 
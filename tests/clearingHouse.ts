@@ -18,6 +18,13 @@ import {
 	QUOTE_PRECISION,
 	MAX_LEVERAGE,
 	convertToNumber,
+	getCollateralVaultPublicKey,
+	getCollateralVaultAuthorityPublicKey,
+	getInsuranceVaultPublicKey,
+	getInsuranceVaultAuthorityPublicKey,
+	toCollateralAmount,
+	toPrice,
+	toQuoteAssetAmount,
 } from '../sdk/src';
 
 import { Markets } from '../sdk/src/constants/markets';
@@ -112,6 +119,24 @@ describe('clearing_house', () => {
 		);
 		assert.ok(state.insuranceVaultNonce == expectedInsuranceAccountNonce);
 
+		const programId = clearingHouse.program.programId;
+		assert.ok(
+			state.collateralVault.equals(await getCollateralVaultPublicKey(programId))
+		);
+		assert.ok(
+			state.collateralVaultAuthority.equals(
+				await getCollateralVaultAuthorityPublicKey(programId)
+			)
+		);
+		assert.ok(
+			state.insuranceVault.equals(await getInsuranceVaultPublicKey(programId))
+		);
+		assert.ok(
+			state.insuranceVaultAuthority.equals(
+				await getInsuranceVaultAuthorityPublicKey(programId)
+			)
+		);
+
 		const marketsAccount = clearingHouse.getMarketsAccount();
 		assert.ok(marketsAccount.markets.length == 64);
 
@@ -157,7 +182,7 @@ describe('clearing_house', () => {
 	it('Initialize user account and deposit collateral atomically', async () => {
 		[, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 
@@ -219,7 +244,7 @@ describe('clearing_house', () => {
 
 	it('Withdraw Collateral', async () => {
 		await clearingHouse.withdrawCollateral(
-			usdcAmount,
+			toCollateralAmount(usdcAmount),
 			userUSDCAccount.publicKey
 		);
 
@@ -263,7 +288,7 @@ describe('clearing_house', () => {
 	it('Long from 0 position', async () => {
 		// Re-Deposit USDC, assuming we have 0 balance here
 		await clearingHouse.depositCollateral(
-			usdcAmount,
+			toCollateralAmount(usdcAmount),
 			userUSDCAccount.publicKey
 		);
 
@@ -271,7 +296,7 @@ describe('clearing_house', () => {
 		const incrementalUSDCNotionalAmount = calculateTradeAmount(usdcAmount);
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			incrementalUSDCNotionalAmount,
+			toQuoteAssetAmount(incrementalUSDCNotionalAmount),
 			marketIndex
 		);
 
@@ -340,7 +365,7 @@ describe('clearing_house', () => {
 		};
 		try {
 			await clearingHouse.withdrawCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 			assert(false, 'Withdrawal succeeded');
@@ -374,9 +399,9 @@ describe('clearing_house', () => {
 
 			await clearingHouse.openPosition(
 				PositionDirection.SHORT,
-				newUSDCNotionalAmount,
+				toQuoteAssetAmount(newUSDCNotionalAmount),
 				marketIndex,
-				limitPriceTooHigh
+				toPrice(limitPriceTooHigh)
 			);
 			assert(false, 'Order succeeded');
 		} catch (e) {
@@ -393,7 +418,7 @@ describe('clearing_house', () => {
 		);
 		await clearingHouse.openPosition(
 			PositionDirection.SHORT,
-			newUSDCNotionalAmount,
+			toQuoteAssetAmount(newUSDCNotionalAmount),
 			new BN(0)
 		);
 
@@ -451,7 +476,7 @@ describe('clearing_house', () => {
 		const newUSDCNotionalAmount = calculateTradeAmount(usdcAmount);
 		await clearingHouse.openPosition(
 			PositionDirection.SHORT,
-			newUSDCNotionalAmount,
+			toQuoteAssetAmount(newUSDCNotionalAmount),
 			new BN(0)
 		);
 
@@ -551,7 +576,7 @@ describe('clearing_house', () => {
 		const incrementalUSDCNotionalAmount = calculateTradeAmount(user.collateral);
 		await clearingHouse.openPosition(
 			PositionDirection.SHORT,
-			incrementalUSDCNotionalAmount,
+			toQuoteAssetAmount(incrementalUSDCNotionalAmount),
 			new BN(0)
 		);
 
@@ -911,7 +936,7 @@ describe('clearing_house', () => {
 		const initialUserUSDCAmount = userUSDCTokenAccount.amount;
 
 		await clearingHouse.depositCollateral(
-			initialUserUSDCAmount,
+			toCollateralAmount(initialUserUSDCAmount),
 			userUSDCAccount.publicKey
 		);
 
@@ -919,7 +944,7 @@ describe('clearing_house', () => {
 		const newUSDCNotionalAmount = calculateTradeAmount(initialUserUSDCAmount);
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			newUSDCNotionalAmount,
+			toQuoteAssetAmount(newUSDCNotionalAmount),
 			new BN(0)
 		);
 
@@ -939,7 +964,7 @@ describe('clearing_house', () => {
 		assert(user.collateral.gt(initialUserUSDCAmount));
 
 		await clearingHouse.withdrawCollateral(
-			user.collateral,
+			toCollateralAmount(user.collateral),
 			userUSDCAccount.publicKey
 		);
 
@@ -974,7 +999,7 @@ describe('clearing_house', () => {
 	it('Trade small size position', async () => {
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			new BN(10000),
+			toQuoteAssetAmount(new BN(10000)),
 			new BN(0)
 		);
 	});
@@ -991,9 +1016,9 @@ describe('clearing_house', () => {
 
 		await clearingHouse.openPosition(
 			PositionDirection.SHORT,
-			newUSDCNotionalAmount,
+			toQuoteAssetAmount(newUSDCNotionalAmount),
 			marketIndex,
-			estTradePrice
+			toPrice(estTradePrice)
 		);
 
 		await clearingHouse.closePosition(marketIndex);
@@ -1011,9 +1036,9 @@ describe('clearing_house', () => {
 
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			newUSDCNotionalAmount,
+			toQuoteAssetAmount(newUSDCNotionalAmount),
 			marketIndex,
-			estTradePrice
+			toPrice(estTradePrice)
 		);
 
 		await clearingHouse.closePosition(marketIndex);
@@ -13,6 +13,12 @@ import {
 	PositionDirection,
 	convertBaseAssetAmountToNumber,
 	convertToNumber,
+	CurveHistoryAccount,
+	findCurveRecordForAmm,
+	getCurveFeeHistory,
+	MarketsAccount,
+	toCollateralAmount,
+	toQuoteAssetAmount,
 } from '../sdk/src';
 
 import { liquidityBook } from './liquidityBook';
@@ -135,6 +141,12 @@ describe('AMM Curve', () => {
 		return totalFeeNum - cumFeeNum;
 	};
 
+	const fetchCurveHistory = async (): Promise<CurveHistoryAccount> => {
+		return (await clearingHouse.program.account.curveHistory.fetch(
+			clearingHouse.getStateAccount().curveHistory
+		)) as CurveHistoryAccount;
+	};
+
 	// const calculateFeeDist = (marketIndex) => {
 	// 	const marketsAccount = clearingHouse.getMarketsAccount();
 	// 	const marketData = marketsAccount.markets[marketIndex.toNumber()];
@@ -183,7 +195,7 @@ describe('AMM Curve', () => {
 
 	it('After Deposit', async () => {
 		await clearingHouse.depositCollateral(
-			usdcAmount,
+			toCollateralAmount(usdcAmount),
 			userUSDCAccount.publicKey
 		);
 
@@ -193,7 +205,7 @@ describe('AMM Curve', () => {
 	it('After Position Taken', async () => {
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			solPositionInitialValue,
+			toQuoteAssetAmount(solPositionInitialValue),
 			marketIndex
 		);
 
@@ -216,7 +228,11 @@ describe('AMM Curve', () => {
 		);
 
 		console.log('arbing', direction, quoteSize.toNumber());
-		await clearingHouse.openPosition(direction, quoteSize, marketIndex);
+		await clearingHouse.openPosition(
+			direction,
+			toQuoteAssetAmount(quoteSize),
+			marketIndex
+		);
 
 		showBook(marketIndex);
 	});
@@ -238,7 +254,7 @@ describe('AMM Curve', () => {
 
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			QUOTE_PRECISION.mul(new BN(10)),
+			toQuoteAssetAmount(QUOTE_PRECISION.mul(new BN(10))),
 			marketIndex
 		);
 		// showBook(marketIndex);
@@ -294,6 +310,21 @@ describe('AMM Curve', () => {
 		// const feeDist2 = calculateFeeDist(marketIndex);
 	});
 
+	it('Find curve record of repeg', async () => {
+		const curveHistory = await fetchCurveHistory();
+		const market = clearingHouse.getMarket(marketIndex);
+
+		const record = findCurveRecordForAmm(curveHistory, marketIndex, market.amm);
+		assert(record !== undefined);
+		assert(
+			record.recordId.eq(
+				curveHistory.depositRecords[curveHistory.head.toNumber() - 1].recordId
+			)
+		);
+		assert(record.pegMultiplierAfter.eq(market.amm.pegMultiplier));
+		assert(!record.pegMultiplierBefore.eq(record.pegMultiplierAfter));
+	});
+
 	it('Repeg Curve SHORT', async () => {
 		const newOraclePrice = 145;
 		const newOraclePriceWithMantissa = new BN(
@@ -304,7 +335,7 @@ describe('AMM Curve', () => {
 
 		await clearingHouse.openPosition(
 			PositionDirection.SHORT,
-			QUOTE_PRECISION.mul(new BN(100000)),
+			toQuoteAssetAmount(QUOTE_PRECISION.mul(new BN(100000))),
 			marketIndex
 		);
 		const marketsAccount1 = clearingHouse.getMarketsAccount();
@@ -356,4 +387,31 @@ describe('AMM Curve', () => {
 
 		await clearingHouse.closePosition(marketIndex);
 	});
+
+	it('Curve fee history', async () => {
+		const curveHistory = await fetchCurveHistory();
+		const feeHistory = getCurveFeeHistory(curveHistory, marketIndex);
+
+		// one record per repeg
+		assert(feeHistory.length === 2);
+		assert(feeHistory[0].recordId.lt(feeHistory[1].recordId));
+		assert(feeHistory[0].totalFee.lte(feeHistory[1].totalFee));
+
+		const lastRecord =
+			curveHistory.depositRecords[curveHistory.head.toNumber() - 1];
+		assert(feeHistory[1].totalFee.eq(lastRecord.totalFee));
+		assert(
+			feeHistory[1].totalFeeMinusDistributions.eq(
+				lastRecord.totalFeeMinusDistributions
+			)
+		);
+		assert(feeHistory[1].adjustmentCost.eq(lastRecord.adjustmentCost));
+
+		// closing after the repeg charged more fees
+		const markets = (await clearingHouse.program.account.markets.fetch(
+			clearingHouse.getStateAccount().markets
+		)) as MarketsAccount;
+		const market = markets.markets[marketIndex.toNumber()];
+		assert(market.amm.totalFee.gt(feeHistory[1].totalFee));
+	});
 });
@@ -0,0 +1,120 @@
+import * as anchor from '@project-serum/anchor';
+import { assert } from 'chai';
+import BN from 'bn.js';
+
+import { Program } from '@project-serum/anchor';
+
+import {
+	Admin,
+	ClearingHouseUser,
+	DeadMansSwitch,
+	MARK_PRICE_PRECISION,
+	PositionDirection,
+	toCollateralAmount,
+	toQuoteAssetAmount,
+} from '../sdk/src';
+
+import { Markets } from '../sdk/src/constants/markets';
+
+import { mockOracle, mockUSDCMint, mockUserUSDCAccount } from './testHelpers';
+
+describe('dead mans switch', () => {
+	const provider = anchor.Provider.local();
+	const connection = provider.connection;
+	anchor.setProvider(provider);
+	const chProgram = anchor.workspace.ClearingHouse as Program;
+
+	let clearingHouse: Admin;
+	let userAccount: ClearingHouseUser;
+
+	let usdcMint;
+	let userUSDCAccount;
+
+	// ammInvariant == k == x * y
+	const mantissaSqrtScale = new BN(Math.sqrt(MARK_PRICE_PRECISION.toNumber()));
+	const ammInitialQuoteAssetReserve = new anchor.BN(5 * 10 ** 13).mul(
+		mantissaSqrtScale
+	);
+	const ammInitialBaseAssetReserve = new anchor.BN(5 * 10 ** 13).mul(
+		mantissaSqrtScale
+	);
+
+	const usdcAmount = new BN(10 * 10 ** 6);
+	const marketIndex = new BN(0);
+
+	before(async () => {
+		usdcMint = await mockUSDCMint(provider);
+		userUSDCAccount = await mockUserUSDCAccount(usdcMint, usdcAmount, provider);
+
+		clearingHouse = Admin.from(
+			connection,
+			provider.wallet,
+			chProgram.programId
+		);
+		await clearingHouse.initialize(usdcMint.publicKey, true);
+		await clearingHouse.subscribe();
+
+		const solUsd = await mockOracle(1);
+		const periodicity = new BN(60 * 60); // 1 HOUR
+
+		await clearingHouse.initializeMarket(
+			Markets[0].marketIndex,
+			solUsd,
+			ammInitialBaseAssetReserve,
+			ammInitialQuoteAssetReserve,
+			periodicity
+		);
+
+		await clearingHouse.initializeUserAccountAndDepositCollateral(
+			toCollateralAmount(usdcAmount),
+			userUSDCAccount.publicKey
+		);
+		userAccount = ClearingHouseUser.from(
+			clearingHouse,
+			provider.wallet.publicKey
+		);
+		await userAccount.subscribe();
+	});
+
+	after(async () => {
+		await clearingHouse.unsubscribe();
+		await userAccount.unsubscribe();
+	});
+
+	it('Sends the presigned close when closing fails without an error listener', async () => {
+		await clearingHouse.openPosition(
+			PositionDirection.LONG,
+			toQuoteAssetAmount(usdcAmount),
+			marketIndex
+		);
+		await userAccount.fetchAccounts();
+
+		const deadMansSwitch = new DeadMansSwitch(clearingHouse, userAccount, {
+			timeoutMs: 0,
+		});
+		await deadMansSwitch.presign();
+
+		const closedMarketIndexes: BN[] = [];
+		deadMansSwitch.eventEmitter.on('positionClosed', (marketIndex) =>
+			closedMarketIndexes.push(marketIndex)
+		);
+
+		const closePosition = clearingHouse.closePosition;
+		clearingHouse.closePosition = async () => {
+			throw new Error('closePosition failed');
+		};
+		try {
+			await deadMansSwitch.check();
+		} finally {
+			clearingHouse.closePosition = closePosition;
+		}
+
+		assert(deadMansSwitch.triggered);
+		assert(closedMarketIndexes.length === 1);
+		assert(closedMarketIndexes[0].eq(marketIndex));
+
+		await userAccount.fetchAccounts();
+		const position = userAccount.getUserPosition(marketIndex);
+		assert(position === undefined || position.baseAssetAmount.eq(new BN(0)));
+	});
+});
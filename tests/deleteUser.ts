@@ -6,7 +6,7 @@ import { Program } from '@project-serum/anchor';
 
 import { PublicKey } from '@solana/web3.js';
 
-import { Admin, MARK_PRICE_PRECISION } from '../sdk/src';
+import { Admin, MARK_PRICE_PRECISION, toCollateralAmount } from '../sdk/src';
 
 import { Markets } from '../sdk/src/constants/markets';
 
@@ -61,7 +61,7 @@ describe('delete user', () => {
 
 		[, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 	});
@@ -93,7 +93,7 @@ describe('delete user', () => {
 		assert(userPositionsAccountInfo.lamports !== 0);
 
 		await clearingHouse.withdrawCollateral(
-			usdcAmount,
+			toCollateralAmount(usdcAmount),
 			userUSDCAccount.publicKey
 		);
 		await clearingHouse.deleteUser();
@@ -11,6 +11,11 @@ import {
 	MARK_PRICE_PRECISION,
 	ClearingHouse,
 	PositionDirection,
+	calculateFee,
+	TradeHistoryAccount,
+	toCollateralAmount,
+	toPrice,
+	toQuoteAssetAmount,
 } from '../sdk/src';
 
 import { Markets } from '../sdk/src/constants/markets';
@@ -74,7 +79,7 @@ describe('fees', () => {
 
 		[, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 
@@ -110,7 +115,7 @@ describe('fees', () => {
 
 		[, referrerUserAccountPublicKey] =
 			await referrerClearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				referrerUSDCAccount.publicKey
 			);
 	});
@@ -119,16 +124,42 @@ describe('fees', () => {
 		await clearingHouse.unsubscribe();
 	});
 
+	// checks the sdk's fee math against the fee the program recorded for the last trade
+	const assertFeeMatchesTradeRecord = async (hasReferrer: boolean) => {
+		const tradeHistory =
+			(await clearingHouse.program.account.tradeHistory.fetch(
+				clearingHouse.getStateAccount().tradeHistory
+			)) as TradeHistoryAccount;
+		const tradeRecord =
+			tradeHistory.tradeRecords[tradeHistory.head.toNumber() - 1];
+		const discountTokenBalance = (
+			await discountMint.getAccountInfo(discountTokenAccount.address)
+		).amount;
+
+		const fee = calculateFee(
+			tradeRecord.quoteAssetAmount,
+			clearingHouse.getStateAccount().feeStructure,
+			discountTokenBalance,
+			hasReferrer
+		);
+		assert(fee.userFee.eq(tradeRecord.fee));
+		assert(fee.tokenDiscount.eq(tradeRecord.tokenDiscount));
+		assert(fee.refereeDiscount.eq(tradeRecord.refereeDiscount));
+		assert(fee.referrerReward.eq(tradeRecord.referrerReward));
+	};
+
 	it('Trade no rebate', async () => {
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0),
+			toPrice(new BN(0)),
 			discountTokenAccount.address
 		);
 
+		await assertFeeMatchesTradeRecord(false);
+
 		const user: any = await clearingHouse.program.account.user.fetch(
 			userAccountPublicKey
 		);
@@ -151,13 +182,15 @@ describe('fees', () => {
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0),
+			toPrice(new BN(0)),
 			discountTokenAccount.address,
 			referrerUserAccountPublicKey
 		);
 
+		await assertFeeMatchesTradeRecord(true);
+
 		const user: any = await clearingHouse.program.account.user.fetch(
 			userAccountPublicKey
 		);
@@ -186,13 +219,15 @@ describe('fees', () => {
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0),
+			toPrice(new BN(0)),
 			discountTokenAccount.address,
 			referrerUserAccountPublicKey
 		);
 
+		await assertFeeMatchesTradeRecord(true);
+
 		const user: any = await clearingHouse.program.account.user.fetch(
 			userAccountPublicKey
 		);
@@ -221,13 +256,15 @@ describe('fees', () => {
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0),
+			toPrice(new BN(0)),
 			discountTokenAccount.address,
 			referrerUserAccountPublicKey
 		);
 
+		await assertFeeMatchesTradeRecord(true);
+
 		const user: any = await clearingHouse.program.account.user.fetch(
 			userAccountPublicKey
 		);
@@ -256,13 +293,15 @@ describe('fees', () => {
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			usdcAmount.mul(new BN(9)).div(new BN(10)),
+			toQuoteAssetAmount(usdcAmount.mul(new BN(9)).div(new BN(10))),
 			marketIndex,
-			new BN(0),
+			toPrice(new BN(0)),
 			discountTokenAccount.address,
 			referrerUserAccountPublicKey
 		);
 
+		await assertFeeMatchesTradeRecord(true);
+
 		const user: any = await clearingHouse.program.account.user.fetch(
 			userAccountPublicKey
 		);
@@ -287,6 +326,8 @@ describe('fees', () => {
 			referrerUserAccountPublicKey
 		);
 
+		await assertFeeMatchesTradeRecord(true);
+
 		const user: any = await clearingHouse.program.account.user.fetch(
 			userAccountPublicKey
 		);
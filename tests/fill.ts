@@ -0,0 +1,201 @@
+import * as anchor from '@project-serum/anchor';
+import { assert } from 'chai';
+import BN from 'bn.js';
+
+import { Program } from '@project-serum/anchor';
+
+import { PublicKey } from '@solana/web3.js';
+
+import {
+	Admin,
+	MARK_PRICE_PRECISION,
+	PositionDirection,
+	fillClosePosition,
+	fillOpenPosition,
+	Market,
+	MarketsAccount,
+	UserAccount,
+	UserPosition,
+	UserPositionsAccount,
+	toCollateralAmount,
+	toQuoteAssetAmount,
+} from '../sdk/src';
+
+import { Markets } from '../sdk/src/constants/markets';
+
+import { mockOracle, mockUSDCMint, mockUserUSDCAccount } from './testHelpers';
+
+describe('fill', () => {
+	const provider = anchor.Provider.local();
+	const connection = provider.connection;
+	anchor.setProvider(provider);
+	const chProgram = anchor.workspace.ClearingHouse as Program;
+
+	let clearingHouse: Admin;
+
+	let userAccountPublicKey: PublicKey;
+
+	let usdcMint;
+	let userUSDCAccount;
+
+	// ammInvariant == k == x * y
+	const mantissaSqrtScale = new BN(Math.sqrt(MARK_PRICE_PRECISION.toNumber()));
+	const ammInitialQuoteAssetReserve = new anchor.BN(5 * 10 ** 13).mul(
+		mantissaSqrtScale
+	);
+	const ammInitialBaseAssetReserve = new anchor.BN(5 * 10 ** 13).mul(
+		mantissaSqrtScale
+	);
+
+	const usdcAmount = new BN(10 * 10 ** 6);
+	const marketIndex = new BN(0);
+
+	const fetchAccounts = async (): Promise<
+		[Market, UserAccount, UserPosition]
+	> => {
+		const markets = (await clearingHouse.program.account.markets.fetch(
+			clearingHouse.getStateAccount().markets
+		)) as MarketsAccount;
+		const user = (await clearingHouse.program.account.user.fetch(
+			userAccountPublicKey
+		)) as UserAccount;
+		const userPositions =
+			(await clearingHouse.program.account.userPositions.fetch(
+				user.positions
+			)) as UserPositionsAccount;
+		return [
+			markets.markets[marketIndex.toNumber()],
+			user,
+			userPositions.positions[0],
+		];
+	};
+
+	const assertFieldsEqual = (expected, actual, fields: string[]) => {
+		for (const field of fields) {
+			const [expectedValue, actualValue] = [expected[field], actual[field]];
+			assert(
+				expectedValue.eq(actualValue),
+				`${field}: expected ${expectedValue.toString()}, got ${actualValue}`
+			);
+		}
+	};
+
+	// applies the sdk's fill to the accounts before a trade and compares them with the accounts after it
+	const assertFillMatchesChain = async (
+		trade: () => Promise<unknown>,
+		fill: (market: Market, user: UserAccount, position: UserPosition) => void
+	) => {
+		const [market, user, position] = await fetchAccounts();
+		await trade();
+		fill(market, user, position);
+
+		const [marketAfter, userAfter, positionAfter] = await fetchAccounts();
+		assertFieldsEqual(market.amm, marketAfter.amm, [
+			'baseAssetReserve',
+			'quoteAssetReserve',
+			'totalFee',
+			'totalFeeMinusDistributions',
+		]);
+		assertFieldsEqual(market, marketAfter, [
+			'baseAssetAmount',
+			'baseAssetAmountLong',
+			'baseAssetAmountShort',
+			'openInterest',
+		]);
+		assertFieldsEqual(user, userAfter, ['collateral', 'totalFeePaid']);
+		assertFieldsEqual(position, positionAfter, [
+			'baseAssetAmount',
+			'quoteAssetAmount',
+			'lastCumulativeFundingRate',
+		]);
+	};
+
+	const assertOpenPositionFill = async (
+		direction: PositionDirection,
+		amount: BN
+	) => {
+		const feeStructure = clearingHouse.getStateAccount().feeStructure;
+		await assertFillMatchesChain(
+			() =>
+				clearingHouse.openPosition(
+					direction,
+					toQuoteAssetAmount(amount),
+					marketIndex
+				),
+			(market, user, position) =>
+				fillOpenPosition(
+					market,
+					user,
+					position,
+					direction,
+					toQuoteAssetAmount(amount),
+					feeStructure
+				)
+		);
+	};
+
+	before(async () => {
+		usdcMint = await mockUSDCMint(provider);
+		userUSDCAccount = await mockUserUSDCAccount(usdcMint, usdcAmount, provider);
+
+		clearingHouse = Admin.from(
+			connection,
+			provider.wallet,
+			chProgram.programId
+		);
+		await clearingHouse.initialize(usdcMint.publicKey, true);
+		await clearingHouse.subscribe();
+
+		const solUsd = await mockOracle(1);
+		const periodicity = new BN(60 * 60); // 1 HOUR
+
+		await clearingHouse.initializeMarket(
+			Markets[0].marketIndex,
+			solUsd,
+			ammInitialBaseAssetReserve,
+			ammInitialQuoteAssetReserve,
+			periodicity
+		);
+
+		[, userAccountPublicKey] =
+			await clearingHouse.initializeUserAccountAndDepositCollateral(
+				toCollateralAmount(usdcAmount),
+				userUSDCAccount.publicKey
+			);
+	});
+
+	after(async () => {
+		await clearingHouse.unsubscribe();
+	});
+
+	it('Open position', async () => {
+		await assertOpenPositionFill(PositionDirection.LONG, usdcAmount);
+	});
+
+	it('Increase position', async () => {
+		await assertOpenPositionFill(PositionDirection.LONG, usdcAmount);
+	});
+
+	it('Reduce position', async () => {
+		await assertOpenPositionFill(
+			PositionDirection.SHORT,
+			usdcAmount.div(new BN(2))
+		);
+	});
+
+	it('Flip position', async () => {
+		await assertOpenPositionFill(
+			PositionDirection.SHORT,
+			usdcAmount.mul(new BN(3))
+		);
+	});
+
+	it('Close position', async () => {
+		const feeStructure = clearingHouse.getStateAccount().feeStructure;
+		await assertFillMatchesChain(
+			() => clearingHouse.closePosition(marketIndex),
+			(market, user, position) =>
+				fillClosePosition(market, user, position, feeStructure)
+		);
+	});
+});
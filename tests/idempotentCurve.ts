@@ -8,10 +8,14 @@ import { Keypair } from '@solana/web3.js';
 
 import {
 	Admin,
+	airdropSol,
 	MARK_PRICE_PRECISION,
 	calculateBaseAssetValue,
 	ClearingHouse,
 	PositionDirection,
+	toCollateralAmount,
+	toPrice,
+	toQuoteAssetAmount,
 } from '../sdk/src';
 
 import { Markets } from '../sdk/src/constants/markets';
@@ -111,7 +115,7 @@ describe('idempotent curve', () => {
 
 	const shrinkProfitableLong = async (chunks: number) => {
 		const userKeypair = new Keypair();
-		await provider.connection.requestAirdrop(userKeypair.publicKey, 10 ** 9);
+		await airdropSol(provider.connection, userKeypair.publicKey, 10 ** 9);
 		const userUSDCAccount = await mockUserUSDCAccount(
 			usdcMint,
 			usdcAmount,
@@ -127,16 +131,16 @@ describe('idempotent curve', () => {
 
 		const [, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0)
+			toPrice(new BN(0))
 		);
 
 		await primaryClearingHouse.moveAmmPrice(
@@ -160,9 +164,9 @@ describe('idempotent curve', () => {
 		for (let i = 0; i < numberOfReduces - 1; i++) {
 			await clearingHouse.openPosition(
 				PositionDirection.SHORT,
-				baseAssetValue.div(new BN(numberOfReduces)),
+				toQuoteAssetAmount(baseAssetValue.div(new BN(numberOfReduces))),
 				marketIndex,
-				new BN(0)
+				toPrice(new BN(0))
 			);
 		}
 		await clearingHouse.closePosition(new BN(0));
@@ -178,7 +182,7 @@ describe('idempotent curve', () => {
 
 	const shrinkUnprofitableLong = async (chunks: number) => {
 		const userKeypair = new Keypair();
-		await provider.connection.requestAirdrop(userKeypair.publicKey, 10 ** 9);
+		await airdropSol(provider.connection, userKeypair.publicKey, 10 ** 9);
 		const userUSDCAccount = await mockUserUSDCAccount(
 			usdcMint,
 			usdcAmount,
@@ -194,16 +198,16 @@ describe('idempotent curve', () => {
 
 		const [, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0)
+			toPrice(new BN(0))
 		);
 
 		await primaryClearingHouse.moveAmmPrice(
@@ -227,9 +231,9 @@ describe('idempotent curve', () => {
 		for (let i = 0; i < numberOfReduces - 1; i++) {
 			await clearingHouse.openPosition(
 				PositionDirection.SHORT,
-				baseAssetValue.div(new BN(numberOfReduces)),
+				toQuoteAssetAmount(baseAssetValue.div(new BN(numberOfReduces))),
 				marketIndex,
-				new BN(0)
+				toPrice(new BN(0))
 			);
 		}
 		await clearingHouse.closePosition(new BN(0));
@@ -245,7 +249,7 @@ describe('idempotent curve', () => {
 
 	const shrinkProfitableShort = async (chunks: number) => {
 		const userKeypair = new Keypair();
-		await provider.connection.requestAirdrop(userKeypair.publicKey, 10 ** 9);
+		await airdropSol(provider.connection, userKeypair.publicKey, 10 ** 9);
 		const userUSDCAccount = await mockUserUSDCAccount(
 			usdcMint,
 			usdcAmount,
@@ -261,16 +265,16 @@ describe('idempotent curve', () => {
 
 		const [, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.SHORT,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0)
+			toPrice(new BN(0))
 		);
 
 		await primaryClearingHouse.moveAmmPrice(
@@ -294,9 +298,9 @@ describe('idempotent curve', () => {
 		for (let i = 0; i < numberOfReduces - 1; i++) {
 			await clearingHouse.openPosition(
 				PositionDirection.LONG,
-				baseAssetValue.div(new BN(numberOfReduces)),
+				toQuoteAssetAmount(baseAssetValue.div(new BN(numberOfReduces))),
 				marketIndex,
-				new BN(0)
+				toPrice(new BN(0))
 			);
 		}
 		await clearingHouse.closePosition(new BN(0));
@@ -311,7 +315,7 @@ describe('idempotent curve', () => {
 
 	const shrinkUnrofitableShort = async (chunks: number) => {
 		const userKeypair = new Keypair();
-		await provider.connection.requestAirdrop(userKeypair.publicKey, 10 ** 9);
+		await airdropSol(provider.connection, userKeypair.publicKey, 10 ** 9);
 		const userUSDCAccount = await mockUserUSDCAccount(
 			usdcMint,
 			usdcAmount,
@@ -327,16 +331,16 @@ describe('idempotent curve', () => {
 
 		const [, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.SHORT,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0)
+			toPrice(new BN(0))
 		);
 
 		await primaryClearingHouse.moveAmmPrice(
@@ -360,9 +364,9 @@ describe('idempotent curve', () => {
 		for (let i = 0; i < numberOfReduces - 1; i++) {
 			await clearingHouse.openPosition(
 				PositionDirection.LONG,
-				baseAssetValue.div(new BN(numberOfReduces)),
+				toQuoteAssetAmount(baseAssetValue.div(new BN(numberOfReduces))),
 				marketIndex,
-				new BN(0)
+				toPrice(new BN(0))
 			);
 		}
 		await clearingHouse.closePosition(new BN(0));
@@ -13,6 +13,8 @@ import {
 	MAX_LEVERAGE,
 	QUOTE_PRECISION,
 	convertToNumber,
+	toCollateralAmount,
+	toQuoteAssetAmount,
 } from '../sdk/src';
 
 import { liquidityBook } from './liquidityBook';
@@ -262,7 +264,7 @@ describe('AMM Curve', () => {
 
 	it('After Deposit', async () => {
 		await clearingHouse.depositCollateral(
-			usdcAmount,
+			toCollateralAmount(usdcAmount),
 			userUSDCAccount.publicKey
 		);
 	});
@@ -270,7 +272,7 @@ describe('AMM Curve', () => {
 	it('After Position Taken', async () => {
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			solPositionInitialValue,
+			toQuoteAssetAmount(solPositionInitialValue),
 			marketIndex
 		);
 
@@ -0,0 +1,103 @@
+import * as anchor from '@project-serum/anchor';
+import { Program } from '@project-serum/anchor';
+import { Keypair } from '@solana/web3.js';
+import BN from 'bn.js';
+import { assert } from 'chai';
+import { Admin, PEG_PRECISION } from '../sdk/src';
+import { loadTest, printLoadTestReport } from '../stress/loadTest';
+import { createPriceFeed, mockUSDCMint } from './testHelpers';
+
+// not part of run-anchor-tests.sh, run with ANCHOR_TEST_FILE=loadTest.ts anchor test
+describe('load test', () => {
+	const provider = anchor.Provider.local();
+	const connection = provider.connection;
+	anchor.setProvider(provider);
+	const chProgram = anchor.workspace.ClearingHouse as Program;
+
+	const clearingHouse = Admin.from(
+		connection,
+		provider.wallet,
+		chProgram.programId
+	);
+
+	const ammInitialQuoteAssetAmount = new anchor.BN(5 * 10 ** 13).mul(
+		new BN(10 ** 5)
+	);
+	const ammInitialBaseAssetAmount = new anchor.BN(5 * 10 ** 13).mul(
+		new BN(10 ** 5)
+	);
+
+	let usdcMint: Keypair;
+
+	before(async () => {
+		usdcMint = await mockUSDCMint(provider);
+
+		await clearingHouse.initialize(usdcMint.publicKey, true);
+		await clearingHouse.subscribe();
+
+		const solUsd = await createPriceFeed({
+			oracleProgram: anchor.workspace.Pyth,
+			initPrice: 1,
+		});
+		const periodicity = new BN(60 * 60); // 1 HOUR
+
+		await clearingHouse.initializeMarket(
+			new BN(0),
+			solUsd,
+			ammInitialBaseAssetAmount,
+			ammInitialQuoteAssetAmount,
+			periodicity,
+			PEG_PRECISION
+		);
+	});
+
+	after(async () => {
+		await clearingHouse.unsubscribe();
+	});
+
+	it('open/close traffic', async () => {
+		const report = await loadTest(usdcMint, provider, {
+			numUsers: Number(process.env.LOAD_TEST_USERS ?? 10),
+			tps: Number(process.env.LOAD_TEST_TPS ?? 5),
+			durationSec: Number(process.env.LOAD_TEST_DURATION_SEC ?? 60),
+		});
+		printLoadTestReport(report);
+
+		assert(report.sent > 0);
+	});
+
+	it('trade path with and without cached addresses', async () => {
+		const config = {
+			numUsers: 5,
+			tps: Number(process.env.LOAD_TEST_TPS ?? 5),
+			durationSec: Number(process.env.LOAD_TEST_DURATION_SEC ?? 30),
+		};
+		const uncached = await loadTest(usdcMint, provider, {
+			...config,
+			uncachedPositionsAddress: true,
+		});
+		const cached = await loadTest(usdcMint, provider, config);
+		const refreshed = await loadTest(usdcMint, provider, {
+			...config,
+			refreshAfterTx: true,
+		});
+
+		for (const [name, report] of [
+			['uncached positions address', uncached],
+			['cached positions address', cached],
+			['cached, refresh after tx', refreshed],
+		] as const) {
+			console.log(name);
+			printLoadTestReport(report);
+		}
+		console.log(
+			`p50 latency saved by caching: ${
+				uncached.latencyMs.p50 - cached.latencyMs.p50
+			}ms, rpc requests saved per tx: ${(
+				uncached.rpcRequestsPerTx - cached.rpcRequestsPerTx
+			).toFixed(2)}`
+		);
+
+		assert(cached.rpcRequestsPerTx < uncached.rpcRequestsPerTx);
+	});
+});
@@ -2,7 +2,7 @@ import * as anchor from '@project-serum/anchor';
 import { Program } from '@project-serum/anchor';
 import BN from 'bn.js';
 import { assert } from 'chai';
-import { Admin, MARK_PRICE_PRECISION } from '../sdk/src';
+import { Admin, MARK_PRICE_PRECISION, toCollateralAmount } from '../sdk/src';
 import { Markets } from '../sdk/src/constants/markets';
 import { mockOracle, mockUSDCMint, mockUserUSDCAccount } from './testHelpers';
 
@@ -60,7 +60,7 @@ describe('max deposit', () => {
 
 	it('successful deposit', async () => {
 		await clearingHouse.initializeUserAccountAndDepositCollateral(
-			usdcAmount.div(new BN(2)),
+			toCollateralAmount(usdcAmount.div(new BN(2))),
 			userUSDCAccount.publicKey
 		);
 	});
@@ -68,7 +68,7 @@ describe('max deposit', () => {
 	it('blocked deposit', async () => {
 		try {
 			await clearingHouse.depositCollateral(
-				usdcAmount.div(new BN(2)),
+				toCollateralAmount(usdcAmount.div(new BN(2))),
 				userUSDCAccount.publicKey
 			);
 		} catch (e) {
@@ -10,6 +10,9 @@ import {
 	findComputeUnitConsumption,
 	MARK_PRICE_PRECISION,
 	PositionDirection,
+	toCollateralAmount,
+	toPrice,
+	toQuoteAssetAmount,
 } from '../sdk/src';
 
 import {
@@ -75,7 +78,7 @@ describe('max positions', () => {
 
 		[, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 	});
@@ -93,9 +96,9 @@ describe('max positions', () => {
 		for (let i = 0; i < maxPositions; i++) {
 			await clearingHouse.openPosition(
 				PositionDirection.LONG,
-				usdcPerPosition,
+				toQuoteAssetAmount(usdcPerPosition),
 				new BN(i),
-				new BN(0)
+				toPrice(new BN(0))
 			);
 		}
 	});
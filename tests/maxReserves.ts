@@ -11,6 +11,9 @@ import {
 	MARK_PRICE_PRECISION,
 	PositionDirection,
 	QUOTE_PRECISION,
+	toCollateralAmount,
+	toPrice,
+	toQuoteAssetAmount,
 } from '../sdk/src';
 
 import {
@@ -80,7 +83,7 @@ describe('max reserves', () => {
 
 		[, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 	});
@@ -98,9 +101,9 @@ describe('max reserves', () => {
 		for (let i = 0; i < maxPositions; i++) {
 			await clearingHouse.openPosition(
 				PositionDirection.LONG,
-				usdcPerPosition,
+				toQuoteAssetAmount(usdcPerPosition),
 				new BN(i),
-				new BN(0)
+				toPrice(new BN(0))
 			);
 		}
 	});
@@ -8,9 +8,13 @@ import { Keypair } from '@solana/web3.js';
 
 import {
 	Admin,
+	airdropSol,
 	ClearingHouse,
 	MAX_LEVERAGE,
 	PositionDirection,
+	toCollateralAmount,
+	toPrice,
+	toQuoteAssetAmount,
 } from '../sdk/src';
 
 import { Markets } from '../sdk/src/constants/markets';
@@ -124,7 +128,7 @@ describe('minimum trade size', () => {
 
 	it('short round', async () => {
 		const keypair = new Keypair();
-		await provider.connection.requestAirdrop(keypair.publicKey, 10 ** 9);
+		await airdropSol(provider.connection, keypair.publicKey, 10 ** 9);
 		const wallet = new Wallet(keypair);
 		const userUSDCAccount = await mockUserUSDCAccount(
 			usdcMint,
@@ -140,16 +144,16 @@ describe('minimum trade size', () => {
 		await clearingHouse.subscribe();
 		const [, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.SHORT,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0)
+			toPrice(new BN(0))
 		);
 
 		let user: UserAccount =
@@ -184,9 +188,9 @@ describe('minimum trade size', () => {
 
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0)
+			toPrice(new BN(0))
 		);
 
 		user = await primaryClearingHouse.program.account.user.fetch(
@@ -205,7 +209,7 @@ describe('minimum trade size', () => {
 
 	it('short dont round', async () => {
 		const keypair = new Keypair();
-		await provider.connection.requestAirdrop(keypair.publicKey, 10 ** 9);
+		await airdropSol(provider.connection, keypair.publicKey, 10 ** 9);
 		const wallet = new Wallet(keypair);
 		const userUSDCAccount = await mockUserUSDCAccount(
 			usdcMint,
@@ -221,16 +225,16 @@ describe('minimum trade size', () => {
 		await clearingHouse.subscribe();
 		const [, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.SHORT,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0)
+			toPrice(new BN(0))
 		);
 
 		let user: UserAccount =
@@ -261,9 +265,9 @@ describe('minimum trade size', () => {
 
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0)
+			toPrice(new BN(0))
 		);
 
 		user = await primaryClearingHouse.program.account.user.fetch(
@@ -282,7 +286,7 @@ describe('minimum trade size', () => {
 
 	it('long round', async () => {
 		const keypair = new Keypair();
-		await provider.connection.requestAirdrop(keypair.publicKey, 10 ** 9);
+		await airdropSol(provider.connection, keypair.publicKey, 10 ** 9);
 		const wallet = new Wallet(keypair);
 		const userUSDCAccount = await mockUserUSDCAccount(
 			usdcMint,
@@ -298,16 +302,16 @@ describe('minimum trade size', () => {
 		await clearingHouse.subscribe();
 		const [, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0)
+			toPrice(new BN(0))
 		);
 
 		let user: UserAccount =
@@ -342,9 +346,9 @@ describe('minimum trade size', () => {
 
 		await clearingHouse.openPosition(
 			PositionDirection.SHORT,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0)
+			toPrice(new BN(0))
 		);
 
 		user = await primaryClearingHouse.program.account.user.fetch(
@@ -363,7 +367,7 @@ describe('minimum trade size', () => {
 
 	it('short dont round', async () => {
 		const keypair = new Keypair();
-		await provider.connection.requestAirdrop(keypair.publicKey, 10 ** 9);
+		await airdropSol(provider.connection, keypair.publicKey, 10 ** 9);
 		const wallet = new Wallet(keypair);
 		const userUSDCAccount = await mockUserUSDCAccount(
 			usdcMint,
@@ -379,16 +383,16 @@ describe('minimum trade size', () => {
 		await clearingHouse.subscribe();
 		const [, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0)
+			toPrice(new BN(0))
 		);
 
 		let user: UserAccount =
@@ -418,9 +422,9 @@ describe('minimum trade size', () => {
 
 		await clearingHouse.openPosition(
 			PositionDirection.SHORT,
-			usdcAmount,
+			toQuoteAssetAmount(usdcAmount),
 			marketIndex,
-			new BN(0)
+			toPrice(new BN(0))
 		);
 
 		user = await primaryClearingHouse.program.account.user.fetch(
@@ -6,7 +6,13 @@ import { Program } from '@project-serum/anchor';
 
 import { PublicKey } from '@solana/web3.js';
 
-import { Admin, MARK_PRICE_PRECISION, PositionDirection } from '../sdk/src';
+import {
+	Admin,
+	MARK_PRICE_PRECISION,
+	PositionDirection,
+	toCollateralAmount,
+	toQuoteAssetAmount,
+} from '../sdk/src';
 
 import { Markets } from '../sdk/src/constants/markets';
 
@@ -61,7 +67,7 @@ describe('admin withdraw', () => {
 
 		[, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 
@@ -69,7 +75,7 @@ describe('admin withdraw', () => {
 		const incrementalUSDCNotionalAmount = usdcAmount.mul(new BN(5));
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			incrementalUSDCNotionalAmount,
+			toQuoteAssetAmount(incrementalUSDCNotionalAmount),
 			marketIndex
 		);
 	});
@@ -88,7 +94,7 @@ describe('admin withdraw', () => {
 		try {
 			await clearingHouse.openPosition(
 				PositionDirection.LONG,
-				usdcAmount,
+				toQuoteAssetAmount(usdcAmount),
 				new BN(0)
 			);
 		} catch (e) {
@@ -121,7 +127,7 @@ describe('admin withdraw', () => {
 	it('Block withdrawal', async () => {
 		try {
 			await clearingHouse.withdrawCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 		} catch (e) {
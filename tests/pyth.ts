@@ -18,6 +18,8 @@ import {
 	QUOTE_PRECISION,
 	calculateTargetPriceTrade,
 	convertToNumber,
+	toCollateralAmount,
+	toQuoteAssetAmount,
 } from '../sdk';
 
 import { Program } from '@project-serum/anchor';
@@ -30,6 +32,10 @@ import {
 	FUNDING_PAYMENT_PRECISION,
 	ClearingHouse,
 	ClearingHouseUser,
+	estimateNextFundingRate,
+	FundingRateHistoryAccount,
+	MarketsAccount,
+	toPrice,
 } from '../sdk/src';
 
 import { initUserAccounts } from '../stress/stressUtils';
@@ -77,11 +83,35 @@ async function updateFundingRateHelper(
 		const cumulativeFundingRateShortOld =
 			ammAccountState0.cumulativeFundingRateShort;
 
+		const marketBefore = (
+			(await clearingHouse.program.account.markets.fetch(
+				clearingHouse.getStateAccount().markets
+			)) as MarketsAccount
+		).markets[marketIndex.toNumber()];
+
 		const _tx = await clearingHouse.updateFundingRate(
 			priceFeedAddress,
 			marketIndex
 		);
 
+		const fundingRateHistory =
+			(await clearingHouse.program.account.fundingRateHistory.fetch(
+				clearingHouse.getStateAccount().fundingRateHistory
+			)) as FundingRateHistoryAccount;
+		const fundingRateRecord =
+			fundingRateHistory.fundingRateRecords[
+				fundingRateHistory.head.toNumber() - 1
+			];
+		const estimatedFundingRate = estimateNextFundingRate(
+			marketBefore,
+			toPrice(newprice),
+			fundingRateRecord.ts
+		);
+		assert(
+			estimatedFundingRate.eq(fundingRateRecord.fundingRate),
+			`estimated funding rate ${estimatedFundingRate.toString()}, recorded ${fundingRateRecord.fundingRate.toString()}`
+		);
+
 		const CONVERSION_SCALE =
 			FUNDING_PAYMENT_PRECISION.mul(MARK_PRICE_PRECISION);
 
@@ -201,7 +231,7 @@ describe('pyth-oracle', () => {
 		await userAccount.subscribe();
 
 		await clearingHouse.depositCollateral(
-			usdcAmount,
+			toCollateralAmount(usdcAmount),
 			userUSDCAccount.publicKey
 		);
 
@@ -306,13 +336,13 @@ describe('pyth-oracle', () => {
 
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			QUOTE_PRECISION,
+			toQuoteAssetAmount(QUOTE_PRECISION),
 			marketIndex
 		);
 
 		await clearingHouse2.openPosition(
 			PositionDirection.SHORT,
-			QUOTE_PRECISION.div(new BN(2)),
+			toQuoteAssetAmount(QUOTE_PRECISION.div(new BN(2))),
 			marketIndex
 		);
 
@@ -391,7 +421,7 @@ describe('pyth-oracle', () => {
 		try {
 			await clearingHouse.openPosition(
 				PositionDirection.LONG,
-				tradeSize,
+				toQuoteAssetAmount(tradeSize),
 				marketIndex
 			);
 			assert(false, 'Order succeeded');
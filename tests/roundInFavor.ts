@@ -8,9 +8,13 @@ import { Keypair } from '@solana/web3.js';
 
 import {
 	Admin,
+	airdropSol,
 	ClearingHouse,
 	MAX_LEVERAGE,
 	PositionDirection,
+	toCollateralAmount,
+	toPrice,
+	toQuoteAssetAmount,
 } from '../sdk/src';
 
 import { Markets } from '../sdk/src/constants/markets';
@@ -109,7 +113,7 @@ describe('round in favor', () => {
 
 	it('short', async () => {
 		const keypair = new Keypair();
-		await provider.connection.requestAirdrop(keypair.publicKey, 10 ** 9);
+		await airdropSol(provider.connection, keypair.publicKey, 10 ** 9);
 		const wallet = new Wallet(keypair);
 		const userUSDCAccount = await mockUserUSDCAccount(
 			usdcMint,
@@ -125,16 +129,16 @@ describe('round in favor', () => {
 		await clearingHouse.subscribe();
 		const [, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.SHORT,
-			calculateTradeAmount(usdcAmount),
+			toQuoteAssetAmount(calculateTradeAmount(usdcAmount)),
 			marketIndex,
-			new BN(0)
+			toPrice(new BN(0))
 		);
 
 		let user: any = await primaryClearingHouse.program.account.user.fetch(
@@ -153,7 +157,7 @@ describe('round in favor', () => {
 
 	it('long', async () => {
 		const keypair = new Keypair();
-		await provider.connection.requestAirdrop(keypair.publicKey, 10 ** 9);
+		await airdropSol(provider.connection, keypair.publicKey, 10 ** 9);
 		const wallet = new Wallet(keypair);
 		const userUSDCAccount = await mockUserUSDCAccount(
 			usdcMint,
@@ -170,16 +174,16 @@ describe('round in favor', () => {
 
 		const [, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 
 		const marketIndex = new BN(0);
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			calculateTradeAmount(usdcAmount),
+			toQuoteAssetAmount(calculateTradeAmount(usdcAmount)),
 			marketIndex,
-			new BN(0)
+			toPrice(new BN(0))
 		);
 
 		let user: any = await primaryClearingHouse.program.account.user.fetch(
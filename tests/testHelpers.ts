@@ -17,7 +17,11 @@ import {
 import { assert } from 'chai';
 import buffer from 'buffer';
 import BN from 'bn.js';
-import { ClearingHouse, ClearingHouseUser } from '../sdk/src';
+import {
+	ClearingHouse,
+	ClearingHouseUser,
+	toCollateralAmount,
+} from '../sdk/src';
 
 export async function mockOracle(
 	price: number = 50 * 10e7,
@@ -208,7 +212,7 @@ export async function initUserAccounts(
 		[, userAccountPublicKey] =
 			await clearingHouse1.initializeUserAccountAndDepositCollateral(
 				// marketPublicKey,
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				newUserAcct.publicKey
 			);
 
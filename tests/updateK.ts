@@ -12,6 +12,9 @@ import {
 	PEG_PRECISION,
 	PositionDirection,
 	convertToNumber,
+	CurveEvent,
+	toCollateralAmount,
+	toQuoteAssetAmount,
 } from '../sdk/src';
 
 import { Markets } from '../sdk/src/constants/markets';
@@ -20,6 +23,7 @@ import {
 	createPriceFeed,
 	mockUSDCMint,
 	mockUserUSDCAccount,
+	setFeedPrice,
 } from './testHelpers';
 import { QUOTE_PRECISION } from '../sdk/lib';
 
@@ -59,7 +63,7 @@ describe('update k', () => {
 			chProgram.programId
 		);
 		await clearingHouse.initialize(usdcMint.publicKey, true);
-		await clearingHouse.subscribe();
+		await clearingHouse.subscribe(['curveHistoryAccount']);
 
 		const periodicity = new BN(60 * 60); // 1 HOUR
 
@@ -127,7 +131,7 @@ describe('update k', () => {
 
 	it('increase k base/quote imbalance (FREE)', async () => {
 		await clearingHouse.depositCollateral(
-			usdcAmount,
+			toCollateralAmount(usdcAmount),
 			userUSDCAccount.publicKey
 		);
 
@@ -189,7 +193,7 @@ describe('update k', () => {
 		console.log('taking position');
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			new BN(QUOTE_PRECISION),
+			toQuoteAssetAmount(new BN(QUOTE_PRECISION)),
 			marketIndex
 		);
 		console.log('$1 position taken');
@@ -274,7 +278,7 @@ describe('update k', () => {
 		console.log('taking position');
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			new BN(QUOTE_PRECISION).mul(new BN(30000)),
+			toQuoteAssetAmount(new BN(QUOTE_PRECISION).mul(new BN(30000))),
 			marketIndex
 		);
 		console.log('$1 position taken');
@@ -345,4 +349,42 @@ describe('update k', () => {
 			convertToNumber(userAccount.getTotalCollateral(), QUOTE_PRECISION)
 		);
 	});
+
+	it('curve events for repeg and update k', async () => {
+		const marketIndex = Markets[0].marketIndex;
+		const events: CurveEvent[] = [];
+		const stopCurveEvents = clearingHouse.onCurveEvent((event) =>
+			events.push(event)
+		);
+
+		// move the oracle above mark so the repeg up is allowed
+		const market = clearingHouse.getMarket(marketIndex);
+		const markPrice = convertToNumber(calculateMarkPrice(market));
+		await setFeedPrice(
+			anchor.workspace.Pyth,
+			Math.ceil(markPrice * 1.01),
+			market.amm.oracle
+		);
+		const newPeg = market.amm.pegMultiplier
+			.mul(new BN(1001))
+			.div(new BN(1000));
+		await clearingHouse.repegAmmCurve(newPeg, marketIndex);
+
+		const ammAfterRepeg = clearingHouse.getMarket(marketIndex).amm;
+		const newSqrtK = ammAfterRepeg.sqrtK.mul(new BN(101)).div(new BN(100));
+		await clearingHouse.updateK(newSqrtK, marketIndex);
+
+		for (let i = 0; i < 20 && events.length < 2; i++) {
+			await clearingHouse.fetchAccounts();
+			await new Promise((r) => setTimeout(r, 500));
+		}
+		stopCurveEvents();
+
+		assert(events.length === 2);
+		assert(events[0].type === 'repeg');
+		assert(events[0].pegMultiplierAfter.eq(newPeg));
+		assert(events[1].type === 'updateK');
+		assert(events[1].sqrtKAfter.eq(newSqrtK));
+		assert(events[0].recordId.lt(events[1].recordId));
+	});
 });
@@ -5,11 +5,21 @@ import {
 	mockUSDCMint,
 	mockUserUSDCAccount,
 } from './testHelpers';
-import { Admin, ClearingHouseUser, PEG_PRECISION } from '../sdk/src';
+import {
+	Admin,
+	ClearingHouseUser,
+	PEG_PRECISION,
+	toCollateralAmount,
+	toQuoteAssetAmount,
+} from '../sdk/src';
 import { Keypair } from '@solana/web3.js';
 import BN from 'bn.js';
 import { assert } from 'chai';
-import { MAX_LEVERAGE, PositionDirection } from '../sdk/src';
+import {
+	InsufficientCollateralError,
+	MAX_LEVERAGE,
+	PositionDirection,
+} from '../sdk/src';
 
 describe('User Account', () => {
 	const provider = anchor.Provider.local();
@@ -133,7 +143,7 @@ describe('User Account', () => {
 
 	it('After Deposit', async () => {
 		await clearingHouse.depositCollateral(
-			usdcAmount,
+			toCollateralAmount(usdcAmount),
 			userUSDCAccount.publicKey
 		);
 
@@ -157,7 +167,7 @@ describe('User Account', () => {
 	it('After Position Taken', async () => {
 		await clearingHouse.openPosition(
 			PositionDirection.LONG,
-			solPositionInitialValue,
+			toQuoteAssetAmount(solPositionInitialValue),
 			marketIndex
 		);
 
@@ -178,6 +188,60 @@ describe('User Account', () => {
 		);
 	});
 
+	it('Trade above max leverage is rejected', async () => {
+		// the position already uses all but $0.0005 of the free collateral
+		const amount = toQuoteAssetAmount(usdcAmount);
+		try {
+			userAccount.validateOpenPosition(
+				PositionDirection.LONG,
+				amount,
+				marketIndex
+			);
+			assert(false, 'Trade validated');
+		} catch (e) {
+			assert(e instanceof InsufficientCollateralError, e.message);
+		}
+
+		// openPosition validates against the attached user before sending
+		clearingHouse.enableOptimisticUpdates(userAccount);
+		try {
+			await clearingHouse.openPosition(
+				PositionDirection.LONG,
+				amount,
+				marketIndex
+			);
+			assert(false, 'Trade sent');
+		} catch (e) {
+			assert(e instanceof InsufficientCollateralError, e.message);
+		} finally {
+			clearingHouse.disableOptimisticUpdates();
+		}
+
+		// skip the local validation so the program checks the trade
+		const insufficientCollateral = chProgram.idl.errors.find(
+			(error) => error.name === 'InsufficientCollateral'
+		);
+		try {
+			await clearingHouse.openPosition(
+				PositionDirection.LONG,
+				amount,
+				marketIndex,
+				undefined,
+				undefined,
+				undefined,
+				{ skipValidation: true }
+			);
+			assert(false, 'Trade succeeded');
+		} catch (e) {
+			assert(
+				e.message.includes(
+					`custom program error: 0x${insufficientCollateral.code.toString(16)}`
+				),
+				e.message
+			);
+		}
+	});
+
 	it('After Position Price Moves', async () => {
 		await clearingHouse.moveAmmPrice(
 			ammInitialBaseAssetAmount,
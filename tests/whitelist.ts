@@ -7,7 +7,7 @@ import { Program } from '@project-serum/anchor';
 import { PublicKey } from '@solana/web3.js';
 import { Token, TOKEN_PROGRAM_ID } from '@solana/spl-token';
 
-import { Admin, MARK_PRICE_PRECISION } from '../sdk/src';
+import { Admin, MARK_PRICE_PRECISION, toCollateralAmount } from '../sdk/src';
 
 import { Markets } from '../sdk/src/constants/markets';
 
@@ -92,7 +92,7 @@ describe('whitelist', () => {
 		try {
 			[, userAccountPublicKey] =
 				await clearingHouse.initializeUserAccountAndDepositCollateral(
-					usdcAmount,
+					toCollateralAmount(usdcAmount),
 					userUSDCAccount.publicKey
 				);
 		} catch (e) {
@@ -116,7 +116,7 @@ describe('whitelist', () => {
 		);
 		[, userAccountPublicKey] =
 			await clearingHouse.initializeUserAccountAndDepositCollateral(
-				usdcAmount,
+				toCollateralAmount(usdcAmount),
 				userUSDCAccount.publicKey
 			);
 